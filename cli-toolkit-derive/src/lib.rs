@@ -3,7 +3,7 @@ use proc_macro::TokenStream;
 mod metadata_table;
 mod from_byte_stream;
 
-#[proc_macro_derive(MetadataTable, attributes(table_index, heap_index, coded_index))]
+#[proc_macro_derive(MetadataTable, attributes(table_index, heap_index, coded_index, try_from))]
 pub fn metadata_table(ast: TokenStream) -> TokenStream {
 	let ast = syn::parse(ast).unwrap();
 	metadata_table::derive(ast)
@@ -28,12 +28,21 @@ pub fn derive(ast: DeriveInput) -> TokenStream {
 	let mut row_size = vec![];
 	let mut row_parsing = vec![];
 	let mut row_getters = vec![];
+	let mut row_indices = vec![];
+	let mut row_encoding = vec![];
+	let mut column_names = vec![];
 
 	for field in &fields {
 		let ty = &field.ty;
 		let ident = field.ident.as_ref().unwrap();
 
 		let mut custom_reader = false;
+		let mut index_doc = None;
+		let docs = field
+			.attrs
+			.iter()
+			.filter(|attr| attr.path.is_ident("doc"))
+			.collect::<Vec<_>>();
 		for attr in &field.attrs {
 			let path = attr.path.to_token_stream().to_string();
 
@@ -55,6 +64,11 @@ pub fn derive(ast: DeriveInput) -> TokenStream {
 
 					row_size.push(quote!(tables.idx_size(TableKind::#value_ident) as usize));
 					row_parsing.push(quote!(#ident: reader.read_table_index(self.#field_ident)?));
+					row_indices.push(quote!(RowIndex::Table(TableKind::#value_ident, self.#ident)));
+					row_encoding.push(quote!(writer.write_index(row.#ident.0, widths.table(TableKind::#value_ident))));
+					index_doc = Some(format!(
+						"A 1-based row of the [`TableKind::{value}`] table, or [`TableIndex::NULL`] for none."
+					));
 				}
 
 				"coded_index" => {
@@ -74,6 +88,24 @@ pub fn derive(ast: DeriveInput) -> TokenStream {
 
 					row_size.push(quote!(CodedIndex::get_size(CodedIndexKind::#value_ident, tables) as usize));
 					row_parsing.push(quote!(#ident: reader.read_coded_index(self.#field_ident)?));
+					row_indices.push(quote!(RowIndex::Coded(CodedIndexKind::#value_ident, self.#ident)));
+					row_encoding
+						.push(quote!(writer.write_index(row.#ident.0, widths.coded(CodedIndexKind::#value_ident))));
+					index_doc = Some(format!(
+						"A row of one of the tables of [`CodedIndexKind::{value}`], \
+						to be read with [`CodedIndex::decode`] and [`CodedIndex::is_null`] for that kind."
+					));
+				}
+
+				// A column stored as an integer and validated by converting it, for enums with invalid bit patterns.
+				"try_from" => {
+					custom_reader = true;
+					let value = attr.tokens.to_string();
+					let repr = Ident::new(&value[1..value.len() - 1], Span::call_site());
+
+					row_size.push(quote!(std::mem::size_of::<#repr>()));
+					row_parsing.push(quote!(#ident: <#ty>::try_from(reader.read::<#repr>()?)?));
+					row_encoding.push(quote!(writer.write(row.#ident as #repr)));
 				}
 
 				"heap_index" => {
@@ -87,6 +119,10 @@ pub fn derive(ast: DeriveInput) -> TokenStream {
 
 							row_size.push(quote!(StringHeap::idx_size(tables) as usize));
 							row_parsing.push(quote!(#ident: reader.read_heap_index(self.str_size)?));
+							row_indices.push(quote!(RowIndex::Heap(HeapKind::String, self.#ident)));
+							row_encoding.push(quote!(writer.write_index(row.#ident.0, widths.string)));
+							index_doc =
+								Some("An offset into the `#Strings` heap, where 0 is the empty string.".to_string());
 						}
 
 						"(Blob)" => {
@@ -96,6 +132,9 @@ pub fn derive(ast: DeriveInput) -> TokenStream {
 
 							row_size.push(quote!(BlobHeap::idx_size(tables) as usize));
 							row_parsing.push(quote!(#ident: reader.read_heap_index(self.blob_size)?));
+							row_indices.push(quote!(RowIndex::Heap(HeapKind::Blob, self.#ident)));
+							row_encoding.push(quote!(writer.write_index(row.#ident.0, widths.blob)));
+							index_doc = Some("An offset into the `#Blob` heap, where 0 is the empty blob.".to_string());
 						}
 
 						"(Guid)" => {
@@ -105,6 +144,9 @@ pub fn derive(ast: DeriveInput) -> TokenStream {
 
 							row_size.push(quote!(GuidHeap::idx_size(tables) as usize));
 							row_parsing.push(quote!(#ident: reader.read_heap_index(self.guid_size)?));
+							row_indices.push(quote!(RowIndex::Heap(HeapKind::Guid, self.#ident)));
+							row_encoding.push(quote!(writer.write_index(row.#ident.0, widths.guid)));
+							index_doc = Some("A 1-based index into the `#GUID` heap, where 0 is no GUID.".to_string());
 						}
 
 						_ => unimplemented!(),
@@ -118,9 +160,21 @@ pub fn derive(ast: DeriveInput) -> TokenStream {
 		if !custom_reader {
 			row_size.push(quote!(std::mem::size_of::<#ty>()));
 			row_parsing.push(quote!(#ident: reader.read()?));
+			row_encoding.push(quote!(writer.write(row.#ident)));
 		}
 
+		// The documentation of the column is followed by what its index refers to, if it is an index.
+		let separator = match docs.is_empty() || index_doc.is_none() {
+			true => None,
+			false => Some(quote!(#[doc = ""])),
+		};
+		let index_doc = index_doc.map(|doc| quote!(#[doc = #doc]));
+
+		column_names.push(ident.to_string());
 		row_getters.push(quote! {
+			#(#docs)*
+			#separator
+			#index_doc
 			pub fn #ident(&self) -> #ty {
 				self.#ident
 			}
@@ -182,6 +236,16 @@ pub fn derive(ast: DeriveInput) -> TokenStream {
 				#(#row_size)+*
 			}
 
+			fn calc_columns(tables: &TableHeap) -> Vec<TableColumn> {
+				let sizes = [#(#row_size),*];
+				let names = [#(#column_names),*];
+				let mut offset = 0;
+				names.into_iter().zip(sizes).map(|(name, size)| {
+					offset += size;
+					TableColumn { name, offset: offset - size, size }
+				}).collect()
+			}
+
 			fn new(bytes: &'l [u8], tables: &TableHeap) -> Result<Self, Error> {
 				Ok(Self {
 					bytes,
@@ -189,6 +253,32 @@ pub fn derive(ast: DeriveInput) -> TokenStream {
 					#(#table_field_readings),*
 				})
 			}
+
+			fn with_bytes(&self, bytes: &'l [u8]) -> Self {
+				Self {
+					bytes,
+					..self.clone()
+				}
+			}
+		}
+
+		// Tables and rows only borrow the assembly bytes, so they can always be scanned from other threads.
+		const _: () = {
+			fn assert_send_sync<T: Send + Sync>() {}
+			fn assert_table() {
+				assert_send_sync::<#table_name<'static>>();
+				assert_send_sync::<#iterator_name<'static>>();
+				assert_send_sync::<#name>();
+			}
+		};
+
+		impl #table_name<'_> {
+			/// Encodes a row with the given index widths, the inverse of parsing it.
+			pub fn encode_row(row: &#name, widths: &IndexWidths) -> Vec<u8> {
+				let mut writer = ByteWriter::default();
+				#(#row_encoding;)*
+				writer.into_bytes()
+			}
 		}
 
 		impl Iterator for #iterator_name<'_> {
@@ -205,6 +295,12 @@ pub fn derive(ast: DeriveInput) -> TokenStream {
 		impl #name {
 			#(#row_getters)*
 		}
+
+		impl MetadataRow for #name {
+			fn indices(&self) -> Vec<RowIndex> {
+				vec![#(#row_indices),*]
+			}
+		}
 	};
 
 	result.into()
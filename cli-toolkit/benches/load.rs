@@ -0,0 +1,34 @@
+use cli_toolkit::raw::{field_flags, method_flags, type_flags};
+use criterion::{criterion_group, criterion_main, Criterion};
+use cli_toolkit::testing::ImageBuilder;
+use cli_toolkit::schema::Context;
+
+/// The TypeDef rows of the assembly, including `<Module>`.
+/// The builder only writes 2 byte indices, and HasCustomAttribute ones cannot address more than 2047 rows.
+const TYPE_COUNT: usize = 2000;
+
+/// Close to as many types as the 2 byte indices of the builder allow, each with a field, a method and a parameter.
+fn large_assembly() -> Vec<u8> {
+	let mut builder = ImageBuilder::new("Large");
+	let core = builder.assembly_ref("System.Runtime", [7, 0, 0, 0]);
+	let object = builder.type_ref(core, "System", "Object");
+
+	for index in 1..TYPE_COUNT {
+		builder.type_def("Large", &format!("T{}", index), type_flags::PUBLIC, Some(object));
+		builder.field("value", field_flags::PRIVATE, &[0x06, 0x08]);
+		builder.method("Run", method_flags::PUBLIC, &[0x20, 0x01, 0x01, 0x08]);
+		builder.param(1, "value", 0);
+	}
+
+	builder.build()
+}
+
+fn load(c: &mut Criterion) {
+	let bytes = large_assembly();
+	c.bench_function("load_large_assembly", |b| {
+		b.iter(|| Context::from_assembly_list([bytes.as_slice()]).unwrap())
+	});
+}
+
+criterion_group!(benches, load);
+criterion_main!(benches);
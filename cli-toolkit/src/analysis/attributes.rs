@@ -0,0 +1,50 @@
+use std::collections::BTreeMap;
+use crate::raw::*;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct AttributeUsage {
+	pub type_name: String,
+	pub count: usize,
+	pub parent_kinds: Vec<(MetadataTokenKind, usize)>,
+}
+
+impl<'l> Assembly<'l> {
+	/// Lists every distinct custom attribute type applied in this assembly, sorted by full name,
+	/// along with how many times it is used and on which kinds of metadata.
+	pub fn attribute_usage_summary(&self) -> Result<Vec<AttributeUsage>, Error> {
+		let Some(tables) = self.get_heap::<TableHeap>()? else { return Ok(vec![]) };
+		let Some(attributes) = tables.get_table::<CustomAttributeTable>()? else { return Ok(vec![]) };
+		let strings = self.get_heap::<StringHeap>()?.unwrap_or_else(StringHeap::empty);
+
+		let mut usages = BTreeMap::<String, AttributeUsage>::new();
+		for attribute in attributes.iter() {
+			let attribute = attribute?;
+			let constructor = attribute
+				.type_()
+				.decode(CodedIndexKind::CustomAttributeType)
+				.ok_or(Error::InvalidData(Some("Invalid custom attribute type")))?;
+
+			let parent_kind = attribute
+				.parent()
+				.decode(CodedIndexKind::HasCustomAttribute)
+				.and_then(|parent| parent.token_kind())
+				.ok_or(Error::InvalidData(Some("Invalid custom attribute parent")))?;
+
+			let attribute_type = attribute_type(&tables, constructor)?;
+			let type_name = type_name(&tables, &strings, attribute_type)?;
+			let usage = usages.entry(type_name.clone()).or_insert_with(|| AttributeUsage {
+				type_name,
+				count: 0,
+				parent_kinds: vec![],
+			});
+
+			usage.count += 1;
+			match usage.parent_kinds.iter_mut().find(|(kind, _)| *kind == parent_kind) {
+				Some((_, count)) => *count += 1,
+				None => usage.parent_kinds.push((parent_kind, 1)),
+			}
+		}
+
+		Ok(usages.into_values().collect())
+	}
+}
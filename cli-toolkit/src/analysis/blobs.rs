@@ -0,0 +1,150 @@
+use std::collections::BTreeMap;
+use std::ops::Range;
+use strum::IntoEnumIterator;
+use crate::raw::*;
+
+/// The result of cross-checking the blob heap against every blob column of the table heap.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct BlobHeapReport {
+	/// The number of distinct blobs referenced by the tables.
+	pub referenced_blobs: usize,
+	/// Byte ranges of the heap that no referenced blob covers, excluding trailing padding.
+	pub unreferenced_regions: Vec<Range<u32>>,
+	/// Referenced blobs which start inside another referenced blob and extend past its end.
+	pub overlaps: Vec<BlobOverlap>,
+	/// References which point inside another referenced blob, rather than at the start of a blob of its own.
+	pub interior_references: Vec<BlobReference>,
+	/// References whose blob header cannot be read or whose data runs past the end of the heap.
+	pub invalid_references: Vec<BlobReference>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct BlobOverlap {
+	pub first: Range<u32>,
+	pub second: Range<u32>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct BlobReference {
+	/// The row holding the reference.
+	pub token: MetadataToken,
+	/// The referenced heap offset.
+	pub offset: u32,
+	/// The blob the reference points into, if any.
+	pub container: Option<Range<u32>>,
+}
+
+impl BlobHeapReport {
+	pub fn is_clean(&self) -> bool {
+		self.unreferenced_regions.is_empty()
+			&& self.overlaps.is_empty()
+			&& self.interior_references.is_empty()
+			&& self.invalid_references.is_empty()
+	}
+}
+
+impl<'l> Assembly<'l> {
+	/// Looks for junk, overlapping and interleaved blobs, as left behind by some obfuscators.
+	pub fn analyze_blob_heap(&self) -> Result<BlobHeapReport, Error> {
+		let Some(blobs) = self.get_heap::<BlobHeap>()? else { return Ok(BlobHeapReport::default()) };
+		let Some(tables) = self.get_heap::<TableHeap>()? else { return Ok(BlobHeapReport::default()) };
+
+		let collector = BlobCollector { tables };
+		let mut references = vec![];
+		for kind in tables.valid_mask().iter() {
+			dispatch_table!(kind, collector.collect(&mut references), Ok(()))?;
+		}
+
+		let mut report = BlobHeapReport::default();
+		let mut blob_refs = BTreeMap::<u32, (Range<u32>, MetadataToken)>::new();
+		for (token, index) in references {
+			match blob_range(&blobs, index) {
+				Some(range) => {
+					blob_refs.entry(index.0).or_insert((range, token));
+				}
+				None => report.invalid_references.push(BlobReference {
+					token,
+					offset: index.0,
+					container: None,
+				}),
+			}
+		}
+
+		report.referenced_blobs = blob_refs.len();
+
+		// Blobs are visited in offset order, so only the blob reaching furthest so far can contain the next one.
+		let mut outer: Option<Range<u32>> = None;
+		let mut covered_until = 1;
+		for (offset, (range, token)) in blob_refs.iter() {
+			if let Some(outer) = outer.as_ref().filter(|outer| outer.contains(offset)) {
+				report.interior_references.push(BlobReference {
+					token: *token,
+					offset: *offset,
+					container: Some(outer.clone()),
+				});
+
+				if range.end > outer.end {
+					report.overlaps.push(BlobOverlap {
+						first: outer.clone(),
+						second: range.clone(),
+					});
+				}
+			}
+
+			if *offset > covered_until {
+				report.unreferenced_regions.push(covered_until..*offset);
+			}
+
+			covered_until = covered_until.max(range.end);
+			if outer.as_ref().map(|outer| range.end > outer.end).unwrap_or(true) {
+				outer = Some(range.clone());
+			}
+		}
+
+		let bytes = blobs.bytes();
+		let end = bytes.iter().rposition(|b| *b != 0).map(|i| i as u32 + 1).unwrap_or(0);
+		if end > covered_until {
+			report.unreferenced_regions.push(covered_until..end);
+		}
+
+		Ok(report)
+	}
+}
+
+struct BlobCollector<'l> {
+	tables: TableHeap<'l>,
+}
+
+impl<'l> BlobCollector<'l> {
+	fn collect<T: MetadataTable<'l>>(&self, references: &mut Vec<(MetadataToken, HeapIndex)>) -> Result<(), Error>
+	where
+		T::Row: MetadataRow,
+	{
+		let Some(table) = self.tables.get_table::<T>()? else { return Ok(()) };
+		for (index, row) in table.iter().enumerate() {
+			let token = MetadataToken::from_table_row(T::cli_identifier(), TableIndex(index as u32 + 1));
+			for column in row?.indices() {
+				if let RowIndex::Heap(HeapKind::Blob, index) = column {
+					if index.0 != 0 {
+						references.push((token, index));
+					}
+				}
+			}
+		}
+
+		Ok(())
+	}
+}
+
+/// The range of heap bytes occupied by the blob at `index`, including its length header.
+fn blob_range(blobs: &BlobHeap, index: HeapIndex) -> Option<Range<u32>> {
+	let data = blobs.get_blob(index).ok()?;
+	let header = blobs.bytes().get(index.0 as usize)?;
+	let header_size = match header {
+		h if h & 0x80 == 0 => 1,
+		h if h & 0xC0 == 0x80 => 2,
+		_ => 4,
+	};
+
+	Some(index.0..index.0 + header_size + data.len() as u32)
+}
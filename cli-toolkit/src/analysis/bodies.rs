@@ -0,0 +1,154 @@
+use std::collections::HashMap;
+use crate::raw::*;
+
+/// A method definition whose IL body is stored in the image.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct MethodBodyLocation {
+	pub token: MetadataToken,
+	pub rva: u32,
+	/// The file offset of the method header.
+	pub offset: usize,
+}
+
+/// A method body, along with the other methods whose RVA points at the same body.
+///
+/// Shared bodies are common after merging or obfuscating assemblies.
+/// Patching a shared body in place changes every method sharing it.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct MethodBody {
+	location: MethodBodyLocation,
+	shared_with: Vec<MetadataToken>,
+}
+
+impl MethodBody {
+	pub fn location(&self) -> MethodBodyLocation {
+		self.location
+	}
+
+	/// The other methods using this body, in token order.
+	pub fn shared_with(&self) -> &[MetadataToken] {
+		&self.shared_with
+	}
+
+	pub fn is_shared(&self) -> bool {
+		!self.shared_with.is_empty()
+	}
+}
+
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct MethodBodyStatistics {
+	/// The methods with an IL body, see [`Assembly::methods_with_bodies`].
+	pub methods: usize,
+	/// The number of distinct body RVAs.
+	pub bodies: usize,
+	/// The bodies used by more than one method.
+	pub shared_bodies: usize,
+	/// The methods whose body is also used by a method with a lower token.
+	pub duplicates: usize,
+}
+
+/// A field definition whose initial data is stored in the image, as mapped by the FieldRVA table.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct FieldDataLocation {
+	pub token: MetadataToken,
+	pub rva: u32,
+	pub offset: usize,
+}
+
+impl<'l> Assembly<'l> {
+	/// The methods with an IL body, in token order, gathered in a single pass over the MethodDef table.
+	/// Methods without an RVA, with native or runtime provided code, or whose RVA is outside every section are skipped.
+	pub fn methods_with_bodies(&self) -> Result<impl Iterator<Item = MethodBodyLocation>, Error> {
+		let mut bodies = vec![];
+		let Some(tables) = self.get_heap::<TableHeap>()? else { return Ok(bodies.into_iter()) };
+		let Some(methods) = tables.get_table::<MethodDefTable>()? else { return Ok(bodies.into_iter()) };
+
+		bodies.reserve(methods.len());
+		for (index, method) in methods.iter().enumerate() {
+			let method = method?;
+			let impl_flags = method.impl_flags();
+			if method.rva() == 0
+				|| impl_flags & method_impl_flags::CODE_TYPE_MASK != method_impl_flags::IL
+				|| impl_flags & method_impl_flags::MANAGED_MASK != method_impl_flags::MANAGED
+			{
+				continue;
+			}
+
+			let Ok(offset) = self.rva_to_offset(method.rva()) else { continue };
+			bodies.push(MethodBodyLocation {
+				token: MetadataToken::new(index as u32 + 1, MetadataTokenKind::Method),
+				rva: method.rva(),
+				offset,
+			});
+		}
+
+		Ok(bodies.into_iter())
+	}
+
+	/// The methods with an IL body, in token order, each with the methods it shares its body with.
+	pub fn method_bodies(&self) -> Result<Vec<MethodBody>, Error> {
+		let locations = self.methods_with_bodies()?.collect::<Vec<_>>();
+		let mut by_rva = HashMap::<u32, Vec<MetadataToken>>::new();
+		for location in locations.iter() {
+			by_rva.entry(location.rva).or_default().push(location.token);
+		}
+
+		let bodies = locations.into_iter().map(|location| {
+			let shared_with = by_rva[&location.rva]
+				.iter()
+				.filter(|token| **token != location.token)
+				.copied()
+				.collect();
+
+			MethodBody { location, shared_with }
+		});
+
+		Ok(bodies.collect())
+	}
+
+	/// The IL of a method returned by [`Assembly::methods_with_bodies`].
+	pub fn method_il(&self, location: &MethodBodyLocation) -> Result<IlBody<'l>, Error> {
+		let bytes = self.bytes().get(location.offset..).ok_or(Error::OffsetOutOfBounds)?;
+		IlBody::parse(bytes)
+	}
+
+	pub fn method_body_statistics(&self) -> Result<MethodBodyStatistics, Error> {
+		let mut statistics = MethodBodyStatistics::default();
+		let mut by_rva = HashMap::<u32, usize>::new();
+		for location in self.methods_with_bodies()? {
+			statistics.methods += 1;
+			*by_rva.entry(location.rva).or_default() += 1;
+		}
+
+		statistics.bodies = by_rva.len();
+		statistics.shared_bodies = by_rva.values().filter(|count| **count > 1).count();
+		statistics.duplicates = statistics.methods - statistics.bodies;
+		Ok(statistics)
+	}
+
+	/// The fields with initial data, in token order.
+	/// Rows without an RVA or whose RVA is outside every section are skipped.
+	pub fn fields_with_data(&self) -> Result<impl Iterator<Item = FieldDataLocation>, Error> {
+		let mut fields = vec![];
+		let Some(tables) = self.get_heap::<TableHeap>()? else { return Ok(fields.into_iter()) };
+		let Some(field_rvas) = tables.get_table::<FieldRVATable>()? else { return Ok(fields.into_iter()) };
+
+		fields.reserve(field_rvas.len());
+		for field_rva in field_rvas.iter() {
+			let field_rva = field_rva?;
+			if field_rva.rva() == 0 {
+				continue;
+			}
+
+			let Ok(offset) = self.rva_to_offset(field_rva.rva()) else { continue };
+			fields.push(FieldDataLocation {
+				token: MetadataToken::new(field_rva.field().0, MetadataTokenKind::Field),
+				rva: field_rva.rva(),
+				offset,
+			});
+		}
+
+		fields.sort_by_key(|field| field.token.index());
+		Ok(fields.into_iter())
+	}
+}
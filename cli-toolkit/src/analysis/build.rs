@@ -0,0 +1,196 @@
+use crate::raw::*;
+
+const READY_TO_RUN_SIGNATURE: u32 = 0x00525452;
+const READY_TO_RUN_COMPILER_IDENTIFIER: u32 = 100;
+
+/// The SHA-256 of ".net core bundle", which single-file hosts embed right after the bundle header offset.
+const BUNDLE_SIGNATURE: [u8; 32] = [
+	0x8b, 0x12, 0x02, 0xb9, 0x6a, 0x61, 0x20, 0x38, 0x72, 0x7b, 0x93, 0x02, 0x14, 0xd7, 0xa0, 0x32, 0x13, 0xf5, 0xb9,
+	0xe6, 0xef, 0xae, 0x33, 0x18, 0xee, 0x3b, 0x2d, 0xce, 0x24, 0xb3, 0x6a, 0xae,
+];
+
+pub mod debugging_modes {
+	pub type DebuggingModes = u32;
+	pub const NONE: DebuggingModes = 0x0000;
+	pub const DEFAULT: DebuggingModes = 0x0001;
+	pub const IGNORE_SYMBOL_STORE_SEQUENCE_POINTS: DebuggingModes = 0x0002;
+	pub const ENABLE_EDIT_AND_CONTINUE: DebuggingModes = 0x0004;
+	pub const DISABLE_OPTIMIZATIONS: DebuggingModes = 0x0100;
+
+	/// Names the flags set in `flags`, e.g. `Default | DisableOptimizations`.
+	pub fn to_string(flags: DebuggingModes) -> String {
+		crate::utilities::flags_to_string(
+			flags,
+			&[],
+			&[
+				(DEFAULT, "Default"),
+				(IGNORE_SYMBOL_STORE_SEQUENCE_POINTS, "IgnoreSymbolStoreSequencePoints"),
+				(ENABLE_EDIT_AND_CONTINUE, "EnableEditAndContinue"),
+				(DISABLE_OPTIMIZATIONS, "DisableOptimizations"),
+			],
+		)
+	}
+}
+
+pub use debugging_modes::DebuggingModes;
+
+/// How an assembly was built and deployed, as far as can be told from the image itself.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct BuildTraits {
+	/// Present if the image contains ReadyToRun (crossgen) precompiled code.
+	pub ready_to_run: Option<ReadyToRunInfo>,
+	/// Whether the assembly declares itself trimmable through `[assembly: AssemblyMetadata("IsTrimmable", "True")]`.
+	pub is_trimmable: bool,
+	/// The offset of the bundle header, if the image is the host of a single-file bundle.
+	pub bundle_header_offset: Option<u64>,
+	/// The modes of the assembly's `DebuggableAttribute`, if it has one.
+	pub debugging_modes: Option<DebuggingModes>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ReadyToRunInfo {
+	pub major_version: u16,
+	pub minor_version: u16,
+	pub flags: u32,
+	/// The identifier of the compiler that produced the native code, e.g. `Crossgen2 7.0.0`.
+	pub compiler_identifier: Option<String>,
+}
+
+impl BuildTraits {
+	pub fn is_ready_to_run(&self) -> bool {
+		self.ready_to_run.is_some()
+	}
+
+	pub fn is_single_file_bundle(&self) -> bool {
+		self.bundle_header_offset.is_some()
+	}
+
+	pub fn is_jit_optimizer_disabled(&self) -> bool {
+		self.debugging_modes
+			.map(|modes| modes & debugging_modes::DISABLE_OPTIMIZATIONS != 0)
+			.unwrap_or(false)
+	}
+}
+
+impl<'l> Assembly<'l> {
+	pub fn build_traits(&self) -> Result<BuildTraits, Error> {
+		let mut traits = BuildTraits {
+			ready_to_run: self.ready_to_run_info()?,
+			bundle_header_offset: bundle_header_offset(self.bytes()),
+			..Default::default()
+		};
+
+		let Some(tables) = self.get_heap::<TableHeap>()? else { return Ok(traits) };
+		let Some(attributes) = tables.get_table::<CustomAttributeTable>()? else { return Ok(traits) };
+		let strings = self.get_heap::<StringHeap>()?.unwrap_or_else(StringHeap::empty);
+		let blobs = self.get_heap::<BlobHeap>()?.unwrap_or_else(BlobHeap::empty);
+
+		for attribute in attributes.iter() {
+			let attribute = attribute?;
+			let parent = attribute.parent().decode(CodedIndexKind::HasCustomAttribute);
+			if parent.and_then(|parent| parent.token_kind()) != Some(MetadataTokenKind::Assembly) {
+				continue;
+			}
+
+			let constructor = attribute
+				.type_()
+				.decode(CodedIndexKind::CustomAttributeType)
+				.ok_or(Error::InvalidData(Some("Invalid custom attribute type")))?;
+
+			let value = blobs.get_blob(attribute.value())?;
+			match type_name(&tables, &strings, attribute_type(&tables, constructor)?)?.as_str() {
+				"System.Diagnostics.DebuggableAttribute" => traits.debugging_modes = debugging_modes_of(value),
+				"System.Reflection.AssemblyMetadataAttribute" => {
+					if let Some(["IsTrimmable", value]) = fixed_strings::<2>(value) {
+						traits.is_trimmable |= value.eq_ignore_ascii_case("true");
+					}
+				}
+				_ => {}
+			}
+		}
+
+		Ok(traits)
+	}
+
+	fn ready_to_run_info(&self) -> Result<Option<ReadyToRunInfo>, Error> {
+		let header = self.cli_header().managed_native_header;
+		let (rva, size) = (header as u32, (header >> 32) as u32);
+		if rva == 0 || size == 0 {
+			return Ok(None);
+		}
+
+		let mut stream = ByteStream::new(self.bytes());
+		stream.seek(self.rva_to_offset(rva)?)?;
+		if stream.read::<u32>()? != READY_TO_RUN_SIGNATURE {
+			return Ok(None);
+		}
+
+		let mut info = ReadyToRunInfo {
+			major_version: stream.read()?,
+			minor_version: stream.read()?,
+			flags: stream.read()?,
+			compiler_identifier: None,
+		};
+
+		let section_count = stream.read::<u32>()?;
+		for _ in 0..section_count {
+			let kind = stream.read::<u32>()?;
+			let section = stream.read::<DataDirectory>()?;
+			if kind != READY_TO_RUN_COMPILER_IDENTIFIER {
+				continue;
+			}
+
+			let mut identifier = ByteStream::new(self.bytes());
+			identifier.seek(self.rva_to_offset(section.rva)?)?;
+			let identifier = identifier.read_slice::<u8>(section.size as usize)?;
+			let identifier = identifier.split(|c| *c == 0).next().unwrap_or(&[]);
+			info.compiler_identifier = Some(String::from_utf8_lossy(identifier).into_owned());
+		}
+
+		Ok(Some(info))
+	}
+}
+
+fn bundle_header_offset(bytes: &[u8]) -> Option<u64> {
+	let position = bytes
+		.windows(BUNDLE_SIGNATURE.len())
+		.position(|w| w == BUNDLE_SIGNATURE)?;
+	let offset = bytes.get(position.checked_sub(8)?..position)?;
+	let offset = u64::from_le_bytes(offset.try_into().ok()?);
+	(offset != 0).then_some(offset)
+}
+
+/// Decodes either constructor of `DebuggableAttribute`:
+/// `(DebuggingModes)` or `(bool isJITTrackingEnabled, bool isJITOptimizerDisabled)`.
+fn debugging_modes_of(value: &[u8]) -> Option<DebuggingModes> {
+	match value {
+		[0x01, 0x00, a, b, c, d, ..] if value.len() == 8 => Some(u32::from_le_bytes([*a, *b, *c, *d])),
+		[0x01, 0x00, tracking, disabled, ..] => {
+			let mut modes = debugging_modes::NONE;
+			if *tracking != 0 {
+				modes |= debugging_modes::DEFAULT;
+			}
+			if *disabled != 0 {
+				modes |= debugging_modes::DISABLE_OPTIMIZATIONS;
+			}
+			Some(modes)
+		}
+		_ => None,
+	}
+}
+
+/// Reads the first `N` fixed arguments of a custom attribute blob as strings.
+fn fixed_strings<const N: usize>(value: &[u8]) -> Option<[&str; N]> {
+	let mut stream = ByteStream::new(value);
+	if stream.read::<u16>().ok()? != 0x0001 {
+		return None;
+	}
+
+	let mut strings = [""; N];
+	for string in strings.iter_mut() {
+		let length = stream.read_compressed_u32().ok()?;
+		*string = std::str::from_utf8(stream.read_slice::<u8>(length as usize).ok()?).ok()?;
+	}
+
+	Some(strings)
+}
@@ -0,0 +1,148 @@
+use crate::raw::*;
+
+/// What each AssemblyRef of an assembly is actually used for.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct DependencyUsage {
+	/// One entry per AssemblyRef row, in token order.
+	pub assembly_refs: Vec<AssemblyRefUsage>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct AssemblyRefUsage {
+	pub token: MetadataToken,
+	pub name: String,
+	/// The TypeRefs resolving into the assembly, directly or through their enclosing type, with their full names.
+	pub type_refs: Vec<(MetadataToken, String)>,
+	/// The MemberRefs whose parent resolves into the assembly, as `Type::member`.
+	/// Members of generic instantiations are attributed to the assembly of the generic type.
+	pub member_refs: Vec<(MetadataToken, String)>,
+	/// The ExportedTypes forwarded to the assembly.
+	pub exported_types: Vec<MetadataToken>,
+}
+
+impl AssemblyRefUsage {
+	pub fn is_unreferenced(&self) -> bool {
+		self.type_refs.is_empty() && self.member_refs.is_empty() && self.exported_types.is_empty()
+	}
+}
+
+impl DependencyUsage {
+	/// The AssemblyRefs that nothing resolves into, which can usually be dropped.
+	pub fn unreferenced(&self) -> impl Iterator<Item = &AssemblyRefUsage> {
+		self.assembly_refs.iter().filter(|usage| usage.is_unreferenced())
+	}
+}
+
+impl<'l> Assembly<'l> {
+	/// Counts, for every AssemblyRef, the TypeRefs, MemberRefs and ExportedTypes that resolve into it.
+	pub fn dependency_usage(&self) -> Result<DependencyUsage, Error> {
+		let mut usage = DependencyUsage::default();
+		let Some(tables) = self.get_heap::<TableHeap>()? else { return Ok(usage) };
+		let Some(assembly_refs) = tables.get_table::<AssemblyRefTable>()? else { return Ok(usage) };
+		let strings = self.get_heap::<StringHeap>()?.unwrap_or_else(StringHeap::empty);
+		let blobs = self.get_heap::<BlobHeap>()?.unwrap_or_else(BlobHeap::empty);
+
+		for (index, assembly_ref) in assembly_refs.iter().enumerate() {
+			usage.assembly_refs.push(AssemblyRefUsage {
+				token: MetadataToken::new(index as u32 + 1, MetadataTokenKind::AssemblyRef),
+				name: strings.get_string(assembly_ref?.name()).to_string(),
+				type_refs: vec![],
+				member_refs: vec![],
+				exported_types: vec![],
+			});
+		}
+
+		let mut scopes = vec![];
+		if let Some(type_refs) = tables.get_table::<TypeRefTable>()? {
+			for type_ref in type_refs.iter() {
+				let scope = type_ref?.resolution_scope().decode(CodedIndexKind::ResolutionScope);
+				scopes.push(scope.unwrap_or(MetadataToken(0)));
+			}
+		}
+
+		// Nested TypeRefs are scoped to their enclosing TypeRef, so walk up to the outermost one.
+		let owner = |token: MetadataToken| -> Option<usize> {
+			let mut token = token;
+			for _ in 0..=scopes.len() {
+				match token.token_kind() {
+					Some(MetadataTokenKind::TypeRef) => token = *scopes.get(token.index().checked_sub(1)?)?,
+					Some(MetadataTokenKind::AssemblyRef) => return token.index().checked_sub(1),
+					_ => return None,
+				}
+			}
+			None
+		};
+
+		for (index, _) in scopes.iter().enumerate() {
+			let token = MetadataToken::new(index as u32 + 1, MetadataTokenKind::TypeRef);
+			if let Some(usage) = owner(token).and_then(|owner| usage.assembly_refs.get_mut(owner)) {
+				usage.type_refs.push((token, type_name(&tables, &strings, token)?));
+			}
+		}
+
+		if let Some(member_refs) = tables.get_table::<MemberRefTable>()? {
+			let type_specs = tables.get_table::<TypeSpecTable>()?;
+			for (index, member_ref) in member_refs.iter().enumerate() {
+				let member_ref = member_ref?;
+				let Some(mut parent) = member_ref.parent().decode(CodedIndexKind::MemberRefParent) else {
+					continue;
+				};
+
+				if parent.token_kind() == Some(MetadataTokenKind::TypeSpec) {
+					let Some(type_specs) = &type_specs else { continue };
+					let type_spec = type_specs.get(TableIndex(parent.index() as u32))?;
+					let signature = TypeSignature::parse(blobs.get_blob(type_spec.signature())?)?;
+					parent = match signature {
+						TypeSignature::GenericInst(ty, _) => match *ty {
+							TypeSignature::Class(token) | TypeSignature::ValueType(token) => token,
+							_ => continue,
+						},
+						_ => continue,
+					};
+				}
+
+				let Some(usage) = owner(parent).and_then(|owner| usage.assembly_refs.get_mut(owner)) else {
+					continue;
+				};
+				let token = MetadataToken::new(index as u32 + 1, MetadataTokenKind::MemberRef);
+				let name = strings.get_string(member_ref.name());
+				usage
+					.member_refs
+					.push((token, format!("{}::{}", type_name(&tables, &strings, parent)?, name)));
+			}
+		}
+
+		if let Some(exported_types) = tables.get_table::<ExportedTypeTable>()? {
+			let mut implementations = vec![];
+			for exported_type in exported_types.iter() {
+				let implementation = exported_type?.implementation().decode(CodedIndexKind::Implementation);
+				implementations.push(implementation.unwrap_or(MetadataToken(0)));
+			}
+
+			// Nested exported types are forwarded along with their enclosing type.
+			for index in 0..implementations.len() {
+				let mut implementation = implementations[index];
+				for _ in 0..implementations.len() {
+					match implementation.token_kind() {
+						Some(MetadataTokenKind::ExportedType) => {
+							let Some(next) = implementations.get(implementation.index().wrapping_sub(1)) else { break };
+							implementation = *next;
+						}
+						_ => break,
+					}
+				}
+
+				if implementation.token_kind() != Some(MetadataTokenKind::AssemblyRef) {
+					continue;
+				}
+
+				if let Some(usage) = usage.assembly_refs.get_mut(implementation.index().wrapping_sub(1)) {
+					let token = MetadataToken::new(index as u32 + 1, MetadataTokenKind::ExportedType);
+					usage.exported_types.push(token);
+				}
+			}
+		}
+
+		Ok(usage)
+	}
+}
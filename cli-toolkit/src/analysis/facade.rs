@@ -0,0 +1,80 @@
+use std::collections::HashMap;
+use crate::schema::AssemblyIdentity;
+use crate::raw::*;
+
+/// The rows a reference facade needs in order to forward every public type of an assembly to `target`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Facade {
+	pub target: AssemblyIdentity,
+	pub exported_types: Vec<ForwardedType>,
+}
+
+/// A row of the facade's ExportedType table.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ForwardedType {
+	pub flags: TypeFlags,
+	pub type_def_id: u32,
+	pub name: String,
+	pub namespace: String,
+	/// The facade's first AssemblyRef for top-level types, or the ExportedType row of the enclosing type.
+	pub implementation: MetadataToken,
+}
+
+impl<'l> Assembly<'l> {
+	pub fn facade_forwarders(&self, target: AssemblyIdentity) -> Result<Facade, Error> {
+		let mut facade = Facade {
+			target,
+			exported_types: vec![],
+		};
+
+		let Some(tables) = self.get_heap::<TableHeap>()? else { return Ok(facade) };
+		let Some(type_defs) = tables.get_table::<TypeDefTable>()? else { return Ok(facade) };
+		let strings = self.get_heap::<StringHeap>()?.unwrap_or_else(StringHeap::empty);
+
+		let mut enclosing = HashMap::new();
+		let mut nested = HashMap::<TableIndex, Vec<TableIndex>>::new();
+		if let Some(nested_classes) = tables.get_table::<NestedClassTable>()? {
+			for row in nested_classes.iter() {
+				let row = row?;
+				enclosing.insert(row.nested_class(), row.enclosing_class());
+				nested.entry(row.enclosing_class()).or_default().push(row.nested_class());
+			}
+		}
+
+		let mut pending = vec![];
+		for index in (1..=type_defs.len() as u32).rev().map(TableIndex) {
+			if !enclosing.contains_key(&index) {
+				pending.push((index, MetadataToken::new(1, MetadataTokenKind::AssemblyRef)));
+			}
+		}
+
+		while let Some((index, implementation)) = pending.pop() {
+			let def = type_defs.get(index)?;
+			let visibility = def.flags() & type_flags::VISIBILITY_MASK;
+
+			let flags = match implementation.token_kind() {
+				Some(MetadataTokenKind::AssemblyRef) if visibility == type_flags::PUBLIC => {
+					type_flags::IS_TYPE_FORWARDER
+				}
+				Some(MetadataTokenKind::ExportedType) if visibility == type_flags::NESTED_PUBLIC => visibility,
+				_ => continue,
+			};
+
+			facade.exported_types.push(ForwardedType {
+				flags,
+				type_def_id: MetadataToken::new(index.0, MetadataTokenKind::TypeDef).0,
+				name: strings.get_string(def.name()).to_string(),
+				namespace: strings.get_string(def.namespace()).to_string(),
+				implementation,
+			});
+
+			let exported = facade.exported_types.len() as u32;
+			let exported = MetadataToken::new(exported, MetadataTokenKind::ExportedType);
+			if let Some(children) = nested.get(&index) {
+				pending.extend(children.iter().rev().map(|child| (*child, exported)));
+			}
+		}
+
+		Ok(facade)
+	}
+}
@@ -0,0 +1,120 @@
+use std::collections::{BTreeMap, HashSet};
+use crate::raw::*;
+
+/// How often an opcode occurs, and how many bytes its instructions take including their operands.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct OpCodeStatistics {
+	pub count: usize,
+	pub bytes: usize,
+}
+
+/// The opcode frequencies of one or more method bodies.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct OpCodeTable {
+	entries: BTreeMap<OpCode, OpCodeStatistics>,
+	instructions: usize,
+	bytes: usize,
+}
+
+impl OpCodeTable {
+	pub fn add(&mut self, instruction: &Instruction) {
+		let entry = self.entries.entry(instruction.opcode).or_default();
+		entry.count += 1;
+		entry.bytes += instruction.size as usize;
+		self.instructions += 1;
+		self.bytes += instruction.size as usize;
+	}
+
+	/// Adds the frequencies of another table, e.g. one built for another assembly or on another thread.
+	pub fn merge(&mut self, other: &OpCodeTable) {
+		for (opcode, statistics) in other.entries.iter() {
+			let entry = self.entries.entry(*opcode).or_default();
+			entry.count += statistics.count;
+			entry.bytes += statistics.bytes;
+		}
+
+		self.instructions += other.instructions;
+		self.bytes += other.bytes;
+	}
+
+	pub fn get(&self, opcode: OpCode) -> OpCodeStatistics {
+		self.entries.get(&opcode).copied().unwrap_or_default()
+	}
+
+	/// Every opcode that occurs at least once, in opcode order.
+	pub fn iter(&self) -> impl Iterator<Item = (OpCode, OpCodeStatistics)> + '_ {
+		self.entries.iter().map(|(opcode, statistics)| (*opcode, *statistics))
+	}
+
+	/// The `count` most frequent opcodes, most frequent first.
+	pub fn most_common(&self, count: usize) -> Vec<(OpCode, OpCodeStatistics)> {
+		let mut entries = self.iter().collect::<Vec<_>>();
+		entries.sort_by(|(a, a_stats), (b, b_stats)| b_stats.count.cmp(&a_stats.count).then(a.cmp(b)));
+		entries.truncate(count);
+		entries
+	}
+
+	pub fn instructions(&self) -> usize {
+		self.instructions
+	}
+
+	pub fn bytes(&self) -> usize {
+		self.bytes
+	}
+
+	/// The average size of an instruction, or 0 if there are none.
+	pub fn bytes_per_instruction(&self) -> f64 {
+		match self.instructions {
+			0 => 0.0,
+			count => self.bytes as f64 / count as f64,
+		}
+	}
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct MethodOpCodes {
+	pub token: MetadataToken,
+	pub opcodes: OpCodeTable,
+}
+
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct OpCodeHistogram {
+	/// The opcodes of every method with an IL body, in token order.
+	pub methods: Vec<MethodOpCodes>,
+	/// The opcodes of every distinct body, so that bodies shared by several methods are only counted once.
+	pub total: OpCodeTable,
+	/// The methods whose body could not be decoded, e.g. because of unknown opcodes left by obfuscators.
+	/// They are not part of the histogram.
+	pub undecodable: Vec<MetadataToken>,
+}
+
+/// Counts the opcodes of every method body of an assembly, see [`Assembly::methods_with_bodies`].
+pub fn opcode_histogram(assembly: &Assembly) -> Result<OpCodeHistogram, Error> {
+	let mut histogram = OpCodeHistogram::default();
+	let mut counted = HashSet::new();
+	for location in assembly.methods_with_bodies()? {
+		let opcodes = assembly.method_il(&location).and_then(|body| {
+			let mut opcodes = OpCodeTable::default();
+			for instruction in body.instructions() {
+				opcodes.add(&instruction?);
+			}
+			Ok(opcodes)
+		});
+
+		let Ok(opcodes) = opcodes else {
+			histogram.undecodable.push(location.token);
+			continue;
+		};
+
+		if counted.insert(location.rva) {
+			histogram.total.merge(&opcodes);
+		}
+
+		histogram.methods.push(MethodOpCodes {
+			token: location.token,
+			opcodes,
+		});
+	}
+
+	Ok(histogram)
+}
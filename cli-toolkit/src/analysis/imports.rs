@@ -0,0 +1,158 @@
+use std::collections::BTreeMap;
+use crate::raw::*;
+
+const DLL_IMPORT_ATTRIBUTE: &str = "System.Runtime.InteropServices.DllImportAttribute";
+
+/// Where a [`NativeImport`] was read from.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum NativeImportSource {
+	ImplMap,
+	/// A `DllImportAttribute` applied to a method without an ImplMap row,
+	/// as emitted by some generators and left in analyzer targets.
+	Attribute,
+}
+
+/// A method implemented by a function exported from a native library.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct NativeImport {
+	pub method: MetadataToken,
+	pub module: String,
+	pub entry_point: String,
+	pub flags: PInvokeAttributes,
+	pub source: NativeImportSource,
+}
+
+impl<'l> Assembly<'l> {
+	/// The platform invoke imports of this assembly, in method token order.
+	///
+	/// Methods without an ImplMap row are looked up among the `DllImportAttribute`s.
+	/// The flags of those are rebuilt from the named arguments of the attribute.
+	pub fn native_imports(&self) -> Result<Vec<NativeImport>, Error> {
+		let Some(tables) = self.get_heap::<TableHeap>()? else { return Ok(vec![]) };
+		let Some(methods) = tables.get_table::<MethodDefTable>()? else { return Ok(vec![]) };
+		let strings = self.get_heap::<StringHeap>()?.unwrap_or_else(StringHeap::empty);
+
+		let mut imports = BTreeMap::new();
+		if let Some(impl_maps) = tables.get_table::<ImplMapTable>()? {
+			let module_refs = tables.get_table::<ModuleRefTable>()?;
+			for impl_map in impl_maps.iter() {
+				let impl_map = impl_map?;
+				let method = impl_map
+					.member_forwarded()
+					.decode(CodedIndexKind::MemberForwarded)
+					.ok_or(Error::InvalidData(Some("Invalid ImplMap member")))?;
+
+				if method.token_kind() != Some(MetadataTokenKind::Method) {
+					continue;
+				}
+
+				let module_ref = match &module_refs {
+					Some(module_refs) => module_refs.get(impl_map.import_scope())?,
+					None => return Err(Error::InvalidData(Some("Invalid ImplMap import scope"))),
+				};
+
+				imports.insert(
+					method.index(),
+					NativeImport {
+						method,
+						module: strings.get_string(module_ref.name()).to_string(),
+						entry_point: strings.get_string(impl_map.import_name()).to_string(),
+						flags: impl_map.mapping_flags(),
+						source: NativeImportSource::ImplMap,
+					},
+				);
+			}
+		}
+
+		let Some(attributes) = tables.get_table::<CustomAttributeTable>()? else {
+			return Ok(imports.into_values().collect());
+		};
+
+		let blobs = self.get_heap::<BlobHeap>()?.unwrap_or_else(BlobHeap::empty);
+		for attribute in attributes.iter() {
+			let attribute = attribute?;
+			let Some(method) = attribute.parent().decode(CodedIndexKind::HasCustomAttribute) else { continue };
+			if method.token_kind() != Some(MetadataTokenKind::Method) || imports.contains_key(&method.index()) {
+				continue;
+			}
+
+			let Some(constructor) = attribute.type_().decode(CodedIndexKind::CustomAttributeType) else {
+				continue;
+			};
+			if type_name(&tables, &strings, attribute_type(&tables, constructor)?)? != DLL_IMPORT_ATTRIBUTE {
+				continue;
+			}
+
+			// The only enums DllImportAttribute uses are CharSet and CallingConvention, both backed by an int.
+			let blob = blobs.get_blob(attribute.value())?;
+			let arguments = AttributeArguments::decode(blob, &[TypeSignature::String], |_| Some(TypeSignature::Int32));
+			let Some(AttributeArgument::Value(AttributeValue::String(Some(module)))) = arguments.fixed.first() else {
+				continue;
+			};
+
+			let definition = methods.get(TableIndex(method.index() as u32))?;
+			let mut import = NativeImport {
+				method,
+				module: module.clone(),
+				entry_point: strings.get_string(definition.name()).to_string(),
+				flags: pinvoke_attributes::CALL_CONV_PLATFORMAPI,
+				source: NativeImportSource::Attribute,
+			};
+
+			for named in arguments.named.iter().filter(|named| !named.is_property) {
+				let AttributeArgument::Value(value) = &named.value else { continue };
+				apply_named_argument(&mut import, &named.name, value);
+			}
+
+			imports.insert(method.index(), import);
+		}
+
+		Ok(imports.into_values().collect())
+	}
+}
+
+fn apply_named_argument(import: &mut NativeImport, name: &str, value: &AttributeValue) {
+	use pinvoke_attributes::*;
+	let set = |flags: &mut PInvokeAttributes, mask: PInvokeAttributes, value: PInvokeAttributes| {
+		*flags = (*flags & !mask) | value;
+	};
+
+	let enabled = |value: &AttributeValue, enabled, disabled| match value {
+		AttributeValue::Bool(true) => enabled,
+		_ => disabled,
+	};
+
+	match (name, value) {
+		("EntryPoint", AttributeValue::String(Some(entry_point))) => import.entry_point = entry_point.clone(),
+		("ExactSpelling", value) => set(&mut import.flags, NO_MANGLE, enabled(value, NO_MANGLE, 0)),
+		("SetLastError", value) => set(&mut import.flags, SUPPORTS_LAST_ERROR, enabled(value, SUPPORTS_LAST_ERROR, 0)),
+		("BestFitMapping", value) => {
+			let flags = enabled(value, BEST_FIT_ENABLED, BEST_FIT_DISABLED);
+			set(&mut import.flags, BEST_FIT_MASK, flags);
+		}
+		("ThrowOnUnmappableChar", value) => {
+			let flags = enabled(value, THROW_ON_UNMAPPABLE_CHAR_ENABLED, THROW_ON_UNMAPPABLE_CHAR_DISABLED);
+			set(&mut import.flags, THROW_ON_UNMAPPABLE_CHAR_MASK, flags);
+		}
+		("CharSet", AttributeValue::Enum(value)) => {
+			let flags = match **value {
+				AttributeValue::Int32(2) => CHAR_SET_ANSI,
+				AttributeValue::Int32(3) => CHAR_SET_UNICODE,
+				AttributeValue::Int32(4) => CHAR_SET_AUTO,
+				_ => CHAR_SET_NOT_SPEC,
+			};
+			set(&mut import.flags, CHAR_SET_MASK, flags);
+		}
+		("CallingConvention", AttributeValue::Enum(value)) => {
+			let flags = match **value {
+				AttributeValue::Int32(2) => CALL_CONV_CDECL,
+				AttributeValue::Int32(3) => CALL_CONV_STDCALL,
+				AttributeValue::Int32(4) => CALL_CONV_THISCALL,
+				AttributeValue::Int32(5) => CALL_CONV_FASTCALL,
+				_ => CALL_CONV_PLATFORMAPI,
+			};
+			set(&mut import.flags, CALL_CONV_MASK, flags);
+		}
+		_ => {}
+	}
+}
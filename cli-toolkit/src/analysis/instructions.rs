@@ -0,0 +1,119 @@
+use crate::raw::*;
+
+/// Receives the instructions of a method body, grouped by [`OpCodeFamily`].
+///
+/// Every method has an empty default, so that analyses only implement the families they care about:
+///
+/// ```ignore
+/// struct CallCounter(usize);
+/// impl InstructionVisitor for CallCounter {
+///     fn visit_call(&mut self, _: &Instruction, _: MetadataToken) {
+///         self.0 += 1;
+///     }
+/// }
+///
+/// body.visit(&mut counter)?;
+/// ```
+pub trait InstructionVisitor {
+	/// Called for every instruction before the method of its family.
+	fn visit_instruction(&mut self, instruction: &Instruction) {}
+
+	fn visit_load(&mut self, instruction: &Instruction) {}
+
+	fn visit_store(&mut self, instruction: &Instruction) {}
+
+	fn visit_branch(&mut self, instruction: &Instruction, targets: &[u32]) {}
+
+	/// `method` is the MethodDef, MemberRef or MethodSpec of the callee, or the StandAloneSig of `calli`.
+	fn visit_call(&mut self, instruction: &Instruction, method: MetadataToken) {}
+
+	fn visit_other(&mut self, instruction: &Instruction) {}
+}
+
+impl IlBody<'_> {
+	/// Decodes the instructions of the body, passing each one to `visitor`.
+	/// Stops at the first instruction that cannot be decoded, after visiting the ones before it.
+	pub fn visit(&self, visitor: &mut impl InstructionVisitor) -> Result<(), Error> {
+		for instruction in self.instructions() {
+			visit_instruction(&instruction?, visitor);
+		}
+
+		Ok(())
+	}
+}
+
+pub fn visit_instruction(instruction: &Instruction, visitor: &mut impl InstructionVisitor) {
+	visitor.visit_instruction(instruction);
+	match instruction.opcode.family() {
+		OpCodeFamily::Load => visitor.visit_load(instruction),
+		OpCodeFamily::Store => visitor.visit_store(instruction),
+		OpCodeFamily::Branch => visitor.visit_branch(instruction, instruction.branch_targets()),
+		OpCodeFamily::Call => match instruction.token() {
+			Some(method) => visitor.visit_call(instruction, method),
+			None => visitor.visit_other(instruction),
+		},
+		OpCodeFamily::Other => visitor.visit_other(instruction),
+	}
+}
+
+/// A string literal passed straight to a method, as in `Type.GetType("Name")` or `Log("message")`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct StringCall {
+	/// The offset of the `ldstr` instruction.
+	pub offset: u32,
+	/// The user string token of the literal.
+	pub string: MetadataToken,
+	pub call: OpCode,
+	pub method: MetadataToken,
+}
+
+/// A static field tested before running initialization code, as in `if (instance == null) { ... }`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct InitGuard {
+	/// The offset of the `ldsfld` instruction.
+	pub offset: u32,
+	pub field: MetadataToken,
+	/// Whether the branch is taken when the field is set, i.e. the guarded code runs when it is not.
+	pub branch_if_set: bool,
+	pub target: u32,
+}
+
+/// Every `ldstr` immediately followed by a `call`, `callvirt` or `newobj`.
+pub fn string_calls(instructions: &[Instruction]) -> impl Iterator<Item = StringCall> + '_ {
+	instructions.windows(2).filter_map(|pair| {
+		let [ldstr, call] = pair else { return None };
+		if ldstr.opcode != OpCode::Ldstr || !matches!(call.opcode, OpCode::Call | OpCode::Callvirt | OpCode::Newobj) {
+			return None;
+		}
+
+		Some(StringCall {
+			offset: ldstr.offset,
+			string: ldstr.token()?,
+			call: call.opcode,
+			method: call.token()?,
+		})
+	})
+}
+
+/// Every `ldsfld` immediately followed by a `brtrue` or `brfalse`, in their short forms as well.
+pub fn init_guards(instructions: &[Instruction]) -> impl Iterator<Item = InitGuard> + '_ {
+	instructions.windows(2).filter_map(|pair| {
+		let [ldsfld, branch] = pair else { return None };
+		let branch_if_set = match branch.opcode {
+			OpCode::Brtrue | OpCode::BrtrueS => true,
+			OpCode::Brfalse | OpCode::BrfalseS => false,
+			_ => return None,
+		};
+
+		if ldsfld.opcode != OpCode::Ldsfld {
+			return None;
+		}
+
+		Some(InitGuard {
+			offset: ldsfld.offset,
+			field: ldsfld.token()?,
+			branch_if_set,
+			target: *branch.branch_targets().first()?,
+		})
+	})
+}
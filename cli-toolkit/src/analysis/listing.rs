@@ -0,0 +1,93 @@
+use std::fmt::{Display, Formatter};
+use crate::analysis::MethodBodyLocation;
+use crate::raw::*;
+
+/// The IL of a method body as text, in the layout of ILSpy and `ildasm`, see [`Assembly::method_listing`].
+///
+/// Annotated listings mark the method with its token, RVA and file offset, and tokens in the code are rendered
+/// as `/* 0A000001 */` comments, so that they can be diffed against the output of those tools:
+///
+/// ```text
+/// .method /* 06000001 */
+/// {
+///     // Method begins at RVA 0x2050
+///     // File offset 0x250
+///     // Header size: 1
+///     // Code size: 7 (0x7)
+///     .maxstack 8
+///
+///     IL_0000: ldarg.0
+///     IL_0001: call /* 0A000001 */
+///     IL_0006: ret
+/// }
+/// ```
+#[derive(Debug, Copy, Clone)]
+pub struct MethodListing<'l> {
+	location: MethodBodyLocation,
+	body: IlBody<'l>,
+	annotated: bool,
+}
+
+impl MethodListing<'_> {
+	pub fn annotated(mut self, annotated: bool) -> Self {
+		self.annotated = annotated;
+		self
+	}
+
+	pub fn location(&self) -> MethodBodyLocation {
+		self.location
+	}
+}
+
+impl Display for MethodListing<'_> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		let header = self.body.header();
+		match self.annotated {
+			true => writeln!(f, ".method /* {:08X} */", self.location.token)?,
+			false => writeln!(f, ".method {:?}", self.location.token)?,
+		}
+
+		writeln!(f, "{{")?;
+		if self.annotated {
+			writeln!(f, "\t// Method begins at RVA 0x{:x}", self.location.rva)?;
+			writeln!(f, "\t// File offset 0x{:x}", self.location.offset)?;
+			writeln!(f, "\t// Header size: {}", header.header_size)?;
+			writeln!(f, "\t// Code size: {} (0x{:x})", header.code_size, header.code_size)?;
+		}
+
+		writeln!(f, "\t.maxstack {}", header.max_stack)?;
+		if !header.local_var_sig.is_null() {
+			let init = if header.init_locals { " init" } else { "" };
+			match self.annotated {
+				true => writeln!(f, "\t.locals{} /* {:08X} */", init, header.local_var_sig)?,
+				false => writeln!(f, "\t.locals{} {:?}", init, header.local_var_sig)?,
+			}
+		}
+
+		writeln!(f)?;
+		for instruction in self.body.instructions() {
+			match instruction {
+				Ok(instruction) if self.annotated => writeln!(f, "\t{:#}", instruction)?,
+				Ok(instruction) => writeln!(f, "\t{}", instruction)?,
+				Err(error) => {
+					writeln!(f, "\t// {:?}", error)?;
+					break;
+				}
+			}
+		}
+
+		writeln!(f, "}}")
+	}
+}
+
+impl<'l> Assembly<'l> {
+	/// The IL of a method returned by [`Assembly::methods_with_bodies`] as text.
+	/// Instructions that cannot be decoded end the listing with a comment describing the error.
+	pub fn method_listing(&self, location: &MethodBodyLocation) -> Result<MethodListing<'l>, Error> {
+		Ok(MethodListing {
+			location: *location,
+			body: self.method_il(location)?,
+			annotated: false,
+		})
+	}
+}
@@ -0,0 +1,29 @@
+mod attributes;
+mod blobs;
+mod bodies;
+mod build;
+mod dependencies;
+mod facade;
+mod histogram;
+mod imports;
+mod instructions;
+mod listing;
+mod security;
+mod sizes;
+mod trivial;
+mod validation;
+
+pub use facade::*;
+pub use histogram::*;
+pub use validation::*;
+pub use attributes::*;
+pub use blobs::*;
+pub use bodies::*;
+pub use build::*;
+pub use dependencies::*;
+pub use imports::*;
+pub use instructions::*;
+pub use listing::*;
+pub use security::*;
+pub use sizes::*;
+pub use trivial::*;
@@ -0,0 +1,198 @@
+use std::collections::HashMap;
+use crate::raw::*;
+
+const SECURITY_CRITICAL: &str = "System.Security.SecurityCriticalAttribute";
+const SECURITY_SAFE_CRITICAL: &str = "System.Security.SecuritySafeCriticalAttribute";
+const SECURITY_TRANSPARENT: &str = "System.Security.SecurityTransparentAttribute";
+const SECURITY_RULES: &str = "System.Security.SecurityRulesAttribute";
+const ALLOW_PARTIALLY_TRUSTED_CALLERS: &str = "System.Security.AllowPartiallyTrustedCallersAttribute";
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum SecurityTransparency {
+	Transparent,
+	SafeCritical,
+	Critical,
+}
+
+/// The transparency of a type or method, and the declarative security the DeclSecurity table attaches to it.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct TransparencyInfo {
+	pub token: MetadataToken,
+	pub transparency: SecurityTransparency,
+	/// Whether the transparency comes from an attribute applied to the item itself, rather than from its container.
+	pub is_explicit: bool,
+	pub declarative_actions: Vec<SecurityAction>,
+}
+
+/// The security transparency of every type and method of an assembly, following the level 2 rules of
+/// the .NET Framework 4 security model.
+///
+/// Code of assemblies without annotations, or with an assembly level `SecurityCritical`, is critical
+/// unless marked otherwise. `SecurityTransparent` on the assembly makes every type and method transparent,
+/// and `AllowPartiallyTrustedCallers` makes unannotated code transparent.
+/// Critical types make their members and nested types critical, whatever the attributes of those.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct SecurityReport {
+	/// The rule set of the `SecurityRulesAttribute` of the assembly, 1 or 2.
+	pub rule_set: Option<u8>,
+	/// Whether the assembly is marked `SecurityTransparent`.
+	pub is_transparent: bool,
+	pub allows_partially_trusted_callers: bool,
+	/// The declarative security applied to the assembly itself.
+	pub declarative_actions: Vec<SecurityAction>,
+	/// Every type definition, in token order.
+	pub types: Vec<TransparencyInfo>,
+	/// Every method definition, in token order.
+	pub methods: Vec<TransparencyInfo>,
+}
+
+impl SecurityReport {
+	pub fn type_transparency(&self, token: MetadataToken) -> Option<&TransparencyInfo> {
+		self.types.get(token.index().checked_sub(1)?)
+	}
+
+	pub fn method_transparency(&self, token: MetadataToken) -> Option<&TransparencyInfo> {
+		self.methods.get(token.index().checked_sub(1)?)
+	}
+}
+
+impl<'l> Assembly<'l> {
+	pub fn security_report(&self) -> Result<SecurityReport, Error> {
+		let mut report = SecurityReport::default();
+		let Some(tables) = self.get_heap::<TableHeap>()? else { return Ok(report) };
+		let strings = self.get_heap::<StringHeap>()?.unwrap_or_else(StringHeap::empty);
+		let blobs = self.get_heap::<BlobHeap>()?.unwrap_or_else(BlobHeap::empty);
+
+		let mut annotations = HashMap::<MetadataToken, SecurityTransparency>::new();
+		let mut assembly_critical = false;
+		if let Some(attributes) = tables.get_table::<CustomAttributeTable>()? {
+			for attribute in attributes.iter() {
+				let attribute = attribute?;
+				let Some(parent) = attribute.parent().decode(CodedIndexKind::HasCustomAttribute) else { continue };
+				let Some(constructor) = attribute.type_().decode(CodedIndexKind::CustomAttributeType) else {
+					continue;
+				};
+
+				let is_assembly = parent.token_kind() == Some(MetadataTokenKind::Assembly);
+				let transparency = match type_name(&tables, &strings, attribute_type(&tables, constructor)?)?.as_str() {
+					SECURITY_CRITICAL => SecurityTransparency::Critical,
+					SECURITY_SAFE_CRITICAL => SecurityTransparency::SafeCritical,
+					SECURITY_TRANSPARENT if is_assembly => {
+						report.is_transparent = true;
+						continue;
+					}
+					ALLOW_PARTIALLY_TRUSTED_CALLERS if is_assembly => {
+						report.allows_partially_trusted_callers = true;
+						continue;
+					}
+					SECURITY_RULES if is_assembly => {
+						// The prolog, followed by the SecurityRuleSet enum, which is backed by a byte.
+						report.rule_set = blobs.get_blob(attribute.value())?.get(2).copied();
+						continue;
+					}
+					_ => continue,
+				};
+
+				match is_assembly {
+					true => assembly_critical |= transparency == SecurityTransparency::Critical,
+					false => {
+						annotations.insert(parent, transparency);
+					}
+				}
+			}
+		}
+
+		let mut declarative = HashMap::<MetadataToken, Vec<SecurityAction>>::new();
+		if let Some(decl_security) = tables.get_table::<DeclSecurityTable>()? {
+			for row in decl_security.iter() {
+				let row = row?;
+				let Some(parent) = row.parent().decode(CodedIndexKind::HasDeclSecurity) else { continue };
+				declarative.entry(parent).or_default().push(row.action());
+			}
+		}
+
+		let assembly = MetadataToken::new(1, MetadataTokenKind::Assembly);
+		report.declarative_actions = declarative.remove(&assembly).unwrap_or_default();
+
+		let default = match report.is_transparent {
+			true => SecurityTransparency::Transparent,
+			false if report.allows_partially_trusted_callers && !assembly_critical => SecurityTransparency::Transparent,
+			false => SecurityTransparency::Critical,
+		};
+
+		if report.is_transparent {
+			annotations.clear();
+		}
+
+		let mut info = |token: MetadataToken, container: Option<SecurityTransparency>| {
+			let annotation = annotations.get(&token).copied();
+			TransparencyInfo {
+				token,
+				transparency: inherit(container.unwrap_or(default), container.is_some(), annotation),
+				is_explicit: annotation.is_some() && container != Some(SecurityTransparency::Critical),
+				declarative_actions: declarative.remove(&token).unwrap_or_default(),
+			}
+		};
+
+		let Some(type_defs) = tables.get_table::<TypeDefTable>()? else { return Ok(report) };
+		let mut enclosing = HashMap::new();
+		if let Some(nested_classes) = tables.get_table::<NestedClassTable>()? {
+			for row in nested_classes.iter() {
+				let row = row?;
+				enclosing.insert(row.nested_class().0, row.enclosing_class().0);
+			}
+		}
+
+		for index in 1..=type_defs.len() as u32 {
+			let mut chain = vec![];
+			let mut current = index;
+			while let Some(parent) = enclosing.get(&current) {
+				if *parent == index || chain.contains(parent) {
+					break;
+				}
+				chain.push(*parent);
+				current = *parent;
+			}
+
+			let container = chain.iter().rev().fold(None, |container, parent| {
+				let token = MetadataToken::new(*parent, MetadataTokenKind::TypeDef);
+				let annotation = annotations.get(&token).copied();
+				Some(inherit(container.unwrap_or(default), container.is_some(), annotation))
+			});
+
+			report
+				.types
+				.push(info(MetadataToken::new(index, MetadataTokenKind::TypeDef), container));
+		}
+
+		let Some(methods) = tables.get_table::<MethodDefTable>()? else { return Ok(report) };
+		let method_lists = type_defs
+			.iter()
+			.map(|def| def.map(|def| def.methods().0))
+			.collect::<Result<Vec<_>, _>>()?;
+
+		for index in 1..=methods.len() as u32 {
+			let owner = method_lists.partition_point(|start| *start <= index);
+			let container = owner.checked_sub(1).map(|owner| report.types[owner].transparency);
+
+			report
+				.methods
+				.push(info(MetadataToken::new(index, MetadataTokenKind::Method), container));
+		}
+
+		Ok(report)
+	}
+}
+
+/// The transparency of an item, given the one of the type or assembly containing it and its own attribute, if any.
+/// Only critical types override the attributes of their members, the default of the assembly does not.
+fn inherit(
+	container: SecurityTransparency,
+	is_type: bool,
+	annotation: Option<SecurityTransparency>,
+) -> SecurityTransparency {
+	match container {
+		SecurityTransparency::Critical if is_type => SecurityTransparency::Critical,
+		_ => annotation.unwrap_or(container),
+	}
+}
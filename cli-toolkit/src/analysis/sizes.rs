@@ -0,0 +1,181 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::ops::Range;
+use crate::analysis::MethodBodyLocation;
+use crate::raw::*;
+use crate::schema::MAX_NESTING;
+
+/// The bytes taken by the body of a method, as returned by [`Assembly::method_size_map`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct MethodSize {
+	pub location: MethodBodyLocation,
+	pub header: usize,
+	pub code: usize,
+	/// The extra data sections holding the exception handling clauses, including the padding before them.
+	pub exception_handling: usize,
+	/// Whether the body is also used by a method with a lower token, which it is attributed to instead.
+	pub is_shared: bool,
+}
+
+impl MethodSize {
+	pub fn total(&self) -> usize {
+		self.header + self.code + self.exception_handling
+	}
+
+	/// The bytes of the body in the file.
+	pub fn region(&self) -> Range<usize> {
+		self.location.offset..self.location.offset + self.total()
+	}
+}
+
+/// The method bodies attributed to a type or namespace, not counting shared bodies more than once.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct SizeTotals {
+	pub methods: usize,
+	pub bytes: usize,
+}
+
+impl SizeTotals {
+	fn add(&mut self, method: &MethodSize) {
+		if !method.is_shared {
+			self.methods += 1;
+			self.bytes += method.total();
+		}
+	}
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct TypeSize {
+	pub token: MetadataToken,
+	/// The full name of the type, with nested types separated from their enclosing type by a `/`.
+	pub name: String,
+	pub totals: SizeTotals,
+}
+
+/// Where the method bodies of an assembly take their bytes, by method, type and namespace.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct MethodSizeMap {
+	methods: Vec<MethodSize>,
+	types: Vec<TypeSize>,
+	namespaces: BTreeMap<String, SizeTotals>,
+	totals: SizeTotals,
+}
+
+impl MethodSizeMap {
+	/// Every method with a body, in token order.
+	pub fn methods(&self) -> &[MethodSize] {
+		&self.methods
+	}
+
+	/// Every type with at least one method body, in token order.
+	pub fn types(&self) -> &[TypeSize] {
+		&self.types
+	}
+
+	/// The totals of every namespace, where nested types count towards the namespace of their outermost type.
+	pub fn namespaces(&self) -> impl Iterator<Item = (&str, SizeTotals)> + '_ {
+		self.namespaces
+			.iter()
+			.map(|(namespace, totals)| (namespace.as_str(), *totals))
+	}
+
+	pub fn totals(&self) -> SizeTotals {
+		self.totals
+	}
+
+	/// The `count` types taking the most bytes, largest first.
+	pub fn largest_types(&self, count: usize) -> Vec<&TypeSize> {
+		let mut types = self.types.iter().collect::<Vec<_>>();
+		types.sort_by(|a, b| b.totals.bytes.cmp(&a.totals.bytes).then(a.token.0.cmp(&b.token.0)));
+		types.truncate(count);
+		types
+	}
+}
+
+impl<'l> Assembly<'l> {
+	/// Measures the header, code and exception handling sections of every method body,
+	/// and sums them up by type and namespace.
+	/// Only the headers of the bodies and of their extra data sections are parsed, not their instructions.
+	pub fn method_size_map(&self) -> Result<MethodSizeMap, Error> {
+		let mut map = MethodSizeMap::default();
+		let mut rvas = HashSet::new();
+		for location in self.methods_with_bodies()? {
+			let body = self.method_il(&location)?;
+			let header = body.header().header_size as usize;
+			let code = body.code().len();
+			let method = MethodSize {
+				location,
+				header,
+				code,
+				exception_handling: body.size()? - header - code,
+				is_shared: !rvas.insert(location.rva),
+			};
+
+			map.totals.add(&method);
+			map.methods.push(method);
+		}
+
+		let Some(tables) = self.get_heap::<TableHeap>()? else { return Ok(map) };
+		let Some(type_defs) = tables.get_table::<TypeDefTable>()? else { return Ok(map) };
+		let strings = self.get_heap::<StringHeap>()?.unwrap_or_else(StringHeap::empty);
+
+		let mut method_lists = Vec::with_capacity(type_defs.len());
+		for type_def in type_defs.iter() {
+			method_lists.push(type_def?.methods().0);
+		}
+
+		let mut enclosing = HashMap::new();
+		if let Some(nested_classes) = tables.get_table::<NestedClassTable>()? {
+			for nested_class in nested_classes.iter() {
+				let nested_class = nested_class?;
+				enclosing.insert(nested_class.nested_class().0, nested_class.enclosing_class().0);
+			}
+		}
+
+		let mut types = BTreeMap::<u32, SizeTotals>::new();
+		for method in map.methods.iter() {
+			// The owner is the last type whose method list starts at or before the method.
+			let index = method.location.token.index() as u32;
+			let owner = method_lists.partition_point(|start| *start <= index);
+			if owner != 0 {
+				types.entry(owner as u32).or_default().add(method);
+			}
+		}
+
+		for (index, totals) in types {
+			let mut name = String::new();
+			let mut namespace = "";
+			let mut current = index;
+			for _ in 0..MAX_NESTING {
+				let type_def = type_defs.get(TableIndex(current))?;
+				let type_name = strings.get_string(type_def.name());
+				name = match name.is_empty() {
+					true => type_name.to_string(),
+					false => format!("{}/{}", type_name, name),
+				};
+
+				match enclosing.get(&current) {
+					Some(outer) => current = *outer,
+					None => {
+						namespace = strings.get_string(type_def.namespace());
+						break;
+					}
+				}
+			}
+
+			if !namespace.is_empty() {
+				name = format!("{}.{}", namespace, name);
+			}
+
+			let entry = map.namespaces.entry(namespace.to_string()).or_default();
+			entry.methods += totals.methods;
+			entry.bytes += totals.bytes;
+			map.types.push(TypeSize {
+				token: MetadataToken::new(index, MetadataTokenKind::TypeDef),
+				name,
+				totals,
+			});
+		}
+
+		Ok(map)
+	}
+}
@@ -0,0 +1,141 @@
+use crate::raw::*;
+
+/// A constant returned by a [`MethodBodyKind::Constant`] body.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ConstantValue {
+	Null,
+	/// Also used for `bool` and `char`, which are loaded as 32 bit integers.
+	Int32(i32),
+	Int64(i64),
+	Float32(f32),
+	Float64(f64),
+	/// The user string token of a string literal.
+	String(MetadataToken),
+}
+
+/// What a method body does, for bodies simple enough to be described without decompiling them.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum MethodBodyKind {
+	/// Returns without doing anything.
+	Empty,
+	/// Returns a constant, as in `=> 42` or `const`-like properties.
+	Constant(ConstantValue),
+	/// Returns a field of `this`, like the getter of an auto-property.
+	TrivialGetter(MetadataToken),
+	/// Returns a static field.
+	StaticGetter(MetadataToken),
+	/// Stores its argument into a field of `this`, like the setter of an auto-property.
+	TrivialSetter(MetadataToken),
+	/// Stores its argument into a static field.
+	StaticSetter(MetadataToken),
+	/// Passes `this` to another constructor and returns, like the default constructor of a class.
+	/// Only meaningful for instance constructors, where the callee is the base constructor or a `this(...)` overload.
+	DefaultConstructor(MetadataToken),
+	/// Anything else.
+	Other,
+}
+
+impl MethodBodyKind {
+	pub fn is_trivial(&self) -> bool {
+		*self != MethodBodyKind::Other
+	}
+}
+
+/// Classifies the instructions of a method body.
+///
+/// `nop`s are ignored, and so is the `stloc.0; br.s; ldloc.0` sequence debug builds return values through,
+/// so that the bodies of both debug and release builds are recognized.
+pub fn classify_body(instructions: &[Instruction]) -> MethodBodyKind {
+	let mut opcodes = instructions
+		.iter()
+		.filter(|instruction| instruction.opcode != OpCode::Nop)
+		.collect::<Vec<_>>();
+
+	if let [.., stloc, branch, ldloc, ret] = opcodes[..] {
+		let stores_result = stloc.opcode == OpCode::Stloc0
+			&& matches!(branch.opcode, OpCode::Br | OpCode::BrS)
+			&& branch.branch_targets() == [ldloc.offset]
+			&& ldloc.opcode == OpCode::Ldloc0
+			&& ret.opcode == OpCode::Ret;
+
+		if stores_result {
+			let len = opcodes.len();
+			opcodes.drain(len - 4..len - 1);
+		}
+	}
+
+	let token = |instruction: &Instruction| instruction.token().unwrap_or(MetadataToken(0));
+	match opcodes[..] {
+		[ret] if ret.opcode == OpCode::Ret => MethodBodyKind::Empty,
+		[load, ret] if ret.opcode == OpCode::Ret => match (load.opcode, constant(load)) {
+			(_, Some(value)) => MethodBodyKind::Constant(value),
+			(OpCode::Ldsfld, _) => MethodBodyKind::StaticGetter(token(load)),
+			_ => MethodBodyKind::Other,
+		},
+		[this, load, ret] if this.opcode == OpCode::Ldarg0 && ret.opcode == OpCode::Ret => match load.opcode {
+			OpCode::Ldfld => MethodBodyKind::TrivialGetter(token(load)),
+			OpCode::Stsfld => MethodBodyKind::StaticSetter(token(load)),
+			OpCode::Call => MethodBodyKind::DefaultConstructor(token(load)),
+			_ => MethodBodyKind::Other,
+		},
+		[this, value, store, ret] => match (this.opcode, value.opcode, store.opcode, ret.opcode) {
+			(OpCode::Ldarg0, OpCode::Ldarg1, OpCode::Stfld, OpCode::Ret) => MethodBodyKind::TrivialSetter(token(store)),
+			_ => MethodBodyKind::Other,
+		},
+		_ => MethodBodyKind::Other,
+	}
+}
+
+fn constant(instruction: &Instruction) -> Option<ConstantValue> {
+	let value = match (instruction.opcode, &instruction.operand) {
+		(OpCode::Ldnull, _) => ConstantValue::Null,
+		(OpCode::LdcI4M1, _) => ConstantValue::Int32(-1),
+		(OpCode::LdcI40, _) => ConstantValue::Int32(0),
+		(OpCode::LdcI41, _) => ConstantValue::Int32(1),
+		(OpCode::LdcI42, _) => ConstantValue::Int32(2),
+		(OpCode::LdcI43, _) => ConstantValue::Int32(3),
+		(OpCode::LdcI44, _) => ConstantValue::Int32(4),
+		(OpCode::LdcI45, _) => ConstantValue::Int32(5),
+		(OpCode::LdcI46, _) => ConstantValue::Int32(6),
+		(OpCode::LdcI47, _) => ConstantValue::Int32(7),
+		(OpCode::LdcI48, _) => ConstantValue::Int32(8),
+		(OpCode::LdcI4S, Operand::Int8(value)) => ConstantValue::Int32(*value as i32),
+		(OpCode::LdcI4, Operand::Int32(value)) => ConstantValue::Int32(*value),
+		(OpCode::LdcI8, Operand::Int64(value)) => ConstantValue::Int64(*value),
+		(OpCode::LdcR4, Operand::Float32(value)) => ConstantValue::Float32(*value),
+		(OpCode::LdcR8, Operand::Float64(value)) => ConstantValue::Float64(*value),
+		(OpCode::Ldstr, Operand::Token(token)) => ConstantValue::String(*token),
+		_ => return None,
+	};
+
+	Some(value)
+}
+
+impl IlBody<'_> {
+	/// Classifies the body, see [`classify_body`].
+	/// Bodies with exception handling clauses are never trivial.
+	pub fn kind(&self) -> Result<MethodBodyKind, Error> {
+		if !self.exception_clauses()?.is_empty() {
+			return Ok(MethodBodyKind::Other);
+		}
+
+		let instructions = self.instructions().collect::<Result<Vec<_>, _>>()?;
+		Ok(classify_body(&instructions))
+	}
+}
+
+impl<'l> Assembly<'l> {
+	/// The methods whose body is trivial, in token order.
+	/// Bodies that cannot be decoded are skipped.
+	pub fn trivial_methods(&self) -> Result<Vec<(MetadataToken, MethodBodyKind)>, Error> {
+		let mut methods = vec![];
+		for location in self.methods_with_bodies()? {
+			let Ok(kind) = self.method_il(&location).and_then(|body| body.kind()) else { continue };
+			if kind.is_trivial() {
+				methods.push((location.token, kind));
+			}
+		}
+
+		Ok(methods)
+	}
+}
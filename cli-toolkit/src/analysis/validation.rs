@@ -0,0 +1,154 @@
+use strum::IntoEnumIterator;
+use crate::raw::*;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ValidationIssue {
+	pub token: MetadataToken,
+	/// The position of the offending column among the row's [`MetadataRow::indices`].
+	pub column: usize,
+	pub message: &'static str,
+}
+
+/// Checks that the heap, table and coded indices stored in metadata rows point at existing data.
+/// Validation can be scoped to a single row or table, so tools can re-check only what changed.
+pub struct Validator<'l> {
+	tables: TableHeap<'l>,
+	strings: StringHeap<'l>,
+	guids: GuidHeap<'l>,
+	blobs: BlobHeap<'l>,
+}
+
+impl<'l> Validator<'l> {
+	pub fn new(assembly: &Assembly<'l>) -> Result<Self, Error> {
+		Ok(Self {
+			tables: assembly
+				.get_heap::<TableHeap>()?
+				.ok_or(Error::InvalidData(Some("Missing table heap")))?,
+			strings: assembly.get_heap::<StringHeap>()?.unwrap_or_else(StringHeap::empty),
+			guids: assembly.get_heap::<GuidHeap>()?.unwrap_or_else(GuidHeap::empty),
+			blobs: assembly.get_heap::<BlobHeap>()?.unwrap_or_else(BlobHeap::empty),
+		})
+	}
+
+	pub fn validate_all(&self) -> Result<Vec<ValidationIssue>, Error> {
+		let mut issues = vec![];
+		for kind in self.tables.valid_mask().iter() {
+			issues.extend(self.validate_table_kind(kind)?);
+		}
+		Ok(issues)
+	}
+
+	pub fn validate_table<T: MetadataTable<'l>>(&self) -> Result<Vec<ValidationIssue>, Error>
+	where
+		T::Row: MetadataRow,
+	{
+		let mut issues = vec![];
+		let Some(table) = self.tables.get_table::<T>()? else { return Ok(issues) };
+
+		for (index, row) in table.iter().enumerate() {
+			let index = TableIndex(index as u32 + 1);
+			self.validate_row(T::cli_identifier(), index, &row?, &mut issues)?;
+		}
+
+		Ok(issues)
+	}
+
+	pub fn validate_token(&self, token: MetadataToken) -> Result<Vec<ValidationIssue>, Error> {
+		let kind = token
+			.table_kind()
+			.ok_or(Error::InvalidData(Some("Invalid metadata token")))?;
+		let index = TableIndex(token.index() as u32);
+		dispatch_table!(
+			kind,
+			self.validate_table_row(index),
+			Err(Error::InvalidData(Some("Unsupported metadata table")))
+		)
+	}
+
+	fn validate_table_kind(&self, kind: TableKind) -> Result<Vec<ValidationIssue>, Error> {
+		dispatch_table!(kind, self.validate_table(), Ok(vec![]))
+	}
+
+	fn validate_table_row<T: MetadataTable<'l>>(&self, index: TableIndex) -> Result<Vec<ValidationIssue>, Error>
+	where
+		T::Row: MetadataRow,
+	{
+		let table = self
+			.tables
+			.get_table::<T>()?
+			.ok_or(Error::InvalidData(Some("Missing metadata table")))?;
+
+		if index.0 == 0 || index.0 as usize > table.len() {
+			return Err(Error::OffsetOutOfBounds);
+		}
+
+		let mut issues = vec![];
+		self.validate_row(T::cli_identifier(), index, &table.get(index)?, &mut issues)?;
+		Ok(issues)
+	}
+
+	fn validate_row(
+		&self,
+		kind: TableKind,
+		index: TableIndex,
+		row: &impl MetadataRow,
+		issues: &mut Vec<ValidationIssue>,
+	) -> Result<(), Error> {
+		let token = MetadataToken::from_table_row(kind, index);
+		for (column, value) in row.indices().into_iter().enumerate() {
+			let message = match value {
+				RowIndex::Heap(HeapKind::String, index) => {
+					match index.0 == 0 || (index.0 as usize) < self.strings.len() {
+						true => continue,
+						false => "String heap index out of bounds",
+					}
+				}
+
+				RowIndex::Heap(HeapKind::Guid, index) => match index.0 as usize <= self.guids.len() {
+					true => continue,
+					false => "Guid heap index out of bounds",
+				},
+
+				RowIndex::Heap(HeapKind::Blob, index) => match index.0 == 0 || self.blobs.get_blob(index).is_ok() {
+					true => continue,
+					false => "Invalid blob heap index",
+				},
+
+				// List columns may point one past the end of their target table.
+				RowIndex::Table(target, index) => {
+					let len = self.tables.row_count(target)? + is_list_column(kind, target) as usize;
+					match index.0 != 0 && index.0 as usize <= len {
+						true => continue,
+						false => "Table index out of bounds",
+					}
+				}
+
+				RowIndex::Coded(kind, index) => match index.decode(kind) {
+					None => "Invalid coded index tag",
+					Some(token) if token.is_null() => continue,
+					Some(token) => match token.table_kind() {
+						Some(kind) if token.index() <= self.tables.row_count(kind)? => continue,
+						_ => "Coded index out of bounds",
+					},
+				},
+			};
+
+			issues.push(ValidationIssue { token, column, message });
+		}
+
+		Ok(())
+	}
+}
+
+/// Whether the column of a `kind` row pointing into `target` starts a list running up to the next row's,
+/// such as the field list of a TypeDef. Only these may point one past the end of the table, for empty lists.
+fn is_list_column(kind: TableKind, target: TableKind) -> bool {
+	matches!(
+		(kind, target),
+		(TableKind::TypeDef, TableKind::Field)
+			| (TableKind::TypeDef, TableKind::MethodDef)
+			| (TableKind::MethodDef, TableKind::Param)
+			| (TableKind::EventMap, TableKind::Event)
+			| (TableKind::PropertyMap, TableKind::Property)
+	)
+}
@@ -0,0 +1,343 @@
+//! A command line front-end over the library, which doubles as an example of its API.
+//!
+//! ```text
+//! clir dump [options] <assembly>...          list the assemblies and the types they define
+//! clir deps [options] <assembly>...          list the references of the assemblies and how they resolved
+//! clir find [options] <pattern> <assembly>... find the types and members whose name matches the pattern
+//! clir api  [options] <assembly>...          print the public types and members of the assemblies
+//! clir il   [options] <assembly>...          print the IL of every method body of the assemblies
+//! ```
+//!
+//! Referenced assemblies are looked up next to the assemblies themselves, in the directories given with
+//! `--probe <directory>`, and in the framework of `--runtime <version>`. `--lenient` loads damaged images
+//! as far as possible and reports what was skipped, and `-o <file>` writes the output to a file.
+//! `--style <csharp|qualified|il>` chooses how type names are rendered, `qualified` C# by default.
+//! `--annotate` marks the output of `dump` and `il` with tokens, RVAs and file offsets in the format of ILSpy,
+//! so that it can be diffed against the output of established tools. `-h` or `--help` prints the usage.
+
+use cli_toolkit::raw::{type_flags, Assembly as RawAssembly};
+use cli_toolkit::read::{ContextBuilder, Error};
+use cli_toolkit::schema::{
+	matches_pattern, Assembly, Context, DumpWriter, Member, NameStyle, SignatureFormatter, Type, TypeData,
+};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::rc::Rc;
+
+const USAGE: &str = "usage: clir <dump|deps|find|api|il> [--probe <directory>] [--runtime <version>] [--lenient] \
+	[--style <csharp|qualified|il>] [--annotate] [-o <file>] [<pattern>] <assembly>...";
+
+type Output = DumpWriter<Box<dyn Write>>;
+
+#[derive(Debug)]
+enum Command {
+	Dump,
+	Deps,
+	Find(String),
+	Api,
+	Il,
+}
+
+#[derive(Debug)]
+struct Options {
+	command: Command,
+	assemblies: Vec<PathBuf>,
+	probe_directories: Vec<PathBuf>,
+	runtime: Option<String>,
+	lenient: bool,
+	style: NameStyle,
+	annotate: bool,
+	output: Option<PathBuf>,
+}
+
+impl Options {
+	fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+		let command = args.next().ok_or("missing command")?;
+		let mut options = Options {
+			command: Command::Dump,
+			assemblies: vec![],
+			probe_directories: vec![],
+			runtime: None,
+			lenient: false,
+			style: NameStyle::default(),
+			annotate: false,
+			output: None,
+		};
+
+		let mut positional = vec![];
+		while let Some(arg) = args.next() {
+			let mut value = |name: &str| args.next().ok_or(format!("missing value for {}", name));
+			match arg.as_str() {
+				"--probe" => options.probe_directories.push(value("--probe")?.into()),
+				"--runtime" => options.runtime = Some(value("--runtime")?),
+				"-o" | "--output" => options.output = Some(value("--output")?.into()),
+				"--lenient" => options.lenient = true,
+				"--annotate" => options.annotate = true,
+				"--style" => {
+					options.style = match value("--style")?.as_str() {
+						"csharp" => NameStyle::CSHARP,
+						"qualified" => NameStyle::CSHARP_QUALIFIED,
+						"il" => NameStyle::IL,
+						style => return Err(format!("unknown style {}", style)),
+					}
+				}
+				_ if arg.starts_with('-') => return Err(format!("unknown option {}", arg)),
+				_ => positional.push(arg),
+			}
+		}
+
+		options.command = match command.as_str() {
+			"dump" => Command::Dump,
+			"deps" => Command::Deps,
+			"find" if !positional.is_empty() => Command::Find(positional.remove(0)),
+			"find" => return Err("missing pattern".to_string()),
+			"api" => Command::Api,
+			"il" => Command::Il,
+			_ => return Err(format!("unknown command {}", command)),
+		};
+
+		options.assemblies = positional.into_iter().map(PathBuf::from).collect();
+		match options.assemblies.is_empty() {
+			true => Err("missing assembly".to_string()),
+			false => Ok(options),
+		}
+	}
+
+	fn load(&self) -> Result<Rc<Context>, Error> {
+		let mut builder = ContextBuilder::new().lenient(self.lenient);
+		if let Some(version) = &self.runtime {
+			builder = builder.with_dotnet_runtime(version)?;
+		}
+
+		for path in self.assemblies.iter() {
+			if let Some(directory) = path.parent().filter(|directory| !directory.as_os_str().is_empty()) {
+				builder = builder.probe_directory(directory);
+			}
+			builder = builder.assembly(path);
+		}
+
+		for directory in self.probe_directories.iter() {
+			builder = builder.probe_directory(directory);
+		}
+
+		let context = builder.build()?;
+		context.set_name_style(self.style);
+		Ok(context)
+	}
+
+	/// The assemblies named on the command line, leaving out the ones they reference.
+	fn roots<'l>(&self, context: &'l Context) -> impl Iterator<Item = &'l Rc<Assembly>> + 'l {
+		let paths = self.assemblies.clone();
+		context.assemblies().iter().filter(move |assembly| {
+			assembly
+				.path()
+				.is_some_and(|path| paths.iter().any(|root| root == path))
+		})
+	}
+}
+
+fn main() -> ExitCode {
+	let args = std::env::args().skip(1).collect::<Vec<_>>();
+	if args.iter().any(|arg| arg == "-h" || arg == "--help") {
+		println!("{}", USAGE);
+		return ExitCode::SUCCESS;
+	}
+
+	let options = match Options::parse(args.into_iter()) {
+		Ok(options) => options,
+		Err(error) => {
+			eprintln!("clir: {}\n{}", error, USAGE);
+			return ExitCode::from(2);
+		}
+	};
+
+	match run(&options) {
+		Ok(()) => ExitCode::SUCCESS,
+		Err(error) => {
+			eprintln!("clir: {}", error);
+			ExitCode::FAILURE
+		}
+	}
+}
+
+fn run(options: &Options) -> Result<(), Error> {
+	let context = options.load()?;
+	for diagnostic in context.load_diagnostics() {
+		eprintln!("warning: {}", diagnostic);
+	}
+
+	let output: Box<dyn Write> = match &options.output {
+		Some(path) => Box::new(std::fs::File::create(path)?),
+		None => Box::new(io::stdout().lock()),
+	};
+
+	let mut out = DumpWriter::new(output);
+	match &options.command {
+		Command::Dump => dump(options, &context, &mut out)?,
+		Command::Deps => deps(options, &context, &mut out)?,
+		Command::Find(pattern) => find(options, pattern, &context, &mut out)?,
+		Command::Api => api(options, &context, &mut out)?,
+		Command::Il => il(options, &context, &mut out)?,
+	}
+
+	out.finish()?;
+	Ok(())
+}
+
+fn dump(options: &Options, context: &Context, out: &mut Output) -> io::Result<()> {
+	for assembly in options.roots(context) {
+		out.write_display(&assembly.summary().annotated(options.annotate))?;
+	}
+	Ok(())
+}
+
+fn deps(options: &Options, context: &Context, out: &mut Output) -> io::Result<()> {
+	for assembly in options.roots(context) {
+		writeln!(out, "{}", assembly)?;
+		let Some(path) = assembly.path() else { continue };
+
+		// The usage of every reference comes from the raw tables, its resolution from the load report.
+		let bytes = std::fs::read(path)?;
+		let usage = RawAssembly::try_from(bytes.as_slice())
+			.and_then(|raw| raw.dependency_usage())
+			.map_err(invalid_data)?;
+
+		let referrer = assembly.full_name();
+		let resolutions = context
+			.load_report()
+			.references()
+			.iter()
+			.filter(|resolution| resolution.referrer() == referrer)
+			.collect::<Vec<_>>();
+
+		for reference in usage.assembly_refs.iter() {
+			let resolution = resolutions
+				.iter()
+				.find(|resolution| resolution.reference().name == reference.name);
+			let target = match resolution.and_then(|resolution| resolution.path()) {
+				Some(path) => path.display().to_string(),
+				None => "unresolved".to_string(),
+			};
+
+			writeln!(
+				out,
+				"\t{} ({} types, {} members{}) -> {}",
+				reference.name,
+				reference.type_refs.len(),
+				reference.member_refs.len(),
+				if reference.is_unreferenced() { ", unused" } else { "" },
+				target,
+			)?;
+		}
+	}
+	Ok(())
+}
+
+fn find(options: &Options, pattern: &str, context: &Context, out: &mut Output) -> io::Result<()> {
+	for assembly in options.roots(context) {
+		for ty in assembly.types() {
+			let Some(data) = ty.data() else { continue };
+			if matches_pattern(pattern, data.name()) || matches_pattern(pattern, &full_name(data)) {
+				writeln!(out, "{}: {}", assembly, context.display(ty).with_depth(0))?;
+			}
+
+			for member in ty.members().named(pattern) {
+				let kind = member_kind(&member);
+				writeln!(out, "{}: {} {}::{}", assembly, kind, full_name(data), member.name())?;
+			}
+		}
+	}
+	Ok(())
+}
+
+fn api(options: &Options, context: &Context, out: &mut Output) -> io::Result<()> {
+	for assembly in options.roots(context) {
+		writeln!(out, "{}", assembly)?;
+		for ty in assembly.types() {
+			let public = ty
+				.data()
+				.is_some_and(|data| data.flags() & type_flags::VISIBILITY_MASK == type_flags::PUBLIC);
+			if public {
+				write_api(context, assembly, ty, 1, out)?;
+			}
+		}
+	}
+	Ok(())
+}
+
+fn il(options: &Options, context: &Context, out: &mut Output) -> io::Result<()> {
+	for assembly in options.roots(context) {
+		writeln!(out, "{}", assembly)?;
+		let Some(path) = assembly.path() else { continue };
+
+		// Method bodies are not part of the schema, they are listed from the raw image.
+		let bytes = std::fs::read(path)?;
+		let raw = RawAssembly::try_from(bytes.as_slice()).map_err(invalid_data)?;
+		for location in raw.methods_with_bodies().map_err(invalid_data)? {
+			match raw.method_listing(&location) {
+				Ok(listing) => out.write_display(&listing.annotated(options.annotate))?,
+				Err(error) => writeln!(out, ".method {:?} // {:?}", location.token, error)?,
+			}
+		}
+	}
+	Ok(())
+}
+
+fn invalid_data(error: cli_toolkit::raw::Error) -> io::Error {
+	io::Error::new(io::ErrorKind::InvalidData, format!("{:?}", error))
+}
+
+fn write_api(context: &Context, assembly: &Assembly, ty: &Type, depth: usize, out: &mut Output) -> io::Result<()> {
+	let Some(data) = ty.data() else { return Ok(()) };
+	let formatter = SignatureFormatter::with_resolver(assembly).with_style(assembly.name_style());
+	let indent = "\t".repeat(depth);
+	writeln!(out, "{}{}", indent, context.display(ty))?;
+
+	for member in ty.members().public_only().hide_synthetic(true) {
+		let line = match &member {
+			Member::NestedType(nested) => {
+				write_api(context, assembly, nested, depth + 1, out)?;
+				continue;
+			}
+			Member::Field(field) => match assembly.field_signature(field) {
+				Ok(signature) => format!("field {} {}", formatter.type_to_string(&signature.ty), field.name()),
+				Err(_) => format!("field {}", field.name()),
+			},
+			Member::Method(method) => match assembly.method_signature(method) {
+				Ok(signature) => {
+					let params = signature
+						.params
+						.iter()
+						.map(|param| formatter.type_to_string(param))
+						.collect::<Vec<_>>();
+					let return_type = formatter.type_to_string(&signature.return_type);
+					format!("method {} {}({})", return_type, method.name(), params.join(", "))
+				}
+				Err(_) => format!("method {}", method.name()),
+			},
+			Member::Property(property) => property.display(data).to_string(),
+			Member::Event(event) => event.display(data).to_string(),
+		};
+
+		writeln!(out, "{}\t{}", indent, line)?;
+	}
+	Ok(())
+}
+
+fn member_kind(member: &Member) -> &'static str {
+	match member {
+		Member::Field(_) => "field",
+		Member::Method(_) => "method",
+		Member::Property(_) => "property",
+		Member::Event(_) => "event",
+		Member::NestedType(_) => "type",
+	}
+}
+
+fn full_name(data: &TypeData) -> String {
+	match data.namespace().is_empty() {
+		true => data.name().to_string(),
+		false => format!("{}.{}", data.namespace(), data.name()),
+	}
+}
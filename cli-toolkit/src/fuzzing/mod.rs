@@ -0,0 +1,180 @@
+//! Small, deterministic entry points for fuzzing harnesses, one per parsing layer.
+//!
+//! ```ignore
+//! fuzz_target!(|bytes: &[u8]| cli_toolkit::fuzzing::fuzz_parse_assembly(bytes));
+//! ```
+//!
+//! The entry points never touch the filesystem or global state. The input is copied into an aligned buffer first,
+//! so that the outcome does not depend on where the harness allocated it.
+//! Parsing errors are expected and ignored: only panics, hangs and runaway allocations are bugs.
+
+use crate::raw::*;
+use crate::schema::Context;
+
+/// Parses a PE image, then every heap and table row it contains, and finally loads it into a [`Context`].
+pub fn fuzz_parse_assembly(bytes: &[u8]) {
+	let Ok(buffer) = AlignedBuffer::try_from(bytes) else { return };
+	if let Ok(assembly) = Assembly::with_heap_slicing(&buffer, HeapSlicing::Clamp) {
+		let _ = assembly.truncated_streams();
+		let _ = assembly.metadata_header().streams();
+		walk_metadata(&assembly.metadata());
+	}
+
+	let _ = Context::from_assembly_list_lenient([bytes]);
+}
+
+/// Parses a standalone metadata root, as found in portable PDBs, along with its heaps and table rows.
+pub fn fuzz_parse_metadata(bytes: &[u8]) {
+	let Ok(buffer) = AlignedBuffer::try_from(bytes) else { return };
+	if let Ok(metadata) = Metadata::with_heap_slicing(&buffer, HeapSlicing::Clamp) {
+		walk_metadata(&metadata);
+	}
+}
+
+/// Parses the contents of a `#~` stream and every row of the tables it describes.
+pub fn fuzz_parse_table_heap(bytes: &[u8]) {
+	let Ok(buffer) = AlignedBuffer::try_from(bytes) else { return };
+	walk_tables(&TableHeap::new(&buffer));
+}
+
+/// Parses a blob as each kind of signature, and as a custom attribute value.
+pub fn fuzz_parse_signature(bytes: &[u8]) {
+	let Ok(buffer) = AlignedBuffer::try_from(bytes) else { return };
+	let _ = TypeSignature::parse(&buffer);
+	let _ = FieldSignature::parse(&buffer);
+	let _ = MethodSignature::parse(&buffer);
+	let _ = PropertySignature::parse(&buffer);
+	let _ = StandAloneSignature::parse(&buffer);
+	let _ = parse_method_spec(&buffer);
+
+	let params = [TypeSignature::String, TypeSignature::Object];
+	let _ = AttributeArguments::decode(&buffer, &params, |_| Some(TypeSignature::Int32));
+}
+
+fn walk_metadata(metadata: &Metadata) {
+	if let Ok(Some(strings)) = metadata.get_heap::<StringHeap>() {
+		let _ = strings.get_string(HeapIndex(0));
+	}
+
+	if let Ok(Some(user_strings)) = metadata.get_heap::<UserStringHeap>() {
+		for string in user_strings.iter().take(4096).flatten() {
+			let _ = string.1.to_string_lossy();
+		}
+	}
+
+	if let Ok(Some(pdb)) = metadata.pdb() {
+		let _ = pdb.pdb_id();
+		let _ = pdb.entry_point();
+	}
+
+	if let Ok(Some(tables)) = metadata.tables() {
+		walk_tables(&tables);
+	}
+}
+
+fn walk_tables(tables: &TableHeap) {
+	let walker = TableWalker { tables };
+	for kind in tables.valid_mask().iter() {
+		let _ = dispatch_table!(kind, walker.walk(), Ok(()));
+		let _ = tables.columns(kind);
+	}
+
+	let _ = tables.index_widths();
+	if let Ok(iter) = tables.iter_tables() {
+		iter.for_each(|table| {
+			let _ = table.row(TableIndex(1));
+		});
+	}
+}
+
+struct TableWalker<'a, 'l> {
+	tables: &'a TableHeap<'l>,
+}
+
+impl<'l> TableWalker<'_, 'l> {
+	fn walk<T: MetadataTable<'l>>(&self) -> Result<(), Error>
+	where
+		T::Row: MetadataRow,
+	{
+		let Some(table) = self.tables.get_table::<T>()? else { return Ok(()) };
+		for row in table.iter() {
+			let _ = row?.indices();
+		}
+
+		Ok(())
+	}
+}
+
+/// The name of an entry point, along with the entry point itself.
+type EntryPoint = (&'static str, fn(&[u8]));
+
+/// An input of [`adversarial_corpus`] that made an entry point panic.
+#[derive(Debug, Clone)]
+pub struct PanicCase {
+	pub entry_point: &'static str,
+	pub input: Vec<u8>,
+	/// The panic message, if it was a string.
+	pub message: Option<String>,
+}
+
+/// Deterministic malformed variants of a well-formed input: every truncation of it,
+/// and copies with a single byte or 4 byte word overwritten by a boundary value.
+///
+/// Seeded with a real assembly, the variants reach deep into the headers, heaps and tables that random bytes do not.
+pub fn adversarial_corpus(seed: &[u8]) -> impl Iterator<Item = Vec<u8>> + '_ {
+	const BYTES: [u8; 3] = [0x00, 0x80, 0xFF];
+	const WORDS: [u32; 4] = [0, 0x0001_0000, 0x8000_0000, u32::MAX];
+
+	let truncated = (0..seed.len()).map(|len| seed[..len].to_vec());
+	let bytes = (0..seed.len()).flat_map(move |offset| {
+		BYTES
+			.into_iter()
+			.filter(move |byte| seed[offset] != *byte)
+			.map(move |byte| {
+				let mut input = seed.to_vec();
+				input[offset] = byte;
+				input
+			})
+	});
+	let words = (0..seed.len().saturating_sub(3)).step_by(4).flat_map(move |offset| {
+		WORDS.into_iter().map(move |word| {
+			let mut input = seed.to_vec();
+			input[offset..offset + 4].copy_from_slice(&word.to_le_bytes());
+			input
+		})
+	});
+
+	truncated.chain(bytes).chain(words)
+}
+
+/// Runs every entry point over the [`adversarial_corpus`] of `seed`, stopping at the first panic.
+///
+/// This enforces the contract of the [`raw`](crate::raw) layer that no parsing API panics on arbitrary input.
+/// Stack overflows cannot be caught and abort the process, which fails the calling test all the same.
+pub fn check_panic_free(seed: &[u8]) -> Result<(), PanicCase> {
+	let entry_points: [EntryPoint; 4] = [
+		("fuzz_parse_assembly", fuzz_parse_assembly),
+		("fuzz_parse_metadata", fuzz_parse_metadata),
+		("fuzz_parse_table_heap", fuzz_parse_table_heap),
+		("fuzz_parse_signature", fuzz_parse_signature),
+	];
+
+	for input in adversarial_corpus(seed) {
+		for (entry_point, parse) in entry_points {
+			if let Err(payload) = std::panic::catch_unwind(|| parse(&input)) {
+				let message = match payload.downcast::<String>() {
+					Ok(message) => Some(*message),
+					Err(payload) => payload.downcast::<&str>().ok().map(|message| message.to_string()),
+				};
+
+				return Err(PanicCase {
+					entry_point,
+					input,
+					message,
+				});
+			}
+		}
+	}
+
+	Ok(())
+}
@@ -1,10 +1,25 @@
 #![allow(unused)]
 
-#[cfg(feature = "read")]
+#[cfg(feature = "raw")]
 pub mod raw;
 
 #[cfg(feature = "read")]
 pub mod read;
 
+#[cfg(feature = "analysis")]
+pub mod analysis;
+
+#[cfg(feature = "schema")]
 pub mod schema;
 pub mod utilities;
+
+pub mod prelude;
+
+#[cfg(feature = "testing")]
+pub mod testing;
+
+#[cfg(feature = "fuzzing")]
+pub mod fuzzing;
+
+#[cfg(feature = "srm")]
+pub mod srm;
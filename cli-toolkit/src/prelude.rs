@@ -0,0 +1,33 @@
+//! The types most programs need, under names that do not collide across layers.
+//!
+//! ```ignore
+//! use cli_toolkit::prelude::*;
+//!
+//! let context = ContextBuilder::new().assembly("Foo.dll").build()?;
+//! let bytes = std::fs::read("Foo.dll")?;
+//! let image = RawAssembly::try_from(bytes.as_slice())?;
+//! ```
+//!
+//! Only the items of the enabled layers are re-exported. The PE image of the `raw` layer is [`RawAssembly`],
+//! its Assembly table row is [`AssemblyDef`], and the loaded assembly of the `schema` layer is `Assembly`.
+//! The error of each layer is named after it.
+
+#[cfg(feature = "raw")]
+pub use crate::raw::{
+	Assembly as RawAssembly, AssemblyDef, CodedIndex, CodedIndexKind, Error as RawError, FieldSignature, HeapIndex,
+	IlBody, Instruction, Metadata, MetadataToken, MetadataTokenKind, MethodSignature, OpCode, TableHeap, TableIndex,
+	TableKind, TypeSignature,
+};
+
+#[cfg(feature = "schema")]
+pub use crate::schema::{
+	Assembly, AssemblyIdentity, AssemblyVersion, Context, CustomAttribute, DecodedAttribute, Event, Field,
+	GenericParam, InterfaceImpl, Member, Method, Module, Param, Property, SignatureFormatter, Type, TypeData,
+	TypeNameResolver, TypeRef, TypeSpec,
+};
+
+#[cfg(feature = "read")]
+pub use crate::read::{AssemblyResolver, ContextBuilder, Error as ReadError};
+
+#[cfg(feature = "analysis")]
+pub use crate::analysis::{InstructionVisitor, MethodSizeMap, OpCodeHistogram, Validator};
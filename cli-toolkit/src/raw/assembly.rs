@@ -1,3 +1,4 @@
+use std::ops::Range;
 use crate::raw::*;
 
 pub struct Assembly<'l> {
@@ -7,6 +8,7 @@ pub struct Assembly<'l> {
 	pe_optional_header: PeOptionalHeader,
 	#[allow(unused)]
 	cli_header: CliHeader,
+	sections: Vec<SectionHeader>,
 
 	bytes: &'l [u8],
 	metadata_header: MetadataHeader<'l>,
@@ -16,6 +18,14 @@ impl<'l> TryFrom<&'l [u8]> for Assembly<'l> {
 	type Error = Error;
 
 	fn try_from(bytes: &'l [u8]) -> Result<Self, Self::Error> {
+		Self::with_heap_slicing(bytes, HeapSlicing::Strict)
+	}
+}
+
+impl<'l> Assembly<'l> {
+	/// Reads an assembly whose streams are sliced according to `slicing`,
+	/// so that the heaps of truncated files can still be inspected with [`HeapSlicing::Clamp`].
+	pub fn with_heap_slicing(bytes: &'l [u8], slicing: HeapSlicing) -> Result<Self, Error> {
 		let mut reader = ByteStream::new(bytes);
 		let dos_header = DosHeader::from_byte_stream(&mut reader)?;
 
@@ -24,39 +34,73 @@ impl<'l> TryFrom<&'l [u8]> for Assembly<'l> {
 
 		let pe_header = PeHeader::from_byte_stream(&mut reader)?;
 		let pe_optional_header = PeOptionalHeader::from_byte_stream(&mut reader)?;
-		let sections = reader.read_slice::<SectionHeader>(pe_header.number_of_sections as usize)?;
+		let sections = reader.read_vec::<SectionHeader>(pe_header.number_of_sections as usize)?;
 
-		reader.seek(resolve_rva(pe_optional_header.data_directories[14].rva, sections)?)?;
+		reader.seek(resolve_rva(pe_optional_header.data_directories[14].rva, &sections)?)?;
 		let cli_header = CliHeader::from_byte_stream(&mut reader)?;
 
-		let metadata_start = resolve_rva(cli_header.metadata.rva, sections)?;
-		let metadata_header = MetadataHeader::new(bytes, metadata_start)?;
+		let metadata_start = resolve_rva(cli_header.metadata.rva, &sections)?;
+		let metadata_header = MetadataHeader::new(bytes, metadata_start)?.with_heap_slicing(slicing);
 
 		Ok(Assembly {
 			bytes,
 			pe_header,
 			pe_optional_header,
 			cli_header,
+			sections,
 			metadata_header,
 		})
 	}
-}
 
-impl<'l> Assembly<'l> {
 	pub fn bytes(&self) -> &'l [u8] {
 		self.bytes
 	}
 
+	pub fn cli_header(&self) -> &CliHeader {
+		&self.cli_header
+	}
+
+	pub fn metadata_header(&self) -> &MetadataHeader<'l> {
+		&self.metadata_header
+	}
+
+	/// The metadata root of the assembly, to be inspected like the one of a standalone portable PDB.
+	pub fn metadata(&self) -> Metadata<'l> {
+		Metadata::from_header(self.metadata_header.clone())
+	}
+
+	pub fn sections(&self) -> &[SectionHeader] {
+		&self.sections
+	}
+
+	pub(crate) fn rva_to_offset(&self, rva: u32) -> Result<usize, Error> {
+		resolve_rva(rva, &self.sections)
+	}
+
 	pub fn get_heap<T: MetadataHeap<'l>>(&self) -> Result<Option<T>, Error> {
 		self.metadata_header.get_heap()
 	}
+
+	pub fn truncated_streams(&self) -> Result<Vec<TruncatedStream<'l>>, Error> {
+		self.metadata_header.truncated_streams()
+	}
+
+	/// The absolute file range of the row a token refers to, see [`MetadataHeader::token_range`].
+	pub fn token_range(&self, token: MetadataToken) -> Result<Option<Range<usize>>, Error> {
+		self.metadata_header.token_range(token)
+	}
+
+	/// Maps an absolute file offset, e.g. from a hex editor, back to the stream, heap index or table row it belongs to.
+	pub fn locate_offset(&self, offset: usize) -> Result<Option<OffsetLocation<'l>>, Error> {
+		self.metadata_header.locate_offset(offset)
+	}
 }
 
 fn resolve_rva(rva: u32, sections: &[SectionHeader]) -> Result<usize, Error> {
 	let section = sections
 		.iter()
-		.find(|s| rva >= s.virtual_address && rva < (s.virtual_address + s.size_of_raw_data))
+		.find(|s| rva >= s.virtual_address && rva - s.virtual_address < s.size_of_raw_data)
 		.ok_or(Error::OffsetOutOfBounds)?;
 
-	Ok((rva - section.virtual_address + section.pointer_to_raw_data) as usize)
+	Ok((rva - section.virtual_address) as usize + section.pointer_to_raw_data as usize)
 }
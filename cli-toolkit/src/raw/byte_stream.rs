@@ -7,6 +7,12 @@ pub enum Error {
 	OffsetOutOfBounds,
 	UnexpectedEndOfStream,
 	InvalidData(Option<&'static str>),
+	/// A row index that is null or past the end of its table.
+	RowIndexOutOfBounds {
+		table: crate::raw::TableKind,
+		index: u32,
+		len: u32,
+	},
 }
 
 mod private {
@@ -19,7 +25,7 @@ mod private {
 	use std::path::{Path, PathBuf};
 	use std::mem::{align_of, size_of};
 	use std::ops::{Deref, DerefMut};
-	use crate::raw::{CodedIndex, Error, IndexSize, TableIndex, HeapIndex};
+	use crate::raw::{codec, CodedIndex, Error, IndexSize, TableIndex, HeapIndex};
 
 	#[derive(Debug, Clone)]
 	pub struct ByteStream<'l> {
@@ -27,6 +33,19 @@ mod private {
 		position: usize,
 	}
 
+	/// A type that any bytes of its size are a valid value of, and that can therefore be read without checks.
+	///
+	/// # Safety
+	/// The type must have no padding and no invalid bit patterns, which rules out enums, `bool` and `char`.
+	pub unsafe trait Pod: 'static {}
+
+	macro_rules! pod {
+		($($ty: ty),*) => { $(unsafe impl Pod for $ty {})* };
+	}
+
+	pod!(u8, u16, u32, u64, i8, i16, i32, i64, f32, f64);
+	unsafe impl<T: Pod, const N: usize> Pod for [T; N] {}
+
 	pub trait FromByteStream<'l>
 	where
 		Self: Sized,
@@ -62,7 +81,7 @@ mod private {
 		}
 
 		pub fn skip(&mut self, count: usize) -> Result<usize, Error> {
-			if self.position + count > self.bytes.len() {
+			if count > self.remaining() {
 				Err(UnexpectedEndOfStream)
 			} else {
 				let prev = self.position;
@@ -71,8 +90,8 @@ mod private {
 			}
 		}
 
-		pub fn read<T: 'static>(&mut self) -> Result<T, Error> {
-			if self.position + size_of::<T>() > self.bytes.len() {
+		pub fn read<T: Pod>(&mut self) -> Result<T, Error> {
+			if size_of::<T>() > self.remaining() {
 				return Err(UnexpectedEndOfStream);
 			}
 
@@ -84,7 +103,7 @@ mod private {
 			}
 		}
 
-		pub fn read_checked<T: 'static + PartialEq>(
+		pub fn read_checked<T: Pod + PartialEq>(
 			&mut self,
 			check: impl FnOnce(&T) -> bool,
 			message: Option<&'static str>,
@@ -96,8 +115,8 @@ mod private {
 			}
 		}
 
-		pub fn read_ref<T>(&mut self) -> Result<&'l T, Error> {
-			if self.position + size_of::<T>() > self.bytes.len() {
+		pub fn read_ref<T: Pod>(&mut self) -> Result<&'l T, Error> {
+			if size_of::<T>() > self.remaining() {
 				return Err(UnexpectedEndOfStream);
 			}
 
@@ -114,8 +133,8 @@ mod private {
 			}
 		}
 
-		pub fn read_slice<T>(&mut self, count: usize) -> Result<&'l [T], Error> {
-			if self.position + size_of::<T>() * count > self.bytes.len() {
+		pub fn read_slice<T: Pod>(&mut self, count: usize) -> Result<&'l [T], Error> {
+			if !self.fits::<T>(count) {
 				return Err(UnexpectedEndOfStream);
 			}
 
@@ -132,6 +151,26 @@ mod private {
 			}
 		}
 
+		pub fn read_vec<T: Pod>(&mut self, count: usize) -> Result<Vec<T>, Error> {
+			if !self.fits::<T>(count) {
+				return Err(UnexpectedEndOfStream);
+			}
+
+			let mut values = Vec::with_capacity(count);
+			for _ in 0..count {
+				values.push(self.read::<T>()?);
+			}
+
+			Ok(values)
+		}
+
+		/// Whether `count` values of `T` fit in the remaining bytes, without overflowing for huge counts.
+		fn fits<T>(&self, count: usize) -> bool {
+			size_of::<T>()
+				.checked_mul(count)
+				.is_some_and(|size| size <= self.remaining())
+		}
+
 		pub fn read_u8_slice_until(&mut self, byte: u8) -> Result<&'l [u8], Error> {
 			let start = self.position;
 			for b in &self.bytes[start..] {
@@ -150,6 +189,25 @@ mod private {
 			std::str::from_utf8(bytes).or(Err(InvalidData(None)))
 		}
 
+		pub fn read_compressed_u32(&mut self) -> Result<u32, Error> {
+			let (value, size) = codec::decode_compressed_u32(&self.bytes[self.position..])?;
+			self.position += size;
+			Ok(value)
+		}
+
+		pub fn read_compressed_i32(&mut self) -> Result<i32, Error> {
+			let (value, size) = codec::decode_compressed_i32(&self.bytes[self.position..])?;
+			self.position += size;
+			Ok(value)
+		}
+
+		/// Reads a `SerString`, which is `None` if it is null.
+		pub fn read_ser_string(&mut self) -> Result<Option<&'l str>, Error> {
+			let (value, size) = codec::decode_ser_string(&self.bytes[self.position..])?;
+			self.position += size;
+			Ok(value)
+		}
+
 		pub(crate) fn read_table_index(&mut self, size: IndexSize) -> Result<TableIndex, Error> {
 			let value = match size {
 				IndexSize::Fat => self.read::<u32>()?,
@@ -178,38 +236,100 @@ mod private {
 		}
 	}
 
+	/// Writes values with the same layout a [`ByteStream`] reads them with.
+	#[derive(Debug, Default)]
+	pub(crate) struct ByteWriter {
+		bytes: Vec<u8>,
+	}
+
+	impl ByteWriter {
+		pub(crate) fn into_bytes(self) -> Vec<u8> {
+			self.bytes
+		}
+
+		/// Writes the bytes of `value` as they are in memory, which [`Pod`] guarantees hold no padding.
+		pub(crate) fn write<T: Pod>(&mut self, value: T) {
+			unsafe {
+				let ptr = &value as *const T as *const u8;
+				self.bytes
+					.extend_from_slice(std::slice::from_raw_parts(ptr, size_of::<T>()));
+			}
+		}
+
+		pub(crate) fn write_index(&mut self, value: u32, size: IndexSize) {
+			match size {
+				IndexSize::Fat => self.write(value),
+				IndexSize::Slim => self.write(value as u16),
+			}
+		}
+
+		pub(crate) fn write_bytes(&mut self, bytes: &[u8]) {
+			self.bytes.extend_from_slice(bytes);
+		}
+
+		/// The inverse of [`ByteStream::read_compressed_u32`], for values of up to 29 bits.
+		pub(crate) fn write_compressed_u32(&mut self, value: u32) -> Result<(), Error> {
+			codec::encode_compressed_u32(value, &mut self.bytes)
+		}
+	}
+
 	pub struct AlignedBuffer<'l> {
 		len: usize,
 		data: *mut u8,
+		/// Whether `data` was allocated by the buffer, rather than borrowed for `'l`.
+		owned: bool,
 		phantom: PhantomData<&'l u8>,
 	}
 
-	impl AlignedBuffer<'_> {
-		fn alloc_new(len: usize) -> Self {
+	impl<'l> AlignedBuffer<'l> {
+		fn alloc_new(len: usize) -> std::io::Result<Self> {
 			if len == 0 {
-				return Self {
-					len,
-					data: null_mut(),
-					phantom: PhantomData,
-				};
+				return Ok(Self::default());
 			}
 
-			let layout = Layout::from_size_align(len, 8).unwrap();
-			unsafe {
-				Self {
-					len,
-					data: std::alloc::alloc(layout),
-					phantom: PhantomData,
-				}
+			let Ok(layout) = Layout::from_size_align(len, 8) else {
+				return Err(std::io::ErrorKind::OutOfMemory.into());
+			};
+
+			let data = unsafe { std::alloc::alloc(layout) };
+			if data.is_null() {
+				std::alloc::handle_alloc_error(layout);
+			}
+
+			Ok(Self {
+				len,
+				data,
+				owned: true,
+				phantom: PhantomData,
+			})
+		}
+
+		/// Wraps bytes that outlive the buffer without copying them, e.g. a memory mapped or embedded image.
+		/// The bytes may have any alignment, such as the alignment of 1 of `include_bytes!`,
+		/// since images are read with unaligned reads only.
+		pub fn borrowed(bytes: &'l [u8]) -> Self {
+			if bytes.is_empty() {
+				return Self::default();
+			}
+
+			Self {
+				len: bytes.len(),
+				data: bytes.as_ptr() as *mut u8,
+				owned: false,
+				phantom: PhantomData,
 			}
 		}
 	}
 
+	// The buffer either owns its bytes or borrows them immutably for 'l, like a `Cow<'l, [u8]>`.
+	unsafe impl Send for AlignedBuffer<'_> {}
+
 	impl Default for AlignedBuffer<'_> {
 		fn default() -> Self {
 			Self {
 				len: 0,
 				data: null_mut(),
+				owned: false,
 				phantom: PhantomData,
 			}
 		}
@@ -219,12 +339,29 @@ mod private {
 		type Target = [u8];
 
 		fn deref(&self) -> &'l Self::Target {
+			// Empty buffers are not allocated, and slices cannot be built from a null pointer.
+			if self.data.is_null() {
+				return &[];
+			}
+
 			unsafe { std::slice::from_raw_parts(self.data, self.len) }
 		}
 	}
 
+	/// Borrowed bytes are copied into a buffer of their own on the first mutable access.
 	impl<'l> DerefMut for AlignedBuffer<'l> {
 		fn deref_mut(&mut self) -> &'l mut Self::Target {
+			if self.data.is_null() {
+				return &mut [];
+			}
+
+			if !self.owned {
+				// The bytes are already in memory, so a buffer of their size can always be laid out.
+				let mut buffer = Self::alloc_new(self.len).expect("buffer larger than the address space");
+				buffer.copy_from_slice(self);
+				*self = buffer;
+			}
+
 			unsafe { std::slice::from_raw_parts_mut(self.data, self.len) }
 		}
 	}
@@ -232,7 +369,7 @@ mod private {
 	impl TryFrom<&[u8]> for AlignedBuffer<'_> {
 		type Error = std::io::Error;
 		fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
-			let mut buffer = Self::alloc_new(bytes.len());
+			let mut buffer = Self::alloc_new(bytes.len())?;
 			buffer.copy_from_slice(bytes);
 			Ok(buffer)
 		}
@@ -242,9 +379,9 @@ mod private {
 		type Error = std::io::Error;
 		fn try_from(path: &Path) -> Result<Self, Self::Error> {
 			let len = path.metadata()?.len() as usize;
-			let mut buffer = Self::alloc_new(len);
+			let mut buffer = Self::alloc_new(len)?;
 			let mut file = File::open(path)?;
-			file.read_exact(&mut buffer);
+			file.read_exact(&mut buffer)?;
 			Ok(buffer)
 		}
 	}
@@ -253,16 +390,16 @@ mod private {
 		type Error = std::io::Error;
 		fn try_from(path: PathBuf) -> Result<Self, Self::Error> {
 			let len = path.metadata()?.len() as usize;
-			let mut buffer = Self::alloc_new(len);
+			let mut buffer = Self::alloc_new(len)?;
 			let mut file = File::open(path)?;
-			file.read_exact(&mut buffer);
+			file.read_exact(&mut buffer)?;
 			Ok(buffer)
 		}
 	}
 
 	impl Drop for AlignedBuffer<'_> {
 		fn drop(&mut self) {
-			if self.len == 0 {
+			if self.len == 0 || !self.owned {
 				return;
 			}
 
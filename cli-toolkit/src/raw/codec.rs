@@ -0,0 +1,116 @@
+//! The compressed integer and `SerString` encodings shared by signatures, custom attributes and the `#Blob` and `#US` heaps.
+//!
+//! Decoders take the bytes starting at the encoded value and return it along with the number of bytes it took.
+//! Encoders append to a buffer, and fail without writing anything if the value cannot be encoded.
+
+use crate::raw::Error::{self, *};
+
+/// The leading byte of a null `SerString`.
+pub const NULL_SER_STRING: u8 = 0xFF;
+/// The largest value an unsigned compressed integer can hold.
+pub const MAX_COMPRESSED_U32: u32 = 0x1FFFFFFF;
+/// The smallest value a signed compressed integer can hold.
+pub const MIN_COMPRESSED_I32: i32 = -0x10000000;
+/// The largest value a signed compressed integer can hold.
+pub const MAX_COMPRESSED_I32: i32 = 0x0FFFFFFF;
+
+/// Decodes an unsigned compressed integer, stored big endian in 1, 2 or 4 bytes depending on its leading bits.
+pub fn decode_compressed_u32(bytes: &[u8]) -> Result<(u32, usize), Error> {
+	let byte_0 = *bytes.first().ok_or(UnexpectedEndOfStream)? as u32;
+	let size = match byte_0 {
+		_ if byte_0 & 0x80 == 0 => 1,
+		_ if byte_0 & 0xC0 == 0x80 => 2,
+		_ if byte_0 & 0xE0 == 0xC0 => 4,
+		_ => return Err(InvalidData(Some("Invalid compressed integer"))),
+	};
+
+	let bytes = bytes.get(..size).ok_or(UnexpectedEndOfStream)?;
+	let value = match size {
+		1 => byte_0,
+		2 => ((byte_0 & 0x3F) << 8) | bytes[1] as u32,
+		_ => ((byte_0 & 0x1F) << 24) | ((bytes[1] as u32) << 16) | ((bytes[2] as u32) << 8) | bytes[3] as u32,
+	};
+
+	Ok((value, size))
+}
+
+/// Decodes a signed compressed integer, whose sign is stored in the lowest bit of its unsigned encoding.
+pub fn decode_compressed_i32(bytes: &[u8]) -> Result<(i32, usize), Error> {
+	let (value, size) = decode_compressed_u32(bytes)?;
+	let sign = match size {
+		1 => 0xFFFFFFC0,
+		2 => 0xFFFFE000,
+		_ => 0xF0000000,
+	};
+
+	let magnitude = value >> 1;
+	match value & 1 != 0 {
+		true => Ok(((magnitude | sign) as i32, size)),
+		false => Ok((magnitude as i32, size)),
+	}
+}
+
+/// Encodes an unsigned compressed integer in as few bytes as possible.
+/// Fails if the value is larger than [`MAX_COMPRESSED_U32`].
+pub fn encode_compressed_u32(value: u32, out: &mut Vec<u8>) -> Result<(), Error> {
+	match value {
+		0..=0x7F => out.push(value as u8),
+		0x80..=0x3FFF => out.extend_from_slice(&((value | 0x8000) as u16).to_be_bytes()),
+		0x4000..=MAX_COMPRESSED_U32 => out.extend_from_slice(&(value | 0xC0000000).to_be_bytes()),
+		_ => return Err(InvalidData(Some("Value too large for a compressed integer"))),
+	}
+
+	Ok(())
+}
+
+/// Encodes a signed compressed integer in as few bytes as possible.
+/// Fails if the value is outside of [`MIN_COMPRESSED_I32`]..=[`MAX_COMPRESSED_I32`].
+pub fn encode_compressed_i32(value: i32, out: &mut Vec<u8>) -> Result<(), Error> {
+	let (size, bits) = match value {
+		-0x40..=0x3F => (1, 6),
+		-0x2000..=0x1FFF => (2, 13),
+		MIN_COMPRESSED_I32..=MAX_COMPRESSED_I32 => (4, 28),
+		_ => return Err(InvalidData(Some("Value out of range for a compressed integer"))),
+	};
+
+	let rotated = (((value as u32) & ((1 << bits) - 1)) << 1) | (value < 0) as u32;
+	match size {
+		1 => out.push(rotated as u8),
+		2 => out.extend_from_slice(&((rotated | 0x8000) as u16).to_be_bytes()),
+		_ => out.extend_from_slice(&(rotated | 0xC0000000).to_be_bytes()),
+	}
+
+	Ok(())
+}
+
+/// Decodes a `SerString`: [`NULL_SER_STRING`] for a null string, or a compressed length followed by as many bytes of UTF-8.
+/// An empty string is stored as a length of 0.
+pub fn decode_ser_string(bytes: &[u8]) -> Result<(Option<&str>, usize), Error> {
+	match bytes.first() {
+		None => return Err(UnexpectedEndOfStream),
+		Some(&NULL_SER_STRING) => return Ok((None, 1)),
+		Some(_) => {}
+	}
+
+	let (length, size) = decode_compressed_u32(bytes)?;
+	let end = size.checked_add(length as usize).ok_or(UnexpectedEndOfStream)?;
+	let string = bytes.get(size..end).ok_or(UnexpectedEndOfStream)?;
+	match std::str::from_utf8(string) {
+		Ok(string) => Ok((Some(string), end)),
+		Err(_) => Err(InvalidData(Some("Invalid UTF-8 in SerString"))),
+	}
+}
+
+/// Encodes a `SerString`, the inverse of [`decode_ser_string`].
+/// Fails if the string is too long for its length to be compressed.
+pub fn encode_ser_string(value: Option<&str>, out: &mut Vec<u8>) -> Result<(), Error> {
+	let Some(value) = value else {
+		out.push(NULL_SER_STRING);
+		return Ok(());
+	};
+
+	let length = u32::try_from(value.len()).unwrap_or(u32::MAX);
+	encode_compressed_u32(length, out)?;
+	out.extend_from_slice(value.as_bytes());
+	Ok(())
+}
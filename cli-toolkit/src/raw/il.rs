@@ -0,0 +1,701 @@
+use crate::raw::{ByteStream, Error, MetadataToken};
+use std::fmt::{Display, Formatter};
+use strum::EnumIter;
+
+const TINY_FORMAT: u8 = 0x2;
+const FAT_FORMAT: u8 = 0x3;
+const MORE_SECTIONS: u16 = 0x8;
+const INIT_LOCALS: u16 = 0x10;
+
+const SECTION_EH_TABLE: u8 = 0x1;
+const SECTION_FAT_FORMAT: u8 = 0x40;
+const SECTION_MORE_SECTIONS: u8 = 0x80;
+
+/// The header of a method body, in either its tiny or its fat format.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct MethodBodyHeader {
+	pub is_fat: bool,
+	/// The size of the header in bytes, 1 for tiny headers.
+	pub header_size: u8,
+	pub max_stack: u16,
+	pub code_size: u32,
+	/// The StandAloneSig of the locals, or a null token if there are none.
+	pub local_var_sig: MetadataToken,
+	pub init_locals: bool,
+	pub has_more_sections: bool,
+}
+
+/// The IL of a method body, along with its header and exception handling clauses.
+#[derive(Debug, Copy, Clone)]
+pub struct IlBody<'l> {
+	header: MethodBodyHeader,
+	code: &'l [u8],
+	/// The bytes following the code, starting at the first extra data section if there is one.
+	sections: &'l [u8],
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ExceptionClauseKind {
+	/// A `catch` block, along with the type it catches.
+	Catch(MetadataToken),
+	/// A `filter` block, along with the offset of the filter code.
+	Filter(u32),
+	Finally,
+	Fault,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct ExceptionClause {
+	pub kind: ExceptionClauseKind,
+	pub try_offset: u32,
+	pub try_length: u32,
+	pub handler_offset: u32,
+	pub handler_length: u32,
+}
+
+impl<'l> IlBody<'l> {
+	/// Parses the method body starting at `bytes`, which usually extend past its end.
+	pub fn parse(bytes: &'l [u8]) -> Result<Self, Error> {
+		let mut stream = ByteStream::new(bytes);
+		let first = stream.read::<u8>()?;
+		let header = match first & 0x3 {
+			TINY_FORMAT => MethodBodyHeader {
+				is_fat: false,
+				header_size: 1,
+				max_stack: 8,
+				code_size: (first >> 2) as u32,
+				local_var_sig: MetadataToken(0),
+				init_locals: false,
+				has_more_sections: false,
+			},
+			FAT_FORMAT => {
+				let flags = u16::from_le_bytes([first, stream.read::<u8>()?]);
+				let header_size = ((flags >> 12) * 4) as u8;
+				if header_size < 12 {
+					return Err(Error::InvalidData(Some("Invalid fat method header size")));
+				}
+
+				MethodBodyHeader {
+					is_fat: true,
+					header_size,
+					max_stack: stream.read()?,
+					code_size: stream.read()?,
+					local_var_sig: MetadataToken(stream.read()?),
+					init_locals: flags & INIT_LOCALS != 0,
+					has_more_sections: flags & MORE_SECTIONS != 0,
+				}
+			}
+			_ => return Err(Error::InvalidData(Some("Invalid method header format"))),
+		};
+
+		let start = header.header_size as usize;
+		let end = start + header.code_size as usize;
+		let code = bytes.get(start..end).ok_or(Error::UnexpectedEndOfStream)?;
+		let sections = match header.has_more_sections {
+			// Extra data sections are aligned to 4 bytes.
+			true => bytes.get((end + 3) & !3..).ok_or(Error::UnexpectedEndOfStream)?,
+			false => &[],
+		};
+
+		Ok(Self { header, code, sections })
+	}
+
+	pub fn header(&self) -> &MethodBodyHeader {
+		&self.header
+	}
+
+	pub fn code(&self) -> &'l [u8] {
+		self.code
+	}
+
+	pub fn instructions(&self) -> Instructions<'l> {
+		Instructions {
+			stream: ByteStream::new(self.code),
+			failed: false,
+		}
+	}
+
+	/// The number of bytes the body takes in the image: its header, its code and its extra data sections,
+	/// along with the padding that aligns the sections to 4 bytes.
+	pub fn size(&self) -> Result<usize, Error> {
+		let code_end = self.header.header_size as usize + self.code.len();
+		if !self.header.has_more_sections {
+			return Ok(code_end);
+		}
+
+		let mut stream = ByteStream::new(self.sections);
+		let mut end = 0;
+		let mut more_sections = true;
+		while more_sections {
+			let start = end;
+			stream.seek(start)?;
+			let kind = stream.read::<u8>()?;
+			let size = match kind & SECTION_FAT_FORMAT != 0 {
+				true => {
+					let size = stream.read::<[u8; 3]>()?;
+					u32::from_le_bytes([size[0], size[1], size[2], 0]) as usize
+				}
+				false => stream.read::<u8>()? as usize,
+			};
+
+			if size < 4 {
+				return Err(Error::InvalidData(Some("Invalid method data section size")));
+			}
+
+			more_sections = kind & SECTION_MORE_SECTIONS != 0;
+			end = start + ((size + 3) & !3);
+			if end > self.sections.len() {
+				return Err(Error::UnexpectedEndOfStream);
+			}
+		}
+
+		Ok(((code_end + 3) & !3) + end)
+	}
+
+	/// The exception handling clauses of every extra data section, in the order they are declared.
+	pub fn exception_clauses(&self) -> Result<Vec<ExceptionClause>, Error> {
+		let mut clauses = vec![];
+		let mut stream = ByteStream::new(self.sections);
+		let mut more_sections = self.header.has_more_sections;
+		while more_sections {
+			let start = stream.position();
+			let kind = stream.read::<u8>()?;
+			let is_fat = kind & SECTION_FAT_FORMAT != 0;
+			let (size, clause_size) = match is_fat {
+				true => {
+					let size = stream.read::<[u8; 3]>()?;
+					(u32::from_le_bytes([size[0], size[1], size[2], 0]) as usize, 24)
+				}
+				false => {
+					let size = stream.read::<u8>()? as usize;
+					stream.skip(2)?;
+					(size, 12)
+				}
+			};
+
+			if size < 4 {
+				return Err(Error::InvalidData(Some("Invalid method data section size")));
+			}
+
+			if kind & SECTION_EH_TABLE == 0 {
+				stream.skip(size - 4)?;
+			} else {
+				for _ in 0..(size - 4) / clause_size {
+					clauses.push(read_exception_clause(&mut stream, is_fat)?);
+				}
+			}
+
+			more_sections = kind & SECTION_MORE_SECTIONS != 0;
+			// Sections are aligned to 4 bytes, which the sizes of well-formed ones already are.
+			let next = start + ((size + 3) & !3);
+			if more_sections && next >= self.sections.len() {
+				return Err(Error::UnexpectedEndOfStream);
+			} else if more_sections {
+				stream.seek(next)?;
+			}
+		}
+
+		Ok(clauses)
+	}
+}
+
+fn read_exception_clause(stream: &mut ByteStream, is_fat: bool) -> Result<ExceptionClause, Error> {
+	let (flags, try_offset, try_length, handler_offset, handler_length) = match is_fat {
+		true => (
+			stream.read::<u32>()?,
+			stream.read()?,
+			stream.read()?,
+			stream.read()?,
+			stream.read()?,
+		),
+		false => (
+			stream.read::<u16>()? as u32,
+			stream.read::<u16>()? as u32,
+			stream.read::<u8>()? as u32,
+			stream.read::<u16>()? as u32,
+			stream.read::<u8>()? as u32,
+		),
+	};
+
+	let value = stream.read::<u32>()?;
+	let kind = match flags {
+		0x0 => ExceptionClauseKind::Catch(MetadataToken(value)),
+		0x1 => ExceptionClauseKind::Filter(value),
+		0x2 => ExceptionClauseKind::Finally,
+		0x4 => ExceptionClauseKind::Fault,
+		_ => return Err(Error::InvalidData(Some("Invalid exception clause kind"))),
+	};
+
+	Ok(ExceptionClause {
+		kind,
+		try_offset,
+		try_length,
+		handler_offset,
+		handler_length,
+	})
+}
+
+/// The kind of inline operand that follows an opcode.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum OperandType {
+	None,
+	Int8,
+	Int32,
+	Int64,
+	Float32,
+	Float64,
+	/// A metadata token, including the user string tokens of `ldstr`.
+	Token,
+	ShortBranchTarget,
+	BranchTarget,
+	Switch,
+	ShortVariable,
+	Variable,
+}
+
+/// Groups opcodes by what they do, see [`InstructionVisitor`](crate::analysis::InstructionVisitor).
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum OpCodeFamily {
+	/// Pushes an argument, local, constant, field, element or address onto the stack.
+	Load,
+	/// Pops a value into an argument, local, field, element or address.
+	Store,
+	/// Transfers control within the method, including `leave` and `switch`.
+	Branch,
+	/// Calls a method, including `newobj` and `jmp`.
+	Call,
+	Other,
+}
+
+macro_rules! opcodes {
+	($($name: ident = $value: literal, $mnemonic: literal, $operand: ident;)*) => {
+		#[repr(u16)]
+		#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Ord, PartialOrd, EnumIter)]
+		pub enum OpCode {
+			$($name = $value,)*
+		}
+
+		impl OpCode {
+			pub fn from_value(value: u16) -> Option<Self> {
+				match value {
+					$($value => Some(Self::$name),)*
+					_ => None,
+				}
+			}
+
+			/// The name of the opcode in IL assembly, e.g. `ldc.i4.s`.
+			pub fn mnemonic(&self) -> &'static str {
+				match self {
+					$(Self::$name => $mnemonic,)*
+				}
+			}
+
+			pub fn operand_type(&self) -> OperandType {
+				match self {
+					$(Self::$name => OperandType::$operand,)*
+				}
+			}
+		}
+	};
+}
+
+opcodes! {
+	Nop = 0x00, "nop", None;
+	Break = 0x01, "break", None;
+	Ldarg0 = 0x02, "ldarg.0", None;
+	Ldarg1 = 0x03, "ldarg.1", None;
+	Ldarg2 = 0x04, "ldarg.2", None;
+	Ldarg3 = 0x05, "ldarg.3", None;
+	Ldloc0 = 0x06, "ldloc.0", None;
+	Ldloc1 = 0x07, "ldloc.1", None;
+	Ldloc2 = 0x08, "ldloc.2", None;
+	Ldloc3 = 0x09, "ldloc.3", None;
+	Stloc0 = 0x0A, "stloc.0", None;
+	Stloc1 = 0x0B, "stloc.1", None;
+	Stloc2 = 0x0C, "stloc.2", None;
+	Stloc3 = 0x0D, "stloc.3", None;
+	LdargS = 0x0E, "ldarg.s", ShortVariable;
+	LdargaS = 0x0F, "ldarga.s", ShortVariable;
+	StargS = 0x10, "starg.s", ShortVariable;
+	LdlocS = 0x11, "ldloc.s", ShortVariable;
+	LdlocaS = 0x12, "ldloca.s", ShortVariable;
+	StlocS = 0x13, "stloc.s", ShortVariable;
+	Ldnull = 0x14, "ldnull", None;
+	LdcI4M1 = 0x15, "ldc.i4.m1", None;
+	LdcI40 = 0x16, "ldc.i4.0", None;
+	LdcI41 = 0x17, "ldc.i4.1", None;
+	LdcI42 = 0x18, "ldc.i4.2", None;
+	LdcI43 = 0x19, "ldc.i4.3", None;
+	LdcI44 = 0x1A, "ldc.i4.4", None;
+	LdcI45 = 0x1B, "ldc.i4.5", None;
+	LdcI46 = 0x1C, "ldc.i4.6", None;
+	LdcI47 = 0x1D, "ldc.i4.7", None;
+	LdcI48 = 0x1E, "ldc.i4.8", None;
+	LdcI4S = 0x1F, "ldc.i4.s", Int8;
+	LdcI4 = 0x20, "ldc.i4", Int32;
+	LdcI8 = 0x21, "ldc.i8", Int64;
+	LdcR4 = 0x22, "ldc.r4", Float32;
+	LdcR8 = 0x23, "ldc.r8", Float64;
+	Dup = 0x25, "dup", None;
+	Pop = 0x26, "pop", None;
+	Jmp = 0x27, "jmp", Token;
+	Call = 0x28, "call", Token;
+	Calli = 0x29, "calli", Token;
+	Ret = 0x2A, "ret", None;
+	BrS = 0x2B, "br.s", ShortBranchTarget;
+	BrfalseS = 0x2C, "brfalse.s", ShortBranchTarget;
+	BrtrueS = 0x2D, "brtrue.s", ShortBranchTarget;
+	BeqS = 0x2E, "beq.s", ShortBranchTarget;
+	BgeS = 0x2F, "bge.s", ShortBranchTarget;
+	BgtS = 0x30, "bgt.s", ShortBranchTarget;
+	BleS = 0x31, "ble.s", ShortBranchTarget;
+	BltS = 0x32, "blt.s", ShortBranchTarget;
+	BneUnS = 0x33, "bne.un.s", ShortBranchTarget;
+	BgeUnS = 0x34, "bge.un.s", ShortBranchTarget;
+	BgtUnS = 0x35, "bgt.un.s", ShortBranchTarget;
+	BleUnS = 0x36, "ble.un.s", ShortBranchTarget;
+	BltUnS = 0x37, "blt.un.s", ShortBranchTarget;
+	Br = 0x38, "br", BranchTarget;
+	Brfalse = 0x39, "brfalse", BranchTarget;
+	Brtrue = 0x3A, "brtrue", BranchTarget;
+	Beq = 0x3B, "beq", BranchTarget;
+	Bge = 0x3C, "bge", BranchTarget;
+	Bgt = 0x3D, "bgt", BranchTarget;
+	Ble = 0x3E, "ble", BranchTarget;
+	Blt = 0x3F, "blt", BranchTarget;
+	BneUn = 0x40, "bne.un", BranchTarget;
+	BgeUn = 0x41, "bge.un", BranchTarget;
+	BgtUn = 0x42, "bgt.un", BranchTarget;
+	BleUn = 0x43, "ble.un", BranchTarget;
+	BltUn = 0x44, "blt.un", BranchTarget;
+	Switch = 0x45, "switch", Switch;
+	LdindI1 = 0x46, "ldind.i1", None;
+	LdindU1 = 0x47, "ldind.u1", None;
+	LdindI2 = 0x48, "ldind.i2", None;
+	LdindU2 = 0x49, "ldind.u2", None;
+	LdindI4 = 0x4A, "ldind.i4", None;
+	LdindU4 = 0x4B, "ldind.u4", None;
+	LdindI8 = 0x4C, "ldind.i8", None;
+	LdindI = 0x4D, "ldind.i", None;
+	LdindR4 = 0x4E, "ldind.r4", None;
+	LdindR8 = 0x4F, "ldind.r8", None;
+	LdindRef = 0x50, "ldind.ref", None;
+	StindRef = 0x51, "stind.ref", None;
+	StindI1 = 0x52, "stind.i1", None;
+	StindI2 = 0x53, "stind.i2", None;
+	StindI4 = 0x54, "stind.i4", None;
+	StindI8 = 0x55, "stind.i8", None;
+	StindR4 = 0x56, "stind.r4", None;
+	StindR8 = 0x57, "stind.r8", None;
+	Add = 0x58, "add", None;
+	Sub = 0x59, "sub", None;
+	Mul = 0x5A, "mul", None;
+	Div = 0x5B, "div", None;
+	DivUn = 0x5C, "div.un", None;
+	Rem = 0x5D, "rem", None;
+	RemUn = 0x5E, "rem.un", None;
+	And = 0x5F, "and", None;
+	Or = 0x60, "or", None;
+	Xor = 0x61, "xor", None;
+	Shl = 0x62, "shl", None;
+	Shr = 0x63, "shr", None;
+	ShrUn = 0x64, "shr.un", None;
+	Neg = 0x65, "neg", None;
+	Not = 0x66, "not", None;
+	ConvI1 = 0x67, "conv.i1", None;
+	ConvI2 = 0x68, "conv.i2", None;
+	ConvI4 = 0x69, "conv.i4", None;
+	ConvI8 = 0x6A, "conv.i8", None;
+	ConvR4 = 0x6B, "conv.r4", None;
+	ConvR8 = 0x6C, "conv.r8", None;
+	ConvU4 = 0x6D, "conv.u4", None;
+	ConvU8 = 0x6E, "conv.u8", None;
+	Callvirt = 0x6F, "callvirt", Token;
+	Cpobj = 0x70, "cpobj", Token;
+	Ldobj = 0x71, "ldobj", Token;
+	Ldstr = 0x72, "ldstr", Token;
+	Newobj = 0x73, "newobj", Token;
+	Castclass = 0x74, "castclass", Token;
+	Isinst = 0x75, "isinst", Token;
+	ConvRUn = 0x76, "conv.r.un", None;
+	Unbox = 0x79, "unbox", Token;
+	Throw = 0x7A, "throw", None;
+	Ldfld = 0x7B, "ldfld", Token;
+	Ldflda = 0x7C, "ldflda", Token;
+	Stfld = 0x7D, "stfld", Token;
+	Ldsfld = 0x7E, "ldsfld", Token;
+	Ldsflda = 0x7F, "ldsflda", Token;
+	Stsfld = 0x80, "stsfld", Token;
+	Stobj = 0x81, "stobj", Token;
+	ConvOvfI1Un = 0x82, "conv.ovf.i1.un", None;
+	ConvOvfI2Un = 0x83, "conv.ovf.i2.un", None;
+	ConvOvfI4Un = 0x84, "conv.ovf.i4.un", None;
+	ConvOvfI8Un = 0x85, "conv.ovf.i8.un", None;
+	ConvOvfU1Un = 0x86, "conv.ovf.u1.un", None;
+	ConvOvfU2Un = 0x87, "conv.ovf.u2.un", None;
+	ConvOvfU4Un = 0x88, "conv.ovf.u4.un", None;
+	ConvOvfU8Un = 0x89, "conv.ovf.u8.un", None;
+	ConvOvfIUn = 0x8A, "conv.ovf.i.un", None;
+	ConvOvfUUn = 0x8B, "conv.ovf.u.un", None;
+	Box = 0x8C, "box", Token;
+	Newarr = 0x8D, "newarr", Token;
+	Ldlen = 0x8E, "ldlen", None;
+	Ldelema = 0x8F, "ldelema", Token;
+	LdelemI1 = 0x90, "ldelem.i1", None;
+	LdelemU1 = 0x91, "ldelem.u1", None;
+	LdelemI2 = 0x92, "ldelem.i2", None;
+	LdelemU2 = 0x93, "ldelem.u2", None;
+	LdelemI4 = 0x94, "ldelem.i4", None;
+	LdelemU4 = 0x95, "ldelem.u4", None;
+	LdelemI8 = 0x96, "ldelem.i8", None;
+	LdelemI = 0x97, "ldelem.i", None;
+	LdelemR4 = 0x98, "ldelem.r4", None;
+	LdelemR8 = 0x99, "ldelem.r8", None;
+	LdelemRef = 0x9A, "ldelem.ref", None;
+	StelemI = 0x9B, "stelem.i", None;
+	StelemI1 = 0x9C, "stelem.i1", None;
+	StelemI2 = 0x9D, "stelem.i2", None;
+	StelemI4 = 0x9E, "stelem.i4", None;
+	StelemI8 = 0x9F, "stelem.i8", None;
+	StelemR4 = 0xA0, "stelem.r4", None;
+	StelemR8 = 0xA1, "stelem.r8", None;
+	StelemRef = 0xA2, "stelem.ref", None;
+	Ldelem = 0xA3, "ldelem", Token;
+	Stelem = 0xA4, "stelem", Token;
+	UnboxAny = 0xA5, "unbox.any", Token;
+	ConvOvfI1 = 0xB3, "conv.ovf.i1", None;
+	ConvOvfU1 = 0xB4, "conv.ovf.u1", None;
+	ConvOvfI2 = 0xB5, "conv.ovf.i2", None;
+	ConvOvfU2 = 0xB6, "conv.ovf.u2", None;
+	ConvOvfI4 = 0xB7, "conv.ovf.i4", None;
+	ConvOvfU4 = 0xB8, "conv.ovf.u4", None;
+	ConvOvfI8 = 0xB9, "conv.ovf.i8", None;
+	ConvOvfU8 = 0xBA, "conv.ovf.u8", None;
+	Refanyval = 0xC2, "refanyval", Token;
+	Ckfinite = 0xC3, "ckfinite", None;
+	Mkrefany = 0xC6, "mkrefany", Token;
+	Ldtoken = 0xD0, "ldtoken", Token;
+	ConvU2 = 0xD1, "conv.u2", None;
+	ConvU1 = 0xD2, "conv.u1", None;
+	ConvI = 0xD3, "conv.i", None;
+	ConvOvfI = 0xD4, "conv.ovf.i", None;
+	ConvOvfU = 0xD5, "conv.ovf.u", None;
+	AddOvf = 0xD6, "add.ovf", None;
+	AddOvfUn = 0xD7, "add.ovf.un", None;
+	MulOvf = 0xD8, "mul.ovf", None;
+	MulOvfUn = 0xD9, "mul.ovf.un", None;
+	SubOvf = 0xDA, "sub.ovf", None;
+	SubOvfUn = 0xDB, "sub.ovf.un", None;
+	Endfinally = 0xDC, "endfinally", None;
+	Leave = 0xDD, "leave", BranchTarget;
+	LeaveS = 0xDE, "leave.s", ShortBranchTarget;
+	StindI = 0xDF, "stind.i", None;
+	ConvU = 0xE0, "conv.u", None;
+	Arglist = 0xFE00, "arglist", None;
+	Ceq = 0xFE01, "ceq", None;
+	Cgt = 0xFE02, "cgt", None;
+	CgtUn = 0xFE03, "cgt.un", None;
+	Clt = 0xFE04, "clt", None;
+	CltUn = 0xFE05, "clt.un", None;
+	Ldftn = 0xFE06, "ldftn", Token;
+	Ldvirtftn = 0xFE07, "ldvirtftn", Token;
+	Ldarg = 0xFE09, "ldarg", Variable;
+	Ldarga = 0xFE0A, "ldarga", Variable;
+	Starg = 0xFE0B, "starg", Variable;
+	Ldloc = 0xFE0C, "ldloc", Variable;
+	Ldloca = 0xFE0D, "ldloca", Variable;
+	Stloc = 0xFE0E, "stloc", Variable;
+	Localloc = 0xFE0F, "localloc", None;
+	Endfilter = 0xFE11, "endfilter", None;
+	Unaligned = 0xFE12, "unaligned.", Int8;
+	Volatile = 0xFE13, "volatile.", None;
+	Tail = 0xFE14, "tail.", None;
+	Initobj = 0xFE15, "initobj", Token;
+	Constrained = 0xFE16, "constrained.", Token;
+	Cpblk = 0xFE17, "cpblk", None;
+	Initblk = 0xFE18, "initblk", None;
+	No = 0xFE19, "no.", Int8;
+	Rethrow = 0xFE1A, "rethrow", None;
+	Sizeof = 0xFE1C, "sizeof", Token;
+	Refanytype = 0xFE1D, "refanytype", None;
+	Readonly = 0xFE1E, "readonly.", None;
+}
+
+impl OpCode {
+	/// The encoded size of the opcode, without its operand.
+	pub fn size(&self) -> usize {
+		match *self as u16 > 0xFF {
+			true => 2,
+			false => 1,
+		}
+	}
+
+	pub fn family(&self) -> OpCodeFamily {
+		match self.operand_type() {
+			OperandType::ShortBranchTarget | OperandType::BranchTarget | OperandType::Switch => {
+				return OpCodeFamily::Branch
+			}
+			_ => {}
+		}
+
+		match self {
+			OpCode::Call | OpCode::Callvirt | OpCode::Calli | OpCode::Newobj | OpCode::Jmp => OpCodeFamily::Call,
+			_ if self.mnemonic().starts_with("ld") => OpCodeFamily::Load,
+			_ if self.mnemonic().starts_with("st") => OpCodeFamily::Store,
+			_ => OpCodeFamily::Other,
+		}
+	}
+}
+
+impl Display for OpCode {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		f.write_str(self.mnemonic())
+	}
+}
+
+/// The inline operand of an instruction. Branch targets are offsets from the start of the code.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operand {
+	None,
+	Int8(i8),
+	Int32(i32),
+	Int64(i64),
+	Float32(f32),
+	Float64(f64),
+	Token(MetadataToken),
+	BranchTarget(u32),
+	Switch(Vec<u32>),
+	Variable(u16),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Instruction {
+	/// The offset of the opcode from the start of the code.
+	pub offset: u32,
+	pub opcode: OpCode,
+	pub operand: Operand,
+	/// The encoded size of the opcode and its operand.
+	pub size: u32,
+}
+
+impl Instruction {
+	pub fn token(&self) -> Option<MetadataToken> {
+		match self.operand {
+			Operand::Token(token) => Some(token),
+			_ => None,
+		}
+	}
+
+	/// The offsets control may be transferred to, besides the next instruction for conditional branches.
+	pub fn branch_targets(&self) -> &[u32] {
+		match &self.operand {
+			Operand::BranchTarget(target) => std::slice::from_ref(target),
+			Operand::Switch(targets) => targets,
+			_ => &[],
+		}
+	}
+
+	/// The offset of the next instruction.
+	pub fn next_offset(&self) -> u32 {
+		self.offset + self.size
+	}
+}
+
+/// Renders the instruction as `IL_0001: call 0xA000001`.
+/// The alternate form `{:#}` renders tokens as the `/* 0A000001 */` comments of ILSpy and `ildasm /TOKENS` instead.
+impl Display for Instruction {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		write!(f, "IL_{:04x}: {}", self.offset, self.opcode)?;
+		match &self.operand {
+			Operand::None => Ok(()),
+			Operand::Int8(value) => write!(f, " {}", value),
+			Operand::Int32(value) => write!(f, " {}", value),
+			Operand::Int64(value) => write!(f, " {}", value),
+			Operand::Float32(value) => write!(f, " {}", value),
+			Operand::Float64(value) => write!(f, " {}", value),
+			Operand::Token(token) if f.alternate() => write!(f, " /* {:08X} */", token),
+			Operand::Token(token) => write!(f, " {:?}", token),
+			Operand::BranchTarget(target) => write!(f, " IL_{:04x}", target),
+			Operand::Variable(index) => write!(f, " {}", index),
+			Operand::Switch(targets) => {
+				let targets = targets.iter().map(|target| format!("IL_{:04x}", target));
+				write!(f, " ({})", targets.collect::<Vec<_>>().join(", "))
+			}
+		}
+	}
+}
+
+/// Decodes the instructions of a method body in order, stopping after the first error.
+#[derive(Debug, Clone)]
+pub struct Instructions<'l> {
+	stream: ByteStream<'l>,
+	failed: bool,
+}
+
+impl Instructions<'_> {
+	fn read_instruction(&mut self) -> Result<Instruction, Error> {
+		let offset = self.stream.position();
+		let mut value = self.stream.read::<u8>()? as u16;
+		if value == 0xFE {
+			value = 0xFE00 | self.stream.read::<u8>()? as u16;
+		}
+
+		let opcode = OpCode::from_value(value).ok_or(Error::InvalidData(Some("Unknown opcode")))?;
+		let operand = match opcode.operand_type() {
+			OperandType::None => Operand::None,
+			OperandType::Int8 => Operand::Int8(self.stream.read()?),
+			OperandType::Int32 => Operand::Int32(self.stream.read()?),
+			OperandType::Int64 => Operand::Int64(self.stream.read()?),
+			OperandType::Float32 => Operand::Float32(self.stream.read()?),
+			OperandType::Float64 => Operand::Float64(self.stream.read()?),
+			OperandType::Token => Operand::Token(MetadataToken(self.stream.read()?)),
+			OperandType::ShortVariable => Operand::Variable(self.stream.read::<u8>()? as u16),
+			OperandType::Variable => Operand::Variable(self.stream.read()?),
+			OperandType::ShortBranchTarget => {
+				let delta = self.stream.read::<i8>()? as i64;
+				Operand::BranchTarget(self.branch_target(delta)?)
+			}
+			OperandType::BranchTarget => {
+				let delta = self.stream.read::<i32>()? as i64;
+				Operand::BranchTarget(self.branch_target(delta)?)
+			}
+			OperandType::Switch => {
+				let count = self.stream.read::<u32>()? as usize;
+				if count > self.stream.remaining() / 4 {
+					return Err(Error::UnexpectedEndOfStream);
+				}
+
+				let deltas = self.stream.read_vec::<i32>(count)?;
+				let targets = deltas.into_iter().map(|delta| self.branch_target(delta as i64));
+				Operand::Switch(targets.collect::<Result<_, _>>()?)
+			}
+		};
+
+		Ok(Instruction {
+			offset: offset as u32,
+			opcode,
+			operand,
+			size: (self.stream.position() - offset) as u32,
+		})
+	}
+
+	/// Branch offsets are relative to the start of the next instruction.
+	fn branch_target(&self, delta: i64) -> Result<u32, Error> {
+		u32::try_from(self.stream.position() as i64 + delta)
+			.map_err(|_| Error::InvalidData(Some("Invalid branch target")))
+	}
+}
+
+impl Iterator for Instructions<'_> {
+	type Item = Result<Instruction, Error>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.failed || self.stream.remaining() == 0 {
+			return None;
+		}
+
+		let instruction = self.read_instruction();
+		self.failed = instruction.is_err();
+		Some(instruction)
+	}
+}
@@ -0,0 +1,301 @@
+use crate::raw::*;
+
+const PROLOG: u16 = 0x0001;
+const NULL_ARRAY: u32 = 0xFFFFFFFF;
+const FIELD: u8 = 0x53;
+const PROPERTY: u8 = 0x54;
+const BOXED: u8 = 0x51;
+const ENUM: u8 = 0x55;
+
+/// A decoded custom attribute argument.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AttributeValue {
+	Bool(bool),
+	Char(u16),
+	Int8(i8),
+	UInt8(u8),
+	Int16(i16),
+	UInt16(u16),
+	Int32(i32),
+	UInt32(u32),
+	Int64(i64),
+	UInt64(u64),
+	Float(f32),
+	Double(f64),
+	String(Option<String>),
+	/// A `System.Type`, by its serialized name.
+	Type(Option<String>),
+	/// The underlying value of an enum.
+	Enum(Box<AttributeValue>),
+	/// An argument of type `object`, along with the value it boxes.
+	Boxed(Box<AttributeValue>),
+	Array(Option<Vec<AttributeValue>>),
+}
+
+/// An argument that either decoded successfully, or the bytes it was read from if it did not.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AttributeArgument {
+	Value(AttributeValue),
+	Raw(Vec<u8>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NamedArgument {
+	pub is_property: bool,
+	pub name: String,
+	pub value: AttributeArgument,
+}
+
+/// Identifies an enum used by an argument, either by the type of a constructor parameter,
+/// or by the serialized type name of a named or boxed argument.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum AttributeEnum<'l> {
+	Token(MetadataToken),
+	Name(&'l str),
+}
+
+/// The arguments of a custom attribute blob.
+///
+/// Decoding never fails as a whole: it stops at the first argument that cannot be decoded,
+/// which holds the remaining bytes of the blob as [`AttributeArgument::Raw`], and the reason is added to `errors`.
+/// Arguments following it are left out.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AttributeArguments {
+	pub fixed: Vec<AttributeArgument>,
+	pub named: Vec<NamedArgument>,
+	pub errors: Vec<&'static str>,
+}
+
+#[derive(Debug, Clone)]
+enum ArgumentType {
+	Primitive(ElementType),
+	String,
+	Type,
+	Object,
+	Enum(ElementType),
+	SzArray(Box<ArgumentType>),
+}
+
+impl AttributeArguments {
+	/// Decodes `blob`, given the parameters of the attribute constructor.
+	/// `enum_type` returns the underlying type of the enums the arguments use, which are otherwise undecodable.
+	pub fn decode(
+		blob: &[u8],
+		params: &[TypeSignature],
+		enum_type: impl Fn(AttributeEnum) -> Option<TypeSignature>,
+	) -> Self {
+		let mut arguments = Self::default();
+		let mut decoder = AttributeDecoder {
+			stream: ByteStream::new(blob),
+			enum_type: &enum_type,
+		};
+
+		if decoder.stream.read::<u16>().ok() != Some(PROLOG) {
+			arguments.fixed.push(AttributeArgument::Raw(blob.to_vec()));
+			arguments.errors.push("Invalid custom attribute prolog");
+			return arguments;
+		}
+
+		for param in params {
+			let start = decoder.stream.position();
+			let value = decoder.parameter_type(param).and_then(|ty| decoder.read_value(&ty));
+			match value {
+				Ok(value) => arguments.fixed.push(AttributeArgument::Value(value)),
+				Err(error) => {
+					arguments.fixed.push(AttributeArgument::Raw(blob[start..].to_vec()));
+					arguments.errors.push(reason(error));
+					return arguments;
+				}
+			}
+		}
+
+		let count = match decoder.stream.read::<u16>() {
+			Ok(count) => count,
+			Err(_) if decoder.stream.remaining() == 0 => return arguments,
+			Err(error) => {
+				arguments.errors.push(reason(error));
+				return arguments;
+			}
+		};
+
+		for _ in 0..count {
+			let start = decoder.stream.position();
+			match decoder.read_named() {
+				Ok(named) => arguments.named.push(named),
+				Err((named, error)) => {
+					let value = AttributeArgument::Raw(blob[start..].to_vec());
+					let (is_property, name) = named.unwrap_or_default();
+					arguments.named.push(NamedArgument {
+						is_property,
+						name,
+						value,
+					});
+					arguments.errors.push(reason(error));
+					return arguments;
+				}
+			}
+		}
+
+		arguments
+	}
+
+	pub fn is_complete(&self) -> bool {
+		self.errors.is_empty()
+	}
+}
+
+fn reason(error: Error) -> &'static str {
+	match error {
+		Error::InvalidData(Some(reason)) => reason,
+		Error::UnexpectedEndOfStream => "Unexpected end of custom attribute blob",
+		_ => "Invalid custom attribute blob",
+	}
+}
+
+struct AttributeDecoder<'l, F> {
+	stream: ByteStream<'l>,
+	enum_type: &'l F,
+}
+
+impl<'l, F: Fn(AttributeEnum) -> Option<TypeSignature>> AttributeDecoder<'l, F> {
+	fn parameter_type(&self, ty: &TypeSignature) -> Result<ArgumentType, Error> {
+		let ty = match ty {
+			TypeSignature::Boolean => ArgumentType::Primitive(ElementType::Bool),
+			TypeSignature::Char => ArgumentType::Primitive(ElementType::Char),
+			TypeSignature::Int8 => ArgumentType::Primitive(ElementType::I1),
+			TypeSignature::UInt8 => ArgumentType::Primitive(ElementType::U1),
+			TypeSignature::Int16 => ArgumentType::Primitive(ElementType::I2),
+			TypeSignature::UInt16 => ArgumentType::Primitive(ElementType::U2),
+			TypeSignature::Int32 => ArgumentType::Primitive(ElementType::I4),
+			TypeSignature::UInt32 => ArgumentType::Primitive(ElementType::U4),
+			TypeSignature::Int64 => ArgumentType::Primitive(ElementType::I8),
+			TypeSignature::UInt64 => ArgumentType::Primitive(ElementType::U8),
+			TypeSignature::Float => ArgumentType::Primitive(ElementType::R4),
+			TypeSignature::Double => ArgumentType::Primitive(ElementType::R8),
+			TypeSignature::String => ArgumentType::String,
+			TypeSignature::Object => ArgumentType::Object,
+			// The only class other than string and object an attribute parameter can have is System.Type.
+			TypeSignature::Class(_) => ArgumentType::Type,
+			TypeSignature::ValueType(token) => self.enum_argument(AttributeEnum::Token(*token))?,
+			TypeSignature::SzArray(ty) => ArgumentType::SzArray(Box::new(self.parameter_type(ty)?)),
+			_ => return Err(Error::InvalidData(Some("Invalid custom attribute parameter type"))),
+		};
+
+		Ok(ty)
+	}
+
+	fn enum_argument(&self, ty: AttributeEnum) -> Result<ArgumentType, Error> {
+		let underlying = (self.enum_type)(ty).ok_or(Error::InvalidData(Some("Unknown enum type")))?;
+		match self.parameter_type(&underlying)? {
+			ArgumentType::Primitive(ty) => Ok(ArgumentType::Enum(ty)),
+			_ => Err(Error::InvalidData(Some("Invalid enum underlying type"))),
+		}
+	}
+
+	/// Reads a `FieldOrPropType`, as found before named and boxed arguments.
+	fn read_argument_type(&mut self) -> Result<ArgumentType, Error> {
+		let tag = self.stream.read::<u8>()?;
+		let ty = match tag {
+			BOXED => ArgumentType::Object,
+			ENUM => {
+				let name = self
+					.read_string()?
+					.ok_or(Error::InvalidData(Some("Invalid enum type name")))?;
+				self.enum_argument(AttributeEnum::Name(&name))?
+			}
+			_ => match ElementType::try_from(tag)? {
+				ElementType::String => ArgumentType::String,
+				ElementType::Type => ArgumentType::Type,
+				ElementType::SzArray => ArgumentType::SzArray(Box::new(self.read_argument_type()?)),
+				ty @ (ElementType::Bool
+				| ElementType::Char
+				| ElementType::I1
+				| ElementType::U1
+				| ElementType::I2
+				| ElementType::U2
+				| ElementType::I4
+				| ElementType::U4
+				| ElementType::I8
+				| ElementType::U8
+				| ElementType::R4
+				| ElementType::R8) => ArgumentType::Primitive(ty),
+				_ => return Err(Error::InvalidData(Some("Invalid custom attribute argument type"))),
+			},
+		};
+
+		Ok(ty)
+	}
+
+	fn read_named(&mut self) -> Result<NamedArgument, (Option<(bool, String)>, Error)> {
+		let is_property = match self.stream.read::<u8>().map_err(|error| (None, error))? {
+			FIELD => false,
+			PROPERTY => true,
+			_ => return Err((None, Error::InvalidData(Some("Invalid named argument kind")))),
+		};
+
+		let ty = self.read_argument_type().map_err(|error| (None, error))?;
+		let name = self
+			.read_string()
+			.and_then(|name| name.ok_or(Error::InvalidData(Some("Invalid named argument name"))))
+			.map_err(|error| (None, error))?;
+
+		match self.read_value(&ty) {
+			Ok(value) => Ok(NamedArgument {
+				is_property,
+				name,
+				value: AttributeArgument::Value(value),
+			}),
+			Err(error) => Err((Some((is_property, name)), error)),
+		}
+	}
+
+	fn read_value(&mut self, ty: &ArgumentType) -> Result<AttributeValue, Error> {
+		let value = match ty {
+			ArgumentType::Primitive(ty) => self.read_primitive(*ty)?,
+			ArgumentType::Enum(ty) => AttributeValue::Enum(Box::new(self.read_primitive(*ty)?)),
+			ArgumentType::String => AttributeValue::String(self.read_string()?),
+			ArgumentType::Type => AttributeValue::Type(self.read_string()?),
+			ArgumentType::Object => {
+				let ty = self.read_argument_type()?;
+				AttributeValue::Boxed(Box::new(self.read_value(&ty)?))
+			}
+			ArgumentType::SzArray(ty) => match self.stream.read::<u32>()? {
+				NULL_ARRAY => AttributeValue::Array(None),
+				count => {
+					let mut values = Vec::with_capacity((count as usize).min(self.stream.remaining()));
+					for _ in 0..count {
+						values.push(self.read_value(ty)?);
+					}
+					AttributeValue::Array(Some(values))
+				}
+			},
+		};
+
+		Ok(value)
+	}
+
+	fn read_primitive(&mut self, ty: ElementType) -> Result<AttributeValue, Error> {
+		let value = match ty {
+			ElementType::Bool => AttributeValue::Bool(self.stream.read::<u8>()? != 0),
+			ElementType::Char => AttributeValue::Char(self.stream.read()?),
+			ElementType::I1 => AttributeValue::Int8(self.stream.read()?),
+			ElementType::U1 => AttributeValue::UInt8(self.stream.read()?),
+			ElementType::I2 => AttributeValue::Int16(self.stream.read()?),
+			ElementType::U2 => AttributeValue::UInt16(self.stream.read()?),
+			ElementType::I4 => AttributeValue::Int32(self.stream.read()?),
+			ElementType::U4 => AttributeValue::UInt32(self.stream.read()?),
+			ElementType::I8 => AttributeValue::Int64(self.stream.read()?),
+			ElementType::U8 => AttributeValue::UInt64(self.stream.read()?),
+			ElementType::R4 => AttributeValue::Float(self.stream.read()?),
+			ElementType::R8 => AttributeValue::Double(self.stream.read()?),
+			_ => return Err(Error::InvalidData(Some("Invalid custom attribute argument type"))),
+		};
+
+		Ok(value)
+	}
+
+	/// Reads a `SerString`, which is null if it starts with [`NULL_SER_STRING`].
+	fn read_string(&mut self) -> Result<Option<String>, Error> {
+		Ok(self.stream.read_ser_string()?.map(str::to_string))
+	}
+}
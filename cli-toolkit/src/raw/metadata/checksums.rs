@@ -0,0 +1,41 @@
+use crate::raw::*;
+use crate::utilities::xxh64;
+
+/// The XXH64 checksum of every stream of a metadata root, in header order,
+/// to tell which streams of an assembly changed between two snapshots without parsing them.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct StreamChecksums {
+	streams: Vec<(String, u64)>,
+}
+
+impl StreamChecksums {
+	pub(crate) fn push(&mut self, name: &str, bytes: &[u8]) {
+		self.streams.push((name.to_string(), xxh64(bytes, 0)));
+	}
+
+	/// The checksum of the first stream with the given name, e.g. `#US`.
+	pub fn get(&self, name: &str) -> Option<u64> {
+		self.streams
+			.iter()
+			.find(|(stream, _)| stream == name)
+			.map(|(_, checksum)| *checksum)
+	}
+
+	pub fn iter(&self) -> impl Iterator<Item = (&str, u64)> {
+		self.streams.iter().map(|(name, checksum)| (name.as_str(), *checksum))
+	}
+
+	/// The streams whose checksum differs from the one in `previous`, including the ones only present in either.
+	/// Streams are listed in the order of `self`, followed by the ones only `previous` has.
+	pub fn changed_streams<'l>(&'l self, previous: &'l StreamChecksums) -> Vec<&'l str> {
+		let changed = self
+			.iter()
+			.filter(|(name, checksum)| previous.get(name) != Some(*checksum))
+			.map(|(name, _)| name);
+		let removed = previous
+			.iter()
+			.filter(|(name, _)| self.get(name).is_none())
+			.map(|(name, _)| name);
+		changed.chain(removed).collect()
+	}
+}
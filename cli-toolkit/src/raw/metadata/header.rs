@@ -1,3 +1,5 @@
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
 use crate::raw::*;
 
 #[derive(Debug, Clone)]
@@ -13,6 +15,81 @@ pub struct MetadataHeader<'l> {
 	offset: usize,
 	streams: &'l [u8],
 	assembly_bytes: &'l [u8],
+	slicing: HeapSlicing,
+}
+
+/// How the streams whose declared range extends past the end of the file are sliced.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum HeapSlicing {
+	/// Getting the heap fails with [`Error::OffsetOutOfBounds`].
+	#[default]
+	Strict,
+	/// The heap is clamped to the available bytes, and the stream is reported by [`MetadataHeader::truncated_streams`].
+	Clamp,
+}
+
+/// A stream whose declared range extends past the end of the file.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct TruncatedStream<'l> {
+	pub name: &'l str,
+	/// The absolute file offset of the start of the stream.
+	pub offset: usize,
+	pub declared_size: u32,
+	pub available_size: u32,
+}
+
+/// What a file offset falls into, as computed by [`MetadataHeader::locate_offset`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum OffsetLocation<'l> {
+	/// The metadata root, including the stream headers.
+	MetadataRoot,
+	/// The header of the `#~` stream, with its version, table masks and row counts.
+	TableHeapHeader,
+	/// A row of a table, along with the column the offset falls into if the table is modeled by a concrete type.
+	Table {
+		table: TableKind,
+		row: TableIndex,
+		column: Option<TableColumn>,
+	},
+	/// A byte of a heap. The index is the 1-based GUID index for `#GUID`, and the offset into the heap otherwise.
+	Heap { stream: &'l str, index: HeapIndex },
+	/// Any other byte of a stream, such as the padding after the last table.
+	Stream { stream: &'l str, offset: usize },
+}
+
+/// The runtime version a metadata root targets, as found in strings like `v4.0.30319`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct RuntimeVersion {
+	pub major: u32,
+	pub minor: u32,
+	pub build: Option<u32>,
+}
+
+impl RuntimeVersion {
+	pub fn parse(version: &str) -> Option<Self> {
+		let version = version.trim_end_matches('\0').strip_prefix('v')?;
+		let mut parts = version.split('.');
+		let major = parts.next()?.parse().ok()?;
+		let minor = parts.next()?.parse().ok()?;
+		let build = match parts.next() {
+			None => None,
+			Some(build) => Some(build.parse().ok()?),
+		};
+
+		match parts.next() {
+			None => Some(Self { major, minor, build }),
+			Some(_) => None,
+		}
+	}
+}
+
+impl Display for RuntimeVersion {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		match self.build {
+			None => write!(f, "v{}.{}", self.major, self.minor),
+			Some(build) => write!(f, "v{}.{}.{}", self.major, self.minor, build),
+		}
+	}
 }
 
 #[repr(C)]
@@ -25,7 +102,7 @@ pub struct StreamHeader<'l> {
 
 impl<'l> MetadataHeader<'l> {
 	pub(crate) fn new(assembly_bytes: &'l [u8], offset: usize) -> Result<Self, Error> {
-		let mut reader = ByteStream::new(&assembly_bytes[offset..]);
+		let mut reader = ByteStream::new(assembly_bytes.get(offset..).ok_or(Error::OffsetOutOfBounds)?);
 		let signature = reader.read::<u32>()?;
 		let major_version = reader.read::<u16>()?;
 		let minor_version = reader.read::<u16>()?;
@@ -33,7 +110,8 @@ impl<'l> MetadataHeader<'l> {
 		let length = reader.read::<u32>()?;
 		let version = {
 			let version = reader.read_null_terminated_str()?;
-			reader.skip(length as usize - version.len() - 1)?;
+			let padding = (length as usize).checked_sub(version.len() + 1);
+			reader.skip(padding.ok_or(Error::InvalidData(Some("Invalid metadata version length")))?)?;
 			version
 		};
 		let flags = reader.read::<u16>()?;
@@ -60,15 +138,146 @@ impl<'l> MetadataHeader<'l> {
 			stream_count,
 			offset,
 			assembly_bytes,
+			slicing: HeapSlicing::Strict,
 		})
 	}
 
-	pub(crate) fn get_heap<T: MetadataHeap<'l>>(&self) -> Result<Option<T>, Error> {
+	pub fn with_heap_slicing(self, slicing: HeapSlicing) -> Self {
+		Self { slicing, ..self }
+	}
+
+	pub fn heap_slicing(&self) -> HeapSlicing {
+		self.slicing
+	}
+
+	/// Reads a metadata root that is not embedded in a PE image, such as a standalone portable PDB.
+	pub fn standalone(bytes: &'l [u8]) -> Result<Self, Error> {
+		Self::new(bytes, 0)
+	}
+
+	pub fn runtime_version(&self) -> Option<RuntimeVersion> {
+		RuntimeVersion::parse(self.version)
+	}
+
+	/// Whether the metadata contains the `#JTD` marker stream of minimal EnC deltas,
+	/// in which every heap and table index is 4 bytes wide regardless of the heap sizes.
+	pub fn is_minimal_delta(&self) -> Result<bool, Error> {
+		Ok(self.get_stream_bytes("#JTD")?.is_some())
+	}
+
+	pub fn get_heap<T: MetadataHeap<'l>>(&self) -> Result<Option<T>, Error> {
 		let bytes = self.get_stream_bytes(T::cli_identifier())?;
-		Ok(bytes.map(|b| T::new(b)))
+		bytes.map(|b| T::new(b).with_header(self)).transpose()
 	}
 
-	fn stream_headers(&self) -> StreamHeaderIterator {
+	/// The absolute file offset of the metadata root.
+	pub fn offset(&self) -> usize {
+		self.offset
+	}
+
+	/// The name, absolute file offset and declared size of every stream, in header order.
+	/// Sizes are not checked against the file, see [`MetadataHeader::truncated_streams`].
+	pub fn streams(&self) -> Result<Vec<(&'l str, usize, u32)>, Error> {
+		self.stream_headers()
+			.map(|header| header.map(|header| (header.name, self.offset + header.offset as usize, header.size)))
+			.collect()
+	}
+
+	/// The streams that do not fit in the file, whichever the slicing mode.
+	pub fn truncated_streams(&self) -> Result<Vec<TruncatedStream<'l>>, Error> {
+		let mut truncated = vec![];
+		for header in self.stream_headers() {
+			let header = header?;
+			let start = self.offset + header.offset as usize;
+			let available = self.assembly_bytes.len().saturating_sub(start);
+			if header.size as usize > available {
+				truncated.push(TruncatedStream {
+					name: header.name,
+					offset: start,
+					declared_size: header.size,
+					available_size: available as u32,
+				});
+			}
+		}
+
+		Ok(truncated)
+	}
+
+	/// Maps an absolute file offset back to the metadata structure it belongs to,
+	/// or returns `None` if it lies outside of the metadata.
+	pub fn locate_offset(&self, offset: usize) -> Result<Option<OffsetLocation<'l>>, Error> {
+		if offset < self.offset || offset >= self.assembly_bytes.len() {
+			return Ok(None);
+		}
+
+		let relative = offset - self.offset;
+		let root_size = 20 + self.length as usize + self.streams.len();
+		if relative < root_size {
+			return Ok(Some(OffsetLocation::MetadataRoot));
+		}
+
+		for header in self.stream_headers() {
+			let header = header?;
+			let start = header.offset as usize;
+			if relative < start || relative >= start + header.size as usize {
+				continue;
+			}
+
+			let (stream, offset) = (header.name, relative - start);
+			let location = match stream {
+				"#~" => {
+					let Some(tables) = self.get_heap::<TableHeap>()? else { return Ok(None) };
+					tables
+						.locate_offset(offset)?
+						.unwrap_or(OffsetLocation::Stream { stream, offset })
+				}
+				"#GUID" => OffsetLocation::Heap {
+					stream,
+					index: HeapIndex((offset / 16) as u32 + 1),
+				},
+				"#Strings" | "#Blob" | "#US" => OffsetLocation::Heap {
+					stream,
+					index: HeapIndex(offset as u32),
+				},
+				_ => OffsetLocation::Stream { stream, offset },
+			};
+
+			return Ok(Some(location));
+		}
+
+		Ok(None)
+	}
+
+	/// The absolute file range of the row a token refers to, the inverse of [`MetadataHeader::locate_offset`] for rows.
+	/// Returns `None` if the token does not refer to a row of a present table.
+	pub fn token_range(&self, token: MetadataToken) -> Result<Option<Range<usize>>, Error> {
+		let Some(kind) = token.table_kind() else { return Ok(None) };
+		let Some(tables) = self.get_heap::<TableHeap>()? else { return Ok(None) };
+		let Some(row) = tables.row_range(kind, TableIndex(token.index() as u32)) else { return Ok(None) };
+		for header in self.stream_headers() {
+			let header = header?;
+			if header.name == TableHeap::cli_identifier() {
+				let start = self.offset + header.offset as usize;
+				return Ok(Some(start + row.start..start + row.end));
+			}
+		}
+
+		Ok(None)
+	}
+
+	/// Checksums every stream, sliced according to the [`HeapSlicing`] of the header.
+	#[cfg(feature = "checksums")]
+	pub fn stream_checksums(&self) -> Result<StreamChecksums, Error> {
+		let mut checksums = StreamChecksums::default();
+		for header in self.stream_headers() {
+			let header = header?;
+			checksums.push(header.name, self.slice_stream(&header)?);
+		}
+
+		Ok(checksums)
+	}
+
+	fn stream_headers(&self) -> StreamHeaderIterator<'l> {
 		StreamHeaderIterator {
 			reader: ByteStream::new(self.streams),
 		}
@@ -77,15 +286,23 @@ impl<'l> MetadataHeader<'l> {
 	fn get_stream_bytes(&self, name: &str) -> Result<Option<&'l [u8]>, Error> {
 		for header in self.stream_headers() {
 			let header = header?;
-			let start = self.offset + header.offset as usize;
-
 			if header.name == name {
-				return Ok(Some(&self.assembly_bytes[start..start + header.size as usize]));
+				return self.slice_stream(&header).map(Some);
 			}
 		}
 
 		Ok(None)
 	}
+
+	fn slice_stream(&self, header: &StreamHeader<'l>) -> Result<&'l [u8], Error> {
+		let start = self.offset.saturating_add(header.offset as usize);
+		let bytes = self.assembly_bytes.get(start..start.saturating_add(header.size as usize));
+		match (bytes, self.slicing) {
+			(Some(bytes), _) => Ok(bytes),
+			(None, HeapSlicing::Strict) => Err(Error::OffsetOutOfBounds),
+			(None, HeapSlicing::Clamp) => Ok(self.assembly_bytes.get(start..).unwrap_or(&[])),
+		}
+	}
 }
 
 pub struct StreamHeaderIterator<'l> {
@@ -112,10 +329,9 @@ impl<'l> Iterator for StreamHeaderIterator<'l> {
 					Err(e) => return Some(Err(e)),
 				};
 
-				//Not sure if this is ok
-				if self.reader.skip((4usize.wrapping_sub(name.len() + 1)) % 4).is_err() {
-					self.reader.skip(self.reader.remaining()).unwrap();
-				}
+				// The names are padded to 4 bytes, which the last one may be missing.
+				let padding = (4usize.wrapping_sub(name.len() + 1)) % 4;
+				let _ = self.reader.skip(padding.min(self.reader.remaining()));
 				Some(Ok(StreamHeader { offset, size, name }))
 			}
 		}
@@ -0,0 +1,240 @@
+use std::collections::hash_map::DefaultHasher;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::ops::Range;
+use crate::raw::*;
+
+/// A string added to a [`StringHeapBuilder`], resolved to its offset by the [`BuiltStringHeap`].
+/// Handles are assigned in insertion order, so that they do not depend on how the heap is laid out.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct StringHandle(u32);
+
+impl StringHandle {
+	/// The empty string, which is always at offset 0.
+	pub const EMPTY: Self = Self(0);
+
+	pub fn get(&self) -> u32 {
+		self.0
+	}
+}
+
+/// The contents of a heap, stored back to back in a single buffer and deduplicated by content hash.
+#[derive(Debug, Default, Clone)]
+struct Arena {
+	bytes: Vec<u8>,
+	entries: Vec<Range<usize>>,
+	lookup: HashMap<u64, Vec<u32>>,
+}
+
+impl Arena {
+	/// Returns the position of the entry, which is only added if it is not present yet.
+	fn intern(&mut self, value: &[u8]) -> u32 {
+		let mut hasher = DefaultHasher::new();
+		value.hash(&mut hasher);
+		let candidates = self.lookup.entry(hasher.finish()).or_default();
+		for entry in candidates.iter() {
+			if self.bytes[self.entries[*entry as usize].clone()] == *value {
+				return *entry;
+			}
+		}
+
+		let entry = self.entries.len() as u32;
+		let start = self.bytes.len();
+		self.bytes.extend_from_slice(value);
+		self.entries.push(start..self.bytes.len());
+		candidates.push(entry);
+		entry
+	}
+
+	fn get(&self, entry: u32) -> Option<&[u8]> {
+		let range = self.entries.get(entry as usize)?;
+		Some(&self.bytes[range.clone()])
+	}
+}
+
+/// Builds a `#Strings` heap, storing every distinct string once.
+///
+/// With [`StringHeapBuilder::suffix_sharing`], strings that end another string are not stored on their own,
+/// e.g. `"BC"` is found in the middle of `"ABC"`, like ILAsm and Roslyn do. Offsets are then only known
+/// once every string has been added, which is why [`StringHeapBuilder::add`] returns a handle rather than an offset.
+#[derive(Debug, Clone)]
+pub struct StringHeapBuilder {
+	arena: Arena,
+	suffix_sharing: bool,
+}
+
+impl Default for StringHeapBuilder {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl StringHeapBuilder {
+	pub fn new() -> Self {
+		let mut arena = Arena::default();
+		arena.intern(&[]);
+		Self {
+			arena,
+			suffix_sharing: false,
+		}
+	}
+
+	pub fn suffix_sharing(mut self, enabled: bool) -> Self {
+		self.suffix_sharing = enabled;
+		self
+	}
+
+	/// Adds a string, returning the handle of the identical string added before if there is one.
+	pub fn add(&mut self, value: &str) -> StringHandle {
+		StringHandle(self.arena.intern(value.as_bytes()))
+	}
+
+	pub fn get(&self, handle: StringHandle) -> Option<&str> {
+		let bytes = self.arena.get(handle.0)?;
+		std::str::from_utf8(bytes).ok()
+	}
+
+	/// The number of distinct strings, including the empty string.
+	pub fn len(&self) -> usize {
+		self.arena.entries.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.len() == 1
+	}
+
+	/// Lays the heap out, padded to 4 bytes. The output only depends on the strings and the order they were added in.
+	pub fn build(&self) -> BuiltStringHeap {
+		let mut offsets = vec![0; self.arena.entries.len()];
+		let mut bytes = vec![0];
+		let mut order = (1..self.arena.entries.len() as u32).collect::<Vec<_>>();
+		if self.suffix_sharing {
+			// Sorting by reversed contents, longest first among equal endings, puts every string right after
+			// the strings it is a suffix of.
+			order.sort_by(|a, b| {
+				let a = self.arena.get(*a).unwrap_or_default();
+				let b = self.arena.get(*b).unwrap_or_default();
+				compare_reversed(b, a)
+			});
+		}
+
+		let mut previous = None::<(&[u8], usize)>;
+		for entry in order {
+			let value = self.arena.get(entry).unwrap_or_default();
+			let shared = previous.filter(|_| self.suffix_sharing).and_then(|(previous, offset)| {
+				let start = previous.len().checked_sub(value.len())?;
+				(previous[start..] == *value).then_some(offset + start)
+			});
+
+			match shared {
+				Some(offset) => offsets[entry as usize] = offset as u32,
+				None => {
+					offsets[entry as usize] = bytes.len() as u32;
+					previous = Some((value, bytes.len()));
+					bytes.extend_from_slice(value);
+					bytes.push(0);
+				}
+			}
+		}
+
+		pad(&mut bytes);
+		BuiltStringHeap { bytes, offsets }
+	}
+}
+
+fn compare_reversed(a: &[u8], b: &[u8]) -> Ordering {
+	a.iter().rev().cmp(b.iter().rev())
+}
+
+/// A `#Strings` heap laid out by [`StringHeapBuilder::build`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct BuiltStringHeap {
+	bytes: Vec<u8>,
+	offsets: Vec<u32>,
+}
+
+impl BuiltStringHeap {
+	pub fn bytes(&self) -> &[u8] {
+		&self.bytes
+	}
+
+	pub fn into_bytes(self) -> Vec<u8> {
+		self.bytes
+	}
+
+	pub fn offset(&self, handle: StringHandle) -> Option<HeapIndex> {
+		self.offsets.get(handle.0 as usize).copied().map(HeapIndex)
+	}
+
+	/// The width of the indices into the heap, to be declared in the `HeapSizes` of the [`TableHeap`].
+	pub fn index_size(&self) -> IndexSize {
+		index_size(self.bytes.len())
+	}
+}
+
+/// Builds a `#Blob` heap, storing every distinct blob once along with its compressed length.
+///
+/// Unlike strings, blobs are never shared with the end of another blob, since their length comes first.
+/// Their offsets are therefore assigned as they are added, and do not change afterwards.
+#[derive(Debug, Default, Clone)]
+pub struct BlobHeapBuilder {
+	/// The heap without its leading null byte, since every entry is stored with its length.
+	arena: Arena,
+}
+
+impl BlobHeapBuilder {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Adds a blob, returning the offset of the identical blob added before if there is one.
+	/// Fails if the blob is too large for its length to be compressed.
+	pub fn add(&mut self, value: &[u8]) -> Result<HeapIndex, Error> {
+		if value.is_empty() {
+			return Ok(HeapIndex(0));
+		}
+
+		let mut entry = ByteWriter::default();
+		entry.write_compressed_u32(u32::try_from(value.len()).unwrap_or(u32::MAX))?;
+		entry.write_bytes(value);
+
+		let entry = self.arena.intern(&entry.into_bytes());
+		let offset = self.arena.entries[entry as usize].start + 1;
+		HeapIndex::new(offset).ok_or(Error::InvalidData(Some("Blob heap too large")))
+	}
+
+	/// The number of distinct blobs, not counting the empty blob.
+	pub fn len(&self) -> usize {
+		self.arena.entries.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	/// The heap padded to 4 bytes.
+	pub fn build(&self) -> Vec<u8> {
+		let mut bytes = Vec::with_capacity(self.arena.bytes.len() + 4);
+		bytes.push(0);
+		bytes.extend_from_slice(&self.arena.bytes);
+		pad(&mut bytes);
+		bytes
+	}
+
+	/// The width of the indices into the heap, to be declared in the `HeapSizes` of the [`TableHeap`].
+	pub fn index_size(&self) -> IndexSize {
+		index_size(self.arena.bytes.len() + 1)
+	}
+}
+
+fn index_size(len: usize) -> IndexSize {
+	match len <= u16::MAX as usize {
+		true => IndexSize::Slim,
+		false => IndexSize::Fat,
+	}
+}
+
+fn pad(bytes: &mut Vec<u8>) {
+	bytes.resize(bytes.len().next_multiple_of(4), 0);
+}
@@ -2,6 +2,7 @@ use std::fmt::{Debug, Formatter};
 use bitvec::array::BitArray;
 use strum::IntoEnumIterator;
 use std::mem::size_of;
+use std::ops::Range;
 use crate::raw::*;
 use indoc::indoc;
 use uuid::Uuid;
@@ -19,24 +20,37 @@ impl<'l> MetadataHeap<'l> for StringHeap<'l> {
 		"#Strings"
 	}
 	fn idx_size(tables: &TableHeap) -> IndexSize {
-		match (tables.heap_sizes().data[0] & 0x1) != 0 {
-			true => IndexSize::Fat,
-			false => IndexSize::Slim,
-		}
+		tables.heap_index_size(0x1)
 	}
 }
 
 impl<'l> StringHeap<'l> {
+	pub fn len(&self) -> usize {
+		self.bytes.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.bytes.is_empty()
+	}
+
+	pub fn bytes(&self) -> &'l [u8] {
+		self.bytes
+	}
+
 	pub fn get_string(&self, index: HeapIndex) -> &'l str {
-		let bytes = &self.bytes[index.0 as usize..];
+		let bytes = self.bytes.get(index.0 as usize..).unwrap_or(&[]);
 		let bytes = &bytes[..bytes.iter().position(|c| *c == 0).unwrap_or(bytes.len())];
-		unsafe { std::str::from_utf8_unchecked(bytes) }
+		// Invalid UTF-8 is cut off rather than trusted, as obfuscators are known to store it.
+		match std::str::from_utf8(bytes) {
+			Ok(string) => string,
+			Err(error) => std::str::from_utf8(&bytes[..error.valid_up_to()]).unwrap_or_default(),
+		}
 	}
 }
 
 impl Debug for StringHeap<'_> {
 	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-		unsafe { write!(f, "{:?}", std::str::from_utf8_unchecked(self.bytes)) }
+		write!(f, "{:?}", String::from_utf8_lossy(self.bytes))
 	}
 }
 
@@ -53,10 +67,29 @@ impl<'l> MetadataHeap<'l> for GuidHeap<'l> {
 		"#GUID"
 	}
 	fn idx_size(tables: &TableHeap) -> IndexSize {
-		match (tables.heap_sizes().data[0] & 0x2) != 0 {
-			true => IndexSize::Fat,
-			false => IndexSize::Slim,
-		}
+		tables.heap_index_size(0x2)
+	}
+}
+
+impl<'l> GuidHeap<'l> {
+	/// The number of GUIDs in the heap. GUID indices are 1-based.
+	pub fn len(&self) -> usize {
+		self.bytes.len() / size_of::<Uuid>()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	/// The GUID at a 1-based index, or the nil GUID for index 0.
+	pub fn get(&self, index: HeapIndex) -> Result<Uuid, Error> {
+		let Some(position) = (index.0 as usize).checked_sub(1) else { return Ok(Uuid::nil()) };
+		let start = position * size_of::<Uuid>();
+		let bytes = self
+			.bytes
+			.get(start..start + size_of::<Uuid>())
+			.ok_or(Error::OffsetOutOfBounds)?;
+		Uuid::from_slice_le(bytes).map_err(|_| Error::OffsetOutOfBounds)
 	}
 }
 
@@ -83,34 +116,28 @@ impl<'l> MetadataHeap<'l> for BlobHeap<'l> {
 		"#Blob"
 	}
 	fn idx_size(tables: &TableHeap) -> IndexSize {
-		match (tables.heap_sizes().data[0] & 0x4) != 0 {
-			true => IndexSize::Fat,
-			false => IndexSize::Slim,
-		}
+		tables.heap_index_size(0x4)
 	}
 }
 
 impl<'l> BlobHeap<'l> {
+	pub fn len(&self) -> usize {
+		self.bytes.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.bytes.is_empty()
+	}
+
+	pub fn bytes(&self) -> &'l [u8] {
+		self.bytes
+	}
+
 	pub fn get_blob(&self, index: HeapIndex) -> Result<&'l [u8], Error> {
 		let mut reader = ByteStream::new(self.bytes);
 		reader.seek(index.0 as usize)?;
 
-		let length = {
-			let byte_0 = reader.read::<u8>()?;
-			if byte_0 & 0x80 == 0 {
-				(byte_0 & 0x7F) as usize
-			} else if byte_0 & 0xC0 == 0x80 {
-				let byte_1 = reader.read::<u8>()?;
-				(((byte_0 & 0x3F) as usize) << 8) + byte_1 as usize
-			} else if byte_0 & 0xE0 == 0xC0 {
-				let byte_1 = reader.read::<u8>()?;
-				let byte_2 = reader.read::<u8>()?;
-				(((byte_0 & 0x3F) as usize) << 16) + ((byte_1 as usize) << 8) + byte_2 as usize
-			} else {
-				return Err(Error::InvalidData(None));
-			}
-		};
-
+		let length = reader.read_compressed_u32()? as usize;
 		reader.read_slice::<u8>(length)
 	}
 }
@@ -121,6 +148,7 @@ impl Debug for BlobHeap<'_> {
 	}
 }
 
+#[derive(Copy, Clone)]
 pub struct UserStringHeap<'l> {
 	bytes: &'l [u8],
 }
@@ -132,25 +160,176 @@ impl<'l> MetadataHeap<'l> for UserStringHeap<'l> {
 	fn cli_identifier() -> &'static str {
 		"#US"
 	}
+	/// No table column indexes the user-string (`#US`) heap, only the tokens of `ldstr` instructions refer to it.
 	fn idx_size(_: &TableHeap) -> IndexSize {
-		unimplemented!()
+		IndexSize::Slim
+	}
+}
+
+impl<'l> UserStringHeap<'l> {
+	pub fn len(&self) -> usize {
+		self.bytes.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.bytes.is_empty()
+	}
+
+	/// The string at the given heap offset, i.e. the low 24 bits of an `ldstr` token.
+	pub fn get(&self, offset: u32) -> Result<UserString<'l>, Error> {
+		let mut reader = ByteStream::new(self.bytes);
+		reader.seek(offset as usize)?;
+
+		let length = reader.read_compressed_u32()? as usize;
+		let bytes = reader.read_slice::<u8>(length)?;
+		match bytes.split_last() {
+			None => Ok(UserString {
+				units: &[],
+				terminal: 0,
+			}),
+			Some((terminal, units)) if units.len() % 2 == 0 => Ok(UserString {
+				units,
+				terminal: *terminal,
+			}),
+			Some(_) => Err(Error::InvalidData(Some("User string of odd length"))),
+		}
+	}
+
+	pub fn get_string(&self, offset: u32, policy: Utf16Policy) -> Result<UserStringValue, Error> {
+		self.get(offset)?.decode(policy)
+	}
+
+	/// Every string in the heap along with its offset, skipping the empty entry at offset 0.
+	///
+	/// Malformed entries are yielded as errors and skipped, unless their length runs past the end of the heap,
+	/// in which case the iteration ends.
+	pub fn iter(&self) -> impl Iterator<Item = Result<(u32, UserString<'l>), Error>> + 'l {
+		let heap = *self;
+		let mut offset = 1;
+		std::iter::from_fn(move || {
+			// The heap is padded with zeroes, which read as empty strings.
+			while heap.bytes.get(offset).is_some_and(|b| *b == 0) {
+				offset += 1;
+			}
+
+			if offset >= heap.bytes.len() {
+				return None;
+			}
+
+			let mut reader = ByteStream::new(&heap.bytes[offset..]);
+			let size = reader.read_compressed_u32().map(|length| reader.position() + length as usize);
+			let entry = heap.get(offset as u32).map(|string| (offset as u32, string));
+			offset = match size {
+				Ok(size) if offset + size <= heap.bytes.len() => offset + size,
+				_ => heap.bytes.len(),
+			};
+			Some(entry)
+		})
 	}
 }
 
 impl Debug for UserStringHeap<'_> {
 	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-		unsafe { write!(f, "{:?}", std::str::from_utf8_unchecked(self.bytes)) }
+		write!(f, "UserStringHeap([u8; {}])", self.bytes.len())
+	}
+}
+
+/// How to decode user strings that are not well-formed UTF-16, which obfuscators store on purpose.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Utf16Policy {
+	/// Fail on unpaired surrogates.
+	Strict,
+	/// Replace unpaired surrogates with U+FFFD.
+	Replace,
+	/// Return the code units of malformed strings as they are.
+	PreserveRaw,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum UserStringValue {
+	Text(String),
+	/// The code units of a string that is not well-formed UTF-16.
+	Raw(Vec<u16>),
+}
+
+/// A string of the `#US` heap, stored as little endian UTF-16 code units.
+#[derive(Copy, Clone, Eq, PartialEq, Hash)]
+pub struct UserString<'l> {
+	units: &'l [u8],
+	terminal: u8,
+}
+
+impl<'l> UserString<'l> {
+	/// The number of UTF-16 code units.
+	pub fn len(&self) -> usize {
+		self.units.len() / 2
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.units.is_empty()
+	}
+
+	/// The raw little endian bytes of the code units, without the terminal byte.
+	pub fn bytes(&self) -> &'l [u8] {
+		self.units
+	}
+
+	pub fn code_units(&self) -> impl Iterator<Item = u16> + 'l {
+		self.units
+			.chunks_exact(2)
+			.map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+	}
+
+	/// The terminal byte, which is set if any code unit needs more than a plain 8 bit representation.
+	pub fn has_special_chars(&self) -> bool {
+		self.terminal != 0
+	}
+
+	pub fn is_well_formed(&self) -> bool {
+		char::decode_utf16(self.code_units()).all(|c| c.is_ok())
+	}
+
+	pub fn to_string_lossy(&self) -> String {
+		char::decode_utf16(self.code_units())
+			.map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+			.collect()
+	}
+
+	pub fn decode(&self, policy: Utf16Policy) -> Result<UserStringValue, Error> {
+		let decoded = char::decode_utf16(self.code_units()).collect::<Result<String, _>>();
+		match (decoded, policy) {
+			(Ok(string), _) => Ok(UserStringValue::Text(string)),
+			(Err(_), Utf16Policy::Strict) => Err(Error::InvalidData(Some("Invalid UTF-16 user string"))),
+			(Err(_), Utf16Policy::Replace) => Ok(UserStringValue::Text(self.to_string_lossy())),
+			(Err(_), Utf16Policy::PreserveRaw) => Ok(UserStringValue::Raw(self.code_units().collect())),
+		}
+	}
+}
+
+impl Debug for UserString<'_> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		Debug::fmt(&self.to_string_lossy(), f)
 	}
 }
 
 #[derive(Copy, Clone)]
 pub struct TableHeap<'l> {
 	bytes: &'l [u8],
+	/// Computed once, since the size of every row depends on them.
+	widths: IndexWidths,
+	minimal_delta: bool,
 }
 
 impl<'l> MetadataHeap<'l> for TableHeap<'l> {
 	fn new(bytes: &'l [u8]) -> Self {
-		Self { bytes }
+		let mut heap = Self {
+			bytes,
+			widths: IndexWidths::SLIM,
+			minimal_delta: false,
+		};
+
+		heap.widths = IndexWidths::compute(&heap);
+		heap
 	}
 	fn cli_identifier() -> &'static str {
 		"#~"
@@ -158,19 +337,107 @@ impl<'l> MetadataHeap<'l> for TableHeap<'l> {
 	fn idx_size(_: &TableHeap) -> IndexSize {
 		unimplemented!()
 	}
+	fn with_header(mut self, header: &MetadataHeader<'l>) -> Result<Self, Error> {
+		if header.is_minimal_delta()? {
+			self = self.minimal_delta();
+		}
+
+		match header.get_heap::<PdbHeap>()? {
+			Some(pdb) => self.with_pdb(&pdb),
+			None => Ok(self),
+		}
+	}
 }
 
 impl<'l> TableHeap<'l> {
 	pub fn major_version(&self) -> u8 {
-		self.bytes[4]
+		self.header_byte(4)
 	}
 
 	pub fn minor_version(&self) -> u8 {
-		self.bytes[5]
+		self.header_byte(5)
 	}
 
+	/// Whether the table is present in the heap, i.e. its bit is set in the `valid` mask.
 	pub fn has_table(&self, kind: TableKind) -> bool {
-		self.valid().get(kind as usize).as_deref().cloned().unwrap_or(false)
+		self.valid_mask().contains(kind)
+	}
+
+	/// Whether the heap claims that the table is sorted, i.e. its bit is set in the `sorted` mask.
+	/// Obfuscators may set it for tables that are not, see [`TableHeap::verify_sorted`].
+	pub fn is_declared_sorted(&self, kind: TableKind) -> bool {
+		self.sorted_mask().contains(kind)
+	}
+
+	/// The tables present in the heap.
+	pub fn valid_mask(&self) -> TableMask {
+		TableMask::from_bits(self.header_mask(8))
+	}
+
+	/// The tables the heap claims to be sorted.
+	pub fn sorted_mask(&self) -> TableMask {
+		TableMask::from_bits(self.header_mask(16))
+	}
+
+	/// Checks whether the rows of a table are actually in order, by reading its key columns, see [`SortedRow`].
+	/// Returns `None` for the tables ECMA-335 does not require to be sorted, and `true` for absent tables.
+	pub fn verify_sorted(&self, kind: TableKind) -> Result<Option<bool>, Error> {
+		match kind {
+			TableKind::ClassLayout => self.verify_table_sorted::<ClassLayoutTable>(),
+			TableKind::Constant => self.verify_table_sorted::<ConstantTable>(),
+			TableKind::CustomAttribute => self.verify_table_sorted::<CustomAttributeTable>(),
+			TableKind::DeclSecurity => self.verify_table_sorted::<DeclSecurityTable>(),
+			TableKind::FieldLayout => self.verify_table_sorted::<FieldLayoutTable>(),
+			TableKind::FieldMarshal => self.verify_table_sorted::<FieldMarshalTable>(),
+			TableKind::FieldRVA => self.verify_table_sorted::<FieldRVATable>(),
+			TableKind::GenericParam => self.verify_table_sorted::<GenericParamTable>(),
+			TableKind::GenericParamConstraint => self.verify_table_sorted::<GenericParamConstraintTable>(),
+			TableKind::ImplMap => self.verify_table_sorted::<ImplMapTable>(),
+			TableKind::InterfaceImpl => self.verify_table_sorted::<InterfaceImplTable>(),
+			TableKind::MethodImpl => self.verify_table_sorted::<MethodImplTable>(),
+			TableKind::MethodSemantics => self.verify_table_sorted::<MethodSemanticsTable>(),
+			TableKind::NestedClass => self.verify_table_sorted::<NestedClassTable>(),
+			_ => Ok(None),
+		}
+	}
+
+	fn verify_table_sorted<T: MetadataTable<'l>>(&self) -> Result<Option<bool>, Error>
+	where
+		T::Row: SortedRow,
+	{
+		match self.get_table::<T>()? {
+			Some(table) => is_table_sorted(&table).map(Some),
+			None => Ok(Some(true)),
+		}
+	}
+
+	pub fn index_widths(&self) -> IndexWidths {
+		self.widths
+	}
+
+	/// Sizes the indices into the type system tables of the assembly of a standalone portable PDB
+	/// by the row counts its `#Pdb` stream records, since those tables are not part of the heap.
+	/// Done by [`MetadataHeader::get_heap`] when the metadata root has a `#Pdb` stream.
+	pub fn with_pdb(mut self, pdb: &PdbHeap) -> Result<Self, Error> {
+		let mut external = [0u32; 64];
+		for kind in pdb.referenced_tables()? {
+			external[kind as usize] = pdb.type_system_row_count(kind)?;
+		}
+
+		self.widths = IndexWidths::compute_with_external_rows(&self, &external);
+		Ok(self)
+	}
+
+	/// Makes every heap and table index 4 bytes wide, as in the tables of a minimal EnC delta.
+	/// Done by [`MetadataHeader::get_heap`] when the metadata root has a `#JTD` stream.
+	pub fn minimal_delta(mut self) -> Self {
+		self.minimal_delta = true;
+		self.widths = IndexWidths::FAT;
+		self
+	}
+
+	pub fn is_minimal_delta(&self) -> bool {
+		self.minimal_delta
 	}
 
 	pub fn get_table<T: MetadataTableImpl<'l>>(&self) -> Result<Option<T>, Error> {
@@ -178,70 +445,136 @@ impl<'l> TableHeap<'l> {
 			return Ok(None);
 		}
 
+		let kind = T::cli_identifier();
 		let mut reader = ByteStream::new(self.bytes);
-		reader.skip(24 + 4 * self.table_count())?;
-
-		let rows = self.rows();
-		let indices = 0..self.table_count();
-		let tables = TableKind::iter().filter(|k| self.has_table(*k));
-
-		for (index, table) in indices.zip(tables) {
-			let rows = rows[index] as usize;
-			let row_size = self.row_size(table);
-			let table_size = rows * row_size;
-
-			if table == T::cli_identifier() {
-				let bytes = reader.read_slice::<u8>(table_size)?;
-				return Ok(Some(T::new(bytes, self)?));
-			} else {
-				reader.skip(table_size)?;
+		reader.skip(self.table_offset(kind)?)?;
+
+		let bytes = reader.read_slice::<u8>(self.row_count(kind)? * self.row_size(kind))?;
+		Ok(Some(T::new(bytes, self)?))
+	}
+
+	/// The offset of the first row of a table relative to the start of the heap.
+	/// Fails if an unknown table comes before it, since the size of its rows cannot be known.
+	fn table_offset(&self, kind: TableKind) -> Result<usize, Error> {
+		let preceding = TableMask::from(kind).bits() - 1;
+		if self.valid_mask().unknown_bits() & preceding != 0 {
+			return Err(Error::InvalidData(Some("Unknown metadata table")));
+		}
+
+		let mut offset = 24 + 4 * self.table_count();
+		for (table, rows) in self.present_tables()? {
+			if table == kind {
+				break;
 			}
+
+			offset += rows as usize * self.row_size(table);
+		}
+
+		Ok(offset)
+	}
+
+	/// The columns of a table, or none for the tables that are not modeled by a concrete type.
+	pub fn columns(&self, kind: TableKind) -> Vec<TableColumn> {
+		dispatch_table!(kind, self.table_columns(), vec![])
+	}
+
+	fn table_columns<T: MetadataTableImpl<'l>>(&self) -> Vec<TableColumn> {
+		T::calc_columns(self)
+	}
+
+	/// Locates an offset relative to the start of the heap, or returns `None` if it lies past the last table.
+	pub fn locate_offset(&self, offset: usize) -> Result<Option<OffsetLocation<'static>>, Error> {
+		let mut start = 24 + 4 * self.table_count();
+		if offset < start {
+			return Ok(Some(OffsetLocation::TableHeapHeader));
+		}
+
+		for table in self.iter_tables()? {
+			let end = start + table.bytes.len();
+			if offset < end {
+				let row = (offset - start) / table.row_size;
+				let column_offset = (offset - start) % table.row_size;
+				let column = self
+					.columns(table.kind)
+					.into_iter()
+					.find(|column| column_offset < column.offset + column.size);
+
+				return Ok(Some(OffsetLocation::Table {
+					table: table.kind,
+					row: TableIndex(row as u32 + 1),
+					column,
+				}));
+			}
+
+			start = end;
 		}
 
 		Ok(None)
 	}
 
-	fn heap_sizes(&self) -> BitArray<[u8; 1]> {
-		BitArray::new([self.bytes[6]])
+	/// The range of a row relative to the start of the heap, or `None` if the table is missing or has no such row.
+	pub fn row_range(&self, kind: TableKind, row: TableIndex) -> Option<Range<usize>> {
+		if row.is_null() || row.0 as usize > self.row_count(kind).ok()? {
+			return None;
+		}
+
+		let row_size = self.row_size(kind);
+		let start = self.table_offset(kind).ok()? + (row.0 as usize - 1) * row_size;
+		Some(start..start + row_size)
 	}
 
-	fn valid(&self) -> BitArray<[u64; 1]> {
-		let mut valid = [0; 8];
-		valid.copy_from_slice(&self.bytes[8..16]);
-		BitArray::new([u64::from_le_bytes(valid)])
+	/// The kind and row count of every present known table, in table order.
+	/// Row counts are looked up by the position of the table in the `valid` mask, skipping those of unknown tables.
+	pub fn present_tables(&self) -> Result<impl Iterator<Item = (TableKind, u32)>, Error> {
+		let valid = self.valid_mask();
+		let rows = self.rows()?;
+		Ok(valid.iter().map(move |kind| (kind, rows[valid.position(kind)])))
 	}
 
-	fn sorted(&self) -> BitArray<[u64; 1]> {
-		let mut valid = [0; 8];
-		valid.copy_from_slice(&self.bytes[16..24]);
-		BitArray::new([u64::from_le_bytes(valid)])
+	// A header that is cut short reads as zeroes, i.e. as a heap without any table.
+	fn header_byte(&self, offset: usize) -> u8 {
+		self.bytes.get(offset).copied().unwrap_or(0)
 	}
 
-	fn table_count(&self) -> usize {
-		self.valid().count_ones()
+	fn header_mask(&self, offset: usize) -> u64 {
+		let mut reader = ByteStream::new(self.bytes);
+		reader.seek(offset).and_then(|_| reader.read::<u64>()).unwrap_or(0)
 	}
 
-	fn rows(&self) -> &[u32] {
-		let count = self.table_count();
-		let bytes = &self.bytes[24..24 + 4 * count];
-		unsafe { std::slice::from_raw_parts(bytes.as_ptr() as *const u32, count) }
+	fn heap_sizes(&self) -> BitArray<[u8; 1]> {
+		BitArray::new([self.header_byte(6)])
 	}
 
-	pub(crate) fn row_count(&self, table: TableKind) -> usize {
-		if !self.has_table(table) {
-			return 0;
+	/// The width of the indices into the heap of the given `heap_sizes` bit.
+	fn heap_index_size(&self, bit: u8) -> IndexSize {
+		match self.minimal_delta || (self.heap_sizes().data[0] & bit) != 0 {
+			true => IndexSize::Fat,
+			false => IndexSize::Slim,
 		}
+	}
 
-		let mut index = 0;
-		for kind in TableKind::iter() {
-			if kind == table {
-				break;
-			} else {
-				index += self.has_table(kind) as usize;
-			}
+	fn table_count(&self) -> usize {
+		self.valid_mask().len()
+	}
+
+	/// The row counts of the present tables, failing if the header is cut short.
+	fn rows(&self) -> Result<Vec<u32>, Error> {
+		let mut reader = ByteStream::new(self.bytes);
+		reader.skip(24)?;
+		reader.read_vec::<u32>(self.table_count())
+	}
+
+	/// The number of rows of the table, or 0 if it is not present.
+	/// Fails if the header is cut short before the row count of the table.
+	pub fn row_count(&self, table: TableKind) -> Result<usize, Error> {
+		let valid = self.valid_mask();
+		if !valid.contains(table) {
+			return Ok(0);
 		}
 
-		return self.rows()[index] as usize;
+		let mut reader = ByteStream::new(self.bytes);
+		reader.skip(24 + 4 * valid.position(table))?;
+		Ok(reader.read::<u32>()? as usize)
 	}
 
 	fn row_size(&self, table: TableKind) -> usize {
@@ -272,16 +605,91 @@ impl<'l> TableHeap<'l> {
 			TableKind::InterfaceImpl => InterfaceImplTable::calc_row_size(self),
 			TableKind::MethodSemantics => MethodSemanticsTable::calc_row_size(self),
 			TableKind::CustomAttribute => CustomAttributeTable::calc_row_size(self),
-			TableKind::StandAloneSig => StandAloneSignatureTable::calc_row_size(self),
-			_ => unimplemented!("Unimplemented table {:?}", table),
+			TableKind::StandAloneSig => StandAloneSigTable::calc_row_size(self),
+			TableKind::FieldPtr => FieldPtrTable::calc_row_size(self),
+			TableKind::MethodPtr => MethodPtrTable::calc_row_size(self),
+			TableKind::ParamPtr => ParamPtrTable::calc_row_size(self),
+			TableKind::EventPtr => EventPtrTable::calc_row_size(self),
+			TableKind::PropertyPtr => PropertyPtrTable::calc_row_size(self),
+			TableKind::EncLog => EncLogTable::calc_row_size(self),
+			TableKind::EncMap => EncMapTable::calc_row_size(self),
+			TableKind::AssemblyProcessor => AssemblyProcessorTable::calc_row_size(self),
+			TableKind::AssemblyOS => AssemblyOSTable::calc_row_size(self),
+			TableKind::AssemblyRefProcessor => AssemblyRefProcessorTable::calc_row_size(self),
+			TableKind::AssemblyRefOS => AssemblyRefOSTable::calc_row_size(self),
+			TableKind::File => FileTable::calc_row_size(self),
+			TableKind::ExportedType => ExportedTypeTable::calc_row_size(self),
+			TableKind::ManifestResource => ManifestResourceTable::calc_row_size(self),
+			TableKind::NestedClass => NestedClassTable::calc_row_size(self),
+			TableKind::GenericParam => GenericParamTable::calc_row_size(self),
+			TableKind::MethodSpec => MethodSpecTable::calc_row_size(self),
+			TableKind::GenericParamConstraint => GenericParamConstraintTable::calc_row_size(self),
+
+			// Portable PDB tables are not modeled yet, but their sizes are still needed to skip over them.
+			TableKind::Document => 2 * self.blob_size() + 2 * self.guid_size(),
+			TableKind::MethodDebugInformation => self.table_size(TableKind::Document) + self.blob_size(),
+			TableKind::LocalScope => {
+				self.table_size(TableKind::MethodDef)
+					+ self.table_size(TableKind::ImportScope)
+					+ self.table_size(TableKind::LocalVariable)
+					+ self.table_size(TableKind::LocalConstant)
+					+ 8
+			}
+			TableKind::LocalVariable => 4 + self.string_size(),
+			TableKind::LocalConstant => self.string_size() + self.blob_size(),
+			TableKind::ImportScope => self.table_size(TableKind::ImportScope) + self.blob_size(),
+			TableKind::StateMachineMethod => 2 * self.table_size(TableKind::MethodDef),
+			TableKind::CustomDebugInformation => {
+				CodedIndex::get_size(CodedIndexKind::HasCustomDebugInformation, self) as usize
+					+ self.guid_size()
+					+ self.blob_size()
+			}
 		}
 	}
 
-	pub(crate) fn idx_size(&self, table: TableKind) -> IndexSize {
-		match self.row_count(table) <= u16::MAX as usize {
-			true => IndexSize::Slim,
-			false => IndexSize::Fat,
+	fn table_size(&self, table: TableKind) -> usize {
+		self.idx_size(table) as usize
+	}
+
+	fn string_size(&self) -> usize {
+		StringHeap::idx_size(self) as usize
+	}
+
+	fn guid_size(&self) -> usize {
+		GuidHeap::idx_size(self) as usize
+	}
+
+	fn blob_size(&self) -> usize {
+		BlobHeap::idx_size(self) as usize
+	}
+
+	/// Iterates over every table present in the heap, including the ones without a dedicated table type.
+	pub fn iter_tables(&self) -> Result<impl Iterator<Item = GenericMetadataTable<'l>>, Error> {
+		let rows = self.rows()?;
+		let mut reader = ByteStream::new(self.bytes);
+		reader.skip(24 + 4 * rows.len())?;
+
+		let mut tables = Vec::with_capacity(rows.len());
+		let valid = self.valid_mask();
+		if valid.unknown_bits() != 0 {
+			return Err(Error::InvalidData(Some("Unknown metadata table")));
+		}
+
+		for (kind, len) in valid.iter().zip(rows) {
+			let row_size = self.row_size(kind);
+			tables.push(GenericMetadataTable {
+				kind,
+				len,
+				row_size,
+				bytes: reader.read_slice::<u8>(len as usize * row_size)?,
+			});
 		}
+
+		Ok(tables.into_iter())
+	}
+
+	pub(crate) fn idx_size(&self, table: TableKind) -> IndexSize {
+		self.widths.table(table)
 	}
 }
 
@@ -295,32 +703,114 @@ impl Debug for TableHeap<'_> {
 						major_version: {},
 						minor_version: {},
 						heap_sizes:    {:b},
-						valid:         {:b},
-						sorted:        {:b},
+						valid:         [{}],
+						sorted:        [{}],
 						rows:          {:?},
 					}}"
 				},
 				self.major_version(),
 				self.minor_version(),
 				self.heap_sizes(),
-				self.valid(),
-				self.sorted(),
-				self.rows(),
+				self.valid_mask(),
+				self.sorted_mask(),
+				self.rows().unwrap_or_default(),
 			)
 		} else {
 			write!(f, "TableHeap {{ ")?;
 			write!(f, "major_version: {}, ", self.major_version())?;
 			write!(f, "minor_version: {}, ", self.minor_version())?;
 			write!(f, "heap_sizes: {:b}, ", self.heap_sizes())?;
-			write!(f, "valid: {:b}, ", self.valid())?;
-			write!(f, "sorted: {:b}, ", self.sorted())?;
-			write!(f, "rows: {:?}, ", self.rows())?;
+			write!(f, "valid: {:b}, ", self.valid_mask().bits())?;
+			write!(f, "sorted: {:b}, ", self.sorted_mask().bits())?;
+			write!(f, "rows: {:?}, ", self.rows().unwrap_or_default())?;
 			write!(f, "}}")?;
 			Ok(())
 		}
 	}
 }
 
+/// The `#Pdb` stream of a standalone portable PDB, which identifies the PDB and records the row counts
+/// of the type system tables that live in the corresponding assembly rather than in the PDB itself.
+#[derive(Copy, Clone)]
+pub struct PdbHeap<'l> {
+	bytes: &'l [u8],
+}
+
+/// The identity shared by a portable PDB and the debug directory of its assembly.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct PdbId {
+	pub guid: Uuid,
+	pub stamp: u32,
+}
+
+impl<'l> MetadataHeap<'l> for PdbHeap<'l> {
+	fn new(bytes: &'l [u8]) -> Self {
+		Self { bytes }
+	}
+	fn cli_identifier() -> &'static str {
+		"#Pdb"
+	}
+	/// No table column indexes the `#Pdb` stream, which is read as a whole.
+	fn idx_size(_: &TableHeap) -> IndexSize {
+		IndexSize::Slim
+	}
+}
+
+impl<'l> PdbHeap<'l> {
+	pub fn pdb_id(&self) -> Result<PdbId, Error> {
+		let mut reader = ByteStream::new(self.bytes);
+		let guid = reader.read_slice::<u8>(16)?;
+		Ok(PdbId {
+			guid: Uuid::from_slice_le(guid).map_err(|_| Error::InvalidData(Some("Invalid PDB id")))?,
+			stamp: reader.read()?,
+		})
+	}
+
+	/// The MethodDef token of the entry point, or a null token if there is none.
+	pub fn entry_point(&self) -> Result<MetadataToken, Error> {
+		let mut reader = ByteStream::new(self.bytes);
+		reader.seek(20)?;
+		Ok(MetadataToken(reader.read()?))
+	}
+
+	/// The type system tables of the assembly that the PDB tables may refer to.
+	pub fn referenced_tables(&self) -> Result<impl Iterator<Item = TableKind>, Error> {
+		Ok(self.referenced_mask()?.iter())
+	}
+
+	/// The row count of a type system table of the assembly, or 0 if it is not referenced.
+	pub fn type_system_row_count(&self, kind: TableKind) -> Result<u32, Error> {
+		let referenced = self.referenced_mask()?;
+		if !referenced.contains(kind) {
+			return Ok(0);
+		}
+
+		let mut reader = ByteStream::new(self.bytes);
+		reader.seek(32 + 4 * referenced.position(kind))?;
+		reader.read()
+	}
+
+	/// Like [`PdbHeap::referenced_tables`], as a set.
+	pub fn referenced_mask(&self) -> Result<TableMask, Error> {
+		let mut reader = ByteStream::new(self.bytes);
+		reader.seek(24)?;
+		reader.read().map(TableMask::from_bits)
+	}
+}
+
+impl Debug for PdbHeap<'_> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		let mut f = f.debug_struct("PdbHeap");
+		f.field("pdb_id", &self.pdb_id());
+		f.field("entry_point", &self.entry_point());
+		f.field(
+			"referenced_tables",
+			&self.referenced_tables().map(|tables| tables.collect::<Vec<_>>()),
+		);
+		f.finish()
+	}
+}
+
 pub(crate) mod private {
 	use crate::raw::*;
 	pub trait MetadataHeap<'l>
@@ -331,6 +821,11 @@ pub(crate) mod private {
 		fn cli_identifier() -> &'static str;
 		fn idx_size(tables: &TableHeap) -> IndexSize;
 
+		/// Completes the heap with what it needs from the other streams of its metadata root.
+		fn with_header(self, _: &MetadataHeader<'l>) -> Result<Self, Error> {
+			Ok(self)
+		}
+
 		fn empty() -> Self {
 			Self::new(&[])
 		}
@@ -1,4 +1,6 @@
-use std::fmt::{Debug, Display, Formatter};
+use std::fmt::{Debug, Display, Formatter, UpperHex};
+use strum::{EnumIter, IntoEnumIterator, IntoStaticStr};
+use std::str::FromStr;
 use crate::raw::*;
 
 #[repr(transparent)]
@@ -11,6 +13,61 @@ pub struct HeapIndex(pub(crate) u32);
 #[derive(Copy, Clone, Eq, PartialEq, Hash)]
 pub struct CodedIndex(pub(crate) u32);
 
+impl TableIndex {
+	/// The null index, which table columns use for the absence of a row.
+	pub const NULL: Self = Self(0);
+
+	/// The index of the 1-based `row`, or `None` if it is 0 or does not fit in the 24 bits of a token.
+	pub fn new(row: u32) -> Option<Self> {
+		match row {
+			1..=0x00FFFFFF => Some(Self(row)),
+			_ => None,
+		}
+	}
+
+	/// Wraps `row` as it is, e.g. for indices read from hostile inputs that must be reported as they were found.
+	pub const fn new_unchecked(row: u32) -> Self {
+		Self(row)
+	}
+
+	pub fn get(&self) -> u32 {
+		self.0
+	}
+
+	pub fn is_null(&self) -> bool {
+		self.0 == 0
+	}
+}
+
+impl TryFrom<usize> for TableIndex {
+	type Error = Error;
+
+	fn try_from(row: usize) -> Result<Self, Self::Error> {
+		let row = u32::try_from(row).ok().and_then(Self::new);
+		row.ok_or(Error::InvalidData(Some("Invalid table index")))
+	}
+}
+
+impl HeapIndex {
+	/// The index of the entry at `offset`, or `None` if it does not fit in the 4 bytes of a fat heap index.
+	pub fn new(offset: usize) -> Option<Self> {
+		u32::try_from(offset).ok().map(Self)
+	}
+
+	pub const fn new_unchecked(offset: u32) -> Self {
+		Self(offset)
+	}
+
+	/// The offset into the `#Strings` and `#Blob` heaps, or the 1-based index into the `#GUID` heap.
+	pub fn get(&self) -> u32 {
+		self.0
+	}
+
+	pub fn is_null(&self) -> bool {
+		self.0 == 0
+	}
+}
+
 impl Debug for CodedIndex {
 	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
 		write!(f, "0x{:X}", self.0)
@@ -22,7 +79,10 @@ pub struct MetadataToken(pub(crate) u32);
 
 impl Display for MetadataToken {
 	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-		write!(f, "0x{:X} as {:?}", self.index(), self.token_kind())
+		match self.token_kind() {
+			Some(kind) => write!(f, "0x{:X} as {:?}", self.index(), kind),
+			None => write!(f, "0x{:X} as Unknown(0x{:X})", self.index(), self.0 >> 24),
+		}
 	}
 }
 
@@ -32,13 +92,42 @@ impl Debug for MetadataToken {
 	}
 }
 
-#[derive(Debug, Copy, Clone)]
+/// Formats the raw value of the token, e.g. `{:08X}` for the `06000001` ildasm and ILSpy print.
+impl UpperHex for MetadataToken {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		UpperHex::fmt(&self.0, f)
+	}
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 pub enum IndexSize {
 	Slim = 0x2,
 	Fat = 0x4,
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+/// The heaps whose indices are 4 bytes wide even though the heap is small enough for 2 byte ones,
+/// as some writers always emit them. Table indices cannot be forced, since readers derive them from row counts.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub struct FatHeapIndices {
+	pub string: bool,
+	pub guid: bool,
+	pub blob: bool,
+}
+
+impl FatHeapIndices {
+	pub const ALL: Self = Self {
+		string: true,
+		guid: true,
+		blob: true,
+	};
+
+	/// The `HeapSizes` byte of a [`TableHeap`] declaring these heaps as fat.
+	pub fn heap_sizes(&self) -> u8 {
+		self.string as u8 | (self.guid as u8) << 1 | (self.blob as u8) << 2
+	}
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, EnumIter)]
 pub enum CodedIndexKind {
 	TypeDefOrRef,
 	HasConstant,
@@ -56,8 +145,295 @@ pub enum CodedIndexKind {
 	HasCustomDebugInformation,
 }
 
-#[repr(u32)]
+/// The tables a kind of coded index can refer to, by tag, see ECMA-335 II.24.2.6.
+#[derive(Debug)]
+pub struct CodedIndexDescriptor {
+	kind: CodedIndexKind,
+	tag_bits: u32,
+	/// `None` for the tags that are not used, e.g. the first two of [`CodedIndexKind::CustomAttributeType`].
+	tables: &'static [Option<TableKind>],
+}
+
+impl CodedIndexDescriptor {
+	pub fn kind(&self) -> CodedIndexKind {
+		self.kind
+	}
+
+	/// The number of low bits holding the tag.
+	pub fn tag_bits(&self) -> u32 {
+		self.tag_bits
+	}
+
+	pub fn tag_mask(&self) -> u32 {
+		(1 << self.tag_bits) - 1
+	}
+
+	/// The table of every tag, in tag order.
+	pub fn tables(&self) -> &'static [Option<TableKind>] {
+		self.tables
+	}
+
+	pub fn table(&self, tag: u32) -> Option<TableKind> {
+		self.tables.get(tag as usize).copied().flatten()
+	}
+
+	pub fn tag(&self, table: TableKind) -> Option<u32> {
+		let tag = self.tables.iter().position(|candidate| *candidate == Some(table))?;
+		Some(tag as u32)
+	}
+
+	/// The highest row count of the referenced tables that still fits in a 2 byte index.
+	pub fn max_slim_rows(&self) -> usize {
+		(1 << (16 - self.tag_bits)) - 1
+	}
+
+	/// The width of the index, given the row count of every table.
+	pub fn size(&self, row_count: impl Fn(TableKind) -> usize) -> IndexSize {
+		let rows = self.tables.iter().flatten().map(|table| row_count(*table)).max();
+		match rows.unwrap_or(0) <= self.max_slim_rows() {
+			true => IndexSize::Slim,
+			false => IndexSize::Fat,
+		}
+	}
+}
+
+impl CodedIndexKind {
+	pub fn descriptor(self) -> &'static CodedIndexDescriptor {
+		&CODED_INDEX_DESCRIPTORS[self as usize]
+	}
+}
+
+const HAS_CUSTOM_ATTRIBUTE: [Option<TableKind>; 22] = [
+	Some(TableKind::MethodDef),
+	Some(TableKind::Field),
+	Some(TableKind::TypeRef),
+	Some(TableKind::TypeDef),
+	Some(TableKind::Param),
+	Some(TableKind::InterfaceImpl),
+	Some(TableKind::MemberRef),
+	Some(TableKind::Module),
+	Some(TableKind::DeclSecurity),
+	Some(TableKind::Property),
+	Some(TableKind::Event),
+	Some(TableKind::StandAloneSig),
+	Some(TableKind::ModuleRef),
+	Some(TableKind::TypeSpec),
+	Some(TableKind::Assembly),
+	Some(TableKind::AssemblyRef),
+	Some(TableKind::File),
+	Some(TableKind::ExportedType),
+	Some(TableKind::ManifestResource),
+	Some(TableKind::GenericParam),
+	Some(TableKind::GenericParamConstraint),
+	Some(TableKind::MethodSpec),
+];
+
+// HasCustomDebugInformation extends HasCustomAttribute with the Portable PDB tables.
+const HAS_CUSTOM_DEBUG_INFORMATION: [Option<TableKind>; 27] = {
+	let mut tables = [None; 27];
+	let mut i = 0;
+	while i < HAS_CUSTOM_ATTRIBUTE.len() {
+		tables[i] = HAS_CUSTOM_ATTRIBUTE[i];
+		i += 1;
+	}
+
+	tables[22] = Some(TableKind::Document);
+	tables[23] = Some(TableKind::LocalScope);
+	tables[24] = Some(TableKind::LocalVariable);
+	tables[25] = Some(TableKind::LocalConstant);
+	tables[26] = Some(TableKind::ImportScope);
+	tables
+};
+
+/// Indexed by [`CodedIndexKind`].
+static CODED_INDEX_DESCRIPTORS: [CodedIndexDescriptor; 14] = [
+	CodedIndexDescriptor {
+		kind: CodedIndexKind::TypeDefOrRef,
+		tag_bits: 2,
+		tables: &[
+			Some(TableKind::TypeDef),
+			Some(TableKind::TypeRef),
+			Some(TableKind::TypeSpec),
+		],
+	},
+	CodedIndexDescriptor {
+		kind: CodedIndexKind::HasConstant,
+		tag_bits: 2,
+		tables: &[
+			Some(TableKind::Field),
+			Some(TableKind::Param),
+			Some(TableKind::Property),
+		],
+	},
+	CodedIndexDescriptor {
+		kind: CodedIndexKind::HasCustomAttribute,
+		tag_bits: 5,
+		tables: &HAS_CUSTOM_ATTRIBUTE,
+	},
+	CodedIndexDescriptor {
+		kind: CodedIndexKind::HasFieldMarshal,
+		tag_bits: 1,
+		tables: &[Some(TableKind::Field), Some(TableKind::Param)],
+	},
+	CodedIndexDescriptor {
+		kind: CodedIndexKind::HasDeclSecurity,
+		tag_bits: 2,
+		tables: &[
+			Some(TableKind::TypeDef),
+			Some(TableKind::MethodDef),
+			Some(TableKind::Assembly),
+		],
+	},
+	CodedIndexDescriptor {
+		kind: CodedIndexKind::MemberRefParent,
+		tag_bits: 3,
+		tables: &[
+			Some(TableKind::TypeDef),
+			Some(TableKind::TypeRef),
+			Some(TableKind::ModuleRef),
+			Some(TableKind::MethodDef),
+			Some(TableKind::TypeSpec),
+		],
+	},
+	CodedIndexDescriptor {
+		kind: CodedIndexKind::HasSemantics,
+		tag_bits: 1,
+		tables: &[Some(TableKind::Event), Some(TableKind::Property)],
+	},
+	CodedIndexDescriptor {
+		kind: CodedIndexKind::MethodDefOrRef,
+		tag_bits: 1,
+		tables: &[Some(TableKind::MethodDef), Some(TableKind::MemberRef)],
+	},
+	CodedIndexDescriptor {
+		kind: CodedIndexKind::MemberForwarded,
+		tag_bits: 1,
+		tables: &[Some(TableKind::Field), Some(TableKind::MethodDef)],
+	},
+	CodedIndexDescriptor {
+		kind: CodedIndexKind::Implementation,
+		tag_bits: 2,
+		tables: &[
+			Some(TableKind::File),
+			Some(TableKind::AssemblyRef),
+			Some(TableKind::ExportedType),
+		],
+	},
+	CodedIndexDescriptor {
+		kind: CodedIndexKind::CustomAttributeType,
+		tag_bits: 3,
+		tables: &[None, None, Some(TableKind::MethodDef), Some(TableKind::MemberRef)],
+	},
+	CodedIndexDescriptor {
+		kind: CodedIndexKind::ResolutionScope,
+		tag_bits: 2,
+		tables: &[
+			Some(TableKind::Module),
+			Some(TableKind::ModuleRef),
+			Some(TableKind::AssemblyRef),
+			Some(TableKind::TypeRef),
+		],
+	},
+	CodedIndexDescriptor {
+		kind: CodedIndexKind::TypeOrMethodDef,
+		tag_bits: 1,
+		tables: &[Some(TableKind::TypeDef), Some(TableKind::MethodDef)],
+	},
+	CodedIndexDescriptor {
+		kind: CodedIndexKind::HasCustomDebugInformation,
+		tag_bits: 5,
+		tables: &HAS_CUSTOM_DEBUG_INFORMATION,
+	},
+];
+
+/// The width of every heap, table and coded index used by the rows of a [`TableHeap`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct IndexWidths {
+	pub string: IndexSize,
+	pub guid: IndexSize,
+	pub blob: IndexSize,
+	tables: [IndexSize; 64],
+	coded: [IndexSize; 14],
+}
+
+impl IndexWidths {
+	/// Every index 2 bytes wide, as in an empty heap.
+	pub(crate) const SLIM: Self = Self {
+		string: IndexSize::Slim,
+		guid: IndexSize::Slim,
+		blob: IndexSize::Slim,
+		tables: [IndexSize::Slim; 64],
+		coded: [IndexSize::Slim; 14],
+	};
+
+	/// Every index 4 bytes wide, as in a minimal EnC delta.
+	pub(crate) const FAT: Self = Self {
+		string: IndexSize::Fat,
+		guid: IndexSize::Fat,
+		blob: IndexSize::Fat,
+		tables: [IndexSize::Fat; 64],
+		coded: [IndexSize::Fat; 14],
+	};
+
+	/// Reads the row counts once, rather than once per column of every table.
+	/// A header cut short reads as no rows here, and fails once its tables are read.
+	pub fn compute(tables: &TableHeap) -> Self {
+		Self::compute_with_external_rows(tables, &[0; 64])
+	}
+
+	/// Like [`IndexWidths::compute`], adding the row counts of tables that live outside of the heap,
+	/// indexed by [`TableKind`], such as the ones a `#Pdb` stream records.
+	pub fn compute_with_external_rows(tables: &TableHeap, external: &[u32; 64]) -> Self {
+		if tables.is_minimal_delta() {
+			return Self::FAT;
+		}
+
+		let mut rows = external.map(|count| count as usize);
+		for (kind, count) in tables.present_tables().into_iter().flatten() {
+			rows[kind as usize] += count as usize;
+		}
+
+		let mut widths = Self {
+			string: StringHeap::idx_size(tables),
+			guid: GuidHeap::idx_size(tables),
+			blob: BlobHeap::idx_size(tables),
+			..Self::SLIM
+		};
+
+		for kind in TableKind::iter() {
+			if rows[kind as usize] > u16::MAX as usize {
+				widths.tables[kind as usize] = IndexSize::Fat;
+			}
+		}
+
+		for kind in CodedIndexKind::iter() {
+			widths.coded[kind as usize] = kind.descriptor().size(|table| rows[table as usize]);
+		}
+
+		widths
+	}
+
+	pub fn table(&self, kind: TableKind) -> IndexSize {
+		self.tables[kind as usize]
+	}
+
+	pub fn coded(&self, kind: CodedIndexKind) -> IndexSize {
+		self.coded[kind as usize]
+	}
+}
+
+/// The index widths a [`TableHeap`] is read with, and which of them the size of the heaps did not require,
+/// so that the layout of another writer can be reproduced with [`FatHeapIndices`].
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct IndexWidthsReport {
+	pub widths: IndexWidths,
+	pub forced_fat: FatHeapIndices,
+}
+
+/// The kind of row or heap entry a token refers to, by its high byte.
+/// Kinds are printed and parsed by their names, e.g. `"Method"`, which differ from the names of some tables.
+#[repr(u32)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Ord, PartialOrd, EnumIter, IntoStaticStr)]
 pub enum MetadataTokenKind {
 	Module = 0x00000000,
 	TypeRef = 0x01000000,
@@ -95,6 +471,37 @@ pub enum MetadataTokenKind {
 	String = 0x70000000,
 }
 
+impl MetadataTokenKind {
+	pub fn name(&self) -> &'static str {
+		self.into()
+	}
+}
+
+impl Display for MetadataTokenKind {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		f.write_str(self.name())
+	}
+}
+
+impl FromStr for MetadataTokenKind {
+	type Err = Error;
+
+	fn from_str(name: &str) -> Result<Self, Self::Err> {
+		let kind = MetadataTokenKind::iter().find(|kind| kind.name() == name);
+		kind.ok_or(Error::InvalidData(Some("Unknown token kind")))
+	}
+}
+
+/// Converts the high byte of a token.
+impl TryFrom<u8> for MetadataTokenKind {
+	type Error = Error;
+
+	fn try_from(value: u8) -> Result<Self, Self::Error> {
+		let kind = MetadataTokenKind::iter().find(|kind| (*kind as u32 >> 24) as u8 == value);
+		kind.ok_or(Error::InvalidData(Some("Unknown token kind")))
+	}
+}
+
 impl MetadataToken {
 	pub(crate) fn new(index: u32, kind: MetadataTokenKind) -> MetadataToken {
 		if index == 0 {
@@ -104,6 +511,16 @@ impl MetadataToken {
 		}
 	}
 
+	pub(crate) fn from_table_row(kind: TableKind, index: TableIndex) -> MetadataToken {
+		MetadataToken(((kind as u32) << 24) | index.0)
+	}
+
+	/// Like [`MetadataToken::new`], null rows decode to the null token whatever table they refer to.
+	/// `None` if rows of the table are not referred to by tokens.
+	fn from_coded_row(kind: TableKind, index: u32) -> Option<MetadataToken> {
+		Some(MetadataToken::new(index, kind.token_kind()?))
+	}
+
 	pub fn is_null(&self) -> bool {
 		self.0 == 0
 	}
@@ -112,439 +529,161 @@ impl MetadataToken {
 		(self.0 & 0x00FFFFFF) as usize
 	}
 
-	pub fn token_kind(&self) -> MetadataTokenKind {
-		unsafe { std::mem::transmute(self.0 & 0xFF000000) }
+	pub(crate) fn table_kind(&self) -> Option<TableKind> {
+		TableKind::try_from((self.0 >> 24) as u8).ok()
+	}
+
+	/// The kind of the token, or `None` if its high byte is not a known kind, as can happen with hostile inputs.
+	pub fn token_kind(&self) -> Option<MetadataTokenKind> {
+		MetadataTokenKind::try_from((self.0 >> 24) as u8).ok()
 	}
 }
 
-impl CodedIndex {
-	pub fn get_size(kind: CodedIndexKind, tables_heap: &TableHeap) -> IndexSize {
-		let (bits, tables): (usize, &[TableKind]) = match kind {
-			CodedIndexKind::TypeDefOrRef => (2, &[TableKind::TypeDef, TableKind::TypeRef, TableKind::TypeSpec]),
-			CodedIndexKind::HasConstant => (2, &[TableKind::Field, TableKind::Param, TableKind::Property]),
-			CodedIndexKind::HasCustomAttribute => (
-				5,
-				&[
-					TableKind::MethodDef,
-					TableKind::Field,
-					TableKind::TypeRef,
-					TableKind::TypeDef,
-					TableKind::Param,
-					TableKind::InterfaceImpl,
-					TableKind::MemberRef,
-					TableKind::Module,
-					TableKind::DeclSecurity,
-					TableKind::Property,
-					TableKind::Event,
-					TableKind::StandAloneSig,
-					TableKind::ModuleRef,
-					TableKind::TypeSpec,
-					TableKind::Assembly,
-					TableKind::AssemblyRef,
-					TableKind::File,
-					TableKind::ExportedType,
-					TableKind::ManifestResource,
-					TableKind::GenericParam,
-					TableKind::GenericParamConstraint,
-					TableKind::MethodSpec,
-				],
-			),
-			CodedIndexKind::HasFieldMarshal => (1, &[TableKind::Field, TableKind::Param]),
-			CodedIndexKind::HasDeclSecurity => (2, &[TableKind::TypeDef, TableKind::MethodDef, TableKind::Assembly]),
-			CodedIndexKind::MemberRefParent => (
-				3,
-				&[
-					TableKind::TypeDef,
-					TableKind::TypeRef,
-					TableKind::ModuleRef,
-					TableKind::MethodDef,
-					TableKind::TypeSpec,
-				],
-			),
-			CodedIndexKind::HasSemantics => (1, &[TableKind::Event, TableKind::Property]),
-			CodedIndexKind::MethodDefOrRef => (1, &[TableKind::MethodDef, TableKind::MemberRef]),
-			CodedIndexKind::MemberForwarded => (1, &[TableKind::Field, TableKind::MethodDef]),
-			CodedIndexKind::Implementation => (2, &[TableKind::File, TableKind::AssemblyRef, TableKind::ExportedType]),
-			CodedIndexKind::CustomAttributeType => (3, &[TableKind::MethodDef, TableKind::MemberRef]),
-			CodedIndexKind::ResolutionScope => (
-				2,
-				&[
-					TableKind::Module,
-					TableKind::ModuleRef,
-					TableKind::AssemblyRef,
-					TableKind::TypeRef,
-				],
-			),
-			CodedIndexKind::TypeOrMethodDef => (1, &[TableKind::TypeDef, TableKind::MethodDef]),
-			CodedIndexKind::HasCustomDebugInformation => (
-				5,
-				&[
-					TableKind::MethodDef,
-					TableKind::Field,
-					TableKind::TypeRef,
-					TableKind::TypeDef,
-					TableKind::Param,
-					TableKind::InterfaceImpl,
-					TableKind::MemberRef,
-					TableKind::Module,
-					TableKind::DeclSecurity,
-					TableKind::Property,
-					TableKind::Event,
-					TableKind::StandAloneSig,
-					TableKind::ModuleRef,
-					TableKind::TypeSpec,
-					TableKind::Assembly,
-					TableKind::AssemblyRef,
-					TableKind::File,
-					TableKind::ExportedType,
-					TableKind::ManifestResource,
-					TableKind::GenericParam,
-					TableKind::GenericParamConstraint,
-					TableKind::MethodSpec,
-					TableKind::Document,
-					TableKind::LocalScope,
-					TableKind::LocalVariable,
-					TableKind::LocalConstant,
-					TableKind::ImportScope,
-				],
-			),
-		};
+/// The table of every token kind that refers to a table row, the one place the two enums are related.
+/// Most pairs share their number, but not always their name, e.g. [`MetadataTokenKind::Permission`]
+/// refers to rows of [`TableKind::DeclSecurity`].
+const TOKEN_TABLES: [(MetadataTokenKind, TableKind); 31] = [
+	(MetadataTokenKind::Module, TableKind::Module),
+	(MetadataTokenKind::TypeRef, TableKind::TypeRef),
+	(MetadataTokenKind::TypeDef, TableKind::TypeDef),
+	(MetadataTokenKind::Field, TableKind::Field),
+	(MetadataTokenKind::Method, TableKind::MethodDef),
+	(MetadataTokenKind::Param, TableKind::Param),
+	(MetadataTokenKind::InterfaceImpl, TableKind::InterfaceImpl),
+	(MetadataTokenKind::MemberRef, TableKind::MemberRef),
+	(MetadataTokenKind::CustomAttribute, TableKind::CustomAttribute),
+	(MetadataTokenKind::Permission, TableKind::DeclSecurity),
+	(MetadataTokenKind::Signature, TableKind::StandAloneSig),
+	(MetadataTokenKind::Event, TableKind::Event),
+	(MetadataTokenKind::Property, TableKind::Property),
+	(MetadataTokenKind::ModuleRef, TableKind::ModuleRef),
+	(MetadataTokenKind::TypeSpec, TableKind::TypeSpec),
+	(MetadataTokenKind::Assembly, TableKind::Assembly),
+	(MetadataTokenKind::AssemblyRef, TableKind::AssemblyRef),
+	(MetadataTokenKind::File, TableKind::File),
+	(MetadataTokenKind::ExportedType, TableKind::ExportedType),
+	(MetadataTokenKind::ManifestResource, TableKind::ManifestResource),
+	(MetadataTokenKind::GenericParam, TableKind::GenericParam),
+	(MetadataTokenKind::MethodSpec, TableKind::MethodSpec),
+	(MetadataTokenKind::GenericParamConstraint, TableKind::GenericParamConstraint),
+	(MetadataTokenKind::Document, TableKind::Document),
+	(MetadataTokenKind::MethodDebugInformation, TableKind::MethodDebugInformation),
+	(MetadataTokenKind::LocalScope, TableKind::LocalScope),
+	(MetadataTokenKind::LocalVariable, TableKind::LocalVariable),
+	(MetadataTokenKind::LocalConstant, TableKind::LocalConstant),
+	(MetadataTokenKind::ImportScope, TableKind::ImportScope),
+	(MetadataTokenKind::StateMachineMethod, TableKind::StateMachineMethod),
+	(MetadataTokenKind::CustomDebugInformation, TableKind::CustomDebugInformation),
+];
+
+/// [`TOKEN_TABLES`] indexed by the high byte of the token kind.
+const TABLES_BY_TOKEN_KIND: [Option<TableKind>; 256] = {
+	let mut tables = [None; 256];
+	let mut i = 0;
+	while i < TOKEN_TABLES.len() {
+		let (token_kind, table) = TOKEN_TABLES[i];
+		tables[(token_kind as u32 >> 24) as usize] = Some(table);
+		i += 1;
+	}
+	tables
+};
+
+/// [`TOKEN_TABLES`] indexed by table number.
+const TOKEN_KINDS_BY_TABLE: [Option<MetadataTokenKind>; 256] = {
+	let mut kinds = [None; 256];
+	let mut i = 0;
+	while i < TOKEN_TABLES.len() {
+		let (token_kind, table) = TOKEN_TABLES[i];
+		kinds[table as usize] = Some(token_kind);
+		i += 1;
+	}
+	kinds
+};
+
+impl MetadataTokenKind {
+	/// The table the tokens of this kind refer to, or `None` for kinds that are not tables,
+	/// such as [`MetadataTokenKind::String`] which refers to the `#US` heap.
+	pub fn table(&self) -> Option<TableKind> {
+		TABLES_BY_TOKEN_KIND[(*self as u32 >> 24) as usize]
+	}
+}
 
-		let map = |t: &TableKind| tables_heap.row_count(*t);
-		match tables.iter().map(map).max().unwrap() < (1 << (16 - bits)) {
-			true => IndexSize::Slim,
-			false => IndexSize::Fat,
+impl TableKind {
+	/// The table a token kind indexes into, or `None` for kinds that are not tables, such as [`MetadataTokenKind::String`].
+	pub fn try_from_token_kind(kind: MetadataTokenKind) -> Option<TableKind> {
+		kind.table()
+	}
+
+	/// The kind of the tokens that refer to rows of this table, or `None` for tables whose rows have no tokens,
+	/// such as [`TableKind::Constant`] or the Ptr tables, whose rows stand for rows of their [`TableKind::pointee`].
+	pub fn token_kind(&self) -> Option<MetadataTokenKind> {
+		TOKEN_KINDS_BY_TABLE[*self as usize]
+	}
+
+	/// The table a Ptr table refers to, e.g. [`TableKind::Field`] for [`TableKind::FieldPtr`].
+	pub fn pointee(&self) -> Option<TableKind> {
+		match self {
+			TableKind::FieldPtr => Some(TableKind::Field),
+			TableKind::MethodPtr => Some(TableKind::MethodDef),
+			TableKind::ParamPtr => Some(TableKind::Param),
+			TableKind::EventPtr => Some(TableKind::Event),
+			TableKind::PropertyPtr => Some(TableKind::Property),
+			_ => None,
+		}
+	}
+
+	/// The Ptr table that may stand between a list column and this table,
+	/// e.g. [`TableKind::FieldPtr`] for [`TableKind::Field`].
+	pub fn pointer_table(&self) -> Option<TableKind> {
+		TableKind::iter().find(|table| table.pointee() == Some(*self))
+	}
+}
+
+impl CodedIndex {
+	/// Encodes a token as a coded index of `kind`, or `None` if the kind cannot refer to its table
+	/// or the row does not leave room for the tag bits.
+	pub fn from_token(token: MetadataToken, kind: CodedIndexKind) -> Option<Self> {
+		let index = Self::encode(token.index(), token.token_kind()?, kind)?;
+		match token.index() == 0 || index.decode(kind) == Some(token) {
+			true => Some(index),
+			false => None,
 		}
 	}
 
+	/// Wraps a raw column value as it is. Whether it decodes depends on the kind it is read as.
+	pub const fn new_unchecked(value: u32) -> Self {
+		Self(value)
+	}
+
+	pub fn get(&self) -> u32 {
+		self.0
+	}
+
+	/// Whether the index refers to no row, whatever the table its tag selects.
+	/// Null indices may carry a tag, so they are not always 0.
+	pub fn is_null(&self, kind: CodedIndexKind) -> bool {
+		self.0 >> kind.descriptor().tag_bits == 0
+	}
+
+	/// The width of the coded indices of `kind` in the rows of `tables`, as precomputed by [`IndexWidths`].
+	pub fn get_size(kind: CodedIndexKind, tables: &TableHeap) -> IndexSize {
+		tables.index_widths().coded(kind)
+	}
+
 	pub fn decode(&self, kind: CodedIndexKind) -> Option<MetadataToken> {
-		let (index, kind) = match kind {
-			CodedIndexKind::TypeDefOrRef => Some((
-				self.0 >> 2,
-				[
-					MetadataTokenKind::TypeDef,
-					MetadataTokenKind::TypeRef,
-					MetadataTokenKind::TypeSpec,
-				]
-				.get((self.0 & 3) as usize)?
-				.clone(),
-			)),
-
-			CodedIndexKind::HasConstant => Some((
-				self.0 >> 2,
-				[
-					MetadataTokenKind::Field,
-					MetadataTokenKind::Param,
-					MetadataTokenKind::Property,
-				]
-				.get((self.0 & 3) as usize)?
-				.clone(),
-			)),
-
-			CodedIndexKind::HasCustomAttribute => Some((
-				self.0 >> 5,
-				[
-					MetadataTokenKind::Method,
-					MetadataTokenKind::Field,
-					MetadataTokenKind::TypeRef,
-					MetadataTokenKind::TypeDef,
-					MetadataTokenKind::Param,
-					MetadataTokenKind::InterfaceImpl,
-					MetadataTokenKind::MemberRef,
-					MetadataTokenKind::Module,
-					MetadataTokenKind::Permission,
-					MetadataTokenKind::Property,
-					MetadataTokenKind::Event,
-					MetadataTokenKind::Signature,
-					MetadataTokenKind::ModuleRef,
-					MetadataTokenKind::TypeSpec,
-					MetadataTokenKind::Assembly,
-					MetadataTokenKind::File,
-					MetadataTokenKind::ExportedType,
-					MetadataTokenKind::ManifestResource,
-					MetadataTokenKind::GenericParam,
-					MetadataTokenKind::GenericParamConstraint,
-					MetadataTokenKind::MethodSpec,
-				]
-				.get((self.0 & 31) as usize)?
-				.clone(),
-			)),
-
-			CodedIndexKind::HasFieldMarshal => Some((
-				self.0 >> 1,
-				[MetadataTokenKind::Field, MetadataTokenKind::Param]
-					.get(((self.0 & 1) as usize) as usize)?
-					.clone(),
-			)),
-
-			CodedIndexKind::HasDeclSecurity => Some((
-				self.0 >> 2,
-				[
-					MetadataTokenKind::TypeDef,
-					MetadataTokenKind::Method,
-					MetadataTokenKind::Assembly,
-				]
-				.get((self.0 & 3) as usize)?
-				.clone(),
-			)),
-
-			CodedIndexKind::MemberRefParent => Some((
-				self.0 >> 3,
-				[
-					MetadataTokenKind::TypeDef,
-					MetadataTokenKind::TypeRef,
-					MetadataTokenKind::ModuleRef,
-					MetadataTokenKind::Method,
-					MetadataTokenKind::TypeSpec,
-				]
-				.get((self.0 & 7) as usize)?
-				.clone(),
-			)),
-
-			CodedIndexKind::HasSemantics => Some((
-				self.0 >> 1,
-				[MetadataTokenKind::Event, MetadataTokenKind::Property]
-					.get((self.0 & 1) as usize)?
-					.clone(),
-			)),
-
-			CodedIndexKind::MethodDefOrRef => Some((
-				self.0 >> 1,
-				[MetadataTokenKind::Method, MetadataTokenKind::MemberRef]
-					.get((self.0 & 1) as usize)?
-					.clone(),
-			)),
-
-			CodedIndexKind::MemberForwarded => Some((
-				self.0 >> 1,
-				[MetadataTokenKind::Field, MetadataTokenKind::Method]
-					.get((self.0 & 1) as usize)?
-					.clone(),
-			)),
-
-			CodedIndexKind::Implementation => Some((
-				self.0 >> 2,
-				[
-					MetadataTokenKind::File,
-					MetadataTokenKind::AssemblyRef,
-					MetadataTokenKind::ExportedType,
-				]
-				.get((self.0 & 3) as usize)?
-				.clone(),
-			)),
-
-			CodedIndexKind::CustomAttributeType => Some((
-				self.0 >> 3,
-				match (self.0 & 7) as usize {
-					2 => Some(MetadataTokenKind::Method),
-					3 => Some(MetadataTokenKind::MemberRef),
-					_ => None,
-				}?,
-			)),
-
-			CodedIndexKind::ResolutionScope => Some((
-				self.0 >> 2,
-				[
-					MetadataTokenKind::Module,
-					MetadataTokenKind::ModuleRef,
-					MetadataTokenKind::AssemblyRef,
-					MetadataTokenKind::TypeRef,
-				]
-				.get((self.0 & 3) as usize)?
-				.clone(),
-			)),
-
-			CodedIndexKind::TypeOrMethodDef => Some((
-				self.0 >> 1,
-				[MetadataTokenKind::TypeDef, MetadataTokenKind::Method]
-					.get((self.0 & 1) as usize)?
-					.clone(),
-			)),
-
-			CodedIndexKind::HasCustomDebugInformation => Some((
-				self.0 >> 5,
-				[
-					MetadataTokenKind::Method,
-					MetadataTokenKind::Field,
-					MetadataTokenKind::TypeRef,
-					MetadataTokenKind::TypeDef,
-					MetadataTokenKind::Param,
-					MetadataTokenKind::InterfaceImpl,
-					MetadataTokenKind::MemberRef,
-					MetadataTokenKind::Module,
-					MetadataTokenKind::Permission,
-					MetadataTokenKind::Property,
-					MetadataTokenKind::Event,
-					MetadataTokenKind::Signature,
-					MetadataTokenKind::ModuleRef,
-					MetadataTokenKind::TypeSpec,
-					MetadataTokenKind::Assembly,
-					MetadataTokenKind::File,
-					MetadataTokenKind::ExportedType,
-					MetadataTokenKind::ManifestResource,
-					MetadataTokenKind::GenericParam,
-					MetadataTokenKind::GenericParamConstraint,
-					MetadataTokenKind::MethodSpec,
-					MetadataTokenKind::Document,
-					MetadataTokenKind::LocalScope,
-					MetadataTokenKind::LocalVariable,
-					MetadataTokenKind::LocalConstant,
-					MetadataTokenKind::ImportScope,
-				]
-				.get((self.0 & 31) as usize)?
-				.clone(),
-			)),
-		}?;
-
-		Some(MetadataToken::new(index, kind))
-	}
-
-	//noinspection DuplicatedCode
+		let descriptor = kind.descriptor();
+		let table = descriptor.table(self.0 & descriptor.tag_mask())?;
+		MetadataToken::from_coded_row(table, self.0 >> descriptor.tag_bits)
+	}
+
 	pub fn encode(index: usize, token_kind: MetadataTokenKind, kind: CodedIndexKind) -> Option<Self> {
 		if index == 0 {
 			return Some(CodedIndex(0));
 		}
 
 		let index: u32 = index.try_into().ok()?;
-
-		match kind {
-			CodedIndexKind::TypeDefOrRef => match token_kind {
-				MetadataTokenKind::TypeDef => Some(CodedIndex((index << 2) | 0x00)),
-				MetadataTokenKind::TypeRef => Some(CodedIndex((index << 2) | 0x01)),
-				MetadataTokenKind::TypeSpec => Some(CodedIndex((index << 2) | 0x02)),
-				_ => None,
-			},
-
-			CodedIndexKind::HasConstant => match token_kind {
-				MetadataTokenKind::Field => Some(CodedIndex((index << 2) | 0x00)),
-				MetadataTokenKind::Param => Some(CodedIndex((index << 2) | 0x01)),
-				MetadataTokenKind::Property => Some(CodedIndex((index << 2) | 0x02)),
-				_ => None,
-			},
-
-			CodedIndexKind::HasCustomAttribute => match token_kind {
-				MetadataTokenKind::Method => Some(CodedIndex((index << 5) | 0x00)),
-				MetadataTokenKind::Field => Some(CodedIndex((index << 5) | 0x01)),
-				MetadataTokenKind::TypeRef => Some(CodedIndex((index << 5) | 0x02)),
-				MetadataTokenKind::TypeDef => Some(CodedIndex((index << 5) | 0x03)),
-				MetadataTokenKind::Param => Some(CodedIndex((index << 5) | 0x04)),
-				MetadataTokenKind::InterfaceImpl => Some(CodedIndex((index << 5) | 0x05)),
-				MetadataTokenKind::MemberRef => Some(CodedIndex((index << 5) | 0x06)),
-				MetadataTokenKind::Module => Some(CodedIndex((index << 5) | 0x07)),
-				MetadataTokenKind::Permission => Some(CodedIndex((index << 5) | 0x08)),
-				MetadataTokenKind::Property => Some(CodedIndex((index << 5) | 0x09)),
-				MetadataTokenKind::Event => Some(CodedIndex((index << 5) | 0x0A)),
-				MetadataTokenKind::Signature => Some(CodedIndex((index << 5) | 0x0B)),
-				MetadataTokenKind::ModuleRef => Some(CodedIndex((index << 5) | 0x0C)),
-				MetadataTokenKind::TypeSpec => Some(CodedIndex((index << 5) | 0x0D)),
-				MetadataTokenKind::Assembly => Some(CodedIndex((index << 5) | 0x0E)),
-				MetadataTokenKind::AssemblyRef => Some(CodedIndex((index << 5) | 0x0F)),
-				MetadataTokenKind::File => Some(CodedIndex((index << 5) | 0x10)),
-				MetadataTokenKind::ExportedType => Some(CodedIndex((index << 5) | 0x11)),
-				MetadataTokenKind::ManifestResource => Some(CodedIndex((index << 5) | 0x12)),
-				MetadataTokenKind::GenericParam => Some(CodedIndex((index << 5) | 0x13)),
-				MetadataTokenKind::GenericParamConstraint => Some(CodedIndex((index << 5) | 0x14)),
-				MetadataTokenKind::MethodSpec => Some(CodedIndex((index << 5) | 0x15)),
-				_ => None,
-			},
-
-			CodedIndexKind::HasFieldMarshal => match token_kind {
-				MetadataTokenKind::Field => Some(CodedIndex((index << 1) | 0x00)),
-				MetadataTokenKind::Param => Some(CodedIndex((index << 1) | 0x01)),
-				_ => None,
-			},
-
-			CodedIndexKind::HasDeclSecurity => match token_kind {
-				MetadataTokenKind::TypeDef => Some(CodedIndex((index << 2) | 0x00)),
-				MetadataTokenKind::Method => Some(CodedIndex((index << 2) | 0x01)),
-				MetadataTokenKind::Assembly => Some(CodedIndex((index << 2) | 0x02)),
-				_ => None,
-			},
-
-			CodedIndexKind::MemberRefParent => match token_kind {
-				MetadataTokenKind::TypeDef => Some(CodedIndex((index << 3) | 0x00)),
-				MetadataTokenKind::TypeRef => Some(CodedIndex((index << 3) | 0x01)),
-				MetadataTokenKind::ModuleRef => Some(CodedIndex((index << 3) | 0x02)),
-				MetadataTokenKind::Method => Some(CodedIndex((index << 3) | 0x03)),
-				MetadataTokenKind::TypeSpec => Some(CodedIndex((index << 3) | 0x04)),
-				_ => None,
-			},
-
-			CodedIndexKind::HasSemantics => match token_kind {
-				MetadataTokenKind::Event => Some(CodedIndex((index << 1) | 0x00)),
-				MetadataTokenKind::Property => Some(CodedIndex((index << 1) | 0x01)),
-				_ => None,
-			},
-
-			CodedIndexKind::MethodDefOrRef => match token_kind {
-				MetadataTokenKind::Method => Some(CodedIndex((index << 1) | 0x00)),
-				MetadataTokenKind::MemberRef => Some(CodedIndex((index << 1) | 0x01)),
-				_ => None,
-			},
-
-			CodedIndexKind::MemberForwarded => match token_kind {
-				MetadataTokenKind::Field => Some(CodedIndex((index << 1) | 0x00)),
-				MetadataTokenKind::Method => Some(CodedIndex((index << 1) | 0x01)),
-				_ => None,
-			},
-
-			CodedIndexKind::Implementation => match token_kind {
-				MetadataTokenKind::File => Some(CodedIndex((index << 2) | 0x00)),
-				MetadataTokenKind::AssemblyRef => Some(CodedIndex((index << 2) | 0x01)),
-				MetadataTokenKind::ExportedType => Some(CodedIndex((index << 2) | 0x02)),
-				_ => None,
-			},
-
-			CodedIndexKind::CustomAttributeType => match token_kind {
-				MetadataTokenKind::Method => Some(CodedIndex((index << 3) | 0x02)),
-				MetadataTokenKind::MemberRef => Some(CodedIndex((index << 3) | 0x03)),
-				_ => None,
-			},
-
-			CodedIndexKind::ResolutionScope => match token_kind {
-				MetadataTokenKind::Module => Some(CodedIndex((index << 2) | 0x00)),
-				MetadataTokenKind::ModuleRef => Some(CodedIndex((index << 2) | 0x01)),
-				MetadataTokenKind::AssemblyRef => Some(CodedIndex((index << 2) | 0x02)),
-				MetadataTokenKind::TypeRef => Some(CodedIndex((index << 2) | 0x03)),
-				_ => None,
-			},
-
-			CodedIndexKind::TypeOrMethodDef => match token_kind {
-				MetadataTokenKind::TypeDef => Some(CodedIndex((index << 1) | 0x00)),
-				MetadataTokenKind::Method => Some(CodedIndex((index << 1) | 0x01)),
-				_ => None,
-			},
-
-			CodedIndexKind::HasCustomDebugInformation => match token_kind {
-				MetadataTokenKind::Method => Some(CodedIndex((index << 5) | 0x00)),
-				MetadataTokenKind::Field => Some(CodedIndex((index << 5) | 0x01)),
-				MetadataTokenKind::TypeRef => Some(CodedIndex((index << 5) | 0x02)),
-				MetadataTokenKind::TypeDef => Some(CodedIndex((index << 5) | 0x03)),
-				MetadataTokenKind::Param => Some(CodedIndex((index << 5) | 0x04)),
-				MetadataTokenKind::InterfaceImpl => Some(CodedIndex((index << 5) | 0x05)),
-				MetadataTokenKind::MemberRef => Some(CodedIndex((index << 5) | 0x06)),
-				MetadataTokenKind::Module => Some(CodedIndex((index << 5) | 0x07)),
-				MetadataTokenKind::Permission => Some(CodedIndex((index << 5) | 0x08)),
-				MetadataTokenKind::Property => Some(CodedIndex((index << 5) | 0x09)),
-				MetadataTokenKind::Event => Some(CodedIndex((index << 5) | 0x0A)),
-				MetadataTokenKind::Signature => Some(CodedIndex((index << 5) | 0x0B)),
-				MetadataTokenKind::ModuleRef => Some(CodedIndex((index << 5) | 0x0C)),
-				MetadataTokenKind::TypeSpec => Some(CodedIndex((index << 5) | 0x0D)),
-				MetadataTokenKind::Assembly => Some(CodedIndex((index << 5) | 0x0E)),
-				MetadataTokenKind::AssemblyRef => Some(CodedIndex((index << 5) | 0x0F)),
-				MetadataTokenKind::File => Some(CodedIndex((index << 5) | 0x10)),
-				MetadataTokenKind::ExportedType => Some(CodedIndex((index << 5) | 0x11)),
-				MetadataTokenKind::ManifestResource => Some(CodedIndex((index << 5) | 0x12)),
-				MetadataTokenKind::GenericParam => Some(CodedIndex((index << 5) | 0x13)),
-				MetadataTokenKind::GenericParamConstraint => Some(CodedIndex((index << 5) | 0x14)),
-				MetadataTokenKind::MethodSpec => Some(CodedIndex((index << 5) | 0x15)),
-				MetadataTokenKind::Document => Some(CodedIndex((index << 5) | 0x16)),
-				MetadataTokenKind::LocalScope => Some(CodedIndex((index << 5) | 0x17)),
-				MetadataTokenKind::LocalVariable => Some(CodedIndex((index << 5) | 0x18)),
-				MetadataTokenKind::LocalConstant => Some(CodedIndex((index << 5) | 0x19)),
-				MetadataTokenKind::ImportScope => Some(CodedIndex((index << 5) | 0x1A)),
-				_ => None,
-			},
-		}
+		let descriptor = kind.descriptor();
+		let tag = descriptor.tag(token_kind.table()?)?;
+		Some(CodedIndex((index << descriptor.tag_bits) | tag))
 	}
 }
@@ -1,12 +1,28 @@
+mod attributes;
+#[cfg(feature = "checksums")]
+mod checksums;
+mod heap_builders;
 mod heaps;
 mod header;
 mod indices;
+mod names;
+mod root;
+mod signatures;
+mod sorting;
 pub(crate) mod tables;
 
+pub use attributes::*;
+#[cfg(feature = "checksums")]
+pub use checksums::*;
+pub use heap_builders::*;
 pub use heaps::*;
+pub use signatures::*;
 pub use header::*;
 pub use tables::*;
 pub use indices::*;
+pub use root::*;
+pub use sorting::*;
 
+pub(crate) use names::*;
 pub(crate) use heaps::private::MetadataHeap;
 pub(crate) use tables::private::MetadataTableImpl;
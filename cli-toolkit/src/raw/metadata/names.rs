@@ -0,0 +1,76 @@
+use crate::raw::*;
+
+pub(crate) fn type_name(tables: &TableHeap, strings: &StringHeap, token: MetadataToken) -> Result<String, Error> {
+	let full_name = |namespace: &str, name: &str| match namespace.is_empty() {
+		true => name.to_string(),
+		false => format!("{}.{}", namespace, name),
+	};
+
+	match token.token_kind() {
+		Some(MetadataTokenKind::TypeDef) => {
+			let table = tables.get_table::<TypeDefTable>()?.ok_or(Error::OffsetOutOfBounds)?;
+			let def = table.get(TableIndex(token.index() as u32))?;
+			Ok(full_name(strings.get_string(def.namespace()), strings.get_string(def.name())))
+		}
+
+		Some(MetadataTokenKind::TypeRef) => {
+			let table = tables.get_table::<TypeRefTable>()?.ok_or(Error::OffsetOutOfBounds)?;
+
+			// Nested TypeRefs are scoped to their enclosing TypeRef, so walk up to the outermost one.
+			// A chain longer than the table has a cycle in it.
+			let mut names = vec![];
+			let mut token = token;
+			for _ in 0..=table.len() {
+				let ty = table.get(TableIndex(token.index() as u32))?;
+				names.push(full_name(strings.get_string(ty.type_namespace()), strings.get_string(ty.type_name())));
+
+				match ty.resolution_scope().decode(CodedIndexKind::ResolutionScope) {
+					Some(scope) if scope.token_kind() == Some(MetadataTokenKind::TypeRef) && !scope.is_null() => {
+						token = scope;
+					}
+					_ => {
+						names.reverse();
+						return Ok(names.join("/"));
+					}
+				}
+			}
+
+			Err(Error::InvalidData(Some("Cyclic type reference scope")))
+		}
+
+		_ => Ok(format!("{:?}", token)),
+	}
+}
+
+/// Resolves the constructor of a custom attribute to the TypeDef or TypeRef of the attribute type.
+pub(crate) fn attribute_type(tables: &TableHeap, constructor: MetadataToken) -> Result<MetadataToken, Error> {
+	match constructor.token_kind() {
+		Some(MetadataTokenKind::MemberRef) => {
+			let member_refs = tables.get_table::<MemberRefTable>()?.ok_or(Error::OffsetOutOfBounds)?;
+			let member_ref = member_refs.get(TableIndex(constructor.index() as u32))?;
+			member_ref
+				.parent()
+				.decode(CodedIndexKind::MemberRefParent)
+				.ok_or(Error::InvalidData(Some("Invalid member reference parent")))
+		}
+
+		_ => {
+			let type_defs = tables.get_table::<TypeDefTable>()?.ok_or(Error::OffsetOutOfBounds)?;
+			let owner = method_owner(&type_defs, TableIndex(constructor.index() as u32))?;
+			let owner = owner.ok_or(Error::InvalidData(Some("Orphaned attribute constructor")))?;
+			Ok(MetadataToken::new(owner.0, MetadataTokenKind::TypeDef))
+		}
+	}
+}
+
+pub(crate) fn method_owner(type_defs: &TypeDefTable, method: TableIndex) -> Result<Option<TableIndex>, Error> {
+	let mut owner = None;
+	for (index, def) in type_defs.iter().enumerate() {
+		match def?.methods() <= method {
+			true => owner = Some(TableIndex(index as u32 + 1)),
+			false => break,
+		}
+	}
+
+	Ok(owner)
+}
@@ -0,0 +1,72 @@
+use crate::raw::*;
+
+/// A metadata root and the streams it describes, read without going through a PE image.
+///
+/// Both standalone portable PDBs, which have no PE wrapper, and the metadata of an [`Assembly`]
+/// can be inspected through this type.
+#[derive(Debug, Clone)]
+pub struct Metadata<'l> {
+	header: MetadataHeader<'l>,
+}
+
+impl<'l> Metadata<'l> {
+	/// Parses a metadata root starting at the first byte of `bytes`.
+	pub fn from_bytes(bytes: &'l [u8]) -> Result<Self, Error> {
+		Self::with_heap_slicing(bytes, HeapSlicing::Strict)
+	}
+
+	pub fn with_heap_slicing(bytes: &'l [u8], slicing: HeapSlicing) -> Result<Self, Error> {
+		let header = MetadataHeader::standalone(bytes)?.with_heap_slicing(slicing);
+		Ok(Self { header })
+	}
+
+	pub(crate) fn from_header(header: MetadataHeader<'l>) -> Self {
+		Self { header }
+	}
+
+	pub fn header(&self) -> &MetadataHeader<'l> {
+		&self.header
+	}
+
+	pub fn get_heap<T: MetadataHeap<'l>>(&self) -> Result<Option<T>, Error> {
+		self.header.get_heap()
+	}
+
+	/// The `#~` stream, which some metadata roots, such as the ones of minimal PDBs, do not have.
+	pub fn tables(&self) -> Result<Option<TableHeap<'l>>, Error> {
+		self.get_heap()
+	}
+
+	/// The `#Pdb` stream, only present in standalone portable PDBs.
+	pub fn pdb(&self) -> Result<Option<PdbHeap<'l>>, Error> {
+		self.get_heap()
+	}
+
+	/// The index widths of the `#~` stream, or `None` if it is missing.
+	/// A heap whose stream is missing counts as empty.
+	pub fn index_widths_report(&self) -> Result<Option<IndexWidthsReport>, Error> {
+		let Some(tables) = self.tables()? else { return Ok(None) };
+		let widths = tables.index_widths();
+		let strings = self.get_heap::<StringHeap>()?.map(|heap| heap.len()).unwrap_or(0);
+		let guids = self.get_heap::<GuidHeap>()?.map(|heap| heap.len()).unwrap_or(0);
+		let blobs = self.get_heap::<BlobHeap>()?.map(|heap| heap.len()).unwrap_or(0);
+
+		let forced = |width: IndexSize, len: usize| width == IndexSize::Fat && len <= u16::MAX as usize;
+		Ok(Some(IndexWidthsReport {
+			widths,
+			forced_fat: FatHeapIndices {
+				string: forced(widths.string, strings),
+				guid: forced(widths.guid, guids),
+				blob: forced(widths.blob, blobs),
+			},
+		}))
+	}
+
+	/// A table of the `#~` stream, or `None` if either the stream or the table is missing.
+	pub fn get_table<T: MetadataTableImpl<'l>>(&self) -> Result<Option<T>, Error> {
+		match self.tables()? {
+			Some(tables) => tables.get_table(),
+			None => Ok(None),
+		}
+	}
+}
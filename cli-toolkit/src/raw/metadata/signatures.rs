@@ -0,0 +1,412 @@
+use crate::raw::*;
+
+const MAX_DEPTH: usize = 64;
+/// The largest rank the runtime supports.
+pub const MAX_ARRAY_RANK: u32 = 32;
+
+pub mod calling_conventions {
+	pub type CallingConvention = u8;
+	pub const KIND_MASK: CallingConvention = 0x0F;
+	pub const DEFAULT: CallingConvention = 0x00;
+	pub const C: CallingConvention = 0x01;
+	pub const STDCALL: CallingConvention = 0x02;
+	pub const THISCALL: CallingConvention = 0x03;
+	pub const FASTCALL: CallingConvention = 0x04;
+	pub const VARARG: CallingConvention = 0x05;
+	pub const FIELD: CallingConvention = 0x06;
+	pub const LOCAL_SIG: CallingConvention = 0x07;
+	pub const PROPERTY: CallingConvention = 0x08;
+	pub const UNMANAGED: CallingConvention = 0x09;
+	pub const GENERIC_INST: CallingConvention = 0x0A;
+	pub const NATIVE_VARARG: CallingConvention = 0x0B;
+	pub const GENERIC: CallingConvention = 0x10;
+	pub const HAS_THIS: CallingConvention = 0x20;
+	pub const EXPLICIT_THIS: CallingConvention = 0x40;
+}
+
+pub use calling_conventions::CallingConvention;
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum TypeSignature {
+	Void,
+	Boolean,
+	Char,
+	Int8,
+	UInt8,
+	Int16,
+	UInt16,
+	Int32,
+	UInt32,
+	Int64,
+	UInt64,
+	Float,
+	Double,
+	String,
+	Object,
+	IntPtr,
+	UIntPtr,
+	TypedByRef,
+	Class(MetadataToken),
+	ValueType(MetadataToken),
+	Var(u32),
+	MVar(u32),
+	Ptr(Box<TypeSignature>),
+	ByRef(Box<TypeSignature>),
+	Pinned(Box<TypeSignature>),
+	SzArray(Box<TypeSignature>),
+	Array(Box<TypeSignature>, ArrayShape),
+	GenericInst(Box<TypeSignature>, Vec<TypeSignature>),
+	FnPtr(Box<MethodSignature>),
+	Modified {
+		required: bool,
+		modifier: MetadataToken,
+		ty: Box<TypeSignature>,
+	},
+}
+
+/// The rank, sizes and lower bounds of a general (non-SZ) array. Dimensions without
+/// a size or lower bound are not listed, so both vectors may be shorter than the rank.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct ArrayShape {
+	pub rank: u32,
+	pub sizes: Vec<u32>,
+	pub lower_bounds: Vec<i32>,
+}
+
+/// A dimension of an [`ArrayShape`], see [`ArrayShape::dimensions`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct ArrayDimension {
+	pub lower_bound: Option<i32>,
+	pub size: Option<u32>,
+}
+
+impl ArrayDimension {
+	/// The last valid index of the dimension, if its size is known and not 0.
+	/// A missing lower bound is taken to be 0, as the runtime does.
+	pub fn upper_bound(&self) -> Option<i64> {
+		let size = self.size.filter(|size| *size != 0)? as i64;
+		Some(self.lower_bound.unwrap_or(0) as i64 + size - 1)
+	}
+}
+
+impl ArrayShape {
+	/// Parses the shape following the element type of an `ELEMENT_TYPE_ARRAY`.
+	pub fn parse(blob: &[u8]) -> Result<Self, Error> {
+		SignatureReader::new(blob).read_array_shape()
+	}
+
+	/// Every dimension of the array, including the ones without a size or lower bound.
+	pub fn dimensions(&self) -> impl Iterator<Item = ArrayDimension> + '_ {
+		(0..self.rank as usize).map(|dimension| ArrayDimension {
+			lower_bound: self.lower_bounds.get(dimension).copied(),
+			size: self.sizes.get(dimension).copied(),
+		})
+	}
+
+	/// Whether the shape gives a size or a non-zero lower bound, unlike C# multi-dimensional arrays such as `int[,]`,
+	/// whose dimensions all start at 0.
+	pub fn has_bounds(&self) -> bool {
+		!self.sizes.is_empty() || self.lower_bounds.iter().any(|lower_bound| *lower_bound != 0)
+	}
+
+	/// Encodes the shape, the inverse of [`ArrayShape::parse`].
+	pub fn encode(&self, out: &mut Vec<u8>) -> Result<(), Error> {
+		encode_compressed_u32(self.rank, out)?;
+		encode_compressed_u32(self.sizes.len() as u32, out)?;
+		for size in self.sizes.iter() {
+			encode_compressed_u32(*size, out)?;
+		}
+
+		encode_compressed_u32(self.lower_bounds.len() as u32, out)?;
+		for lower_bound in self.lower_bounds.iter() {
+			encode_compressed_i32(*lower_bound, out)?;
+		}
+
+		Ok(())
+	}
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct MethodSignature {
+	pub calling_convention: CallingConvention,
+	pub generic_param_count: u32,
+	pub return_type: TypeSignature,
+	pub params: Vec<TypeSignature>,
+	/// The parameters following the sentinel of a vararg call site.
+	pub vararg_params: Vec<TypeSignature>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct FieldSignature {
+	pub ty: TypeSignature,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct PropertySignature {
+	pub has_this: bool,
+	pub ty: TypeSignature,
+	pub params: Vec<TypeSignature>,
+}
+
+/// The blob referenced by a StandAloneSig row, used either for the locals of a method body
+/// or for the call site of a `calli` instruction.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum StandAloneSignature {
+	Locals(Vec<TypeSignature>),
+	CallSite(MethodSignature),
+}
+
+impl MethodSignature {
+	pub fn parse(blob: &[u8]) -> Result<Self, Error> {
+		SignatureReader::new(blob).read_method()
+	}
+
+	pub fn has_this(&self) -> bool {
+		self.calling_convention & calling_conventions::HAS_THIS != 0
+	}
+
+	pub fn is_generic(&self) -> bool {
+		self.calling_convention & calling_conventions::GENERIC != 0
+	}
+
+	pub fn is_vararg(&self) -> bool {
+		self.calling_convention & calling_conventions::KIND_MASK == calling_conventions::VARARG
+	}
+}
+
+impl FieldSignature {
+	pub fn parse(blob: &[u8]) -> Result<Self, Error> {
+		let mut reader = SignatureReader::new(blob);
+		match reader.stream.read::<u8>()? & calling_conventions::KIND_MASK {
+			calling_conventions::FIELD => Ok(Self {
+				ty: reader.read_type()?,
+			}),
+			_ => Err(Error::InvalidData(Some("Invalid field signature"))),
+		}
+	}
+}
+
+impl PropertySignature {
+	pub fn parse(blob: &[u8]) -> Result<Self, Error> {
+		let mut reader = SignatureReader::new(blob);
+		let header = reader.stream.read::<u8>()?;
+		if header & calling_conventions::KIND_MASK != calling_conventions::PROPERTY {
+			return Err(Error::InvalidData(Some("Invalid property signature")));
+		}
+
+		let count = reader.stream.read_compressed_u32()?;
+		let ty = reader.read_type()?;
+		let params = reader.read_types(count)?;
+
+		Ok(Self {
+			has_this: header & calling_conventions::HAS_THIS != 0,
+			ty,
+			params,
+		})
+	}
+}
+
+impl StandAloneSignature {
+	pub fn parse(blob: &[u8]) -> Result<Self, Error> {
+		let mut reader = SignatureReader::new(blob);
+		match blob.first().map(|b| b & calling_conventions::KIND_MASK) {
+			Some(calling_conventions::LOCAL_SIG) => {
+				reader.stream.read::<u8>()?;
+				let count = reader.stream.read_compressed_u32()?;
+				Ok(Self::Locals(reader.read_types(count)?))
+			}
+
+			Some(calling_conventions::FIELD | calling_conventions::PROPERTY | calling_conventions::GENERIC_INST) => {
+				Err(Error::InvalidData(Some("Invalid stand-alone signature")))
+			}
+
+			_ => Ok(Self::CallSite(reader.read_method()?)),
+		}
+	}
+}
+
+impl StandAloneSig {
+	pub fn decode(&self, blobs: &BlobHeap) -> Result<StandAloneSignature, Error> {
+		StandAloneSignature::parse(blobs.get_blob(self.signature())?)
+	}
+}
+
+impl TypeSignature {
+	pub fn parse(blob: &[u8]) -> Result<Self, Error> {
+		SignatureReader::new(blob).read_type()
+	}
+}
+
+/// Parses the instantiation blob of a MethodSpec row into its type arguments.
+pub fn parse_method_spec(blob: &[u8]) -> Result<Vec<TypeSignature>, Error> {
+	let mut reader = SignatureReader::new(blob);
+	match reader.stream.read::<u8>()? {
+		calling_conventions::GENERIC_INST => {
+			let count = reader.stream.read_compressed_u32()?;
+			reader.read_types(count)
+		}
+		_ => Err(Error::InvalidData(Some("Invalid method specification signature"))),
+	}
+}
+
+struct SignatureReader<'l> {
+	stream: ByteStream<'l>,
+	depth: usize,
+}
+
+impl<'l> SignatureReader<'l> {
+	fn new(blob: &'l [u8]) -> Self {
+		Self {
+			stream: ByteStream::new(blob),
+			depth: 0,
+		}
+	}
+
+	fn read_method(&mut self) -> Result<MethodSignature, Error> {
+		let calling_convention = self.stream.read::<u8>()?;
+		let generic_param_count = match calling_convention & calling_conventions::GENERIC != 0 {
+			true => self.stream.read_compressed_u32()?,
+			false => 0,
+		};
+
+		let count = self.stream.read_compressed_u32()?;
+		let return_type = self.read_type()?;
+
+		let mut params = vec![];
+		let mut vararg_params = vec![];
+		for _ in 0..count {
+			if self.stream.bytes().get(self.stream.position()) == Some(&(ElementType::Sentinel as u8)) {
+				self.stream.skip(1)?;
+				vararg_params.push(self.read_type()?);
+				continue;
+			}
+
+			match vararg_params.is_empty() {
+				true => params.push(self.read_type()?),
+				false => vararg_params.push(self.read_type()?),
+			}
+		}
+
+		Ok(MethodSignature {
+			calling_convention,
+			generic_param_count,
+			return_type,
+			params,
+			vararg_params,
+		})
+	}
+
+	fn read_types(&mut self, count: u32) -> Result<Vec<TypeSignature>, Error> {
+		let mut types = Vec::with_capacity(count.min(self.stream.remaining() as u32) as usize);
+		for _ in 0..count {
+			types.push(self.read_type()?);
+		}
+		Ok(types)
+	}
+
+	fn read_token(&mut self) -> Result<MetadataToken, Error> {
+		CodedIndex(self.stream.read_compressed_u32()?)
+			.decode(CodedIndexKind::TypeDefOrRef)
+			.ok_or(Error::InvalidData(Some("Invalid TypeDefOrRefOrSpecEncoded token")))
+	}
+
+	/// Reads an `ArrayShape`, which lists at most one size and one lower bound per dimension.
+	/// Ranks beyond [`MAX_ARRAY_RANK`] are rejected, so that rendering a hostile shape stays cheap.
+	fn read_array_shape(&mut self) -> Result<ArrayShape, Error> {
+		let rank = self.stream.read_compressed_u32()?;
+		if rank == 0 || rank > MAX_ARRAY_RANK {
+			return Err(Error::InvalidData(Some("Invalid array rank")));
+		}
+
+		let size_count = self.stream.read_compressed_u32()?;
+		if size_count > rank {
+			return Err(Error::InvalidData(Some("Array shape with more sizes than dimensions")));
+		}
+
+		let mut sizes = Vec::with_capacity(size_count.min(self.stream.remaining() as u32) as usize);
+		for _ in 0..size_count {
+			sizes.push(self.stream.read_compressed_u32()?);
+		}
+
+		let bound_count = self.stream.read_compressed_u32()?;
+		if bound_count > rank {
+			return Err(Error::InvalidData(Some("Array shape with more lower bounds than dimensions")));
+		}
+
+		let mut lower_bounds = Vec::with_capacity(bound_count.min(self.stream.remaining() as u32) as usize);
+		for _ in 0..bound_count {
+			lower_bounds.push(self.stream.read_compressed_i32()?);
+		}
+
+		Ok(ArrayShape {
+			rank,
+			sizes,
+			lower_bounds,
+		})
+	}
+
+	fn read_type(&mut self) -> Result<TypeSignature, Error> {
+		if self.depth >= MAX_DEPTH {
+			return Err(Error::InvalidData(Some("Signature nesting too deep")));
+		}
+
+		self.depth += 1;
+		let ty = self.read_type_inner();
+		self.depth -= 1;
+		ty
+	}
+
+	fn read_type_inner(&mut self) -> Result<TypeSignature, Error> {
+		let element_type = ElementType::try_from(self.stream.read::<u8>()?)?;
+		let ty = match element_type {
+			ElementType::Void => TypeSignature::Void,
+			ElementType::Bool => TypeSignature::Boolean,
+			ElementType::Char => TypeSignature::Char,
+			ElementType::I1 => TypeSignature::Int8,
+			ElementType::U1 => TypeSignature::UInt8,
+			ElementType::I2 => TypeSignature::Int16,
+			ElementType::U2 => TypeSignature::UInt16,
+			ElementType::I4 => TypeSignature::Int32,
+			ElementType::U4 => TypeSignature::UInt32,
+			ElementType::I8 => TypeSignature::Int64,
+			ElementType::U8 => TypeSignature::UInt64,
+			ElementType::R4 => TypeSignature::Float,
+			ElementType::R8 => TypeSignature::Double,
+			ElementType::String => TypeSignature::String,
+			ElementType::Object => TypeSignature::Object,
+			ElementType::IPtr => TypeSignature::IntPtr,
+			ElementType::UPtr => TypeSignature::UIntPtr,
+			ElementType::TypedByRef => TypeSignature::TypedByRef,
+			ElementType::Class => TypeSignature::Class(self.read_token()?),
+			ElementType::ValueType => TypeSignature::ValueType(self.read_token()?),
+			ElementType::Var => TypeSignature::Var(self.stream.read_compressed_u32()?),
+			ElementType::MVar => TypeSignature::MVar(self.stream.read_compressed_u32()?),
+			ElementType::Ptr => TypeSignature::Ptr(Box::new(self.read_type()?)),
+			ElementType::ByRef => TypeSignature::ByRef(Box::new(self.read_type()?)),
+			ElementType::Pinned => TypeSignature::Pinned(Box::new(self.read_type()?)),
+			ElementType::SzArray => TypeSignature::SzArray(Box::new(self.read_type()?)),
+			ElementType::FnPtr => TypeSignature::FnPtr(Box::new(self.read_method()?)),
+
+			ElementType::Array => {
+				let ty = self.read_type()?;
+				TypeSignature::Array(Box::new(ty), self.read_array_shape()?)
+			}
+
+			ElementType::GenericInst => {
+				let ty = self.read_type()?;
+				let count = self.stream.read_compressed_u32()?;
+				TypeSignature::GenericInst(Box::new(ty), self.read_types(count)?)
+			}
+
+			ElementType::CModReqd | ElementType::CModOpt => TypeSignature::Modified {
+				required: element_type == ElementType::CModReqd,
+				modifier: self.read_token()?,
+				ty: Box::new(self.read_type()?),
+			},
+
+			_ => return Err(Error::InvalidData(Some("Unexpected element type in signature"))),
+		};
+
+		Ok(ty)
+	}
+}
@@ -0,0 +1,105 @@
+use crate::raw::*;
+
+/// A row of one of the tables that ECMA-335 requires to be sorted, such as CustomAttribute by parent.
+pub trait SortedRow: MetadataRow {
+	/// The raw values of the columns the table is sorted by, primary column first.
+	/// Coded indices are compared by their encoded value, as the runtime does.
+	fn sort_key(&self) -> (u32, u32);
+}
+
+macro_rules! sorted_row {
+	($row: ident, |$self: ident| $key: expr) => {
+		impl SortedRow for $row {
+			fn sort_key(&$self) -> (u32, u32) {
+				$key
+			}
+		}
+	};
+}
+
+sorted_row!(ClassLayout, |self| (self.parent().0, 0));
+sorted_row!(Constant, |self| (self.parent().0, 0));
+sorted_row!(CustomAttribute, |self| (self.parent().0, 0));
+sorted_row!(DeclSecurity, |self| (self.parent().0, 0));
+sorted_row!(FieldLayout, |self| (self.field().0, 0));
+sorted_row!(FieldMarshal, |self| (self.parent().0, 0));
+sorted_row!(FieldRVA, |self| (self.field().0, 0));
+sorted_row!(GenericParam, |self| (self.owner().0, self.number() as u32));
+sorted_row!(GenericParamConstraint, |self| (self.owner().0, 0));
+sorted_row!(ImplMap, |self| (self.member_forwarded().0, 0));
+sorted_row!(InterfaceImpl, |self| (self.class().0, self.interface().0));
+sorted_row!(MethodImpl, |self| (self.class().0, 0));
+sorted_row!(MethodSemantics, |self| (self.association().0, 0));
+sorted_row!(NestedClass, |self| (self.nested_class().0, 0));
+
+/// Where a new row goes in a sorted table, and how the indices of the existing rows move because of it.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct SortedInsertion {
+	table: TableKind,
+	index: TableIndex,
+}
+
+impl SortedInsertion {
+	/// Finds the position of `row` in `table`, after every row with an equal key so that insertion order is kept.
+	pub fn find<'l, T: MetadataTable<'l>>(table: &T, row: &T::Row) -> Result<Self, Error>
+	where
+		T::Row: SortedRow,
+	{
+		let key = row.sort_key();
+		let (mut low, mut high) = (0, table.len());
+		while low < high {
+			let middle = (low + high) / 2;
+			match table.get(TableIndex(middle as u32 + 1))?.sort_key() <= key {
+				true => low = middle + 1,
+				false => high = middle,
+			}
+		}
+
+		Ok(Self {
+			table: T::cli_identifier(),
+			index: TableIndex(low as u32 + 1),
+		})
+	}
+
+	/// The index of the new row.
+	pub fn index(&self) -> TableIndex {
+		self.index
+	}
+
+	pub fn token(&self) -> MetadataToken {
+		MetadataToken::from_table_row(self.table, self.index)
+	}
+
+	/// The index an existing row has once the new one is inserted.
+	pub fn remap(&self, index: TableIndex) -> TableIndex {
+		match index.0 >= self.index.0 {
+			true => TableIndex(index.0 + 1),
+			false => index,
+		}
+	}
+
+	/// Like [`SortedInsertion::remap`], for tokens of any table. Tokens of other tables are left untouched.
+	pub fn remap_token(&self, token: MetadataToken) -> MetadataToken {
+		match token.table_kind() == Some(self.table) && !token.is_null() {
+			true => MetadataToken::from_table_row(self.table, self.remap(TableIndex(token.index() as u32))),
+			false => token,
+		}
+	}
+}
+
+/// Whether the rows of `table` are actually in order, which is what the `sorted` bit of the table claims.
+pub fn is_table_sorted<'l, T: MetadataTable<'l>>(table: &T) -> Result<bool, Error>
+where
+	T::Row: SortedRow,
+{
+	let mut previous = (0, 0);
+	for row in table.iter() {
+		let key = row?.sort_key();
+		if key < previous {
+			return Ok(false);
+		}
+		previous = key;
+	}
+
+	Ok(true)
+}
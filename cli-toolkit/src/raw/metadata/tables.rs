@@ -9,11 +9,20 @@ pub use method_flags::MethodFlags;
 pub use field_flags::FieldFlags;
 pub use param_flags::ParamFlags;
 pub use event_flags::EventFlags;
+pub use file_flags::FileFlags;
+pub use generic_param_flags::GenericParamFlags;
+pub use manifest_resource_flags::ManifestResourceFlags;
+pub use security_action::SecurityAction;
 use private::ParseRow;
-use strum::EnumIter;
+use strum::{EnumIter, IntoEnumIterator, IntoStaticStr};
+use std::fmt::{Display, Formatter};
+use std::ops::{BitAnd, BitOr, Sub};
+use std::str::FromStr;
 use crate::raw::*;
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq, EnumIter)]
+/// A metadata table, by the number of its bit in the `valid` mask of the [`TableHeap`].
+/// Tables are printed and parsed by the names of ECMA-335 II.22, e.g. `"TypeDef"`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Ord, PartialOrd, EnumIter, IntoStaticStr)]
 pub enum TableKind {
 	Module = 0x00,
 	TypeRef = 0x01,
@@ -71,6 +80,251 @@ pub enum TableKind {
 	CustomDebugInformation = 0x37,
 }
 
+impl TableKind {
+	pub fn name(&self) -> &'static str {
+		self.into()
+	}
+}
+
+impl Display for TableKind {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		f.write_str(self.name())
+	}
+}
+
+impl FromStr for TableKind {
+	type Err = Error;
+
+	fn from_str(name: &str) -> Result<Self, Self::Err> {
+		let kind = TableKind::iter().find(|kind| kind.name() == name);
+		kind.ok_or(Error::InvalidData(Some("Unknown table kind")))
+	}
+}
+
+/// Converts the number of a table, e.g. the high byte of a token or a bit of the `valid` mask.
+impl TryFrom<u8> for TableKind {
+	type Error = Error;
+
+	fn try_from(value: u8) -> Result<Self, Self::Error> {
+		let kind = TableKind::iter().find(|kind| *kind as u8 == value);
+		kind.ok_or(Error::InvalidData(Some("Unknown table kind")))
+	}
+}
+
+/// A set of tables, stored like the `valid` and `sorted` masks of a [`TableHeap`], i.e. one bit per [`TableKind`].
+///
+/// ```ignore
+/// let mask = TableMask::from_iter([TableKind::TypeDef, TableKind::MethodDef]);
+/// assert!(tables.valid_mask().is_superset(mask));
+/// ```
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub struct TableMask(u64);
+
+impl TableMask {
+	pub const EMPTY: Self = Self(0);
+
+	pub fn from_bits(bits: u64) -> Self {
+		Self(bits)
+	}
+
+	pub fn bits(&self) -> u64 {
+		self.0
+	}
+
+	/// The set bits that do not correspond to a known table, which are invalid in well-formed images.
+	pub fn unknown_bits(&self) -> u64 {
+		self.0 & !TableKind::iter().fold(0, |known, kind| known | TableMask::from(kind).0)
+	}
+
+	pub fn contains(&self, kind: TableKind) -> bool {
+		self.0 & TableMask::from(kind).0 != 0
+	}
+
+	pub fn insert(&mut self, kind: TableKind) {
+		self.0 |= TableMask::from(kind).0;
+	}
+
+	pub fn remove(&mut self, kind: TableKind) {
+		self.0 &= !TableMask::from(kind).0;
+	}
+
+	/// The number of set bits, including unknown ones.
+	pub fn len(&self) -> usize {
+		self.0.count_ones() as usize
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.0 == 0
+	}
+
+	pub fn union(self, other: Self) -> Self {
+		Self(self.0 | other.0)
+	}
+
+	pub fn intersection(self, other: Self) -> Self {
+		Self(self.0 & other.0)
+	}
+
+	pub fn difference(self, other: Self) -> Self {
+		Self(self.0 & !other.0)
+	}
+
+	pub fn is_superset(&self, other: Self) -> bool {
+		other.0 & !self.0 == 0
+	}
+
+	/// The number of set bits below the bit of `kind`, i.e. the position of its row count in a [`TableHeap`].
+	pub fn position(&self, kind: TableKind) -> usize {
+		(self.0 & (TableMask::from(kind).0 - 1)).count_ones() as usize
+	}
+
+	/// The known tables of the set, in table order.
+	pub fn iter(&self) -> impl Iterator<Item = TableKind> {
+		let mask = *self;
+		TableKind::iter().filter(move |kind| mask.contains(*kind))
+	}
+}
+
+impl From<TableKind> for TableMask {
+	fn from(kind: TableKind) -> Self {
+		Self(1 << kind as u64)
+	}
+}
+
+impl FromIterator<TableKind> for TableMask {
+	fn from_iter<T: IntoIterator<Item = TableKind>>(iter: T) -> Self {
+		let mut mask = Self::EMPTY;
+		iter.into_iter().for_each(|kind| mask.insert(kind));
+		mask
+	}
+}
+
+impl BitOr for TableMask {
+	type Output = Self;
+	fn bitor(self, rhs: Self) -> Self {
+		self.union(rhs)
+	}
+}
+
+impl BitAnd for TableMask {
+	type Output = Self;
+	fn bitand(self, rhs: Self) -> Self {
+		self.intersection(rhs)
+	}
+}
+
+impl Sub for TableMask {
+	type Output = Self;
+	fn sub(self, rhs: Self) -> Self {
+		self.difference(rhs)
+	}
+}
+
+/// Lists the tables of the set, e.g. `Module, TypeRef, TypeDef`, followed by the unknown bits if there are any.
+impl Display for TableMask {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		if self.is_empty() {
+			return f.write_str("none");
+		}
+
+		let mut separator = "";
+		for kind in self.iter() {
+			write!(f, "{}{}", separator, kind)?;
+			separator = ", ";
+		}
+
+		match self.unknown_bits() {
+			0 => Ok(()),
+			bits => write!(f, "{}unknown 0x{:X}", separator, bits),
+		}
+	}
+}
+
+/// A type-erased view over one of the tables of a [`TableHeap`].
+#[derive(Debug, Copy, Clone)]
+pub struct GenericMetadataTable<'l> {
+	pub(crate) kind: TableKind,
+	pub(crate) len: u32,
+	pub(crate) row_size: usize,
+	pub(crate) bytes: &'l [u8],
+}
+
+impl<'l> GenericMetadataTable<'l> {
+	pub fn kind(&self) -> TableKind {
+		self.kind
+	}
+
+	pub fn len(&self) -> usize {
+		self.len as usize
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+
+	pub fn row_size(&self) -> usize {
+		self.row_size
+	}
+
+	pub fn bytes(&self) -> &'l [u8] {
+		self.bytes
+	}
+
+	/// The raw bytes of the row at the given 1-based index.
+	pub fn row(&self, index: TableIndex) -> Result<&'l [u8], Error> {
+		if index.0 == 0 || index.0 > self.len {
+			let (table, len) = (self.kind, self.len);
+			return Err(Error::RowIndexOutOfBounds {
+				table,
+				index: index.0,
+				len,
+			});
+		}
+
+		let start = (index.0 as usize - 1) * self.row_size;
+		self.bytes
+			.get(start..start + self.row_size)
+			.ok_or(Error::OffsetOutOfBounds)
+	}
+
+	/// The typed view of the table, or `None` if it is a table of another kind.
+	///
+	/// The view is parsed from the bytes of the table rather than cast, and `tables` must be the heap
+	/// the table was read from, so that its rows are laid out with the same index sizes.
+	/// Table types cannot be implemented outside of this crate, so the kind of `T` always matches its layout.
+	///
+	/// ```ignore
+	/// for table in heap.iter_tables()? {
+	///     if let Some(type_defs) = table.downcast::<TypeDefTable>(&heap)? {
+	///         println!("{} types", type_defs.len());
+	///     }
+	/// }
+	/// ```
+	pub fn downcast<T: MetadataTable<'l>>(&self, tables: &TableHeap) -> Result<Option<T>, Error> {
+		if self.kind != T::cli_identifier() {
+			return Ok(None);
+		}
+
+		if self.row_size != T::calc_row_size(tables) {
+			return Err(Error::InvalidData(Some("Table read from another heap")));
+		}
+
+		T::new(self.bytes, tables).map(Some)
+	}
+
+	pub fn is<T: MetadataTable<'l>>(&self) -> bool {
+		self.kind == T::cli_identifier()
+	}
+}
+
+/// A column of a table row, with its offset and size in bytes within the row.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct TableColumn {
+	pub name: &'static str,
+	pub offset: usize,
+	pub size: usize,
+}
+
 pub trait MetadataTable<'l>
 where
 	Self: MetadataTableImpl<'l> + ParseRow,
@@ -85,29 +339,361 @@ where
 		self.bytes().len() / self.row_size()
 	}
 
+	fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	/// The row at the given 1-based index.
 	fn get(&self, index: TableIndex) -> Result<Self::Row, Error> {
+		let len = self.len() as u32;
+		if index.0 == 0 || index.0 > len {
+			let table = Self::cli_identifier();
+			return Err(Error::RowIndexOutOfBounds {
+				table,
+				index: index.0,
+				len,
+			});
+		}
+
 		let mut reader = ByteStream::new(self.bytes());
 		reader.seek(self.row_size() * ((index.0 - 1) as usize))?;
 		self.parse_row(&mut reader)
 	}
+
+	/// Splits the table into chunks of at most `rows` rows, so that they can be scanned in parallel.
+	///
+	/// ```ignore
+	/// let chunks = table.par_chunks(4096);
+	/// let rows = chunks.par_iter().map(|chunk| chunk.table.iter().count()).sum::<usize>();
+	/// ```
+	fn par_chunks(&self, rows: usize) -> Vec<TableChunk<Self>> {
+		let rows = rows.max(1);
+		self.bytes()
+			.chunks(rows.saturating_mul(self.row_size()).max(1))
+			.enumerate()
+			.map(|(index, bytes)| TableChunk {
+				first_row: TableIndex((index * rows + 1) as u32),
+				table: self.with_bytes(bytes),
+			})
+			.collect()
+	}
+
+	/// Like [`MetadataTable::get`], but treats the null index as the absence of a row.
+	fn try_get(&self, index: TableIndex) -> Result<Option<Self::Row>, Error> {
+		match index.0 {
+			0 => Ok(None),
+			_ => self.get(index).map(Some),
+		}
+	}
+
+	/// A cursor positioned on the first row.
+	fn cursor(&self) -> TableCursor<'_, Self> {
+		TableCursor {
+			table: self,
+			position: 1,
+		}
+	}
+}
+
+/// A range of rows of a table, as produced by [`MetadataTable::par_chunks`].
+/// Rows are indexed relative to the chunk, so the row at index `i` of the chunk is row `first_row + i - 1` of the table.
+#[derive(Clone)]
+pub struct TableChunk<T> {
+	pub first_row: TableIndex,
+	pub table: T,
+}
+
+/// A position within a table, for scans that move back and forth or jump to the rows of a parent.
+///
+/// The cursor is always between two rows: [`TableCursor::peek`] and [`TableCursor::next`] return the row after it,
+/// and [`TableCursor::prev`] the row before it. Once past the last row, the position is `len + 1`.
+///
+/// ```ignore
+/// let mut cursor = custom_attributes.cursor();
+/// cursor.seek_partition_point(|row| row.parent().0 < parent.0)?;
+/// while let Some(row) = cursor.peek().transpose()? {
+///     if row.parent() != parent { break }
+///     cursor.next();
+/// }
+/// ```
+pub struct TableCursor<'t, T> {
+	table: &'t T,
+	position: u32,
+}
+
+impl<'t, 'l, T: MetadataTable<'l>> TableCursor<'t, T> {
+	/// The index of the row [`TableCursor::next`] returns.
+	pub fn index(&self) -> TableIndex {
+		TableIndex(self.position)
+	}
+
+	/// The token of the row [`TableCursor::next`] returns.
+	pub fn token(&self) -> MetadataToken {
+		MetadataToken::from_table_row(T::cli_identifier(), self.index())
+	}
+
+	pub fn is_at_end(&self) -> bool {
+		self.position as usize > self.table.len()
+	}
+
+	/// Moves before the row at `index`, or past the last row if `index` is `len + 1`.
+	pub fn seek(&mut self, index: TableIndex) -> Result<(), Error> {
+		let len = self.table.len() as u32;
+		if index.0 == 0 || index.0 > len + 1 {
+			let table = T::cli_identifier();
+			return Err(Error::RowIndexOutOfBounds {
+				table,
+				index: index.0,
+				len,
+			});
+		}
+
+		self.position = index.0;
+		Ok(())
+	}
+
+	/// Like [`TableCursor::seek`], for a token of the table.
+	pub fn seek_token(&mut self, token: MetadataToken) -> Result<(), Error> {
+		match token.table_kind() == Some(T::cli_identifier()) {
+			true => self.seek(TableIndex(token.index() as u32)),
+			false => Err(Error::InvalidData(Some("Token of another table"))),
+		}
+	}
+
+	/// Moves before the first row for which `predicate` is false, assuming that it is true for every row before it
+	/// and false for every row after it, as for the keys of a sorted table. Returns the new index.
+	pub fn seek_partition_point(&mut self, mut predicate: impl FnMut(&T::Row) -> bool) -> Result<TableIndex, Error> {
+		let (mut low, mut high) = (0, self.table.len());
+		while low < high {
+			let middle = (low + high) / 2;
+			match predicate(&self.table.get(TableIndex(middle as u32 + 1))?) {
+				true => low = middle + 1,
+				false => high = middle,
+			}
+		}
+
+		self.position = low as u32 + 1;
+		Ok(self.index())
+	}
+
+	/// The row after the cursor, without moving it.
+	pub fn peek(&self) -> Option<Result<T::Row, Error>> {
+		match self.is_at_end() {
+			true => None,
+			false => Some(self.table.get(self.index())),
+		}
+	}
+
+	/// Moves back over the row before the cursor, and returns it.
+	pub fn prev(&mut self) -> Option<Result<T::Row, Error>> {
+		if self.position <= 1 {
+			return None;
+		}
+
+		self.position -= 1;
+		Some(self.table.get(self.index()))
+	}
+}
+
+impl<'t, 'l, T: MetadataTable<'l>> Iterator for TableCursor<'t, T> {
+	type Item = Result<T::Row, Error>;
+
+	/// Moves over the row after the cursor, and returns it.
+	fn next(&mut self) -> Option<Self::Item> {
+		let row = self.peek()?;
+		self.position += 1;
+		Some(row)
+	}
+}
+
+/// A row that could not be read, and its position within the iteration.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct RowError {
+	/// The one-based position of the row among the items of the iterator,
+	/// which is its index when iterating over a whole table.
+	pub index: TableIndex,
+	pub error: Error,
+}
+
+impl Display for RowError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		write!(f, "row {}: {:?}", self.index.0, self.error)
+	}
+}
+
+/// Collects the errors of [`RowResults::skip_errors`], and decides whether they end the iteration.
+#[derive(Debug, Clone, Default)]
+pub struct RowDiagnostics {
+	fail_fast: bool,
+	errors: Vec<RowError>,
+}
+
+impl RowDiagnostics {
+	/// Records every error and keeps iterating over the rows after it.
+	pub fn lenient() -> Self {
+		Self::default()
+	}
+
+	/// Records the first error and ends the iteration.
+	pub fn fail_fast() -> Self {
+		Self {
+			fail_fast: true,
+			errors: vec![],
+		}
+	}
+
+	pub fn is_fail_fast(&self) -> bool {
+		self.fail_fast
+	}
+
+	pub fn errors(&self) -> &[RowError] {
+		&self.errors
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.errors.is_empty()
+	}
+
+	/// The first recorded error, if any, for callers that tolerate nothing after all.
+	pub fn result(&self) -> Result<(), Error> {
+		match self.errors.first() {
+			Some(error) => Err(error.error),
+			None => Ok(()),
+		}
+	}
+}
+
+/// Adapters for iterators of rows, such as [`MetadataTable::iter`], that should not give up on the first bad row.
+///
+/// ```ignore
+/// let mut diagnostics = RowDiagnostics::lenient();
+/// let names: Vec<_> = type_defs.iter().skip_errors(&mut diagnostics).map(|row| row.type_name()).collect();
+/// for error in diagnostics.errors() {
+///     eprintln!("skipped {}", error);
+/// }
+/// ```
+pub trait RowResults<R>: Iterator<Item = Result<R, Error>> + Sized {
+	/// Yields the rows that could be read, and records the others in `diagnostics`.
+	/// A [`RowDiagnostics::fail_fast`] ends the iteration on the first error instead.
+	fn skip_errors(self, diagnostics: &mut RowDiagnostics) -> SkipErrors<'_, Self> {
+		SkipErrors {
+			rows: self,
+			diagnostics,
+			position: 0,
+			done: false,
+		}
+	}
+
+	/// Yields the rows up to the first one that cannot be read.
+	fn take_valid(self) -> TakeValid<Self> {
+		TakeValid {
+			rows: self,
+			done: false,
+		}
+	}
+}
+
+impl<R, I: Iterator<Item = Result<R, Error>>> RowResults<R> for I {}
+
+pub struct SkipErrors<'d, I> {
+	rows: I,
+	diagnostics: &'d mut RowDiagnostics,
+	position: u32,
+	done: bool,
+}
+
+impl<R, I: Iterator<Item = Result<R, Error>>> Iterator for SkipErrors<'_, I> {
+	type Item = R;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		while !self.done {
+			self.position += 1;
+			match self.rows.next()? {
+				Ok(row) => return Some(row),
+				Err(error) => {
+					let index = TableIndex(self.position);
+					self.diagnostics.errors.push(RowError { index, error });
+					self.done = self.diagnostics.fail_fast;
+				}
+			}
+		}
+
+		None
+	}
+}
+
+pub struct TakeValid<I> {
+	rows: I,
+	done: bool,
+}
+
+impl<R, I: Iterator<Item = Result<R, Error>>> Iterator for TakeValid<I> {
+	type Item = R;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.done {
+			return None;
+		}
+
+		let row = self.rows.next()?.ok();
+		self.done = row.is_none();
+		row
+	}
+}
+
+const _: () = {
+	fn assert_send_sync<T: Send + Sync>() {}
+	fn assert_heaps() {
+		assert_send_sync::<TableHeap<'static>>();
+		assert_send_sync::<StringHeap<'static>>();
+		assert_send_sync::<BlobHeap<'static>>();
+		assert_send_sync::<GuidHeap<'static>>();
+		assert_send_sync::<UserStringHeap<'static>>();
+		assert_send_sync::<GenericMetadataTable<'static>>();
+	}
+};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum HeapKind {
+	String,
+	Guid,
+	Blob,
+}
+
+/// An index stored in one of a row's columns, along with what it points into.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum RowIndex {
+	Heap(HeapKind, HeapIndex),
+	Table(TableKind, TableIndex),
+	Coded(CodedIndexKind, CodedIndex),
+}
+
+pub trait MetadataRow {
+	/// Every heap, table and coded index column of the row, in declaration order.
+	fn indices(&self) -> Vec<RowIndex>;
 }
 
 #[derive(MetadataTable)]
 pub struct Module {
+	/// Reserved by ECMA-335 and 0 in compiled images.
+	/// Edit and Continue counts the generations of the module in it, starting from 0 for the original image.
 	generation: u16,
 	#[heap_index(String)]
 	name: HeapIndex,
+	/// The MVID, which identifies this build of the module.
 	#[heap_index(Guid)]
 	module_version_id: HeapIndex,
+	/// Reserved by ECMA-335. Edit and Continue stores the id of the generation in it.
 	#[heap_index(Guid)]
 	enc_id: HeapIndex,
+	/// Reserved by ECMA-335. Edit and Continue stores the id of the previous generation in it.
 	#[heap_index(Guid)]
 	enc_base_id: HeapIndex,
 }
 
 #[derive(MetadataTable)]
 pub struct TypeRef {
-	#[coded_index(TypeOrMethodDef)]
+	#[coded_index(ResolutionScope)]
 	resolution_scope: CodedIndex,
 	#[heap_index(String)]
 	type_name: HeapIndex,
@@ -124,8 +710,10 @@ pub struct TypeDef {
 	namespace: HeapIndex,
 	#[coded_index(TypeDefOrRef)]
 	base_type: CodedIndex,
+	/// The first field of the type, whose fields run up to the first field of the next type or the end of the table.
 	#[table_index(Field)]
 	fields: TableIndex,
+	/// The first method of the type, whose methods run up to the first method of the next type or the end of the table.
 	#[table_index(MethodDef)]
 	methods: TableIndex,
 }
@@ -172,12 +760,70 @@ pub mod type_flags {
 	pub const CUSTOM_FORMAT_CLASS: TypeFlags = 0x0000030000;
 
 	//Class Initialization Attributes
-	pub const BEFORE_FIELD_INIT: TypeFlags = 0x0010000000;
+	pub const BEFORE_FIELD_INIT: TypeFlags = 0x0000100000;
 
 	//Additional Flags
 	pub const RT_SPECIAL_NAME: TypeFlags = 0x0000000800;
 	pub const HAS_SECURITY: TypeFlags = 0x0000040000;
 	pub const IS_TYPE_FORWARDER: TypeFlags = 0x0000200000;
+
+	/// Names the flags set in `flags`, e.g. `Public | Abstract | Sealed | BeforeFieldInit`.
+	pub fn to_string(flags: TypeFlags) -> String {
+		crate::utilities::flags_to_string(
+			flags,
+			&[
+				(
+					VISIBILITY_MASK,
+					&[
+						(NOT_PUBLIC, "NotPublic"),
+						(PUBLIC, "Public"),
+						(NESTED_PUBLIC, "NestedPublic"),
+						(NESTED_PRIVATE, "NestedPrivate"),
+						(NESTED_FAMILY, "NestedFamily"),
+						(NESTED_ASSEMBLY, "NestedAssembly"),
+						(NESTED_FAMILY_AND_ASSEMBLY, "NestedFamilyAndAssembly"),
+						(NESTED_FAMILY_OR_ASSEMBLY, "NestedFamilyOrAssembly"),
+					],
+				),
+				(
+					LAYOUT_MASK,
+					&[
+						(AUTO_LAYOUT, "AutoLayout"),
+						(SEQUENTIAL_LAYOUT, "SequentialLayout"),
+						(EXPLICIT_LAYOUT, "ExplicitLayout"),
+					],
+				),
+				(CLASS_SEMANTICS_MASK, &[(CLASS, "Class"), (INTERFACE, "Interface")]),
+				(
+					STRING_FORMAT_MASK,
+					&[
+						(ANSI_CLASS, "AnsiClass"),
+						(UNICODE_CLASS, "UnicodeClass"),
+						(AUTO_CLASS, "AutoClass"),
+						(CUSTOM_FORMAT_CLASS, "CustomFormatClass"),
+					],
+				),
+			],
+			&[
+				(ABSTRACT, "Abstract"),
+				(SEALED, "Sealed"),
+				(SPECIAL_NAME, "SpecialName"),
+				(RT_SPECIAL_NAME, "RTSpecialName"),
+				(IMPORT, "Import"),
+				(SERIALIZABLE, "Serializable"),
+				(HAS_SECURITY, "HasSecurity"),
+				(BEFORE_FIELD_INIT, "BeforeFieldInit"),
+				(IS_TYPE_FORWARDER, "IsTypeForwarder"),
+			],
+		)
+	}
+}
+
+#[derive(MetadataTable)]
+pub struct FieldPtr {
+	/// The field that stands at the position of this row in the field lists.
+	#[table_index(Field)]
+	field: TableIndex,
 }
 
 #[derive(MetadataTable)]
@@ -209,6 +855,44 @@ pub mod field_flags {
 	pub const HAS_FIELD_MARSHAL: FieldFlags = 0x1000;
 	pub const HAS_DEFAULT: FieldFlags = 0x8000;
 	pub const HAS_FIELD_RVA: FieldFlags = 0x0100;
+
+	/// Names the flags set in `flags`, e.g. `Private | Static | InitOnly`.
+	pub fn to_string(flags: FieldFlags) -> String {
+		crate::utilities::flags_to_string(
+			flags,
+			&[(
+				FIELD_ACCESS_MASK,
+				&[
+					(COMPILER_CONTROLLED, "CompilerControlled"),
+					(PRIVATE, "Private"),
+					(FAMILY_AND_ASSEMBLY, "FamilyAndAssembly"),
+					(ASSEMBLY, "Assembly"),
+					(FAMILY, "Family"),
+					(FAMILY_OR_ASSEMBLY, "FamilyOrAssembly"),
+					(PUBLIC, "Public"),
+				],
+			)],
+			&[
+				(STATIC, "Static"),
+				(INIT_ONLY, "InitOnly"),
+				(LITERAL, "Literal"),
+				(NOT_SERIALIZED, "NotSerialized"),
+				(SPECIAL_NAME, "SpecialName"),
+				(RT_SPECIAL_NAME, "RTSpecialName"),
+				(PINVOKE_IMPL, "PInvokeImpl"),
+				(HAS_FIELD_MARSHAL, "HasFieldMarshal"),
+				(HAS_DEFAULT, "HasDefault"),
+				(HAS_FIELD_RVA, "HasFieldRVA"),
+			],
+		)
+	}
+}
+
+#[derive(MetadataTable)]
+pub struct MethodPtr {
+	/// The method that stands at the position of this row in the method lists.
+	#[table_index(MethodDef)]
+	method: TableIndex,
 }
 
 #[derive(MetadataTable)]
@@ -220,6 +904,8 @@ pub struct MethodDef {
 	name: HeapIndex,
 	#[heap_index(Blob)]
 	signature: HeapIndex,
+	/// The first parameter of the method, whose parameters run up to the first parameter of the next method
+	/// or the end of the table.
 	#[table_index(Param)]
 	params: TableIndex,
 }
@@ -234,6 +920,21 @@ pub mod method_impl_flags {
 	pub const MANAGED_MASK: MethodImplFlags = 0x0004;
 	pub const UNMANAGED: MethodImplFlags = 0x0004;
 	pub const MANAGED: MethodImplFlags = 0x0000;
+
+	/// Names the flags set in `flags`, e.g. `Native | Unmanaged`.
+	pub fn to_string(flags: MethodImplFlags) -> String {
+		crate::utilities::flags_to_string(
+			flags,
+			&[
+				(
+					CODE_TYPE_MASK,
+					&[(IL, "IL"), (NATIVE, "Native"), (OPT_IL, "OptIL"), (RUNTIME, "Runtime")],
+				),
+				(MANAGED_MASK, &[(MANAGED, "Managed"), (UNMANAGED, "Unmanaged")]),
+			],
+			&[],
+		)
+	}
 }
 
 pub mod method_flags {
@@ -261,6 +962,49 @@ pub mod method_flags {
 	pub const RT_SPECIAL_NAME: MethodFlags = 0x1000;
 	pub const HAS_SECURITY: MethodFlags = 0x4000;
 	pub const REQUIRE_SECURITY_OBJECT: MethodFlags = 0x8000;
+
+	/// Names the flags set in `flags`, e.g. `Public | NewSlot | Virtual | HideBySig`.
+	pub fn to_string(flags: MethodFlags) -> String {
+		crate::utilities::flags_to_string(
+			flags,
+			&[
+				(
+					MEMBER_ACCESS_MASK,
+					&[
+						(COMPILER_CONTROLLED, "CompilerControlled"),
+						(PRIVATE, "Private"),
+						(FAMILY_AND_ASSEMBLY, "FamilyAndAssembly"),
+						(ASSEMBLY, "Assembly"),
+						(FAMILY, "Family"),
+						(FAMILY_OR_ASSEMBLY, "FamilyOrAssembly"),
+						(PUBLIC, "Public"),
+					],
+				),
+				(VTABLE_LAYOUT_MASK, &[(REUSE_SLOT, "ReuseSlot"), (NEW_SLOT, "NewSlot")]),
+			],
+			&[
+				(UNMANAGED_EXPORT, "UnmanagedExport"),
+				(STATIC, "Static"),
+				(FINAL, "Final"),
+				(VIRTUAL, "Virtual"),
+				(HIDE_BY_SIGNATURE, "HideBySig"),
+				(STRICT, "Strict"),
+				(ABSTRACT, "Abstract"),
+				(SPECIAL_NAME, "SpecialName"),
+				(RT_SPECIAL_NAME, "RTSpecialName"),
+				(PINVOKE_IMPL, "PInvokeImpl"),
+				(HAS_SECURITY, "HasSecurity"),
+				(REQUIRE_SECURITY_OBJECT, "RequireSecObject"),
+			],
+		)
+	}
+}
+
+#[derive(MetadataTable)]
+pub struct ParamPtr {
+	/// The parameter that stands at the position of this row in the parameter lists.
+	#[table_index(Param)]
+	param: TableIndex,
 }
 
 #[derive(MetadataTable)]
@@ -279,12 +1023,27 @@ pub mod param_flags {
 	pub const HAS_DEFAULT: ParamFlags = 0x1000;
 	pub const HAS_FIELD_MARSHAL: ParamFlags = 0x2000;
 	pub const UNUSED: ParamFlags = 0xcfe0;
+
+	/// Names the flags set in `flags`, e.g. `In | Optional`.
+	pub fn to_string(flags: ParamFlags) -> String {
+		crate::utilities::flags_to_string(
+			flags,
+			&[],
+			&[
+				(IN, "In"),
+				(OUT, "Out"),
+				(OPTIONAL, "Optional"),
+				(HAS_DEFAULT, "HasDefault"),
+				(HAS_FIELD_MARSHAL, "HasFieldMarshal"),
+			],
+		)
+	}
 }
 
 #[derive(MetadataTable)]
 pub struct InterfaceImpl {
-	#[table_index(TypeRef)]
-	type_: TableIndex,
+	#[table_index(TypeDef)]
+	class: TableIndex,
 	#[coded_index(TypeDefOrRef)]
 	interface: CodedIndex,
 }
@@ -311,8 +1070,10 @@ pub struct CustomAttribute {
 
 #[derive(MetadataTable)]
 pub struct Constant {
+	#[try_from(u8)]
 	type_: ElementType,
-	__padding: u8,
+	/// Reserved, 0 in valid images.
+	padding: u8,
 	#[coded_index(HasConstant)]
 	parent: CodedIndex,
 	#[heap_index(Blob)]
@@ -345,8 +1106,8 @@ pub enum ElementType {
 	Array = 0x14,
 	GenericInst = 0x15,
 	TypedByRef = 0x16,
-	IPtr = 0x17,
-	UPtr = 0x18,
+	IPtr = 0x18,
+	UPtr = 0x19,
 	FnPtr = 0x1B,
 	Object = 0x1C,
 	SzArray = 0x1D,
@@ -360,6 +1121,55 @@ pub enum ElementType {
 	Type = 0x50,
 }
 
+impl TryFrom<u8> for ElementType {
+	type Error = Error;
+
+	fn try_from(value: u8) -> Result<Self, Self::Error> {
+		use ElementType::*;
+		let element_type = match value {
+			0x00 => End,
+			0x01 => Void,
+			0x02 => Bool,
+			0x03 => Char,
+			0x04 => I1,
+			0x05 => U1,
+			0x06 => I2,
+			0x07 => U2,
+			0x08 => I4,
+			0x09 => U4,
+			0x0A => I8,
+			0x0B => U8,
+			0x0C => R4,
+			0x0D => R8,
+			0x0E => String,
+			0x0F => Ptr,
+			0x10 => ByRef,
+			0x11 => ValueType,
+			0x12 => Class,
+			0x13 => Var,
+			0x14 => Array,
+			0x15 => GenericInst,
+			0x16 => TypedByRef,
+			0x18 => IPtr,
+			0x19 => UPtr,
+			0x1B => FnPtr,
+			0x1C => Object,
+			0x1D => SzArray,
+			0x1E => MVar,
+			0x1F => CModReqd,
+			0x20 => CModOpt,
+			0x21 => Internal,
+			0x40 => Modifier,
+			0x41 => Sentinel,
+			0x45 => Pinned,
+			0x50 => Type,
+			_ => return Err(Error::InvalidData(Some("Invalid element type"))),
+		};
+
+		Ok(element_type)
+	}
+}
+
 #[derive(MetadataTable)]
 pub struct ClassLayout {
 	packing_size: u16,
@@ -372,10 +1182,19 @@ pub struct ClassLayout {
 pub struct PropertyMap {
 	#[table_index(TypeDef)]
 	parent: TableIndex,
+	/// The first property of the type, whose properties run up to the first property of the next row
+	/// or the end of the table.
 	#[table_index(Property)]
 	property_list: TableIndex,
 }
 
+#[derive(MetadataTable)]
+pub struct PropertyPtr {
+	/// The property that stands at the position of this row in the property lists.
+	#[table_index(Property)]
+	property: TableIndex,
+}
+
 #[derive(MetadataTable)]
 pub struct Property {
 	flags: PropertyFlags,
@@ -391,6 +1210,19 @@ pub mod property_flags {
 	pub const RT_SPECIAL_NAME: PropertyFlags = 0x0400;
 	pub const HAS_DEFAULT: PropertyFlags = 0x1000;
 	pub const UNUSED: PropertyFlags = 0xE9FF;
+
+	/// Names the flags set in `flags`, e.g. `SpecialName | RTSpecialName`.
+	pub fn to_string(flags: PropertyFlags) -> String {
+		crate::utilities::flags_to_string(
+			flags,
+			&[],
+			&[
+				(SPECIAL_NAME, "SpecialName"),
+				(RT_SPECIAL_NAME, "RTSpecialName"),
+				(HAS_DEFAULT, "HasDefault"),
+			],
+		)
+	}
 }
 
 #[derive(MetadataTable)]
@@ -410,6 +1242,22 @@ pub mod method_semantics_flags {
 	pub const ADD_ON: MethodSemanticsFlags = 0x0008;
 	pub const REMOVE_ON: MethodSemanticsFlags = 0x0010;
 	pub const FIRE: MethodSemanticsFlags = 0x0020;
+
+	/// Names the flags set in `flags`, e.g. `Getter`.
+	pub fn to_string(flags: MethodSemanticsFlags) -> String {
+		crate::utilities::flags_to_string(
+			flags,
+			&[],
+			&[
+				(SETTER, "Setter"),
+				(GETTER, "Getter"),
+				(OTHER, "Other"),
+				(ADD_ON, "AddOn"),
+				(REMOVE_ON, "RemoveOn"),
+				(FIRE, "Fire"),
+			],
+		)
+	}
 }
 
 #[derive(MetadataTable)]
@@ -455,18 +1303,55 @@ pub struct ImplMap {
 
 pub mod pinvoke_attributes {
 	pub type PInvokeAttributes = u16;
-	//TODO Add flags II.23.1.8
+	pub const NO_MANGLE: PInvokeAttributes = 0x0001;
+	pub const CHAR_SET_MASK: PInvokeAttributes = 0x0006;
+	pub const CHAR_SET_NOT_SPEC: PInvokeAttributes = 0x0000;
+	pub const CHAR_SET_ANSI: PInvokeAttributes = 0x0002;
+	pub const CHAR_SET_UNICODE: PInvokeAttributes = 0x0004;
+	pub const CHAR_SET_AUTO: PInvokeAttributes = 0x0006;
+	pub const BEST_FIT_MASK: PInvokeAttributes = 0x0030;
+	pub const BEST_FIT_ENABLED: PInvokeAttributes = 0x0010;
+	pub const BEST_FIT_DISABLED: PInvokeAttributes = 0x0020;
+	pub const SUPPORTS_LAST_ERROR: PInvokeAttributes = 0x0040;
+	pub const CALL_CONV_MASK: PInvokeAttributes = 0x0700;
+	pub const CALL_CONV_PLATFORMAPI: PInvokeAttributes = 0x0100;
+	pub const CALL_CONV_CDECL: PInvokeAttributes = 0x0200;
+	pub const CALL_CONV_STDCALL: PInvokeAttributes = 0x0300;
+	pub const CALL_CONV_THISCALL: PInvokeAttributes = 0x0400;
+	pub const CALL_CONV_FASTCALL: PInvokeAttributes = 0x0500;
+	pub const THROW_ON_UNMAPPABLE_CHAR_MASK: PInvokeAttributes = 0x3000;
+	pub const THROW_ON_UNMAPPABLE_CHAR_ENABLED: PInvokeAttributes = 0x1000;
+	pub const THROW_ON_UNMAPPABLE_CHAR_DISABLED: PInvokeAttributes = 0x2000;
 }
 
 #[derive(MetadataTable)]
 pub struct DeclSecurity {
-	action: u16,
+	action: SecurityAction,
 	#[coded_index(HasDeclSecurity)]
 	parent: CodedIndex,
 	#[heap_index(Blob)]
 	permission_set: HeapIndex,
 }
 
+pub mod security_action {
+	pub type SecurityAction = u16;
+	pub const REQUEST: SecurityAction = 0x0001;
+	pub const DEMAND: SecurityAction = 0x0002;
+	pub const ASSERT: SecurityAction = 0x0003;
+	pub const DENY: SecurityAction = 0x0004;
+	pub const PERMIT_ONLY: SecurityAction = 0x0005;
+	pub const LINK_DEMAND: SecurityAction = 0x0006;
+	pub const INHERITANCE_DEMAND: SecurityAction = 0x0007;
+	pub const REQUEST_MINIMUM: SecurityAction = 0x0008;
+	pub const REQUEST_OPTIONAL: SecurityAction = 0x0009;
+	pub const REQUEST_REFUSE: SecurityAction = 0x000A;
+	pub const PREJIT_GRANT: SecurityAction = 0x000B;
+	pub const PREJIT_DENIED: SecurityAction = 0x000C;
+	pub const NON_CAS_DEMAND: SecurityAction = 0x000D;
+	pub const NON_CAS_LINK_DEMAND: SecurityAction = 0x000E;
+	pub const NON_CAS_INHERITANCE: SecurityAction = 0x000F;
+}
+
 #[derive(MetadataTable)]
 pub struct FieldRVA {
 	rva: u32,
@@ -485,10 +1370,18 @@ pub struct FieldLayout {
 pub struct EventMap {
 	#[table_index(TypeDef)]
 	parent: TableIndex,
+	/// The first event of the type, whose events run up to the first event of the next row or the end of the table.
 	#[table_index(Event)]
 	event_list: TableIndex,
 }
 
+#[derive(MetadataTable)]
+pub struct EventPtr {
+	/// The event that stands at the position of this row in the event lists.
+	#[table_index(Event)]
+	event: TableIndex,
+}
+
 #[derive(MetadataTable)]
 pub struct Event {
 	flags: EventFlags,
@@ -500,7 +1393,28 @@ pub struct Event {
 
 pub mod event_flags {
 	pub type EventFlags = u16;
-	//TODO Add flags §II.23.1.4
+	pub const SPECIAL_NAME: EventFlags = 0x0200;
+	pub const RT_SPECIAL_NAME: EventFlags = 0x0400;
+
+	/// Names the flags set in `flags`, e.g. `SpecialName`.
+	pub fn to_string(flags: EventFlags) -> String {
+		crate::utilities::flags_to_string(
+			flags,
+			&[],
+			&[(SPECIAL_NAME, "SpecialName"), (RT_SPECIAL_NAME, "RTSpecialName")],
+		)
+	}
+}
+
+#[derive(MetadataTable)]
+pub struct EncLog {
+	token: u32,
+	func_code: u32,
+}
+
+#[derive(MetadataTable)]
+pub struct EncMap {
+	token: u32,
 }
 
 //<editor-fold desc="Assembly">
@@ -514,7 +1428,7 @@ pub struct AssemblyTable<'l> {
 
 #[derive(Debug, Clone)]
 pub struct Assembly {
-	hash_algorithm: AssemblyHashAlgorithm,
+	hash_algorithm: u32,
 	major_version: u16,
 	minor_version: u16,
 	build_number: u16,
@@ -531,6 +1445,25 @@ pub enum AssemblyHashAlgorithm {
 	None = 0x0000,
 	MD5 = 0x8003,
 	SHA1 = 0x8004,
+	SHA256 = 0x800C,
+	SHA384 = 0x800D,
+	SHA512 = 0x800E,
+}
+
+impl TryFrom<u32> for AssemblyHashAlgorithm {
+	type Error = Error;
+
+	fn try_from(value: u32) -> Result<Self, Self::Error> {
+		match value {
+			0x0000 => Ok(Self::None),
+			0x8003 => Ok(Self::MD5),
+			0x8004 => Ok(Self::SHA1),
+			0x800C => Ok(Self::SHA256),
+			0x800D => Ok(Self::SHA384),
+			0x800E => Ok(Self::SHA512),
+			_ => Err(Error::InvalidData(Some("Unknown assembly hash algorithm"))),
+		}
+	}
 }
 
 impl<'l> MetadataTable<'l> for AssemblyTable<'l> {
@@ -550,6 +1483,23 @@ impl<'l> MetadataTable<'l> for AssemblyTable<'l> {
 	}
 }
 
+impl AssemblyTable<'_> {
+	/// Encodes a row with the given index widths, the inverse of parsing it.
+	pub fn encode_row(row: &Assembly, widths: &IndexWidths) -> Vec<u8> {
+		let mut writer = ByteWriter::default();
+		writer.write(row.hash_algorithm);
+		writer.write(row.major_version);
+		writer.write(row.minor_version);
+		writer.write(row.build_number);
+		writer.write(row.revision_number);
+		writer.write(row.flags);
+		writer.write_index(row.public_key.0, widths.blob);
+		writer.write_index(row.name.0, widths.string);
+		writer.write_index(row.culture.0, widths.string);
+		writer.into_bytes()
+	}
+}
+
 impl ParseRow for AssemblyTable<'_> {
 	type Row = Assembly;
 
@@ -579,6 +1529,35 @@ impl<'l> MetadataTableImpl<'l> for AssemblyTable<'l> {
 		16 + b + s * 2
 	}
 
+	fn calc_columns(tables: &TableHeap) -> Vec<TableColumn> {
+		let b = BlobHeap::idx_size(tables) as usize;
+		let s = StringHeap::idx_size(tables) as usize;
+		let columns = [
+			("hash_algorithm", 4),
+			("major_version", 2),
+			("minor_version", 2),
+			("build_number", 2),
+			("revision_number", 2),
+			("flags", 4),
+			("public_key", b),
+			("name", s),
+			("culture", s),
+		];
+
+		let mut offset = 0;
+		columns
+			.into_iter()
+			.map(|(name, size)| {
+				offset += size;
+				TableColumn {
+					name,
+					offset: offset - size,
+					size,
+				}
+			})
+			.collect()
+	}
+
 	fn new(bytes: &'l [u8], tables: &TableHeap) -> Result<Self, Error> {
 		Ok(Self {
 			bytes,
@@ -587,12 +1566,21 @@ impl<'l> MetadataTableImpl<'l> for AssemblyTable<'l> {
 			str_size: StringHeap::idx_size(tables),
 		})
 	}
+
+	fn with_bytes(&self, bytes: &'l [u8]) -> Self {
+		Self { bytes, ..self.clone() }
+	}
 }
 
 impl Assembly {
-	pub fn hash_algorithm(&self) -> AssemblyHashAlgorithm {
+	/// The raw HashAlgId column, which the runtime ignores and may hold any value.
+	pub fn hash_algorithm_id(&self) -> u32 {
 		self.hash_algorithm
 	}
+	/// The HashAlgId column, decoded on demand so that an unknown value does not fail the whole row.
+	pub fn hash_algorithm(&self) -> Result<AssemblyHashAlgorithm, Error> {
+		AssemblyHashAlgorithm::try_from(self.hash_algorithm)
+	}
 	pub fn major_version(&self) -> u16 {
 		self.major_version
 	}
@@ -619,12 +1607,48 @@ impl Assembly {
 	}
 }
 
+#[derive(MetadataTable)]
+pub struct AssemblyProcessor {
+	processor: u32,
+}
+
+#[derive(MetadataTable)]
+pub struct AssemblyOS {
+	os_platform_id: u32,
+	os_major_version: u32,
+	os_minor_version: u32,
+}
+
+impl MetadataRow for Assembly {
+	fn indices(&self) -> Vec<RowIndex> {
+		vec![
+			RowIndex::Heap(HeapKind::Blob, self.public_key),
+			RowIndex::Heap(HeapKind::String, self.name),
+			RowIndex::Heap(HeapKind::String, self.culture),
+		]
+	}
+}
+
 pub mod assembly_flags {
 	pub type AssemblyFlags = u32;
 	pub const PUBLIC_KEY: AssemblyFlags = 0x0001;
 	pub const RETARGETABLE: AssemblyFlags = 0x0100;
 	pub const DISABLE_JIT_COMPILE_OPTIMIZER: AssemblyFlags = 0x4000;
 	pub const ENABLE_JIT_COMPILE_TRACKING: AssemblyFlags = 0x8000;
+
+	/// Names the flags set in `flags`, e.g. `PublicKey | Retargetable`.
+	pub fn to_string(flags: AssemblyFlags) -> String {
+		crate::utilities::flags_to_string(
+			flags,
+			&[],
+			&[
+				(PUBLIC_KEY, "PublicKey"),
+				(RETARGETABLE, "Retargetable"),
+				(DISABLE_JIT_COMPILE_OPTIMIZER, "DisableJITcompileOptimizer"),
+				(ENABLE_JIT_COMPILE_TRACKING, "EnableJITcompileTracking"),
+			],
+		)
+	}
 }
 //</editor-fold>
 
@@ -645,54 +1669,148 @@ pub struct AssemblyRef {
 	hash_value: HeapIndex,
 }
 
-#[derive(Clone)]
-pub struct StandAloneSignatureTable<'l> {
-	bytes: &'l [u8],
-	blob_size: IndexSize,
+#[derive(MetadataTable)]
+pub struct AssemblyRefProcessor {
+	processor: u32,
+	#[table_index(AssemblyRef)]
+	assembly_ref: TableIndex,
 }
 
-impl<'l> MetadataTable<'l> for StandAloneSignatureTable<'l> {
-	type Iter = std::option::IntoIter<Result<HeapIndex, Error>>;
+#[derive(MetadataTable)]
+pub struct AssemblyRefOS {
+	os_platform_id: u32,
+	os_major_version: u32,
+	os_minor_version: u32,
+	#[table_index(AssemblyRef)]
+	assembly_ref: TableIndex,
+}
 
-	fn bytes(&self) -> &'l [u8] {
-		self.bytes
-	}
+#[derive(MetadataTable)]
+pub struct File {
+	flags: FileFlags,
+	#[heap_index(String)]
+	name: HeapIndex,
+	#[heap_index(Blob)]
+	hash_value: HeapIndex,
+}
 
-	fn row_size(&self) -> usize {
-		self.blob_size as usize
-	}
+pub mod file_flags {
+	pub type FileFlags = u32;
+	pub const CONTAINS_METADATA: FileFlags = 0x0000;
+	pub const CONTAINS_NO_METADATA: FileFlags = 0x0001;
 
-	fn iter(&self) -> Self::Iter {
-		let mut reader = ByteStream::new(self.bytes);
-		Some(self.parse_row(&mut reader)).into_iter()
+	/// Names the flags set in `flags`, e.g. `ContainsNoMetaData`.
+	pub fn to_string(flags: FileFlags) -> String {
+		crate::utilities::flags_to_string(flags, &[], &[(CONTAINS_NO_METADATA, "ContainsNoMetaData")])
 	}
 }
 
-impl ParseRow for StandAloneSignatureTable<'_> {
-	type Row = HeapIndex;
+#[derive(MetadataTable)]
+pub struct ExportedType {
+	flags: TypeFlags,
+	type_def_id: u32,
+	#[heap_index(String)]
+	type_name: HeapIndex,
+	#[heap_index(String)]
+	type_namespace: HeapIndex,
+	#[coded_index(Implementation)]
+	implementation: CodedIndex,
+}
 
-	fn parse_row(&self, reader: &mut ByteStream) -> Result<Self::Row, Error> {
-		reader.read_heap_index(self.blob_size)
-	}
+#[derive(MetadataTable)]
+pub struct ManifestResource {
+	offset: u32,
+	flags: ManifestResourceFlags,
+	#[heap_index(String)]
+	name: HeapIndex,
+	#[coded_index(Implementation)]
+	implementation: CodedIndex,
 }
 
-impl<'l> MetadataTableImpl<'l> for StandAloneSignatureTable<'l> {
-	fn cli_identifier() -> TableKind {
-		TableKind::StandAloneSig
-	}
+pub mod manifest_resource_flags {
+	pub type ManifestResourceFlags = u32;
+	pub const VISIBILITY_MASK: ManifestResourceFlags = 0x0007;
+	pub const PUBLIC: ManifestResourceFlags = 0x0001;
+	pub const PRIVATE: ManifestResourceFlags = 0x0002;
 
-	fn calc_row_size(tables: &TableHeap) -> usize {
-		BlobHeap::idx_size(tables) as usize
+	/// Names the flags set in `flags`, e.g. `Public`.
+	pub fn to_string(flags: ManifestResourceFlags) -> String {
+		crate::utilities::flags_to_string(flags, &[(VISIBILITY_MASK, &[(PUBLIC, "Public"), (PRIVATE, "Private")])], &[])
 	}
+}
 
-	fn new(bytes: &'l [u8], tables: &TableHeap) -> Result<Self, Error> {
-		Ok(Self {
-			bytes,
-			blob_size: BlobHeap::idx_size(tables),
-		})
+#[derive(MetadataTable)]
+pub struct NestedClass {
+	#[table_index(TypeDef)]
+	nested_class: TableIndex,
+	#[table_index(TypeDef)]
+	enclosing_class: TableIndex,
+}
+
+#[derive(MetadataTable)]
+pub struct GenericParam {
+	number: u16,
+	flags: GenericParamFlags,
+	#[coded_index(TypeOrMethodDef)]
+	owner: CodedIndex,
+	#[heap_index(String)]
+	name: HeapIndex,
+}
+
+pub mod generic_param_flags {
+	pub type GenericParamFlags = u16;
+	pub const VARIANCE_MASK: GenericParamFlags = 0x0003;
+	pub const NONE: GenericParamFlags = 0x0000;
+	pub const COVARIANT: GenericParamFlags = 0x0001;
+	pub const CONTRAVARIANT: GenericParamFlags = 0x0002;
+	pub const SPECIAL_CONSTRAINT_MASK: GenericParamFlags = 0x001C;
+	pub const REFERENCE_TYPE_CONSTRAINT: GenericParamFlags = 0x0004;
+	pub const NOT_NULLABLE_VALUE_TYPE_CONSTRAINT: GenericParamFlags = 0x0008;
+	pub const DEFAULT_CONSTRUCTOR_CONSTRAINT: GenericParamFlags = 0x0010;
+
+	/// Names the flags set in `flags`, e.g. `Covariant | ReferenceTypeConstraint`.
+	pub fn to_string(flags: GenericParamFlags) -> String {
+		crate::utilities::flags_to_string(
+			flags,
+			&[(
+				VARIANCE_MASK,
+				&[
+					(NONE, "None"),
+					(COVARIANT, "Covariant"),
+					(CONTRAVARIANT, "Contravariant"),
+				],
+			)],
+			&[
+				(REFERENCE_TYPE_CONSTRAINT, "ReferenceTypeConstraint"),
+				(NOT_NULLABLE_VALUE_TYPE_CONSTRAINT, "NotNullableValueTypeConstraint"),
+				(DEFAULT_CONSTRUCTOR_CONSTRAINT, "DefaultConstructorConstraint"),
+			],
+		)
 	}
 }
 
+#[derive(MetadataTable)]
+pub struct MethodSpec {
+	#[coded_index(MethodDefOrRef)]
+	method: CodedIndex,
+	#[heap_index(Blob)]
+	instantiation: HeapIndex,
+}
+
+#[derive(MetadataTable)]
+pub struct GenericParamConstraint {
+	#[table_index(GenericParam)]
+	owner: TableIndex,
+	#[coded_index(TypeDefOrRef)]
+	constraint: CodedIndex,
+}
+
+#[derive(MetadataTable)]
+pub struct StandAloneSig {
+	#[heap_index(Blob)]
+	signature: HeapIndex,
+}
+
 pub(crate) mod private {
 	use crate::raw::*;
 
@@ -707,6 +1825,66 @@ pub(crate) mod private {
 	{
 		fn cli_identifier() -> TableKind;
 		fn calc_row_size(tables: &TableHeap) -> usize;
+		fn calc_columns(tables: &TableHeap) -> Vec<TableColumn>;
 		fn new(bytes: &'l [u8], tables: &TableHeap) -> Result<Self, Error>;
+		/// The same table, over a subset of its rows.
+		fn with_bytes(&self, bytes: &'l [u8]) -> Self;
 	}
 }
+
+/// Expands `$self.$method::<T>($args)` with `T` being the table type matching `$kind`,
+/// or evaluates `$default` for tables that are not modeled by a concrete type.
+macro_rules! dispatch_table {
+	($kind: expr, $self: ident.$method: ident($($arg: expr),*), $default: expr) => {
+		match $kind {
+			TableKind::Module => $self.$method::<ModuleTable>($($arg),*),
+			TableKind::TypeRef => $self.$method::<TypeRefTable>($($arg),*),
+			TableKind::TypeDef => $self.$method::<TypeDefTable>($($arg),*),
+			TableKind::FieldPtr => $self.$method::<FieldPtrTable>($($arg),*),
+			TableKind::Field => $self.$method::<FieldTable>($($arg),*),
+			TableKind::MethodPtr => $self.$method::<MethodPtrTable>($($arg),*),
+			TableKind::MethodDef => $self.$method::<MethodDefTable>($($arg),*),
+			TableKind::ParamPtr => $self.$method::<ParamPtrTable>($($arg),*),
+			TableKind::Param => $self.$method::<ParamTable>($($arg),*),
+			TableKind::InterfaceImpl => $self.$method::<InterfaceImplTable>($($arg),*),
+			TableKind::MemberRef => $self.$method::<MemberRefTable>($($arg),*),
+			TableKind::Constant => $self.$method::<ConstantTable>($($arg),*),
+			TableKind::CustomAttribute => $self.$method::<CustomAttributeTable>($($arg),*),
+			TableKind::FieldMarshal => $self.$method::<FieldMarshalTable>($($arg),*),
+			TableKind::DeclSecurity => $self.$method::<DeclSecurityTable>($($arg),*),
+			TableKind::ClassLayout => $self.$method::<ClassLayoutTable>($($arg),*),
+			TableKind::FieldLayout => $self.$method::<FieldLayoutTable>($($arg),*),
+			TableKind::StandAloneSig => $self.$method::<StandAloneSigTable>($($arg),*),
+			TableKind::EventMap => $self.$method::<EventMapTable>($($arg),*),
+			TableKind::EventPtr => $self.$method::<EventPtrTable>($($arg),*),
+			TableKind::Event => $self.$method::<EventTable>($($arg),*),
+			TableKind::PropertyMap => $self.$method::<PropertyMapTable>($($arg),*),
+			TableKind::PropertyPtr => $self.$method::<PropertyPtrTable>($($arg),*),
+			TableKind::Property => $self.$method::<PropertyTable>($($arg),*),
+			TableKind::MethodSemantics => $self.$method::<MethodSemanticsTable>($($arg),*),
+			TableKind::MethodImpl => $self.$method::<MethodImplTable>($($arg),*),
+			TableKind::ModuleRef => $self.$method::<ModuleRefTable>($($arg),*),
+			TableKind::TypeSpec => $self.$method::<TypeSpecTable>($($arg),*),
+			TableKind::ImplMap => $self.$method::<ImplMapTable>($($arg),*),
+			TableKind::FieldRVA => $self.$method::<FieldRVATable>($($arg),*),
+			TableKind::EncLog => $self.$method::<EncLogTable>($($arg),*),
+			TableKind::EncMap => $self.$method::<EncMapTable>($($arg),*),
+			TableKind::Assembly => $self.$method::<AssemblyTable>($($arg),*),
+			TableKind::AssemblyProcessor => $self.$method::<AssemblyProcessorTable>($($arg),*),
+			TableKind::AssemblyOS => $self.$method::<AssemblyOSTable>($($arg),*),
+			TableKind::AssemblyRef => $self.$method::<AssemblyRefTable>($($arg),*),
+			TableKind::AssemblyRefProcessor => $self.$method::<AssemblyRefProcessorTable>($($arg),*),
+			TableKind::AssemblyRefOS => $self.$method::<AssemblyRefOSTable>($($arg),*),
+			TableKind::File => $self.$method::<FileTable>($($arg),*),
+			TableKind::ExportedType => $self.$method::<ExportedTypeTable>($($arg),*),
+			TableKind::ManifestResource => $self.$method::<ManifestResourceTable>($($arg),*),
+			TableKind::NestedClass => $self.$method::<NestedClassTable>($($arg),*),
+			TableKind::GenericParam => $self.$method::<GenericParamTable>($($arg),*),
+			TableKind::MethodSpec => $self.$method::<MethodSpecTable>($($arg),*),
+			TableKind::GenericParamConstraint => $self.$method::<GenericParamConstraintTable>($($arg),*),
+			_ => $default,
+		}
+	};
+}
+
+pub(crate) use dispatch_table;
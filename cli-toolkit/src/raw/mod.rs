@@ -1,11 +1,32 @@
+//! Parsing of PE images, metadata tables, heaps, signatures and IL.
+//!
+//! # Malformed input
+//!
+//! No parsing function of this layer panics, whatever bytes it is given: truncated structures, out of range
+//! offsets, indices and lengths, and values that are not valid for their type are all reported as an [`Error`].
+//! Lengths read from the input are checked against the bytes actually available before anything is allocated,
+//! and bytes are only reinterpreted as types that every bit pattern is valid for.
+//!
+//! Chains found in the input, such as nested signatures and the scopes of nested TypeRefs, are walked with a bound
+//! rather than by unbounded recursion, since a stack overflow aborts the process instead of unwinding.
+//!
+//! The guarantee is checked by `fuzzing::check_panic_free`, which runs every parsing layer over malformed
+//! variants of an assembly, and by the `panic_free` tests, which seed it with images built by `testing::ImageBuilder`
+//! that include cyclic TypeRef scopes, cyclic nested classes and huge array ranks.
+//! Builders such as [`StringHeapBuilder`] are not covered, since they do not parse anything.
+
 mod assembly;
 mod metadata;
 mod byte_stream;
+mod codec;
+mod il;
 mod portable_executable;
 
 pub use assembly::*;
 pub use metadata::*;
 pub use byte_stream::*;
+pub use codec::*;
+pub use il::*;
 pub use portable_executable::*;
 
 pub use assembly::Assembly;
@@ -1,10 +1,11 @@
-use std::ffi::c_char;
-use crate::raw::{ByteStream, Error, FromByteStream};
+use std::fmt::{Debug, Formatter};
+use crate::raw::{ByteStream, Error, FromByteStream, Pod};
 
 #[repr(C)]
 #[derive(Debug, Clone, FromByteStream)]
 pub struct DosHeader {
-	#[check_value(|v: &[u8; 128]| match v {
+	#[check_value(|v: &[u8; 128]| matches!(
+		v,
 		[
 			0x4d, 0x5a, 0x90, 0x00, 0x03, 0x00, 0x00, 0x00,
 			0x04, 0x00, 0x00, 0x00, 0xFF, 0xFF, 0x00, 0x00,
@@ -22,10 +23,8 @@ pub struct DosHeader {
 			0x20, 0x69, 0x6e, 0x20, 0x44, 0x4f, 0x53, 0x20,
 			0x6d, 0x6f, 0x64, 0x65, 0x2e, 0x0d, 0x0d, 0x0a,
 			0x24, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-		] => true,
-
-		_ => false,
-	})]
+		]
+	))]
 	bytes: [u8; 128],
 }
 
@@ -247,6 +246,8 @@ pub struct DataDirectory {
 	pub size: u32,
 }
 
+unsafe impl Pod for DataDirectory {}
+
 #[repr(C)]
 #[derive(Debug, Clone, FromByteStream)]
 pub struct DataDirectories {
@@ -299,11 +300,17 @@ pub struct SectionHeader {
 	pub characteristics: u32,
 }
 
+unsafe impl Pod for SectionHeader {}
+
 impl SectionHeader {
+	/// The name of the section, which fills all 8 bytes when it has no terminating null.
+	/// A name that is not valid UTF-8 is cut off before the first invalid byte.
 	pub fn name(&self) -> &str {
-		unsafe {
-			let ptr = &self.name as *const u64 as *const c_char;
-			std::ffi::CStr::from_ptr(ptr).to_str().unwrap()
+		let bytes = unsafe { &*(&self.name as *const u64 as *const [u8; 8]) };
+		let bytes = &bytes[..bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len())];
+		match std::str::from_utf8(bytes) {
+			Ok(name) => name,
+			Err(error) => std::str::from_utf8(&bytes[..error.valid_up_to()]).unwrap_or_default(),
 		}
 	}
 }
@@ -326,6 +333,20 @@ pub mod pe_header_characteristics {
 	pub const IMAGE_FILE_EXECUTABLE_IMAGE: u16 = 0x02;
 	pub const IMAGE_FILE_32BIT_MACHINE: u16 = 0x0100;
 	pub const IMAGE_FILE_DLL: u16 = 0x2000;
+
+	/// Names the flags set in `flags`, e.g. `ExecutableImage | Dll`.
+	pub fn to_string(flags: u16) -> String {
+		crate::utilities::flags_to_string(
+			flags,
+			&[],
+			&[
+				(IMAGE_FILE_RELOCS_STRIPPED, "RelocsStripped"),
+				(IMAGE_FILE_EXECUTABLE_IMAGE, "ExecutableImage"),
+				(IMAGE_FILE_32BIT_MACHINE, "32BitMachine"),
+				(IMAGE_FILE_DLL, "Dll"),
+			],
+		)
+	}
 }
 
 pub mod section_header_characteristics {
@@ -335,6 +356,22 @@ pub mod section_header_characteristics {
 	pub const IMAGE_SCN_MEM_EXECUTE: u32 = 0x20000000;
 	pub const IMAGE_SCN_MEM_READ: u32 = 0x40000000;
 	pub const IMAGE_SCN_MEM_WRITE: u32 = 0x80000000;
+
+	/// Names the flags set in `flags`, e.g. `CntCode | MemExecute | MemRead`.
+	pub fn to_string(flags: u32) -> String {
+		crate::utilities::flags_to_string(
+			flags,
+			&[],
+			&[
+				(IMAGE_SCN_CNT_CODE, "CntCode"),
+				(IMAGE_SCN_CNT_INITIALIZED_DATA, "CntInitializedData"),
+				(IMAGE_SCN_CNT_UNINITIALIZED_DATA, "CntUninitializedData"),
+				(IMAGE_SCN_MEM_EXECUTE, "MemExecute"),
+				(IMAGE_SCN_MEM_READ, "MemRead"),
+				(IMAGE_SCN_MEM_WRITE, "MemWrite"),
+			],
+		)
+	}
 }
 
 #[repr(C)]
@@ -356,10 +393,76 @@ pub struct CliHeader {
 	pub managed_native_header: u64,
 }
 
+impl CliHeader {
+	pub fn runtime_flags(&self) -> RuntimeFlags {
+		RuntimeFlags(self.flags)
+	}
+}
+
 pub mod runtime_flags {
 	pub const IL_ONLY: u32 = 0x01;
 	pub const REQUIRE_32BIT: u32 = 0x02;
 	pub const STRONG_NAME_SIGNED: u32 = 0x08;
 	pub const NATIVE_ENTRYPOINT: u32 = 0x10;
 	pub const TRACK_DEBUG_DATA: u32 = 0x10000;
+	pub const PREFER_32BIT: u32 = 0x20000;
+
+	/// Names the flags set in `flags`, e.g. `ILOnly | StrongNameSigned`.
+	pub fn to_string(flags: u32) -> String {
+		crate::utilities::flags_to_string(
+			flags,
+			&[],
+			&[
+				(IL_ONLY, "ILOnly"),
+				(REQUIRE_32BIT, "Require32Bit"),
+				(STRONG_NAME_SIGNED, "StrongNameSigned"),
+				(NATIVE_ENTRYPOINT, "NativeEntryPoint"),
+				(TRACK_DEBUG_DATA, "TrackDebugData"),
+				(PREFER_32BIT, "Prefer32Bit"),
+			],
+		)
+	}
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash)]
+pub struct RuntimeFlags(pub u32);
+
+impl RuntimeFlags {
+	pub fn bits(&self) -> u32 {
+		self.0
+	}
+
+	pub fn contains(&self, flags: u32) -> bool {
+		self.0 & flags == flags
+	}
+
+	pub fn is_il_only(&self) -> bool {
+		self.contains(runtime_flags::IL_ONLY)
+	}
+
+	pub fn requires_32bit(&self) -> bool {
+		self.contains(runtime_flags::REQUIRE_32BIT)
+	}
+
+	pub fn prefers_32bit(&self) -> bool {
+		self.contains(runtime_flags::REQUIRE_32BIT | runtime_flags::PREFER_32BIT)
+	}
+
+	pub fn is_strong_name_signed(&self) -> bool {
+		self.contains(runtime_flags::STRONG_NAME_SIGNED)
+	}
+
+	pub fn has_native_entry_point(&self) -> bool {
+		self.contains(runtime_flags::NATIVE_ENTRYPOINT)
+	}
+
+	pub fn tracks_debug_data(&self) -> bool {
+		self.contains(runtime_flags::TRACK_DEBUG_DATA)
+	}
+}
+
+impl Debug for RuntimeFlags {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		write!(f, "RuntimeFlags(0x{:X})", self.0)
+	}
 }
@@ -1,13 +1,20 @@
 use crate::raw::{
-	AlignedBuffer, AssemblyRefTable, AssemblyTable, BlobHeap, CodedIndexKind, MetadataHeap, MetadataTable,
-	MetadataTableImpl, StringHeap, TableHeap, TableIndex, TypeDefTable, TypeRefTable,
+	parse_method_spec, AlignedBuffer, AssemblyRefTable, AssemblyTable, BlobHeap, CodedIndexKind, HeapSlicing,
+	MemberRefTable, MetadataHeap, MetadataTable, MetadataTableImpl, MetadataToken, MetadataTokenKind, MethodSpecTable,
+	ModuleRefTable, ModuleTable, StringHeap, TableHeap, TableIndex, TruncatedStream, TypeDefTable, TypeRefTable,
+	TypeSignature, TypeSpecTable,
+};
+use crate::schema::{
+	full_name, Assembly, AssemblyName, AssemblyRef, AssemblyVersion, Context, DecodeCache, MethodSpec, Module,
+	NameStyle, PublicKeyToken, Recovery, RecoveryRule, Type, TypeSpec,
 };
-use crate::schema::{Assembly, AssemblyName, AssemblyRef, AssemblyVersion, Context, Type};
-use crate::utilities::get_mut_unchecked;
 use std::marker::PhantomData;
 use lazy_static::lazy_static;
 use std::iter::repeat_with;
+use std::cell::{Cell, OnceCell, RefCell};
 use std::rc::{Rc, Weak};
+use crate::read::members::{MemberReader, TypeMembers};
+use crate::read::context::Diagnostics;
 use crate::read::Error;
 use std::path::PathBuf;
 use std::ptr::null;
@@ -20,12 +27,18 @@ pub(crate) struct AssemblyReader<'l> {
 	tables: TableHeap<'l>,
 	strings: StringHeap<'l>,
 	raw_assembly: raw::Assembly<'l>,
+	/// The file the assembly is read from, if it is known.
+	pub(super) path: Option<PathBuf>,
 }
 
 impl Assembly {
 	pub(crate) fn default() -> Self {
 		Self {
-			ctx: Weak::new(),
+			ctx: RefCell::new(Weak::new()),
+			path: None,
+			decode_cache: DecodeCache::default(),
+			name_style: Cell::new(NameStyle::default()),
+			enclosing_types: OnceCell::new(),
 			name: AssemblyName {
 				flags: 0,
 				public_key: vec![],
@@ -39,16 +52,29 @@ impl Assembly {
 				},
 			},
 
-			types: Rc::new([]),
+			module: Module::default(),
+			types: OnceCell::new(),
+			module_refs: vec![],
 			type_refs: vec![],
+			type_ref_cache: RefCell::new(vec![]),
+			type_specs: vec![],
+			member_refs: vec![],
+			method_specs: vec![],
+			custom_attributes: vec![],
 			dependencies: vec![],
+			strings: None,
+			#[cfg(feature = "checksums")]
+			stream_checksums: Default::default(),
 		}
 	}
 }
 
 impl<'l> AssemblyReader<'l> {
-	pub(super) fn new(bytes: AlignedBuffer<'l>) -> Result<Self, Error> {
-		let raw_assembly = raw::Assembly::try_from(unsafe { std::mem::transmute::<_, &'l [u8]>(bytes.as_ref()) })?;
+	pub(super) fn new(bytes: AlignedBuffer<'l>, slicing: HeapSlicing) -> Result<Self, Error> {
+		// The bytes live on the heap, or are borrowed for 'l, so they do not move along with the buffer they are
+		// stored in. They are never mutated, so borrowed bytes are not copied behind the back of the raw assembly.
+		let raw_bytes = unsafe { std::mem::transmute::<&[u8], &'l [u8]>(bytes.as_ref()) };
+		let raw_assembly = raw::Assembly::with_heap_slicing(raw_bytes, slicing)?;
 
 		let blobs = raw_assembly
 			.get_heap::<BlobHeap>()?
@@ -68,9 +94,28 @@ impl<'l> AssemblyReader<'l> {
 			tables,
 			strings,
 			raw_assembly,
+			path: None,
 		})
 	}
 
+	/// The bytes of the `#Strings` heap, for contexts that retain them.
+	pub(super) fn strings(&self) -> &'l [u8] {
+		self.strings.bytes()
+	}
+
+	/// The streams that were clamped to the end of the file.
+	pub(super) fn truncated_streams(&self) -> Result<Vec<TruncatedStream<'l>>, Error> {
+		match self.raw_assembly.metadata_header().heap_slicing() {
+			HeapSlicing::Strict => Ok(vec![]),
+			HeapSlicing::Clamp => Ok(self.raw_assembly.truncated_streams()?),
+		}
+	}
+
+	#[cfg(feature = "checksums")]
+	pub(super) fn stream_checksums(&self) -> Result<raw::StreamChecksums, Error> {
+		Ok(self.raw_assembly.metadata_header().stream_checksums()?)
+	}
+
 	pub(super) fn get_ident(&self) -> Result<String, Error> {
 		let def = self
 			.tables
@@ -78,41 +123,79 @@ impl<'l> AssemblyReader<'l> {
 			.ok_or(Error::MissingMetadataTable(AssemblyTable::cli_identifier()))?
 			.get(TableIndex(1))?;
 
-		let major = def.major_version();
-		let minor = def.minor_version();
-		let build = def.build_number();
-		let revision = def.revision_number();
-		let name = self.strings.get_string(def.name()).to_string();
-		let culture = self.strings.get_string(def.culture()).to_string();
+		let version = AssemblyVersion {
+			major: def.major_version(),
+			minor: def.minor_version(),
+			build: def.build_number(),
+			revision: def.revision_number(),
+		};
 
-		Ok(format!("{} {} {}.{}.{}.{}", name, culture, major, minor, build, revision))
+		let name = self.strings.get_string(def.name());
+		let culture = self.strings.get_string(def.culture());
+		let public_key = self.blobs.get_blob(def.public_key())?;
+		let token = PublicKeyToken::from_blob(public_key, def.flags());
+		Ok(full_name(name, culture, &version, token, false))
 	}
 
-	pub(super) fn read_assembly_definition(&self, mut assembly: Rc<Assembly>) -> Result<Rc<Assembly>, Error> {
+	pub(super) fn read_assembly_definition(&self, assembly: &mut Assembly) -> Result<(), Error> {
 		let def = self
 			.tables
 			.get_table::<AssemblyTable>()?
 			.ok_or(Error::MissingMetadataTable(AssemblyTable::cli_identifier()))?
 			.get(TableIndex(1))?;
 
-		{
-			let assembly = Rc::get_mut(&mut assembly).unwrap();
+		let assembly_name = &mut assembly.name;
+		let assembly_version = &mut assembly_name.version;
 
-			let assembly_name = &mut assembly.name;
-			let assembly_version = &mut assembly_name.version;
+		assembly_name.flags = def.flags();
+		assembly_name.name = self.strings.get_string(def.name()).to_string();
+		assembly_name.culture = self.strings.get_string(def.culture()).to_string();
+		assembly_name.public_key = self.blobs.get_blob(def.public_key())?.to_vec();
 
-			assembly_name.flags = def.flags();
-			assembly_name.name = self.strings.get_string(def.name()).to_string();
-			assembly_name.culture = self.strings.get_string(def.culture()).to_string();
-			assembly_name.public_key = self.blobs.get_blob(def.public_key())?.to_vec();
+		assembly_version.major = def.major_version();
+		assembly_version.minor = def.minor_version();
+		assembly_version.build = def.build_number();
+		assembly_version.revision = def.revision_number();
 
-			assembly_version.major = def.major_version();
-			assembly_version.minor = def.minor_version();
-			assembly_version.build = def.build_number();
-			assembly_version.revision = def.revision_number();
+		if let Some(module) = self.tables.get_table::<ModuleTable>()? {
+			let module = module.get(TableIndex(1))?;
+			assembly.module.name = self.strings.get_string(module.name()).to_string();
+			assembly.module.generation = module.generation();
 		}
 
-		Ok(assembly)
+		Ok(())
+	}
+
+	/// Reads the custom attributes of every parent other than type and method definitions, generic parameters
+	/// and interface implementations, which are attached to them along with their members.
+	pub(super) fn read_assembly_attributes(
+		&self,
+		assembly: &mut Assembly,
+		diagnostics: &mut Diagnostics,
+	) -> Result<(), Error> {
+		let reader = MemberReader::new(self.blobs, self.tables, self.strings);
+		let kind = |parent: MetadataToken| parent.token_kind();
+		let mut kept_raw = vec![];
+		assembly.module.custom_attributes =
+			reader.read_attributes(|parent| kind(parent) == Some(MetadataTokenKind::Module), &mut kept_raw)?;
+		let other = |parent| {
+			!matches!(
+				kind(parent),
+				Some(
+					MetadataTokenKind::Module
+						| MetadataTokenKind::TypeDef
+						| MetadataTokenKind::Method
+						| MetadataTokenKind::GenericParam
+						| MetadataTokenKind::InterfaceImpl
+				)
+			)
+		};
+		assembly.custom_attributes = reader.read_attributes(other, &mut kept_raw)?;
+		self.note_kept_raw(diagnostics, &assembly.name.name, kept_raw);
+
+		// The table is sorted by the coded index of the parent, which interleaves the kinds of parents.
+		assembly.custom_attributes.sort_by_key(|attribute| attribute.parent.0);
+		Ok(())
 	}
 
 	pub(super) fn read_assembly_refs(&self, assembly: &mut Assembly) -> Result<(), Error> {
@@ -134,18 +217,13 @@ impl<'l> AssemblyReader<'l> {
 				revision: ass_ref.revision_number(),
 			};
 
+			let public_key = self.blobs.get_blob(ass_ref.public_key())?;
+			let token = PublicKeyToken::from_blob(public_key, ass_ref.flags());
 			assembly.dependencies.push(AssemblyRef {
 				flags: ass_ref.flags(),
-				public_key: self.blobs.get_blob(ass_ref.public_key())?.to_vec(),
-				hash_value: self.blobs.get_blob(ass_ref.public_key())?.to_vec(),
-				ident_key: format! {
-					"{} {} {}.{}.{}.{}",
-					name, culture,
-					ass_ref.major_version(),
-					ass_ref.minor_version(),
-					ass_ref.build_number(),
-					ass_ref.revision_number(),
-				},
+				public_key: public_key.to_vec(),
+				hash_value: self.blobs.get_blob(ass_ref.hash_value())?.to_vec(),
+				ident_key: full_name(&name, &culture, &version, token, false),
 				name,
 				culture,
 				version,
@@ -155,48 +233,231 @@ impl<'l> AssemblyReader<'l> {
 		Ok(())
 	}
 
-	pub(super) fn read_assembly_type_refs(&self, assembly: &mut Assembly) -> Result<(), Error> {
+	pub(super) fn read_assembly_module_refs(&self, assembly: &mut Assembly) -> Result<(), Error> {
+		let Some(table) = self.tables.get_table::<ModuleRefTable>()? else { return Ok(()) };
+
+		assembly.module_refs = Vec::with_capacity(table.len());
+		for module_ref in table.iter() {
+			let name = self.strings.get_string(module_ref?.name()).to_string();
+			assembly.module_refs.push(name);
+		}
+
+		Ok(())
+	}
+
+	pub(super) fn read_assembly_type_refs(
+		&self,
+		assembly: &mut Assembly,
+		diagnostics: &mut Diagnostics,
+	) -> Result<(), Error> {
 		let table = match self.tables.get_table::<TypeRefTable>()? {
 			Some(table) => table,
 			None => return Ok(()),
 		};
 
 		assembly.type_refs = Vec::with_capacity(table.len());
-		for ty in table.iter() {
-			let ty = ty?;
-			let name = self.strings.get_string(ty.type_name()).to_string();
-			let namespace = self.strings.get_string(ty.type_namespace()).to_string();
-			let token = ty
-				.resolution_scope()
-				.decode(CodedIndexKind::ResolutionScope)
-				.ok_or(raw::Error::InvalidData(Some("Invalid resolution scope")))?;
-
-			assembly.type_refs.push((token, namespace, name))
+		assembly.type_ref_cache = RefCell::new(repeat_with(OnceCell::new).take(table.len()).collect());
+		for (index, ty) in table.iter().enumerate() {
+			let row = ty.map_err(Error::from).and_then(|ty| {
+				let name = self.strings.get_string(ty.type_name()).to_string();
+				let namespace = self.strings.get_string(ty.type_namespace()).to_string();
+				let token = ty
+					.resolution_scope()
+					.decode(CodedIndexKind::ResolutionScope)
+					.ok_or(raw::Error::InvalidData(Some("Invalid resolution scope")))?;
+				Ok((token, namespace, name))
+			});
+
+			// Rows that fail to load keep their slot with a null scope, so that they simply never resolve.
+			let token = MetadataToken::new(index as u32 + 1, MetadataTokenKind::TypeRef);
+			let row = self.check_row(diagnostics, &assembly.name.name, token, RecoveryRule::RowNulled, row)?;
+			assembly
+				.type_refs
+				.push(row.unwrap_or((MetadataToken(0), String::new(), String::new())));
 		}
 
 		Ok(())
 	}
 
-	pub(super) fn read_assembly_types(&self, assembly: Rc<Assembly>) -> Result<(), Error> {
-		let table = match self.tables.get_table::<TypeDefTable>()? {
-			Some(table) => table,
-			None => return Ok(()),
-		};
+	pub(super) fn read_assembly_type_specs(
+		&self,
+		assembly: &mut Assembly,
+		diagnostics: &mut Diagnostics,
+	) -> Result<(), Error> {
+		let Some(table) = self.tables.get_table::<TypeSpecTable>()? else { return Ok(()) };
+
+		assembly.type_specs = Vec::with_capacity(table.len());
+		for (index, spec) in table.iter().enumerate() {
+			let signature = spec.map_err(Error::from).and_then(|spec| {
+				let blob = self.blobs.get_blob(spec.signature())?;
+				Ok(TypeSignature::parse(blob)?)
+			});
+
+			let token = MetadataToken::new(index as u32 + 1, MetadataTokenKind::TypeSpec);
+			let signature =
+				self.check_row(diagnostics, &assembly.name.name, token, RecoveryRule::RowSkipped, signature);
+			if let Some(signature) = signature? {
+				assembly.type_specs.push(TypeSpec { token, signature });
+			}
+		}
+
+		Ok(())
+	}
 
-		let mut types = Rc::from_iter(repeat_with(Type::default).take(table.len()));
+	pub(super) fn read_assembly_member_refs(
+		&self,
+		assembly: &mut Assembly,
+		diagnostics: &mut Diagnostics,
+	) -> Result<(), Error> {
+		let Some(table) = self.tables.get_table::<MemberRefTable>()? else { return Ok(()) };
+
+		assembly.member_refs = Vec::with_capacity(table.len());
+		for (index, member) in table.iter().enumerate() {
+			let row = member.map_err(Error::from).and_then(|member| {
+				let parent = member
+					.parent()
+					.decode(CodedIndexKind::MemberRefParent)
+					.ok_or(raw::Error::InvalidData(Some("Invalid member reference parent")))?;
+				let name = self.strings.get_string(member.name()).to_string();
+				let signature = self.blobs.get_blob(member.signature())?.to_vec();
+				Ok((parent, name, signature))
+			});
+
+			// Like TypeRefs, rows that fail to load keep their slot with a null parent.
+			let token = MetadataToken::new(index as u32 + 1, MetadataTokenKind::MemberRef);
+			let row = self.check_row(diagnostics, &assembly.name.name, token, RecoveryRule::RowNulled, row)?;
+			assembly
+				.member_refs
+				.push(row.unwrap_or((MetadataToken(0), String::new(), vec![])));
+		}
+
+		Ok(())
+	}
+
+	pub(super) fn read_assembly_method_specs(
+		&self,
+		assembly: &mut Assembly,
+		diagnostics: &mut Diagnostics,
+	) -> Result<(), Error> {
+		let Some(table) = self.tables.get_table::<MethodSpecTable>()? else { return Ok(()) };
+
+		assembly.method_specs = Vec::with_capacity(table.len());
+		for (index, spec) in table.iter().enumerate() {
+			let spec = spec.map_err(Error::from).and_then(|spec| {
+				let method = spec
+					.method()
+					.decode(CodedIndexKind::MethodDefOrRef)
+					.ok_or(raw::Error::InvalidData(Some("Invalid method specification method")))?;
+				let type_arguments = parse_method_spec(self.blobs.get_blob(spec.instantiation())?)?;
+				Ok((method, type_arguments))
+			});
+
+			let token = MetadataToken::new(index as u32 + 1, MetadataTokenKind::MethodSpec);
+			let spec = self.check_row(diagnostics, &assembly.name.name, token, RecoveryRule::RowSkipped, spec);
+			if let Some((method, type_arguments)) = spec? {
+				assembly.method_specs.push(MethodSpec {
+					token,
+					method,
+					type_arguments,
+				});
+			}
+		}
+
+		Ok(())
+	}
 
-		for index in 0..table.len() {
-			let reader = Type::read(self.blobs, self.tables, self.strings, table.clone(), assembly.clone());
-			reader.read_type_definition(index, &mut types);
+	/// Like [`Diagnostics::check`], recording where the row is and what it contains.
+	fn check_row<T>(
+		&self,
+		diagnostics: &mut Diagnostics,
+		assembly: &str,
+		token: MetadataToken,
+		rule: RecoveryRule,
+		result: Result<T, Error>,
+	) -> Result<Option<T>, Error> {
+		match result {
+			Ok(value) => Ok(Some(value)),
+			Err(error) => {
+				let recovery = || self.row_recovery(rule, token);
+				diagnostics
+					.recover(assembly, Some(token), error, recovery)
+					.map(|_| None)
+			}
+		}
+	}
+
+	/// Records the custom attributes the member reader kept raw, which never fail the load.
+	fn note_kept_raw(&self, diagnostics: &mut Diagnostics, assembly: &str, kept_raw: Vec<(MetadataToken, Error)>) {
+		for (token, error) in kept_raw {
+			let recovery = || self.row_recovery(RecoveryRule::AttributeKeptRaw, token);
+			diagnostics.note(assembly, Some(token), error, recovery);
+		}
+	}
+
+	fn row_recovery(&self, rule: RecoveryRule, token: MetadataToken) -> Recovery {
+		let range = self.raw_assembly.token_range(token).ok().flatten();
+		let bytes = range.clone().and_then(|range| self.bytes.get(range));
+		Recovery {
+			rule,
+			offset: range.map(|range| range.start),
+			observed: bytes.map(|bytes| bytes.iter().map(|byte| format!("{:02x}", byte)).collect()),
+			expected: None,
+		}
+	}
+
+	pub(super) fn read_assembly_types(
+		&self,
+		assembly: &Rc<Assembly>,
+		diagnostics: &mut Diagnostics,
+	) -> Result<(), Error> {
+		let Some(table) = self.tables.get_table::<TypeDefTable>()? else {
+			let _ = assembly.types.set(Rc::new([]));
+			return Ok(());
+		};
+
+		// Every row is parsed once, then shared by the member reader and the type definitions.
+		let name = assembly.name.name.as_str();
+		let rows = table.iter().collect::<Vec<_>>();
+		let mut types = Rc::from_iter(repeat_with(Type::default).take(rows.len()));
+
+		let list_starts = rows
+			.iter()
+			.map(|row| {
+				row.as_ref()
+					.map(|def| (def.fields(), def.methods()))
+					.map_err(|err| Error::from(*err))
+			})
+			.collect::<Result<Vec<_>, _>>();
+
+		let reader = MemberReader::new(self.blobs, self.tables, self.strings).retaining(assembly.strings.clone());
+		let mut kept_raw = vec![];
+		let members = list_starts.and_then(|list_starts| reader.read(&list_starts, &mut kept_raw));
+		self.note_kept_raw(diagnostics, name, kept_raw);
+		let members = diagnostics
+			.check(name, None, members)?
+			.unwrap_or_else(|| repeat_with(TypeMembers::default).take(rows.len()).collect());
+
+		let reader = Type::read(self.strings, assembly.clone());
+		for (index, (row, members)) in rows.into_iter().zip(members).enumerate() {
+			let result = row.map_err(Error::from);
+			if let Err(error) = result.and_then(|def| reader.read_type_definition(index, &def, &mut types, members)) {
+				let token = MetadataToken::new(index as u32 + 1, MetadataTokenKind::TypeDef);
+				let recovery = || self.row_recovery(RecoveryRule::TypeReplaced, token);
+				let reason = diagnostics.recover(name, Some(token), error, recovery)?;
+				Rc::get_mut(&mut types).unwrap()[index] = Type::Error(token, reason);
+			}
 		}
 
-		for index in 0..table.len() {
-			let reader = Type::read(self.blobs, self.tables, self.strings, table.clone(), assembly.clone());
-			reader.read_base(index, &mut types);
+		for index in 0..types.len() {
+			if let Err(error) = reader.read_base(index, &mut types) {
+				let token = MetadataToken::new(index as u32 + 1, MetadataTokenKind::TypeDef);
+				let recovery = || self.row_recovery(RecoveryRule::TypeReplaced, token);
+				let reason = diagnostics.recover(name, Some(token), error, recovery)?;
+				Rc::get_mut(&mut types).unwrap()[index] = Type::Error(token, reason);
+			}
 		}
 
-		let mut_assembly = unsafe { get_mut_unchecked(&assembly) };
-		mut_assembly.types = types;
+		let _ = assembly.types.set(types);
 
 		Ok(())
 	}
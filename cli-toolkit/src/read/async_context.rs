@@ -0,0 +1,85 @@
+use crate::read::assembly::AssemblyReader;
+use crate::raw::{AlignedBuffer, HeapSlicing};
+use crate::schema::Context;
+use tokio::runtime::{Handle, RuntimeFlavor};
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use std::path::Path;
+use crate::read::context::Diagnostics;
+use crate::read::Error;
+use std::sync::Arc;
+use std::rc::Rc;
+
+/// How [`Context::from_paths_async`] loads the files.
+#[derive(Debug, Copy, Clone)]
+pub struct AsyncLoadOptions {
+	/// The maximum number of files that are read and checked at the same time.
+	pub concurrency: usize,
+	/// Whether to load the context like [`Context::from_assembly_list_lenient`] does.
+	pub lenient: bool,
+}
+
+impl Default for AsyncLoadOptions {
+	fn default() -> Self {
+		Self {
+			concurrency: 16,
+			lenient: false,
+		}
+	}
+}
+
+impl Context {
+	/// Loads a context like [`Context::from_assembly_list`], reading and parsing the files off the event loop.
+	///
+	/// The files are read with `tokio::fs`, and copied and parsed into readers on blocking tasks, so that invalid
+	/// files are rejected before anything else is loaded. Contexts are reference counted and cannot move between
+	/// threads, so the types and members are then decoded from those readers on the calling task: within
+	/// [`block_in_place`](tokio::task::block_in_place) on the multi-threaded runtime, so that the other tasks of the
+	/// worker keep running in the meantime. On the current-thread runtime this last step blocks the event loop.
+	pub async fn from_paths_async<P: AsRef<Path>>(
+		paths: impl IntoIterator<Item = P>,
+		options: AsyncLoadOptions,
+	) -> Result<Rc<Context>, Error> {
+		let slicing = match options.lenient {
+			true => HeapSlicing::Clamp,
+			false => HeapSlicing::Strict,
+		};
+
+		let semaphore = Arc::new(Semaphore::new(options.concurrency.max(1)));
+		let mut tasks = JoinSet::new();
+		let mut count = 0;
+		for (index, path) in paths.into_iter().enumerate() {
+			let path = path.as_ref().to_path_buf();
+			let semaphore = semaphore.clone();
+			tasks.spawn(async move {
+				let _permit = semaphore.acquire_owned().await.map_err(std::io::Error::other)?;
+				let bytes = tokio::fs::read(&path).await?;
+				let task = tokio::task::spawn_blocking(move || parse_assembly(bytes, slicing));
+				let reader = task.await.map_err(std::io::Error::other)??;
+				Ok::<_, Error>((index, reader))
+			});
+			count += 1;
+		}
+
+		let mut readers = Vec::with_capacity(count);
+		readers.resize_with(count, || None);
+		while let Some(result) = tasks.join_next().await {
+			let (index, reader) = result.map_err(std::io::Error::other)??;
+			readers[index] = Some(reader);
+		}
+
+		let readers = readers.into_iter().flatten().collect();
+		let diagnostics = Diagnostics::new(options.lenient);
+		let depth = Context::DEFAULT_MAX_RESOLUTION_DEPTH;
+		let load = || Context::from_readers(readers, diagnostics, None, false, depth);
+		match Handle::current().runtime_flavor() {
+			RuntimeFlavor::MultiThread => tokio::task::block_in_place(load),
+			_ => load(),
+		}
+	}
+}
+
+/// Parses the headers and heaps of an assembly into a reader owning a copy of its bytes.
+fn parse_assembly(bytes: Vec<u8>, slicing: HeapSlicing) -> Result<AssemblyReader<'static>, Error> {
+	AssemblyReader::new(AlignedBuffer::try_from(bytes.as_slice())?, slicing)
+}
@@ -0,0 +1,180 @@
+use crate::read::assembly::AssemblyReader;
+use crate::read::context::Diagnostics;
+use crate::read::resolver::{
+	dotnet_framework_directories, dotnet_runtime_directories, windows_gac_directories, AssemblyResolver,
+};
+use crate::schema::{Assembly, AssemblyIdentity, Context, Recovery, RecoveryRule};
+use crate::raw::{AlignedBuffer, HeapSlicing};
+use std::collections::{HashSet, VecDeque};
+use std::path::PathBuf;
+use crate::read::Error;
+use std::rc::Rc;
+
+/// Loads a context from assembly files, along with every assembly they reference that the resolver can find.
+///
+/// ```ignore
+/// let builder = ContextBuilder::new().with_dotnet_runtime("8.0")?;
+/// let context = builder.assembly("bin/App.dll").build()?;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ContextBuilder {
+	assemblies: Vec<PathBuf>,
+	resolver: AssemblyResolver,
+	lenient: bool,
+	corlib: Option<AssemblyIdentity>,
+	retain_strings: bool,
+	hide_synthetic_members: bool,
+	max_resolution_depth: Option<usize>,
+}
+
+impl ContextBuilder {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn assembly(mut self, path: impl Into<PathBuf>) -> Self {
+		self.assemblies.push(path.into());
+		self
+	}
+
+	/// Adds a directory in which referenced assemblies are looked up, after the ones added so far.
+	pub fn probe_directory(mut self, directory: impl Into<PathBuf>) -> Self {
+		self.resolver.add_directory(directory);
+		self
+	}
+
+	/// Probes the framework assemblies of an installed .NET runtime, e.g. `"8.0"`, see [`dotnet_runtime_directories`].
+	pub fn with_dotnet_runtime(mut self, version: &str) -> Result<Self, Error> {
+		let directories = dotnet_runtime_directories(version);
+		if directories.is_empty() {
+			return Err(Error::RuntimeNotFound(version.to_string()));
+		}
+
+		for directory in directories {
+			self.resolver.add_directory(directory);
+		}
+
+		Ok(self)
+	}
+
+	/// Probes the global assembly caches and the runtime directories of the .NET Framework, if there are any.
+	pub fn with_dotnet_framework(mut self) -> Self {
+		for directory in windows_gac_directories() {
+			self.resolver.add_gac_directory(directory);
+		}
+
+		for directory in dotnet_framework_directories() {
+			self.resolver.add_directory(directory);
+		}
+
+		self
+	}
+
+	pub fn with_resolver(mut self, resolver: AssemblyResolver) -> Self {
+		self.resolver = resolver;
+		self
+	}
+
+	/// Loads the context like [`Context::from_assembly_list_lenient`] does,
+	/// also recording the referenced assemblies that were found but could not be read.
+	pub fn lenient(mut self, lenient: bool) -> Self {
+		self.lenient = lenient;
+		self
+	}
+
+	/// Recognizes well-known types such as `System.Object` only in the assembly with the name and public key token
+	/// of `corlib`, e.g. the `mscorlib` of Mono or Unity, whatever its version.
+	pub fn corlib(mut self, corlib: AssemblyIdentity) -> Self {
+		self.corlib = Some(corlib);
+		self
+	}
+
+	/// Keeps a copy of the `#Strings` heap of every assembly, so that names can be borrowed
+	/// for as long as the context lives, see [`Context::string_views`].
+	///
+	/// The names of types and members then view that copy instead of being copied one by one,
+	/// which saves an allocation per name while loading, at the cost of finding the end of a name when it is read.
+	pub fn retain_strings(mut self, retain: bool) -> Self {
+		self.retain_strings = retain;
+		self
+	}
+
+	/// Leaves accessors, backing fields and the other members the compiler emits on its own out of
+	/// [`Type::members`](crate::schema::Type::members), see [`Context::set_hide_synthetic_members`].
+	pub fn hide_synthetic_members(mut self, hide: bool) -> Self {
+		self.hide_synthetic_members = hide;
+		self
+	}
+
+	/// Limits how far type lookups go while the context is loaded and afterwards,
+	/// see [`Context::max_resolution_depth`]. Defaults to [`Context::DEFAULT_MAX_RESOLUTION_DEPTH`].
+	pub fn max_resolution_depth(mut self, depth: usize) -> Self {
+		self.max_resolution_depth = Some(depth);
+		self
+	}
+
+	pub fn resolver(&self) -> &AssemblyResolver {
+		&self.resolver
+	}
+
+	pub fn build(self) -> Result<Rc<Context>, Error> {
+		let slicing = match self.lenient {
+			true => HeapSlicing::Clamp,
+			false => HeapSlicing::Strict,
+		};
+
+		let mut diagnostics = Diagnostics::new(self.lenient);
+		let mut readers = vec![];
+		let mut loaded = HashSet::new();
+		let mut probed = HashSet::new();
+
+		let mut queue = self
+			.assemblies
+			.into_iter()
+			.map(|path| (path, true))
+			.collect::<VecDeque<_>>();
+		while let Some((path, is_root)) = queue.pop_front() {
+			let reader = AlignedBuffer::try_from(path.as_path())
+				.map_err(Error::from)
+				.and_then(|bytes| AssemblyReader::new(bytes, slicing));
+
+			let mut reader = match reader {
+				Ok(reader) => reader,
+				Err(error) if is_root => return Err(error),
+				Err(error) => {
+					let recovery = || Recovery::new(RecoveryRule::DependencySkipped);
+					diagnostics.recover(&path.to_string_lossy(), None, error, recovery)?;
+					continue;
+				}
+			};
+
+			if !loaded.insert(reader.get_ident()?) {
+				continue;
+			}
+
+			let mut assembly = Assembly::default();
+			reader.read_assembly_refs(&mut assembly)?;
+			for dependency in assembly.dependencies {
+				if loaded.contains(&dependency.ident_key) || !probed.insert(dependency.name.clone()) {
+					continue;
+				}
+
+				if let Some(path) = self.resolver.resolve_identity(&dependency.identity()) {
+					queue.push_back((path, false));
+				}
+			}
+
+			reader.path = Some(path);
+			readers.push(reader);
+		}
+
+		// Base types are resolved while loading, so referenced assemblies are loaded before the ones referring to them.
+		readers.reverse();
+		let depth = self
+			.max_resolution_depth
+			.unwrap_or(Context::DEFAULT_MAX_RESOLUTION_DEPTH);
+		let context = Context::from_readers(readers, diagnostics, self.corlib, self.retain_strings, depth)?;
+		context.set_hide_synthetic_members(self.hide_synthetic_members);
+		Ok(context)
+	}
+}
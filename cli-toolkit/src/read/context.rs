@@ -1,17 +1,103 @@
 use crate::read::assembly::AssemblyReader;
-use crate::schema::{Assembly, Context};
+use crate::schema::{
+	Assembly, AssemblyIdentity, Context, LoadDiagnostic, LoadReport, MergePolicy, Recovery, RecoveryRule,
+	ReferenceResolution, ReloadReport, ResolutionRule,
+};
 use std::path::{Path, PathBuf};
 use std::collections::HashMap;
-use crate::raw::AlignedBuffer;
+use crate::raw::{AlignedBuffer, HeapSlicing, MetadataToken};
 use std::iter::repeat_with;
 use crate::read::Error;
 use std::pin::Pin;
 use std::rc::Rc;
-use crate::utilities::get_mut_unchecked;
+use std::cell::{Cell, OnceCell, RefCell};
+use std::borrow::Borrow;
 
 pub struct ContextReader<'l> {
-	context: Rc<Context>,
 	readers: Vec<AssemblyReader<'l>>,
+	diagnostics: Diagnostics,
+	corlib: Option<AssemblyIdentity>,
+	retain_strings: bool,
+	max_resolution_depth: usize,
+}
+
+/// Decides whether recoverable failures abort the load or are recorded as [`LoadDiagnostic`]s.
+pub(crate) struct Diagnostics {
+	accumulate: bool,
+	entries: Vec<LoadDiagnostic>,
+}
+
+impl Diagnostics {
+	pub(crate) fn new(accumulate: bool) -> Self {
+		Self {
+			accumulate,
+			entries: vec![],
+		}
+	}
+
+	/// Returns the reason of the failure if it was recorded, or the error itself if it must abort the load.
+	/// Failures of single rows are recorded as [`RecoveryRule::RowSkipped`], others as [`RecoveryRule::SectionSkipped`].
+	pub(crate) fn record(
+		&mut self,
+		assembly: &str,
+		token: Option<MetadataToken>,
+		error: Error,
+	) -> Result<String, Error> {
+		let rule = match token {
+			Some(_) => RecoveryRule::RowSkipped,
+			None => RecoveryRule::SectionSkipped,
+		};
+
+		self.recover(assembly, token, error, || Recovery::new(rule))
+	}
+
+	/// Like [`Diagnostics::record`], with the recovery to record. It is only computed if the failure is recorded.
+	pub(crate) fn recover(
+		&mut self,
+		assembly: &str,
+		token: Option<MetadataToken>,
+		error: Error,
+		recovery: impl FnOnce() -> Recovery,
+	) -> Result<String, Error> {
+		if !self.accumulate {
+			return Err(error);
+		}
+
+		let reason = error.to_string();
+		self.entries.push(LoadDiagnostic {
+			assembly: assembly.to_string(),
+			token,
+			reason: reason.clone(),
+			recovery: recovery(),
+		});
+		Ok(reason)
+	}
+
+	/// Records a failure the load recovers from in either mode, e.g. by keeping a custom attribute raw.
+	/// Only error accumulation mode keeps the diagnostic.
+	pub(crate) fn note(
+		&mut self,
+		assembly: &str,
+		token: Option<MetadataToken>,
+		error: Error,
+		recovery: impl FnOnce() -> Recovery,
+	) {
+		if self.accumulate {
+			let _ = self.recover(assembly, token, error, recovery);
+		}
+	}
+
+	pub(crate) fn check<T>(
+		&mut self,
+		assembly: &str,
+		token: Option<MetadataToken>,
+		result: Result<T, Error>,
+	) -> Result<Option<T>, Error> {
+		match result {
+			Ok(value) => Ok(Some(value)),
+			Err(error) => self.record(assembly, token, error).map(|_| None),
+		}
+	}
 }
 
 impl Context {
@@ -21,54 +107,403 @@ impl Context {
 	where
 		Error: From<<T as TryInto<AlignedBuffer<'l>>>::Error>,
 	{
-		let mut readers = vec![];
-		for i in assemblies {
-			readers.push(AssemblyReader::new(i.try_into()?)?)
+		Self::load(assemblies, false)
+	}
+
+	/// Like [`Context::from_assembly_list`], but failures of single rows and tables are recorded in
+	/// [`Context::load_diagnostics`], and the affected type definitions become [`Type::Error`](crate::schema::Type::Error).
+	/// Streams that extend past the end of the file are clamped with [`HeapSlicing::Clamp`], and recorded as well.
+	pub fn from_assembly_list_lenient<'l, T: TryInto<AlignedBuffer<'l>>>(
+		assemblies: impl IntoIterator<Item = T>,
+	) -> Result<Rc<Context>, Error>
+	where
+		Error: From<<T as TryInto<AlignedBuffer<'l>>>::Error>,
+	{
+		Self::load(assemblies, true)
+	}
+
+	/// Like [`Context::from_assembly_list`], but reads the images in place rather than copying them into aligned buffers,
+	/// for images that are already memory mapped or embedded, whatever their alignment.
+	///
+	/// The images only need to outlive the load, since the context owns everything it decodes from them.
+	pub fn from_borrowed_slices(assemblies: &[&[u8]]) -> Result<Rc<Context>, Error> {
+		let mut readers = Vec::with_capacity(assemblies.len());
+		for bytes in assemblies {
+			readers.push(AssemblyReader::new(AlignedBuffer::borrowed(bytes), HeapSlicing::Strict)?);
 		}
 
-		let reader = ContextReader {
-			readers,
-			context: Rc::new(Context::default()),
+		let depth = Context::DEFAULT_MAX_RESOLUTION_DEPTH;
+		Self::from_readers(readers, Diagnostics::new(false), None, false, depth)
+	}
+
+	/// Combines two contexts, e.g. framework assemblies loaded once with the assemblies of a single request.
+	///
+	/// The assemblies of `base` are shared with the merged context, and keep resolving their references against `base`.
+	/// The assemblies of `other` are moved into the merged context, so that the type references they could not
+	/// resolve so far are looked up in the union of both, which is why `other` must not be shared.
+	/// Base types are resolved once while loading, and are not re-linked.
+	pub fn merge(base: &Rc<Context>, other: Rc<Context>, policy: MergePolicy) -> Result<Rc<Context>, Error> {
+		let other = Rc::try_unwrap(other).map_err(|_| Error::SharedContext)?;
+
+		let mut base_assemblies = base.keyed_assemblies();
+		let mut other_assemblies = other.keyed_assemblies();
+		for (key, _) in other_assemblies.iter() {
+			if base.assembly_map.contains_key(*key) {
+				match policy {
+					MergePolicy::PreferBase => {}
+					MergePolicy::PreferOther => base_assemblies.retain(|(base_key, _)| base_key != key),
+					MergePolicy::Fail => return Err(Error::DuplicateAssembly(key.to_string())),
+				}
+			}
+		}
+
+		if policy == MergePolicy::PreferBase {
+			other_assemblies.retain(|(key, _)| !base.assembly_map.contains_key(*key));
+		}
+
+		let mut assembly_map = HashMap::new();
+		let mut assembly_vec = Vec::with_capacity(base_assemblies.len() + other_assemblies.len());
+		for (key, assembly) in base_assemblies.into_iter().chain(other_assemblies.iter().cloned()) {
+			assembly_map.insert(key.to_string(), assembly_vec.len());
+			assembly_vec.push(assembly.clone());
+		}
+
+		let (reference_map, load_report) = resolve_references(&assembly_vec, &assembly_map);
+		let diagnostics = base.load_diagnostics().iter().chain(other.load_diagnostics());
+		let context = Rc::new(Context {
+			assembly_map,
+			assembly_vec,
+			reference_map,
+			diagnostics: OnceCell::from(diagnostics.cloned().collect::<Vec<_>>()),
+			load_report,
+			corlib: base.corlib.clone(),
+			extension_methods: OnceCell::new(),
+			type_names: OnceCell::new(),
+			hide_synthetic_members: base.hide_synthetic_members.clone(),
+			max_resolution_depth: base.max_resolution_depth.clone(),
+		});
+
+		for (_, assembly) in other_assemblies {
+			*assembly.ctx.borrow_mut() = Rc::downgrade(&context);
+		}
+
+		Ok(context)
+	}
+
+	/// Replaces the assembly registered under `identity` with a new build of it, e.g. after it was recompiled,
+	/// without reloading the rest of the context.
+	///
+	/// The references of every assembly are resolved again, and the TypeRefs and decoded attributes of the assemblies
+	/// that referred to the replaced assembly are dropped, so that they are looked up in the new build on first use.
+	/// Base types are resolved once while loading, and are not re-linked, which is why the dependents are listed
+	/// in the [`ReloadReport`] for the caller to reload as well if needed.
+	/// Like with [`Context::merge`], the context must not be shared.
+	pub fn reload_assembly<'l, T: TryInto<AlignedBuffer<'l>>>(
+		self: Rc<Self>,
+		identity: &str,
+		bytes: T,
+	) -> Result<(Rc<Context>, ReloadReport), Error>
+	where
+		Error: From<<T as TryInto<AlignedBuffer<'l>>>::Error>,
+	{
+		let context = Rc::try_unwrap(self).map_err(|_| Error::SharedContext)?;
+		let Some(index) = context.assembly_map.get(identity).copied() else {
+			return Err(Error::UnknownAssembly(identity.to_string()));
+		};
+
+		let reader = AssemblyReader::new(bytes.try_into()?, HeapSlicing::Strict)?;
+		let key = reader.get_ident()?;
+		if key != identity && context.assembly_map.contains_key(&key) {
+			return Err(Error::DuplicateAssembly(key));
+		}
+
+		let previous = context.assembly_vec[index].clone();
+		let mut diagnostics = Diagnostics::new(false);
+		let mut assembly = read_assembly(&reader, &mut diagnostics, previous.strings.is_some())?;
+		assembly.path = previous.path.clone();
+		assembly.name_style = previous.name_style.clone();
+
+		let mut assembly_map = context.assembly_map;
+		let mut assembly_vec = context.assembly_vec;
+		assembly_map.remove(identity);
+		assembly_map.insert(key, index);
+		assembly_vec[index] = Rc::new(assembly);
+
+		let (reference_map, load_report) = resolve_references(&assembly_vec, &assembly_map);
+		let refers_to_index = |reference_map: &HashMap<String, usize>, assembly: &Assembly| {
+			let dependencies = assembly.dependencies.iter();
+			dependencies
+				.filter_map(|dependency| reference_map.get(&dependency.ident_key))
+				.any(|i| *i == index)
+		};
+
+		let dependents = assembly_vec
+			.iter()
+			.enumerate()
+			.filter(|(i, assembly)| {
+				*i != index
+					&& (refers_to_index(&context.reference_map, assembly) || refers_to_index(&reference_map, assembly))
+			})
+			.map(|(_, assembly)| assembly.clone())
+			.collect::<Vec<_>>();
+
+		let previous_name = previous.full_name();
+		let old_diagnostics = context.diagnostics.into_inner().unwrap_or_default();
+		let old_diagnostics = old_diagnostics
+			.into_iter()
+			.filter(|diagnostic| diagnostic.assembly != previous.name.name);
+		let new_context = Rc::new(Context {
+			assembly_map,
+			assembly_vec,
+			reference_map,
+			diagnostics: OnceCell::new(),
+			load_report,
+			corlib: context.corlib,
+			extension_methods: OnceCell::new(),
+			type_names: OnceCell::new(),
+			hide_synthetic_members: context.hide_synthetic_members,
+			max_resolution_depth: context.max_resolution_depth,
+		});
+
+		for assembly in new_context.assembly_vec.iter() {
+			*assembly.ctx.borrow_mut() = Rc::downgrade(&new_context);
+		}
+
+		for assembly in dependents.iter() {
+			let mut type_ref_cache = assembly.type_ref_cache.borrow_mut();
+			let len = type_ref_cache.len();
+			*type_ref_cache = repeat_with(OnceCell::new).take(len).collect();
+			let attributes = &assembly.decode_cache.attributes;
+			attributes.set_capacity(attributes.statistics().capacity);
+		}
+
+		let reloaded = &new_context.assembly_vec[index];
+		reader.read_assembly_types(reloaded, &mut diagnostics)?;
+
+		let diagnostics = old_diagnostics.chain(diagnostics.entries).collect::<Vec<_>>();
+		let _ = new_context.diagnostics.set(diagnostics);
+		let report = ReloadReport {
+			previous: previous_name,
+			reloaded: reloaded.full_name(),
+			invalidated: dependents.iter().map(|assembly| assembly.full_name()).collect(),
+			#[cfg(feature = "checksums")]
+			changed_streams: reloaded
+				.stream_checksums
+				.changed_streams(&previous.stream_checksums)
+				.into_iter()
+				.map(str::to_string)
+				.collect(),
 		};
 
-		reader.read()
+		Ok((new_context, report))
 	}
 
-	pub(crate) fn default() -> Self {
-		Self {
-			assembly_vec: vec![],
-			assembly_map: HashMap::default(),
+	/// Every assembly along with the identity it is registered under, in load order.
+	fn keyed_assemblies(&self) -> Vec<(&str, &Rc<Assembly>)> {
+		let mut assemblies = self
+			.assembly_map
+			.iter()
+			.filter_map(|(key, index)| Some((*index, key.as_str(), self.assembly_vec.get(*index)?)))
+			.collect::<Vec<_>>();
+
+		assemblies.sort_by_key(|(index, _, _)| *index);
+		assemblies
+			.into_iter()
+			.map(|(_, key, assembly)| (key, assembly))
+			.collect()
+	}
+
+	pub(super) fn load<'l, T: TryInto<AlignedBuffer<'l>>>(
+		assemblies: impl IntoIterator<Item = T>,
+		accumulate: bool,
+	) -> Result<Rc<Context>, Error>
+	where
+		Error: From<<T as TryInto<AlignedBuffer<'l>>>::Error>,
+	{
+		let slicing = match accumulate {
+			true => HeapSlicing::Clamp,
+			false => HeapSlicing::Strict,
+		};
+
+		let mut readers = vec![];
+		for i in assemblies {
+			readers.push(AssemblyReader::new(i.try_into()?, slicing)?)
+		}
+
+		let depth = Context::DEFAULT_MAX_RESOLUTION_DEPTH;
+		Self::from_readers(readers, Diagnostics::new(accumulate), None, false, depth)
+	}
+
+	pub(super) fn from_readers(
+		readers: Vec<AssemblyReader>,
+		diagnostics: Diagnostics,
+		corlib: Option<AssemblyIdentity>,
+		retain_strings: bool,
+		max_resolution_depth: usize,
+	) -> Result<Rc<Context>, Error> {
+		ContextReader {
+			readers,
+			diagnostics,
+			corlib,
+			retain_strings,
+			max_resolution_depth,
 		}
+		.read()
 	}
 }
 
 impl<'l> ContextReader<'l> {
 	fn read(mut self) -> Result<Rc<Context>, Error> {
-		let mut_context = unsafe { get_mut_unchecked(&self.context) };
-		mut_context.assembly_vec = Vec::with_capacity(self.readers.len());
-
+		let mut assembly_map = HashMap::new();
 		for (index, reader) in self.readers.iter().enumerate() {
 			let ident = reader.get_ident()?;
-			mut_context.assembly_map.insert(ident, index);
+			assembly_map.insert(ident, index);
 		}
 
+		// Everything but the type definitions is read before the assemblies are shared,
+		// since types need the whole context in order to resolve their base types.
+		let mut assemblies = Vec::with_capacity(self.readers.len());
 		for reader in self.readers.iter() {
-			let mut assembly = Rc::new(Assembly::default());
-			let assembly = reader.read_assembly_definition(assembly)?;
-			mut_context.assembly_vec.push(assembly);
+			assemblies.push(read_assembly(reader, &mut self.diagnostics, self.retain_strings)?);
+		}
+
+		let (reference_map, load_report) = resolve_references(&assemblies, &assembly_map);
+		let context = Rc::new_cyclic(|ctx| Context {
+			assembly_map,
+			reference_map,
+			diagnostics: OnceCell::new(),
+			load_report,
+			corlib: self.corlib.take(),
+			extension_methods: OnceCell::new(),
+			type_names: OnceCell::new(),
+			hide_synthetic_members: Cell::new(false),
+			max_resolution_depth: Cell::new(self.max_resolution_depth),
+			assembly_vec: assemblies
+				.into_iter()
+				.map(|assembly| {
+					Rc::new(Assembly {
+						ctx: RefCell::new(ctx.clone()),
+						..assembly
+					})
+				})
+				.collect(),
+		});
+
+		for (reader, assembly) in self.readers.iter().zip(context.assembly_vec.iter()) {
+			reader.read_assembly_types(assembly, &mut self.diagnostics)?;
 		}
 
-		for (reader, assembly) in self.readers.iter().zip(mut_context.assembly_vec.iter().cloned()) {
-			{
-				let mut_assembly = unsafe { get_mut_unchecked(&assembly) };
-				mut_assembly.ctx = Rc::downgrade(&self.context);
+		let _ = context.diagnostics.set(std::mem::take(&mut self.diagnostics.entries));
+		Ok(context)
+	}
+}
 
-				reader.read_assembly_refs(mut_assembly);
-				reader.read_assembly_type_refs(mut_assembly);
+/// Reads everything of an assembly but its type definitions, which need the whole context.
+fn read_assembly(
+	reader: &AssemblyReader,
+	diagnostics: &mut Diagnostics,
+	retain_strings: bool,
+) -> Result<Assembly, Error> {
+	let mut assembly = Assembly::default();
+	assembly.path = reader.path.clone();
+	if retain_strings {
+		assembly.strings = Some(Rc::from(reader.strings()));
+	}
+	#[cfg(feature = "checksums")]
+	{
+		assembly.stream_checksums = reader.stream_checksums()?;
+	}
+	reader.read_assembly_definition(&mut assembly)?;
+
+	for stream in reader.truncated_streams()? {
+		let error = Error::TruncatedStream {
+			name: stream.name.to_string(),
+			declared_size: stream.declared_size,
+			available_size: stream.available_size,
+		};
+		let recovery = || Recovery {
+			rule: RecoveryRule::StreamClamped,
+			offset: Some(stream.offset),
+			observed: Some(stream.declared_size.to_string()),
+			expected: Some(format!("<={}", stream.available_size)),
+		};
+		diagnostics.recover(&assembly.name.name, None, error, recovery)?;
+	}
+
+	let result = reader.read_assembly_attributes(&mut assembly, diagnostics);
+	diagnostics.check(&assembly.name.name, None, result)?;
+	let result = reader.read_assembly_refs(&mut assembly);
+	diagnostics.check(&assembly.name.name, None, result)?;
+	let result = reader.read_assembly_module_refs(&mut assembly);
+	diagnostics.check(&assembly.name.name, None, result)?;
+	let result = reader.read_assembly_type_refs(&mut assembly, diagnostics);
+	diagnostics.check(&assembly.name.name, None, result)?;
+	let result = reader.read_assembly_type_specs(&mut assembly, diagnostics);
+	diagnostics.check(&assembly.name.name, None, result)?;
+	let result = reader.read_assembly_member_refs(&mut assembly, diagnostics);
+	diagnostics.check(&assembly.name.name, None, result)?;
+	let result = reader.read_assembly_method_specs(&mut assembly, diagnostics);
+	diagnostics.check(&assembly.name.name, None, result)?;
+	Ok(assembly)
+}
+
+/// Matches every reference of the assemblies with an assembly of the context, following [`ResolutionRule`].
+fn resolve_references<T: Borrow<Assembly>>(
+	assemblies: &[T],
+	assembly_map: &HashMap<String, usize>,
+) -> (HashMap<String, usize>, LoadReport) {
+	let identities = assemblies
+		.iter()
+		.map(|assembly| assembly.borrow().name.identity())
+		.collect::<Vec<_>>();
+
+	let mut reference_map = HashMap::new();
+	let mut references = vec![];
+	for assembly in assemblies.iter().map(Borrow::borrow) {
+		for dependency in assembly.dependencies.iter() {
+			let reference = dependency.identity();
+			let resolution = match assembly_map.get(&dependency.ident_key) {
+				Some(index) => Some((*index, ResolutionRule::Exact)),
+				None => resolve_reference(&reference, &identities),
+			};
+
+			if let Some((index, _)) = resolution {
+				reference_map.insert(dependency.ident_key.clone(), index);
 			}
-			reader.read_assembly_types(assembly);
+
+			let target = resolution
+				.and_then(|(index, _)| assemblies.get(index))
+				.map(Borrow::borrow);
+			references.push(ReferenceResolution {
+				referrer: assembly.full_name(),
+				reference,
+				rule: resolution.map(|(_, rule)| rule),
+				assembly: target.map(Assembly::full_name),
+				path: target.and_then(|target| target.path.clone()),
+			});
 		}
+	}
+
+	(reference_map, LoadReport { references })
+}
+
+fn resolve_reference(reference: &AssemblyIdentity, identities: &[AssemblyIdentity]) -> Option<(usize, ResolutionRule)> {
+	let token = reference.public_key_token();
+	let candidates = identities
+		.iter()
+		.enumerate()
+		.filter(|(_, identity)| identity.name.eq_ignore_ascii_case(&reference.name));
+
+	let unified = candidates.clone().filter(|(_, identity)| {
+		identity.culture.eq_ignore_ascii_case(&reference.culture)
+			&& (token.is_none() || (identity.public_key_token() == token && identity.version > reference.version))
+	});
 
-		Ok(self.context)
+	let latest = |(_, identity): &(usize, &AssemblyIdentity)| identity.version.clone();
+	match unified.max_by_key(latest) {
+		Some((index, _)) => Some((index, ResolutionRule::Unified)),
+		None => candidates
+			.max_by_key(latest)
+			.map(|(index, _)| (index, ResolutionRule::Fallback)),
 	}
 }
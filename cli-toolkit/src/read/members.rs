@@ -0,0 +1,547 @@
+use crate::raw::{
+	attribute_type, type_name, BlobHeap, CodedIndexKind, CustomAttributeTable, EncLogTable, EventMapTable,
+	EventPtrTable, EventTable, FieldPtrTable, FieldTable, GenericParamConstraintTable, GenericParamTable,
+	InterfaceImplTable, MetadataTable, MetadataToken, MetadataTokenKind, MethodDefTable, MethodImplTable,
+	MethodPtrTable, MethodSemanticsTable, ModuleTable, NestedClassTable, ParamPtrTable, ParamTable, PropertyMapTable,
+	PropertyPtrTable, PropertyTable, StringHeap, TableHeap, HeapIndex, TableIndex, TypeDefTable,
+};
+use crate::schema::{
+	CustomAttribute, Event, Field, GenericParam, InterfaceImpl, Method, Name, Param, Property, Provenance,
+};
+use crate::read::Error;
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use std::rc::Rc;
+use crate::raw;
+
+#[derive(Default)]
+pub(crate) struct TypeMembers {
+	pub(crate) fields: Vec<Field>,
+	pub(crate) methods: Vec<Method>,
+	pub(crate) properties: Vec<Property>,
+	pub(crate) events: Vec<Event>,
+	pub(crate) nested_types: Vec<MetadataToken>,
+	pub(crate) generic_params: Vec<GenericParam>,
+	pub(crate) interfaces: Vec<InterfaceImpl>,
+	pub(crate) custom_attributes: Vec<CustomAttribute>,
+	pub(crate) provenance: Provenance,
+}
+
+pub(crate) struct MemberReader<'l> {
+	blobs: BlobHeap<'l>,
+	tables: TableHeap<'l>,
+	strings: StringHeap<'l>,
+	/// The copy of the `#Strings` heap the assembly retains, if any, which names are viewed in.
+	retained: Option<Rc<[u8]>>,
+}
+
+impl<'l> MemberReader<'l> {
+	pub(crate) fn new(blobs: BlobHeap<'l>, tables: TableHeap<'l>, strings: StringHeap<'l>) -> Self {
+		Self {
+			blobs,
+			tables,
+			strings,
+			retained: None,
+		}
+	}
+
+	/// Views member names in the heap retained by the assembly rather than copying them.
+	pub(crate) fn retaining(mut self, retained: Option<Rc<[u8]>>) -> Self {
+		self.retained = retained;
+		self
+	}
+
+	fn name(&self, index: HeapIndex) -> Name {
+		Name::read(self.strings, self.retained.as_ref(), index)
+	}
+
+	/// Reads the members of every type definition, indexed by TypeDef row,
+	/// given the field and method lists of each row.
+	/// Custom attributes that cannot be fully read are kept raw, and their failures added to `kept_raw`.
+	pub(crate) fn read(
+		&self,
+		list_starts: &[(TableIndex, TableIndex)],
+		kept_raw: &mut Vec<(MetadataToken, Error)>,
+	) -> Result<Vec<TypeMembers>, Error> {
+		let mut members = Vec::with_capacity(list_starts.len());
+		members.resize_with(list_starts.len(), TypeMembers::default);
+
+		let edits = self.read_edits()?;
+		for (index, members) in members.iter_mut().enumerate() {
+			let token = MetadataToken::new(index as u32 + 1, MetadataTokenKind::TypeDef);
+			members.provenance = edits.provenance(token, false);
+		}
+
+		let field_starts = list_starts.iter().map(|(fields, _)| *fields).collect::<Vec<_>>();
+		let method_starts = list_starts.iter().map(|(_, methods)| *methods).collect::<Vec<_>>();
+
+		let fields = self.tables.get_table::<FieldTable>()?;
+		let field_ptrs = self.tables.get_table::<FieldPtrTable>()?;
+		let field_count = field_ptrs
+			.as_ref()
+			.map(|t| t.len())
+			.or(fields.as_ref().map(|t| t.len()));
+		for (index, range) in list_ranges(&field_starts, field_count.unwrap_or(0)).enumerate() {
+			let Some(fields) = &fields else { break };
+			let parent = MetadataToken::new(index as u32 + 1, MetadataTokenKind::TypeDef);
+
+			for row in range {
+				let row = deref_ptr(&field_ptrs, row, |ptr| ptr.field())?;
+				let field = fields.get(row)?;
+				let token = MetadataToken::new(row.0, MetadataTokenKind::Field);
+				members[index].fields.push(Field {
+					token,
+					parent,
+					name: self.name(field.name()),
+					name_index: field.name(),
+					flags: field.flags(),
+					signature: self.blobs.get_blob(field.signature())?.to_vec(),
+					signature_index: field.signature(),
+					provenance: edits.provenance(token, field_ptrs.is_some()),
+				});
+			}
+		}
+
+		let methods = self.tables.get_table::<MethodDefTable>()?;
+		let method_ptrs = self.tables.get_table::<MethodPtrTable>()?;
+		let method_count = method_ptrs
+			.as_ref()
+			.map(|t| t.len())
+			.or(methods.as_ref().map(|t| t.len()));
+		let method_defs = match &methods {
+			None => vec![],
+			Some(methods) => methods.iter().collect::<Result<Vec<_>, _>>()?,
+		};
+
+		let params = self.tables.get_table::<ParamTable>()?;
+		let param_ptrs = self.tables.get_table::<ParamPtrTable>()?;
+		let param_count = param_ptrs
+			.as_ref()
+			.map(|t| t.len())
+			.or(params.as_ref().map(|t| t.len()));
+		let param_starts = method_defs.iter().map(|def| def.params()).collect::<Vec<_>>();
+
+		let mut method_params = Vec::with_capacity(method_defs.len());
+		for range in list_ranges(&param_starts, param_count.unwrap_or(0)) {
+			let mut list = vec![];
+			if let Some(params) = &params {
+				for row in range {
+					let row = deref_ptr(&param_ptrs, row, |ptr| ptr.param())?;
+					let param = params.get(row)?;
+					let token = MetadataToken::new(row.0, MetadataTokenKind::Param);
+					list.push(Param {
+						token,
+						name: self.name(param.name()),
+						name_index: param.name(),
+						flags: param.flags(),
+						sequence: param.sequence(),
+						provenance: edits.provenance(token, param_ptrs.is_some()),
+					});
+				}
+			}
+			method_params.push(Some(list));
+		}
+
+		// The type and position of every method, so that their custom attributes can be attached to them.
+		let mut method_positions = HashMap::new();
+		for (index, range) in list_ranges(&method_starts, method_count.unwrap_or(0)).enumerate() {
+			let parent = MetadataToken::new(index as u32 + 1, MetadataTokenKind::TypeDef);
+
+			for row in range {
+				let row = deref_ptr(&method_ptrs, row, |ptr| ptr.method())?;
+				let Some(method) = row_index(row).and_then(|row| method_defs.get(row)) else {
+					return Err(raw::Error::OffsetOutOfBounds.into());
+				};
+
+				let params = row_index(row).and_then(|row| method_params.get_mut(row)?.take());
+				let token = MetadataToken::new(row.0, MetadataTokenKind::Method);
+				method_positions.insert(row.0, (index, members[index].methods.len()));
+				members[index].methods.push(Method {
+					token,
+					parent,
+					name: self.name(method.name()),
+					name_index: method.name(),
+					rva: method.rva(),
+					flags: method.flags(),
+					impl_flags: method.impl_flags(),
+					signature: self.blobs.get_blob(method.signature())?.to_vec(),
+					signature_index: method.signature(),
+					params: params.unwrap_or_default(),
+					generic_params: vec![],
+					custom_attributes: vec![],
+					overrides: vec![],
+					provenance: edits.provenance(token, method_ptrs.is_some()),
+				});
+			}
+		}
+
+		if let Some(table) = self.tables.get_table::<MethodImplTable>()? {
+			for row in table.iter() {
+				let row = row?;
+				let declaration = row
+					.declaration()
+					.decode(CodedIndexKind::MethodDefOrRef)
+					.ok_or(raw::Error::InvalidData(Some("Invalid method implementation declaration")))?;
+
+				// Bodies which are MemberRefs belong to another type, and are not attached to a definition.
+				let Some(body) = row.body().decode(CodedIndexKind::MethodDefOrRef) else { continue };
+				if body.token_kind() != Some(MetadataTokenKind::Method) {
+					continue;
+				}
+
+				let method = method_positions
+					.get(&(body.index() as u32))
+					.and_then(|(ty, method)| members.get_mut(*ty)?.methods.get_mut(*method));
+				if let Some(method) = method {
+					method.overrides.push(declaration);
+				}
+			}
+		}
+
+		let mut semantics = vec![];
+		if let Some(table) = self.tables.get_table::<MethodSemanticsTable>()? {
+			for row in table.iter() {
+				let row = row?;
+				let association = row
+					.association()
+					.decode(CodedIndexKind::HasSemantics)
+					.ok_or(raw::Error::InvalidData(Some("Invalid method semantics association")))?;
+
+				let method = MetadataToken::new(row.method().0, MetadataTokenKind::Method);
+				semantics.push((association, row.semantics(), method));
+			}
+		}
+
+		let accessors = |token: MetadataToken| {
+			semantics
+				.iter()
+				.filter(|(association, _, _)| *association == token)
+				.map(|(_, flags, method)| (*flags, *method))
+				.collect::<Vec<_>>()
+		};
+
+		if let (Some(map), Some(properties)) = (
+			self.tables.get_table::<PropertyMapTable>()?,
+			self.tables.get_table::<PropertyTable>()?,
+		) {
+			let ptrs = self.tables.get_table::<PropertyPtrTable>()?;
+			let count = ptrs.as_ref().map(|t| t.len()).unwrap_or(properties.len());
+			let rows = map.iter().collect::<Result<Vec<_>, _>>()?;
+			let starts = rows.iter().map(|row| row.property_list()).collect::<Vec<_>>();
+
+			for (row, range) in rows.iter().zip(list_ranges(&starts, count)) {
+				let Some(members) = row_index(row.parent()).and_then(|row| members.get_mut(row)) else { continue };
+				let parent = MetadataToken::new(row.parent().0, MetadataTokenKind::TypeDef);
+
+				for index in range {
+					let index = deref_ptr(&ptrs, index, |ptr| ptr.property())?;
+					let property = properties.get(index)?;
+					let token = MetadataToken::new(index.0, MetadataTokenKind::Property);
+					members.properties.push(Property {
+						token,
+						parent,
+						name: self.name(property.name()),
+						name_index: property.name(),
+						flags: property.flags(),
+						signature: self.blobs.get_blob(property.signature())?.to_vec(),
+						methods: accessors(token),
+						provenance: edits.provenance(token, ptrs.is_some()),
+					});
+				}
+			}
+		}
+
+		if let (Some(map), Some(events)) = (
+			self.tables.get_table::<EventMapTable>()?,
+			self.tables.get_table::<EventTable>()?,
+		) {
+			let ptrs = self.tables.get_table::<EventPtrTable>()?;
+			let count = ptrs.as_ref().map(|t| t.len()).unwrap_or(events.len());
+			let rows = map.iter().collect::<Result<Vec<_>, _>>()?;
+			let starts = rows.iter().map(|row| row.event_list()).collect::<Vec<_>>();
+
+			for (row, range) in rows.iter().zip(list_ranges(&starts, count)) {
+				let Some(members) = row_index(row.parent()).and_then(|row| members.get_mut(row)) else { continue };
+				let parent = MetadataToken::new(row.parent().0, MetadataTokenKind::TypeDef);
+
+				for index in range {
+					let index = deref_ptr(&ptrs, index, |ptr| ptr.event())?;
+					let event = events.get(index)?;
+					let token = MetadataToken::new(index.0, MetadataTokenKind::Event);
+					members.events.push(Event {
+						token,
+						parent,
+						name: self.name(event.name()),
+						name_index: event.name(),
+						flags: event.flags(),
+						event_type: event
+							.type_()
+							.decode(CodedIndexKind::TypeDefOrRef)
+							.ok_or(raw::Error::InvalidData(Some("Invalid event type")))?,
+						methods: accessors(token),
+						provenance: edits.provenance(token, ptrs.is_some()),
+					});
+				}
+			}
+		}
+
+		if let Some(table) = self.tables.get_table::<NestedClassTable>()? {
+			for row in table.iter() {
+				let row = row?;
+				let Some(members) = row_index(row.enclosing_class()).and_then(|row| members.get_mut(row)) else {
+					continue;
+				};
+				let nested = MetadataToken::new(row.nested_class().0, MetadataTokenKind::TypeDef);
+				members.nested_types.push(nested);
+			}
+		}
+
+		// The owner and position of every generic parameter, so that their constraints and attributes can be attached to them.
+		let mut generic_param_positions = HashMap::new();
+		if let Some(table) = self.tables.get_table::<GenericParamTable>()? {
+			for (index, row) in table.iter().enumerate() {
+				let row = row?;
+				let owner = row
+					.owner()
+					.decode(CodedIndexKind::TypeOrMethodDef)
+					.ok_or(raw::Error::InvalidData(Some("Invalid generic parameter owner")))?;
+
+				let Some(list) = generic_params_of(&mut members, &method_positions, owner) else { continue };
+				let token = MetadataToken::new(index as u32 + 1, MetadataTokenKind::GenericParam);
+				generic_param_positions.insert(index as u32 + 1, (owner, list.len()));
+				list.push(GenericParam {
+					token,
+					owner,
+					number: row.number(),
+					name: self.name(row.name()),
+					name_index: row.name(),
+					flags: row.flags(),
+					constraints: vec![],
+					custom_attributes: vec![],
+				});
+			}
+		}
+
+		if let Some(table) = self.tables.get_table::<GenericParamConstraintTable>()? {
+			for row in table.iter() {
+				let row = row?;
+				let constraint = row
+					.constraint()
+					.decode(CodedIndexKind::TypeDefOrRef)
+					.ok_or(raw::Error::InvalidData(Some("Invalid generic parameter constraint")))?;
+
+				if let Some(param) =
+					generic_param_mut(&mut members, &method_positions, &generic_param_positions, row.owner().0)
+				{
+					param.constraints.push(constraint);
+				}
+			}
+		}
+
+		let mut interface_positions = HashMap::new();
+		if let Some(table) = self.tables.get_table::<InterfaceImplTable>()? {
+			for (index, row) in table.iter().enumerate() {
+				let row = row?;
+				let interface = row
+					.interface()
+					.decode(CodedIndexKind::TypeDefOrRef)
+					.ok_or(raw::Error::InvalidData(Some("Invalid implemented interface")))?;
+
+				let Some(ty) = row_index(row.class()) else { continue };
+				let Some(members) = members.get_mut(ty) else { continue };
+				interface_positions.insert(index as u32 + 1, (ty, members.interfaces.len()));
+				members.interfaces.push(InterfaceImpl {
+					token: MetadataToken::new(index as u32 + 1, MetadataTokenKind::InterfaceImpl),
+					class: MetadataToken::new(row.class().0, MetadataTokenKind::TypeDef),
+					interface,
+					custom_attributes: vec![],
+				});
+			}
+		}
+
+		if let Some(table) = self.tables.get_table::<CustomAttributeTable>()? {
+			for (index, row) in table.iter().enumerate() {
+				let row = row?;
+				let parent = row
+					.parent()
+					.decode(CodedIndexKind::HasCustomAttribute)
+					.ok_or(raw::Error::InvalidData(Some("Invalid custom attribute parent")))?;
+
+				let attributes = match parent.token_kind() {
+					Some(MetadataTokenKind::TypeDef) => parent
+						.index()
+						.checked_sub(1)
+						.and_then(|row| members.get_mut(row))
+						.map(|members| &mut members.custom_attributes),
+					Some(MetadataTokenKind::Method) => method_positions
+						.get(&(parent.index() as u32))
+						.and_then(|(ty, method)| members.get_mut(*ty)?.methods.get_mut(*method))
+						.map(|method| &mut method.custom_attributes),
+					Some(MetadataTokenKind::GenericParam) => generic_param_mut(
+						&mut members,
+						&method_positions,
+						&generic_param_positions,
+						parent.index() as u32,
+					)
+					.map(|param| &mut param.custom_attributes),
+					Some(MetadataTokenKind::InterfaceImpl) => interface_positions
+						.get(&(parent.index() as u32))
+						.and_then(|(ty, interface)| members.get_mut(*ty)?.interfaces.get_mut(*interface))
+						.map(|interface| &mut interface.custom_attributes),
+					_ => None,
+				};
+
+				let Some(attributes) = attributes else { continue };
+				attributes.push(self.read_custom_attribute(index, &row, parent, kept_raw));
+			}
+		}
+
+		Ok(members)
+	}
+
+	/// Reads the tokens the EncLog records as added or changed, along with the generation of the module.
+	fn read_edits(&self) -> Result<Edits, Error> {
+		let mut edits = Edits::default();
+		if let Some(module) = self.tables.get_table::<ModuleTable>()? {
+			edits.generation = module.get(TableIndex(1))?.generation();
+		}
+
+		if let Some(log) = self.tables.get_table::<EncLogTable>()? {
+			for row in log.iter() {
+				edits.tokens.insert(MetadataToken(row?.token()));
+			}
+		}
+
+		Ok(edits)
+	}
+
+	/// Reads the custom attributes whose parent matches `filter`, in table order.
+	/// Attributes that cannot be fully read are kept raw, and their failures added to `kept_raw`.
+	pub(crate) fn read_attributes(
+		&self,
+		filter: impl Fn(MetadataToken) -> bool,
+		kept_raw: &mut Vec<(MetadataToken, Error)>,
+	) -> Result<Vec<CustomAttribute>, Error> {
+		let mut attributes = vec![];
+		let Some(table) = self.tables.get_table::<CustomAttributeTable>()? else { return Ok(attributes) };
+
+		for (index, row) in table.iter().enumerate() {
+			let row = row?;
+			let parent = row
+				.parent()
+				.decode(CodedIndexKind::HasCustomAttribute)
+				.ok_or(raw::Error::InvalidData(Some("Invalid custom attribute parent")))?;
+
+			if filter(parent) {
+				attributes.push(self.read_custom_attribute(index, &row, parent, kept_raw));
+			}
+		}
+
+		Ok(attributes)
+	}
+
+	/// Reads a custom attribute, keeping it with a null constructor, no type name or no value when these cannot
+	/// be read, so that one broken attribute does not cost its parent, or every member of the assembly.
+	/// [`Assembly::decode_attribute`](crate::schema::Assembly::decode_attribute) then returns its value as raw bytes.
+	fn read_custom_attribute(
+		&self,
+		index: usize,
+		row: &raw::CustomAttribute,
+		parent: MetadataToken,
+		kept_raw: &mut Vec<(MetadataToken, Error)>,
+	) -> CustomAttribute {
+		let token = MetadataToken::new(index as u32 + 1, MetadataTokenKind::CustomAttribute);
+		let constructor = row
+			.type_()
+			.decode(CodedIndexKind::CustomAttributeType)
+			.ok_or(raw::Error::InvalidData(Some("Invalid custom attribute type")));
+
+		let attribute_type = constructor
+			.and_then(|constructor| attribute_type(&self.tables, constructor))
+			.and_then(|attribute_type| type_name(&self.tables, &self.strings, attribute_type));
+
+		let value = self.blobs.get_blob(row.value()).map(<[u8]>::to_vec);
+		let mut keep_raw = |error: raw::Error| kept_raw.push((token, error.into()));
+
+		CustomAttribute {
+			token,
+			parent,
+			constructor: constructor.unwrap_or(MetadataToken(0)),
+			attribute_type: attribute_type.unwrap_or_else(|error| {
+				keep_raw(error);
+				String::new()
+			}),
+			value: value.unwrap_or_else(|error| {
+				keep_raw(error);
+				vec![]
+			}),
+			value_index: row.value(),
+		}
+	}
+}
+
+#[derive(Default)]
+struct Edits {
+	generation: u16,
+	tokens: HashSet<MetadataToken>,
+}
+
+impl Edits {
+	fn provenance(&self, token: MetadataToken, indirected: bool) -> Provenance {
+		let generation = self.tokens.contains(&token).then_some(self.generation);
+		Provenance { indirected, generation }
+	}
+}
+
+/// Converts the start indices of a list column into the row ranges they own.
+/// Each list runs until the start of the next one, and the last one until the end of the target table.
+fn list_ranges(starts: &[TableIndex], len: usize) -> impl Iterator<Item = Range<u32>> + '_ {
+	let end = len as u32 + 1;
+	starts.iter().enumerate().map(move |(index, start)| {
+		let next = starts.get(index + 1).map(|next| next.0).unwrap_or(end);
+		start.0.min(end)..next.clamp(start.0.min(end), end)
+	})
+}
+
+/// The generic parameters of a type or method definition, given the position of every method.
+fn generic_params_of<'m>(
+	members: &'m mut [TypeMembers],
+	method_positions: &HashMap<u32, (usize, usize)>,
+	owner: MetadataToken,
+) -> Option<&'m mut Vec<GenericParam>> {
+	match owner.token_kind()? {
+		MetadataTokenKind::TypeDef => Some(&mut members.get_mut(owner.index().checked_sub(1)?)?.generic_params),
+		MetadataTokenKind::Method => {
+			let (ty, method) = method_positions.get(&(owner.index() as u32))?;
+			Some(&mut members.get_mut(*ty)?.methods.get_mut(*method)?.generic_params)
+		}
+		_ => None,
+	}
+}
+
+/// The generic parameter in the given 1-based row, found through the owner and position recorded when reading it.
+fn generic_param_mut<'m>(
+	members: &'m mut [TypeMembers],
+	method_positions: &HashMap<u32, (usize, usize)>,
+	generic_param_positions: &HashMap<u32, (MetadataToken, usize)>,
+	row: u32,
+) -> Option<&'m mut GenericParam> {
+	let (owner, position) = generic_param_positions.get(&row)?;
+	generic_params_of(members, method_positions, *owner)?.get_mut(*position)
+}
+
+/// Converts a 1-based table index into a 0-based row position, if it is not null.
+fn row_index(index: TableIndex) -> Option<usize> {
+	(index.0 as usize).checked_sub(1)
+}
+
+fn deref_ptr<'l, T: MetadataTable<'l>>(
+	ptrs: &Option<T>,
+	index: u32,
+	target: impl FnOnce(T::Row) -> TableIndex,
+) -> Result<TableIndex, Error> {
+	match ptrs {
+		None => Ok(TableIndex(index)),
+		Some(ptrs) => Ok(target(ptrs.get(TableIndex(index))?)),
+	}
+}
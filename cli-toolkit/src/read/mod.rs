@@ -1,16 +1,70 @@
 use lazy_static::lazy_static;
 use crate::raw::TableKind;
+use std::fmt::{Display, Formatter};
 
 mod assembly;
+#[cfg(feature = "async")]
+mod async_context;
+mod builder;
 mod context;
+mod members;
+mod resolver;
 mod types;
 
+#[cfg(feature = "async")]
+pub use async_context::*;
+pub use builder::*;
+pub use resolver::*;
+
 #[derive(Debug)]
 pub enum Error {
 	IOError(std::io::Error),
 	ReadError(crate::raw::Error),
 	MissingMetadataTable(TableKind),
 	MissingMetadataHeap(&'static str),
+	/// A stream that extends past the end of the file, and was clamped to the available bytes.
+	TruncatedStream {
+		name: String,
+		declared_size: u32,
+		available_size: u32,
+	},
+	/// An assembly identity found in both contexts of a [`Context::merge`](crate::schema::Context::merge),
+	/// or the new identity of an assembly replaced by
+	/// [`Context::reload_assembly`](crate::schema::Context::reload_assembly) if another assembly already has it.
+	DuplicateAssembly(String),
+	/// The context moved into a [`Context::merge`](crate::schema::Context::merge) or
+	/// [`Context::reload_assembly`](crate::schema::Context::reload_assembly) is still in use elsewhere.
+	SharedContext,
+	/// No assembly of the context is registered under the identity passed to
+	/// [`Context::reload_assembly`](crate::schema::Context::reload_assembly).
+	UnknownAssembly(String),
+	/// No installed .NET runtime matches the requested version.
+	RuntimeNotFound(String),
+}
+
+impl Display for Error {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Error::IOError(err) => Display::fmt(err, f),
+			Error::ReadError(crate::raw::Error::InvalidData(Some(reason))) => write!(f, "{}", reason),
+			Error::ReadError(err) => write!(f, "{:?}", err),
+			Error::MissingMetadataTable(table) => write!(f, "Missing metadata table {:?}", table),
+			Error::MissingMetadataHeap(heap) => write!(f, "Missing metadata heap {}", heap),
+			Error::TruncatedStream {
+				name,
+				declared_size,
+				available_size,
+			} => write!(
+				f,
+				"Stream {} was truncated from {} to {} bytes",
+				name, declared_size, available_size
+			),
+			Error::DuplicateAssembly(assembly) => write!(f, "Assembly {} is present in both contexts", assembly),
+			Error::SharedContext => write!(f, "The context is still in use elsewhere"),
+			Error::UnknownAssembly(assembly) => write!(f, "No assembly {} in the context", assembly),
+			Error::RuntimeNotFound(version) => write!(f, "No installed .NET runtime matches version {}", version),
+		}
+	}
 }
 
 impl From<std::io::Error> for Error {
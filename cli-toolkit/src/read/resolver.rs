@@ -0,0 +1,204 @@
+use crate::schema::{AssemblyIdentity, PublicKeyToken};
+use std::path::{Path, PathBuf};
+
+const RUNTIME_FRAMEWORK: &str = "Microsoft.NETCore.App";
+const REFERENCE_PACK: &str = "Microsoft.NETCore.App.Ref";
+
+/// Finds the files of referenced assemblies in global assembly caches and in a list of probing directories.
+#[derive(Debug, Clone, Default)]
+pub struct AssemblyResolver {
+	directories: Vec<PathBuf>,
+	gac_directories: Vec<PathBuf>,
+}
+
+impl AssemblyResolver {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn add_directory(&mut self, directory: impl Into<PathBuf>) {
+		self.directories.push(directory.into());
+	}
+
+	pub fn directories(&self) -> &[PathBuf] {
+		&self.directories
+	}
+
+	/// Adds a global assembly cache laid out like `GAC_MSIL`, i.e. `<name>/<version>_<culture>_<token>/<name>.dll`,
+	/// where the directory of the 4.0 caches is additionally prefixed by `v4.0_`.
+	pub fn add_gac_directory(&mut self, directory: impl Into<PathBuf>) {
+		self.gac_directories.push(directory.into());
+	}
+
+	pub fn gac_directories(&self) -> &[PathBuf] {
+		&self.gac_directories
+	}
+
+	/// Looks a reference up in the global assembly caches first, following the rules of [`AssemblyIdentity::matches`],
+	/// and then by name in the probing directories.
+	pub fn resolve_identity(&self, identity: &AssemblyIdentity) -> Option<PathBuf> {
+		self.gac_directories
+			.iter()
+			.find_map(|gac| resolve_in_gac(gac, identity))
+			.or_else(|| self.resolve(&identity.name))
+	}
+
+	/// The first `<name>.dll` or `<name>.exe` file found in the probing directories, in the order they were added.
+	pub fn resolve(&self, name: &str) -> Option<PathBuf> {
+		self.directories.iter().find_map(|directory| {
+			["dll", "exe"]
+				.iter()
+				.map(|extension| directory.join(format!("{}.{}", name, extension)))
+				.find(|path| path.is_file())
+		})
+	}
+}
+
+/// The installation directories of the `dotnet` host, starting with `DOTNET_ROOT` if it is set.
+pub fn dotnet_roots() -> Vec<PathBuf> {
+	let mut roots = vec![];
+	roots.extend(std::env::var_os("DOTNET_ROOT").map(PathBuf::from));
+	roots.extend(home_directory().map(|home| home.join(".dotnet")));
+
+	if cfg!(windows) {
+		roots.extend(std::env::var_os("ProgramFiles").map(|path| PathBuf::from(path).join("dotnet")));
+	} else if cfg!(target_os = "macos") {
+		roots.push(PathBuf::from("/usr/local/share/dotnet"));
+	} else {
+		roots.push(PathBuf::from("/usr/share/dotnet"));
+		roots.push(PathBuf::from("/usr/lib/dotnet"));
+	}
+
+	roots.into_iter().filter(|root| root.is_dir()).collect()
+}
+
+/// The directories holding the framework assemblies of an installed .NET runtime, e.g. `"8.0"` or `"8.0.1"`.
+///
+/// For every [`dotnet_roots`] entry, the latest matching shared runtime (the `dotnet --list-runtimes` layout)
+/// comes first, followed by its reference pack. The reference pack of the NuGet global packages folder comes last.
+pub fn dotnet_runtime_directories(version: &str) -> Vec<PathBuf> {
+	let mut directories = vec![];
+	for root in dotnet_roots() {
+		let shared = root.join("shared").join(RUNTIME_FRAMEWORK);
+		directories.extend(latest_version_directory(&shared, version));
+
+		let pack = root.join("packs").join(REFERENCE_PACK);
+		directories.extend(latest_version_directory(&pack, version).and_then(|pack| reference_directory(&pack)));
+	}
+
+	if let Some(packages) = nuget_packages_directory() {
+		let pack = packages.join(REFERENCE_PACK.to_lowercase());
+		directories.extend(latest_version_directory(&pack, version).and_then(|pack| reference_directory(&pack)));
+	}
+
+	directories
+}
+
+/// The global assembly caches of the .NET Framework found on this machine, the 4.0 ones first.
+pub fn windows_gac_directories() -> Vec<PathBuf> {
+	let Some(windows) = windows_directory() else { return vec![] };
+	let caches = [windows.join("Microsoft.NET").join("assembly"), windows.join("assembly")];
+	caches
+		.iter()
+		.flat_map(|cache| ["GAC_MSIL", "GAC_64", "GAC_32", "GAC"].map(|gac| cache.join(gac)))
+		.filter(|gac| gac.is_dir())
+		.collect()
+}
+
+/// The runtime directories of the .NET Framework found on this machine, the latest and 64-bit ones first.
+pub fn dotnet_framework_directories() -> Vec<PathBuf> {
+	let Some(windows) = windows_directory() else { return vec![] };
+	let frameworks = ["Framework64", "Framework"].map(|framework| windows.join("Microsoft.NET").join(framework));
+	["v4.0.30319", "v2.0.50727"]
+		.iter()
+		.flat_map(|version| frameworks.iter().map(move |framework| framework.join(version)))
+		.filter(|directory| directory.is_dir())
+		.collect()
+}
+
+fn windows_directory() -> Option<PathBuf> {
+	std::env::var_os("WINDIR")
+		.or_else(|| std::env::var_os("SystemRoot"))
+		.map(PathBuf::from)
+}
+
+/// The assembly of the cache that matches the reference, with the highest version if the reference is simple-named.
+fn resolve_in_gac(gac: &Path, identity: &AssemblyIdentity) -> Option<PathBuf> {
+	std::fs::read_dir(gac.join(&identity.name))
+		.ok()?
+		.filter_map(|entry| entry.ok())
+		.filter_map(|entry| {
+			let definition = parse_gac_directory(&identity.name, entry.file_name().to_str()?)?;
+			let path = entry.path().join(format!("{}.dll", identity.name));
+			(identity.matches(&definition) && path.is_file()).then_some((definition.version, path))
+		})
+		.max_by_key(|(version, _)| version.clone())
+		.map(|(_, path)| path)
+}
+
+/// Parses the identity encoded in the name of a cache directory, e.g. `v4.0_4.0.0.0__b77a5c561934e089`.
+fn parse_gac_directory(name: &str, directory: &str) -> Option<AssemblyIdentity> {
+	let directory = match directory.starts_with('v') {
+		true => directory.split_once('_')?.1,
+		false => directory,
+	};
+
+	let mut parts = directory.splitn(3, '_');
+	let version = parts.next()?.parse().ok()?;
+	let culture = parts.next()?.to_string();
+	let token = parts.next()?.parse::<PublicKeyToken>().ok()?;
+	Some(AssemblyIdentity {
+		name: name.to_string(),
+		culture,
+		version,
+		flags: 0,
+		public_key: token.0.to_vec(),
+	})
+}
+
+fn home_directory() -> Option<PathBuf> {
+	std::env::var_os("HOME")
+		.or_else(|| std::env::var_os("USERPROFILE"))
+		.map(PathBuf::from)
+}
+
+fn nuget_packages_directory() -> Option<PathBuf> {
+	match std::env::var_os("NUGET_PACKAGES") {
+		Some(path) => Some(PathBuf::from(path)),
+		None => home_directory().map(|home| home.join(".nuget").join("packages")),
+	}
+}
+
+/// The subdirectory of `parent` with the highest version that is either `version` or a patch of it.
+fn latest_version_directory(parent: &Path, version: &str) -> Option<PathBuf> {
+	let prefix = format!("{}.", version);
+	std::fs::read_dir(parent)
+		.ok()?
+		.filter_map(|entry| entry.ok())
+		.filter(|entry| entry.path().is_dir())
+		.filter_map(|entry| entry.file_name().into_string().ok())
+		.filter(|name| name == version || name.starts_with(&prefix))
+		.max_by_key(|name| version_key(name))
+		.map(|name| parent.join(name))
+}
+
+/// Orders versions numerically, and prereleases like `8.0.0-rc.1` before the release they precede.
+fn version_key(version: &str) -> (Vec<u64>, bool) {
+	let (release, prerelease) = match version.split_once('-') {
+		Some((release, _)) => (release, true),
+		None => (version, false),
+	};
+
+	let numbers = release.split('.').map(|part| part.parse().unwrap_or(0)).collect();
+	(numbers, !prerelease)
+}
+
+/// The `ref/netX.Y` directory of a reference pack, matching the major and minor version of the pack.
+fn reference_directory(pack: &Path) -> Option<PathBuf> {
+	let version = pack.file_name()?.to_str()?;
+	let mut parts = version.split(['.', '-']);
+	let moniker = format!("net{}.{}", parts.next()?, parts.next()?);
+
+	let directory = pack.join("ref").join(moniker);
+	directory.is_dir().then_some(directory)
+}
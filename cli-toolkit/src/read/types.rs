@@ -1,10 +1,11 @@
 use crate::raw::{
-	BlobHeap, CodedIndexKind, FieldTable, MetadataTable, MetadataToken, MetadataTokenKind, StringHeap, TableHeap,
-	TableIndex, type_flags, TypeDef, TypeDefTable,
+	BlobHeap, CodedIndexKind, FieldTable, HeapIndex, MetadataTable, MetadataToken, MetadataTokenKind, StringHeap,
+	TableHeap, TableIndex, type_flags, TypeDef, TypeDefTable,
 };
-use crate::schema::{Assembly, get_type, Type, TypeData};
+use crate::schema::{Assembly, Context, Name, Type, TypeData, TypeResolver};
 use std::rc::{Rc, Weak};
 use std::cell::RefCell;
+use crate::read::members::TypeMembers;
 use crate::read::{Error, types};
 use std::ops::{Deref, DerefMut};
 use std::ptr::null;
@@ -12,11 +13,8 @@ use bitvec::index;
 use crate::raw;
 
 pub struct TypeReader<'l> {
-	blobs: BlobHeap<'l>,
-	tables: TableHeap<'l>,
 	assembly: Rc<Assembly>,
 	strings: StringHeap<'l>,
-	type_defs: TypeDefTable<'l>,
 }
 
 impl Type {
@@ -24,20 +22,8 @@ impl Type {
 		Self::Void
 	}
 
-	pub(crate) fn read<'l>(
-		blobs: BlobHeap<'l>,
-		tables: TableHeap<'l>,
-		strings: StringHeap<'l>,
-		type_defs: TypeDefTable<'l>,
-		assembly: Rc<Assembly>,
-	) -> TypeReader<'l> {
-		TypeReader {
-			blobs,
-			tables,
-			strings,
-			type_defs,
-			assembly,
-		}
+	pub(crate) fn read<'l>(strings: StringHeap<'l>, assembly: Rc<Assembly>) -> TypeReader<'l> {
+		TypeReader { strings, assembly }
 	}
 }
 
@@ -45,20 +31,35 @@ impl TypeData {
 	pub(crate) fn default() -> TypeData {
 		Self {
 			assembly: Weak::new(),
-			name: "".to_string(),
-			namespace: "".to_string(),
+			name: Name::default(),
+			namespace: Name::default(),
+			name_index: HeapIndex(0),
+			namespace_index: HeapIndex(0),
 			flags: 0,
 			base: MetadataToken(0),
 			token: MetadataToken(0),
 			fields: vec![],
+			methods: vec![],
+			properties: vec![],
+			events: vec![],
+			nested_types: vec![],
+			generic_params: vec![],
+			interfaces: vec![],
+			custom_attributes: vec![],
+			provenance: Default::default(),
 		}
 	}
 }
 
 impl<'l> TypeReader<'l> {
-	pub(crate) fn read_type_definition(&self, index: usize, types: &mut Rc<[Type]>) -> Result<(), Error> {
+	pub(crate) fn read_type_definition(
+		&self,
+		index: usize,
+		def: &TypeDef,
+		types: &mut Rc<[Type]>,
+		members: TypeMembers,
+	) -> Result<(), Error> {
 		let metadata_index = (index + 1) as u32;
-		let def = self.type_defs.get(TableIndex(metadata_index))?;
 
 		let base = def
 			.base_type()
@@ -68,11 +69,21 @@ impl<'l> TypeReader<'l> {
 		let types = Rc::get_mut(types).unwrap();
 		types[index] = Type::Uninitialized(TypeData {
 			base,
-			fields: vec![],
+			fields: members.fields,
+			methods: members.methods,
+			properties: members.properties,
+			events: members.events,
+			nested_types: members.nested_types,
+			generic_params: members.generic_params,
+			interfaces: members.interfaces,
+			custom_attributes: members.custom_attributes,
+			provenance: members.provenance,
 			flags: def.flags(),
 			assembly: Rc::downgrade(&self.assembly),
-			name: self.strings.get_string(def.name()).to_string(),
-			namespace: self.strings.get_string(def.namespace()).to_string(),
+			name: Name::read(self.strings, self.assembly.strings.as_ref(), def.name()),
+			namespace: Name::read(self.strings, self.assembly.strings.as_ref(), def.namespace()),
+			name_index: def.name(),
+			namespace_index: def.namespace(),
 			token: MetadataToken::new(metadata_index, MetadataTokenKind::TypeDef),
 		});
 
@@ -80,6 +91,21 @@ impl<'l> TypeReader<'l> {
 	}
 
 	pub(crate) fn read_base(&self, index: usize, types: &mut Rc<[Type]>) -> Result<(), Error> {
+		self.read_base_within(index, types, &mut vec![])
+	}
+
+	/// Like [`TypeReader::read_base`], reading the base types defined in the same assembly first.
+	/// `derived` holds the types waiting for this one, which must not be reached again.
+	fn read_base_within(&self, index: usize, types: &mut Rc<[Type]>, derived: &mut Vec<usize>) -> Result<(), Error> {
+		let ctx = self.assembly.ctx.borrow().upgrade().unwrap();
+		if derived.contains(&index) {
+			return Err(raw::Error::InvalidData(Some("Cyclic base type")).into());
+		}
+
+		if derived.len() >= ctx.max_resolution_depth() {
+			return Err(raw::Error::InvalidData(Some("Base type chain too deep")).into());
+		}
+
 		let data = {
 			let types = Rc::get_mut(types).unwrap();
 			let mut ty = &mut types[index];
@@ -88,21 +114,30 @@ impl<'l> TypeReader<'l> {
 			std::mem::replace(data, TypeData::default())
 		};
 
-		let ctx = self.assembly.ctx.upgrade().unwrap();
-		let dependencies = &self.assembly.dependencies;
-		let type_refs = &self.assembly.type_refs;
-
 		macro_rules! set_ty {
-			($idx: expr, $types: expr, $val: expr) => {
-				set_ty!($idx, $types, $val, 0)
-			};
-
-			($idx: expr, $types: expr, $val: expr, $base: expr) => {{
-				drop($base);
+			($idx: expr, $types: expr, $val: expr) => {{
 				let types = Rc::get_mut($types).unwrap();
 				types[$idx] = $val;
 				Ok(())
 			}};
+
+			($idx: expr, $types: expr, $val: expr, $base: expr) => {{
+				drop($base);
+				set_ty!($idx, $types, $val)
+			}};
+		}
+
+		// Failed types are put back, so that they are read again on their own rather than lost with a derived type.
+		macro_rules! fail {
+			($error: expr) => {{
+				Rc::get_mut(types).unwrap()[index] = Type::Uninitialized(data);
+				return Err($error);
+			}};
+
+			($error: expr, $base: expr) => {{
+				drop($base);
+				fail!($error)
+			}};
 		}
 
 		if data.base.is_null() {
@@ -110,59 +145,65 @@ impl<'l> TypeReader<'l> {
 				return set_ty!(index, types, Type::Interface(data));
 			}
 
-			match (data.namespace.as_str(), data.name.as_str(), data.flags) {
-				("System", "Object", 0x102001) => {
-					return set_ty! {
-						index,
-						types,
-						Type::Class(data)
-					}
-				}
-				("", "<Module>", 0x0) => {
-					return set_ty! {
-						index,
-						types,
-						Type::CustomUnknown(data)
-					}
-				}
-				_ => unimplemented!("{:?}", data),
+			if is_system_type(&ctx, &data, "Object") {
+				return set_ty!(index, types, Type::Class(data));
 			}
+
+			if data.namespace.is_empty() && data.name == "<Module>" {
+				return set_ty!(index, types, Type::CustomUnknown(data));
+			}
+
+			fail!(raw::Error::InvalidData(Some("Type without a base type")).into());
 		}
 
 		loop {
-			match get_type(data.base, &ctx, types, &dependencies, type_refs) {
+			let type_ref_cache = self.assembly.type_ref_cache.borrow();
+			let resolver = TypeResolver {
+				ctx: &ctx,
+				types,
+				dependencies: &self.assembly.dependencies,
+				module_refs: &self.assembly.module_refs,
+				type_refs: &self.assembly.type_refs,
+				type_ref_cache: &type_ref_cache,
+			};
+
+			match resolver.get_type(data.base) {
 				Some(base_ref) => {
 					let base = base_ref.deref();
 					match base {
-						Type::Class(base) => match (base.namespace.as_str(), base.name.as_str(), base.flags) {
-							("System", "ValueType", 0x102081) => {
-								return set_ty! {
-									index,
-									types,
-									Type::Struct(data),
-									base_ref
-								}
+						// System.ValueType derives from System.Object, which tells it apart from a root type of that name.
+						Type::Class(base) if is_system_type(&ctx, base, "ValueType") && !base.base.is_null() => {
+							return set_ty! {
+								index,
+								types,
+								Type::Struct(data),
+								base_ref
 							}
+						}
 
-							_ => {
-								return set_ty! {
-									index,
-									types,
-									Type::Class(data),
-									base_ref
-								}
+						Type::Class(_) => {
+							return set_ty! {
+								index,
+								types,
+								Type::Class(data),
+								base_ref
 							}
-						},
+						}
 
 						Type::Uninitialized(base) => match base.token.token_kind() {
-							MetadataTokenKind::TypeDef => {
-								let index = base.token.index() - 1;
+							Some(MetadataTokenKind::TypeDef) => {
+								let base_index = base.token.index() - 1;
 
 								drop(base_ref);
-								self.read_base(index, types);
+								derived.push(index);
+								let result = self.read_base_within(base_index, types, derived);
+								derived.pop();
+								if let Err(error) = result {
+									fail!(error);
+								}
 							}
 
-							_ => unimplemented!("{:?}", base),
+							_ => fail!(raw::Error::InvalidData(Some("Cyclic base type")).into(), base_ref),
 						},
 
 						Type::CustomUnknown(_) => {
@@ -174,20 +215,25 @@ impl<'l> TypeReader<'l> {
 							}
 						}
 
-						Type::Struct(base) => match (base.namespace.as_str(), base.name.as_str(), base.flags) {
-							("System", "Enum", 0x102081) => {
-								return set_ty! {
-									index,
-									types,
-									Type::Enum(data),
-									base_ref
-								}
+						Type::Struct(base) if is_system_type(&ctx, base, "Enum") => {
+							return set_ty! {
+								index,
+								types,
+								Type::Enum(data),
+								base_ref
 							}
+						}
 
-							_ => unimplemented!("{:?}", base),
-						},
+						Type::Error(..) => {
+							return set_ty! {
+								index,
+								types,
+								Type::CustomUnknown(data),
+								base_ref
+							}
+						}
 
-						_ => unimplemented!("{:?}", base),
+						_ => fail!(raw::Error::InvalidData(Some("Invalid base type")).into(), base_ref),
 					}
 				}
 
@@ -202,3 +248,11 @@ impl<'l> TypeReader<'l> {
 		}
 	}
 }
+
+/// Whether `data` is the well-known type `System.<name>` of the context.
+/// Flags and versions vary between the core libraries of different runtimes, so only the name and assembly are checked.
+fn is_system_type(ctx: &Context, data: &TypeData, name: &str) -> bool {
+	data.namespace == "System"
+		&& data.name == name
+		&& data.assembly.upgrade().is_some_and(|assembly| ctx.is_corlib(&assembly))
+}
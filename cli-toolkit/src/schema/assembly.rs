@@ -1,22 +1,64 @@
-use crate::raw::{AssemblyFlags, MetadataToken, MetadataTokenKind};
+use crate::raw::{
+	assembly_flags, type_flags, AssemblyFlags, AttributeArgument, AttributeArguments, AttributeEnum, FieldSignature,
+	MetadataToken, MetadataTokenKind, MethodSignature, TypeSignature,
+};
+use crate::utilities::sha1;
+use std::str::FromStr;
+use crate::raw;
 use std::fmt::{Debug, Display, Formatter};
-use crate::schema::context::Context;
+use crate::schema::context::{Context, LoadDiagnostic, Recovery, RecoveryRule};
 use crate::schema::types::TypeData;
 use crate::utilities::IndexedRcRef;
-use std::cell::{Ref, RefCell};
+use std::cell::{Cell, OnceCell, Ref, RefCell};
+use std::collections::{HashMap, HashSet};
 use std::marker::PhantomData;
 use std::ops::Deref;
-use crate::schema::{Type, TypeRef};
+use std::path::{Path, PathBuf};
+use crate::schema::format::{NameStyle, SignatureFormatter, MAX_NESTING};
+use crate::schema::{
+	CustomAttribute, DecodeCache, DecodeCacheStatistics, DecodedAttribute, DumpWriter, Field, GenericParam,
+	InterfaceImpl, Method, MethodRef, MethodSpec, Module, Type, TypeRef, TypeSpec,
+};
 use std::rc::{Rc, Weak};
+use std::io;
 
 pub struct Assembly {
-	pub(crate) ctx: Weak<Context>,
+	/// Replaced when the assembly is moved into another context by [`Context::merge`].
+	pub(crate) ctx: RefCell<Weak<Context>>,
+	/// The file the assembly was loaded from, if it was loaded by a [`ContextBuilder`](crate::read::ContextBuilder).
+	pub(crate) path: Option<PathBuf>,
 
 	pub(crate) name: AssemblyName,
+	pub(crate) module: Module,
 
-	pub(crate) types: Rc<[Type]>,
+	/// Set once the type definitions have been read, after every assembly of the context has been created.
+	pub(crate) types: OnceCell<Rc<[Type]>>,
 	pub(crate) dependencies: Vec<AssemblyRef>,
+	pub(crate) module_refs: Vec<String>,
 	pub(crate) type_refs: Vec<(MetadataToken, String, String)>,
+	/// Resolved TypeRefs, indexed by TypeRef row. Only successful resolutions are cached,
+	/// since a lookup can fail while the target assembly is still being loaded.
+	/// Dropped by [`Context::reload_assembly`](crate::schema::Context::reload_assembly) when the assembly a TypeRef
+	/// pointed into is replaced.
+	pub(crate) type_ref_cache: RefCell<Vec<OnceCell<TypeRef>>>,
+	/// The TypeSpecs whose signature could be decoded, in token order.
+	pub(crate) type_specs: Vec<TypeSpec>,
+	/// The parent, name and signature of every MemberRef row, indexed by row.
+	pub(crate) member_refs: Vec<(MetadataToken, String, Vec<u8>)>,
+	/// The MethodSpecs whose instantiation could be decoded, in token order.
+	pub(crate) method_specs: Vec<MethodSpec>,
+	/// The custom attributes of the parents other than the module, type and method definitions, sorted by parent.
+	pub(crate) custom_attributes: Vec<CustomAttribute>,
+	pub(crate) decode_cache: DecodeCache,
+	/// See [`Assembly::name_style`].
+	pub(crate) name_style: Cell<NameStyle>,
+	/// The enclosing type of every nested type definition, computed on first use.
+	pub(crate) enclosing_types: OnceCell<HashMap<MetadataToken, MetadataToken>>,
+	/// A copy of the `#Strings` heap, if the context was loaded with [`ContextBuilder::retain_strings`](crate::read::ContextBuilder::retain_strings).
+	pub(crate) strings: Option<Rc<[u8]>>,
+	/// The checksums of the metadata streams, computed when the assembly was read.
+	#[cfg(feature = "checksums")]
+	pub(crate) stream_checksums: raw::StreamChecksums,
 }
 
 impl Debug for Assembly {
@@ -24,14 +66,40 @@ impl Debug for Assembly {
 		f.debug_struct("Assembly")
 			.field("name", &self.name)
 			.field("dependencies", &Deps(&self.dependencies))
-			.field("types", &self.types)
+			.field("types", &self.sorted_types())
 			.finish()
 	}
 }
 
+impl Display for Assembly {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		Display::fmt(&self.name, f)
+	}
+}
+
 impl Assembly {
+	/// Finds a top-level type of the assembly, or of the assemblies it references, depth first.
+	/// Each assembly is searched once, and no deeper than [`Context::max_resolution_depth`].
 	pub fn find_type(&self, name: &str, namespace: &str) -> Option<TypeRef> {
-		if let Some(ty) = self.types.iter().find(|ty| ty.matches_name(name, namespace)) {
+		self.find_type_within(name, namespace, 0, &mut HashSet::new())
+	}
+
+	/// Like [`Assembly::find_type`], skipping the assemblies searched so far,
+	/// for an assembly reached through `depth` references.
+	fn find_type_within(
+		&self,
+		name: &str,
+		namespace: &str,
+		depth: usize,
+		searched: &mut HashSet<*const Assembly>,
+	) -> Option<TypeRef> {
+		let ctx = self.ctx.borrow().upgrade()?;
+		if depth >= ctx.max_resolution_depth() || !searched.insert(self) {
+			return None;
+		}
+
+		let types = self.types.get();
+		if let Some(ty) = types.and_then(|types| types.iter().find(|ty| ty.matches_name(name, namespace))) {
 			match ty {
 				Type::Enum(data)
 				| Type::Class(data)
@@ -39,19 +107,18 @@ impl Assembly {
 				| Type::Interface(data)
 				| Type::CustomUnknown(data) => {
 					let index = data.token.index() - 1;
-					return Some(TypeRef::new(self.types.clone(), index));
+					return Some(TypeRef::new(types?.clone(), index));
 				}
 				_ => unimplemented!(),
 			}
 		}
 
 		for assembly in self.dependencies.iter() {
-			let ctx = self.ctx.upgrade().unwrap();
-			let Some(assembly) = ctx.assembly_map.get(&assembly.ident_key) else { continue };
+			let Some(assembly) = ctx.reference_map.get(&assembly.ident_key) else { continue };
 			let Some(assembly) = ctx.assembly_vec.get(*assembly) else { continue };
 
 			let assembly = assembly.clone();
-			if let Some(ty) = assembly.find_type(name, namespace) {
+			if let Some(ty) = assembly.find_type_within(name, namespace, depth + 1, searched) {
 				return Some(ty);
 			}
 		}
@@ -59,13 +126,458 @@ impl Assembly {
 		None
 	}
 
+	/// The type definitions of the assembly, indexed by TypeDef row.
+	pub fn types(&self) -> &[Type] {
+		self.types.get().map_or(&[], |types| types)
+	}
+
 	pub fn get_type(&self, token: MetadataToken) -> Option<TypeRef> {
-		let ctx = self.ctx.upgrade().unwrap();
-		get_type(token, &ctx, &self.types, &self.dependencies, &self.type_refs)
+		let ctx = self.ctx.borrow().upgrade().unwrap();
+		let type_ref_cache = self.type_ref_cache.borrow();
+		let resolver = TypeResolver {
+			ctx: &ctx,
+			types: self.types.get()?,
+			dependencies: &self.dependencies,
+			module_refs: &self.module_refs,
+			type_refs: &self.type_refs,
+			type_ref_cache: &type_ref_cache,
+		};
+
+		resolver.get_type(token)
+	}
+
+	pub fn type_specs(&self) -> &[TypeSpec] {
+		&self.type_specs
+	}
+
+	pub fn type_spec(&self, token: MetadataToken) -> Option<&TypeSpec> {
+		let index = self
+			.type_specs
+			.binary_search_by_key(&token.0, |spec| spec.token.0)
+			.ok()?;
+		self.type_specs.get(index)
+	}
+
+	pub fn method_specs(&self) -> &[MethodSpec] {
+		&self.method_specs
+	}
+
+	pub fn method_spec(&self, token: MetadataToken) -> Option<&MethodSpec> {
+		let index = self
+			.method_specs
+			.binary_search_by_key(&token.0, |spec| spec.token.0)
+			.ok()?;
+		self.method_specs.get(index)
+	}
+
+	/// Resolves a MethodDef or MemberRef token to the method definition it designates,
+	/// which may belong to another assembly of the context.
+	pub fn resolve_method(&self, token: MetadataToken) -> Option<MethodRef> {
+		match token.token_kind()? {
+			MetadataTokenKind::Method => {
+				let types = self.types.get()?;
+				let index = types.iter().position(|ty| {
+					let Some(data) = ty.data() else { return false };
+					data.methods.iter().any(|method| method.token == token)
+				})?;
+
+				let declaring_type = TypeRef::new(types.clone(), index);
+				Some(MethodRef { declaring_type, token })
+			}
+
+			MetadataTokenKind::MemberRef => {
+				let (parent, name, signature) = self.member_refs.get(token.index().checked_sub(1)?)?;
+				let parent = match parent.token_kind()? {
+					MetadataTokenKind::TypeSpec => match &self.type_spec(*parent)?.signature {
+						TypeSignature::GenericInst(ty, _) => match ty.as_ref() {
+							TypeSignature::Class(token) | TypeSignature::ValueType(token) => *token,
+							_ => return None,
+						},
+						_ => return None,
+					},
+					_ => *parent,
+				};
+
+				let declaring_type = self.get_type(parent)?;
+				let data = declaring_type.data()?;
+				let target = data.assembly.upgrade()?;
+				let signature = MethodSignature::parse(signature).ok()?;
+				let method = data.methods.iter().find(|method| {
+					let Ok(definition) = target.method_signature(method) else { return false };
+					method.name == *name && signatures_match((&signature, self), (&definition, &target))
+				})?;
+
+				let token = method.token;
+				Some(MethodRef { declaring_type, token })
+			}
+
+			_ => None,
+		}
 	}
+
+	/// The custom attributes applied to the assembly, e.g. `System.Reflection.AssemblyVersionAttribute`.
+	pub fn assembly_attributes(&self) -> &[CustomAttribute] {
+		self.attributes_of(MetadataToken::new(1, MetadataTokenKind::Assembly))
+	}
+
+	pub fn module_attributes(&self) -> &[CustomAttribute] {
+		&self.module.custom_attributes
+	}
+
+	/// The custom attributes applied to a row of this assembly, e.g. a field, a parameter or a generic parameter.
+	/// Those of type and method definitions are the ones of [`TypeData::custom_attributes`](crate::schema::TypeData::custom_attributes)
+	/// and [`Method::custom_attributes`], and those of generic parameters and interface implementations
+	/// the ones of [`GenericParam::attributes`] and [`InterfaceImpl::attributes`].
+	pub fn attributes_of(&self, parent: MetadataToken) -> &[CustomAttribute] {
+		match parent.token_kind() {
+			Some(MetadataTokenKind::Module) => &self.module.custom_attributes,
+			Some(MetadataTokenKind::TypeDef) => {
+				let ty = self
+					.types
+					.get()
+					.and_then(|types| types.get(parent.index().checked_sub(1)?));
+				ty.and_then(|ty| ty.data()).map_or(&[], |data| &data.custom_attributes)
+			}
+			Some(MetadataTokenKind::Method) => {
+				let types = self.types.get().into_iter().flat_map(|types| types.iter());
+				let method = types.filter_map(|ty| ty.data()?.method(parent)).next();
+				method.map_or(&[], |method| &method.custom_attributes)
+			}
+			Some(MetadataTokenKind::GenericParam) => {
+				let types = self.types.get().into_iter().flat_map(|types| types.iter());
+				let mut params = types.filter_map(|ty| ty.data()).flat_map(|data| {
+					let methods = data.methods.iter().flat_map(|method| &method.generic_params);
+					data.generic_params.iter().chain(methods)
+				});
+				let param = params.find(|param| param.token == parent);
+				param.map_or(&[], |param| &param.custom_attributes)
+			}
+			Some(MetadataTokenKind::InterfaceImpl) => {
+				let types = self.types.get().into_iter().flat_map(|types| types.iter());
+				let mut interfaces = types.filter_map(|ty| ty.data()).flat_map(|data| &data.interfaces);
+				let interface = interfaces.find(|interface| interface.token == parent);
+				interface.map_or(&[], |interface| &interface.custom_attributes)
+			}
+			_ => {
+				let start = self
+					.custom_attributes
+					.partition_point(|attribute| attribute.parent.0 < parent.0);
+				let end = self
+					.custom_attributes
+					.partition_point(|attribute| attribute.parent.0 <= parent.0);
+				&self.custom_attributes[start..end]
+			}
+		}
+	}
+
+	/// Memoizes the signatures and attributes decoded by [`Assembly::method_signature`], [`Assembly::field_signature`]
+	/// and [`Assembly::decode_attribute`], keeping at most `capacity` entries of each kind and evicting the oldest first.
+	/// The caches are disabled by default, and a capacity of 0 disables them again, dropping their entries.
+	pub fn set_decode_cache_capacity(&self, capacity: usize) {
+		self.decode_cache.set_capacity(capacity);
+	}
+
+	pub fn decode_cache_statistics(&self) -> DecodeCacheStatistics {
+		self.decode_cache.statistics()
+	}
+
+	/// Decodes the signature of a method of this assembly.
+	pub fn method_signature(&self, method: &Method) -> Result<Rc<MethodSignature>, raw::Error> {
+		let cache = &self.decode_cache.method_signatures;
+		cache.get_or_insert_with(method.signature_index, || {
+			MethodSignature::parse(&method.signature).map(Rc::new)
+		})
+	}
+
+	/// Decodes the signature of a field of this assembly.
+	pub fn field_signature(&self, field: &Field) -> Result<Rc<FieldSignature>, raw::Error> {
+		let cache = &self.decode_cache.field_signatures;
+		cache.get_or_insert_with(field.signature_index, || FieldSignature::parse(&field.signature).map(Rc::new))
+	}
+
+	/// Decodes the arguments of a custom attribute of this assembly.
+	/// Arguments that cannot be decoded are kept as raw bytes, and a diagnostic is recorded for each of them.
+	pub fn decode_attribute(&self, attribute: &CustomAttribute) -> DecodedAttribute {
+		let key = (attribute.value_index, attribute.constructor);
+		let cache = &self.decode_cache.attributes;
+		let mut decoded = cache.get_or_insert_with(key, || self.decode_attribute_uncached(attribute));
+		// Attributes sharing a blob share an entry, but their diagnostics must point at this one.
+		for diagnostic in decoded.diagnostics.iter_mut() {
+			diagnostic.token = Some(attribute.token);
+		}
+
+		decoded
+	}
+
+	fn decode_attribute_uncached(&self, attribute: &CustomAttribute) -> DecodedAttribute {
+		let diagnostic = |reason: &str| LoadDiagnostic {
+			assembly: self.name.name.clone(),
+			token: Some(attribute.token),
+			reason: reason.to_string(),
+			recovery: Recovery {
+				observed: Some(attribute.value.iter().map(|byte| format!("{:02x}", byte)).collect()),
+				..Recovery::new(RecoveryRule::AttributeKeptRaw)
+			},
+		};
+
+		let Some(params) = self.constructor_params(attribute.constructor) else {
+			let arguments = AttributeArguments {
+				fixed: vec![AttributeArgument::Raw(attribute.value.clone())],
+				..Default::default()
+			};
+
+			let diagnostics = vec![diagnostic("Unresolved attribute constructor")];
+			return DecodedAttribute { arguments, diagnostics };
+		};
+
+		let arguments = AttributeArguments::decode(&attribute.value, &params, |ty| {
+			let ty = match ty {
+				AttributeEnum::Token(token) => self.get_type(token)?,
+				AttributeEnum::Name(name) => {
+					let name = name.split(',').next()?.trim();
+					let (namespace, name) = name.rsplit_once('.').unwrap_or(("", name));
+					self.find_type(name, namespace)?
+				}
+			};
+
+			let Type::Enum(data) = &*ty else { return None };
+			let field = data.fields.iter().find(|field| !field.is_static())?;
+			let signature = data.assembly.upgrade()?.field_signature(field).ok()?;
+			Some(signature.ty.clone())
+		});
+
+		let diagnostics = arguments.errors.iter().map(|error| diagnostic(error)).collect();
+		DecodedAttribute { arguments, diagnostics }
+	}
+
+	/// The parameter types of a MethodDef or MemberRef attribute constructor, in the token space of this assembly.
+	fn constructor_params(&self, token: MetadataToken) -> Option<Vec<TypeSignature>> {
+		let signature = match token.token_kind()? {
+			MetadataTokenKind::Method => {
+				let types = self.types.get()?;
+				let method = types.iter().find_map(|ty| ty.data()?.method(token))?;
+				self.method_signature(method).map(|signature| signature.params.clone())
+			}
+			MetadataTokenKind::MemberRef => {
+				let (_, _, signature) = self.member_refs.get(token.index().checked_sub(1)?)?;
+				MethodSignature::parse(signature).map(|signature| signature.params)
+			}
+			_ => return None,
+		};
+
+		signature.ok()
+	}
+
+	pub fn module(&self) -> &Module {
+		&self.module
+	}
+
+	pub fn path(&self) -> Option<&Path> {
+		self.path.as_deref()
+	}
+
+	/// The checksums of the metadata streams the assembly was read from,
+	/// to be compared with the ones of a newer build of it, see [`raw::StreamChecksums::changed_streams`].
+	#[cfg(feature = "checksums")]
+	pub fn stream_checksums(&self) -> &raw::StreamChecksums {
+		&self.stream_checksums
+	}
+
+	pub fn public_key_token(&self) -> Option<PublicKeyToken> {
+		self.name.public_key_token()
+	}
+
+	/// The display name of the assembly, e.g. `System.Runtime, Version=7.0.0.0, Culture=neutral, PublicKeyToken=...`.
+	pub fn full_name(&self) -> String {
+		self.name.full_name()
+	}
+
+	/// A compact, token-sorted listing of the assembly and its types, one line each.
+	pub fn summary(&self) -> AssemblySummary<'_> {
+		AssemblySummary {
+			assembly: self,
+			annotated: false,
+		}
+	}
+
+	/// Writes [`Assembly::summary`] to `writer` in chunks, see [`DumpWriter`].
+	pub fn write_summary<W: io::Write>(&self, writer: W) -> io::Result<W> {
+		let mut writer = DumpWriter::new(writer);
+		writer.write_display(&self.summary())?;
+		writer.finish()
+	}
+
+	pub(crate) fn sorted_types(&self) -> Vec<&Type> {
+		let mut types = self
+			.types
+			.get()
+			.into_iter()
+			.flat_map(|types| types.iter())
+			.collect::<Vec<_>>();
+		types.sort_by_key(|ty| match ty {
+			Type::NotLoaded(token) | Type::Error(token, _) => token.0,
+			_ => ty.data().map(|data| data.token.0).unwrap_or(0),
+		});
+		types
+	}
+
+	/// The full name of a TypeDef or TypeRef token, without resolving references to other assemblies.
+	pub fn type_name(&self, token: MetadataToken) -> Option<String> {
+		let (namespace, name) = match token.token_kind() {
+			Some(MetadataTokenKind::TypeDef) => {
+				let data = self.types.get()?.get(token.index().checked_sub(1)?)?.data()?;
+				(data.namespace.as_str(), data.name.as_str())
+			}
+			Some(MetadataTokenKind::TypeRef) => {
+				let (_, namespace, name) = self.type_refs.get(token.index().checked_sub(1)?)?;
+				(namespace.as_str(), name.as_str())
+			}
+			_ => return None,
+		};
+
+		match namespace.is_empty() {
+			true => Some(name.to_string()),
+			false => Some(format!("{}.{}", namespace, name)),
+		}
+	}
+
+	/// The name of a TypeDef or TypeRef token in `style`, including the types enclosing it.
+	pub fn styled_type_name(&self, token: MetadataToken, style: &NameStyle) -> Option<String> {
+		let mut names = vec![];
+		let mut token = token;
+		for _ in 0..MAX_NESTING {
+			let (namespace, name, enclosing) = match token.token_kind() {
+				Some(MetadataTokenKind::TypeDef) => {
+					let data = self.types.get()?.get(token.index().checked_sub(1)?)?.data()?;
+					(data.namespace.as_str(), data.name.as_str(), self.enclosing_type(token))
+				}
+				Some(MetadataTokenKind::TypeRef) => {
+					let (scope, namespace, name) = self.type_refs.get(token.index().checked_sub(1)?)?;
+					let enclosing = scope.token_kind() == Some(MetadataTokenKind::TypeRef);
+					(namespace.as_str(), name.as_str(), Some(*scope).filter(|_| enclosing))
+				}
+				_ => return None,
+			};
+
+			names.push(name);
+			match enclosing {
+				Some(enclosing) => token = enclosing,
+				None => return Some(style.join(namespace, names.into_iter().rev())),
+			}
+		}
+
+		None
+	}
+
+	/// The TypeDef enclosing the nested type definition `token`, if it is nested.
+	pub fn enclosing_type(&self, token: MetadataToken) -> Option<MetadataToken> {
+		let enclosing_types = self.enclosing_types.get_or_init(|| {
+			let types = self.types().iter().filter_map(|ty| ty.data());
+			let nested = types.flat_map(|data| data.nested_types.iter().map(|nested| (*nested, data.token)));
+			nested.collect()
+		});
+		enclosing_types.get(&token).copied()
+	}
+
+	/// How the names of the types of this assembly and of the types its signatures refer to are rendered.
+	pub fn name_style(&self) -> NameStyle {
+		self.name_style.get()
+	}
+
+	pub fn set_name_style(&self, style: NameStyle) {
+		self.name_style.set(style);
+	}
+}
+
+/// Compares two method signatures from possibly different assemblies, by the names of the types they use.
+fn signatures_match(a: (&MethodSignature, &Assembly), b: (&MethodSignature, &Assembly)) -> bool {
+	let ((a, a_assembly), (b, b_assembly)) = (a, b);
+	if a.calling_convention != b.calling_convention
+		|| a.generic_param_count != b.generic_param_count
+		|| a.params.len() != b.params.len()
+	{
+		return false;
+	}
+
+	// Both sides are rendered in the same style, whatever the styles of their assemblies.
+	let a_formatter = SignatureFormatter::new(Some(a_assembly)).with_style(NameStyle::default());
+	let b_formatter = SignatureFormatter::new(Some(b_assembly)).with_style(NameStyle::default());
+	let a_types = std::iter::once(&a.return_type).chain(a.params.iter());
+	let b_types = std::iter::once(&b.return_type).chain(b.params.iter());
+	a_types
+		.zip(b_types)
+		.all(|(a, b)| a_formatter.type_to_string(a) == b_formatter.type_to_string(b))
 }
 
-#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+pub struct AssemblySummary<'l> {
+	assembly: &'l Assembly,
+	annotated: bool,
+}
+
+impl AssemblySummary<'_> {
+	/// Renders tokens as the `/* 02000002 */` comments of ILSpy and `ildasm /TOKENS`,
+	/// and lists the methods of every type along with the RVA of their body.
+	/// The file offsets of the bodies need the image, see `raw::Assembly::method_listing`.
+	pub fn annotated(mut self, annotated: bool) -> Self {
+		self.annotated = annotated;
+		self
+	}
+}
+
+impl Display for AssemblySummary<'_> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		let assembly = self.assembly;
+		writeln!(f, "{}", assembly.name)?;
+
+		let mut dependencies = assembly.dependencies.iter().collect::<Vec<_>>();
+		dependencies.sort_by(|a, b| a.ident_key.cmp(&b.ident_key));
+		for dependency in dependencies {
+			writeln!(f, "\tref {} {}", dependency.name, dependency.version)?;
+		}
+
+		for ty in assembly.sorted_types() {
+			let Some(data) = ty.data() else {
+				writeln!(f, "\t{:?}", ty)?;
+				continue;
+			};
+
+			match self.annotated {
+				true => write!(f, "\t/* {:08X} */ {} ", data.token, ty.kind())?,
+				false => write!(f, "\t{:?} {} ", data.token, ty.kind())?,
+			}
+
+			match data.namespace.is_empty() {
+				true => write!(f, "{}", data.name)?,
+				false => write!(f, "{}.{}", data.namespace, data.name)?,
+			}
+
+			writeln!(
+				f,
+				" ({} fields, {} methods, {} properties, {} events)",
+				data.fields.len(),
+				data.methods.len(),
+				data.properties.len(),
+				data.events.len(),
+			)?;
+
+			if !self.annotated {
+				continue;
+			}
+
+			for method in data.methods.iter() {
+				write!(f, "\t\t/* {:08X} */ {}", method.token, method.name)?;
+				match method.rva {
+					0 => writeln!(f)?,
+					rva => writeln!(f, " // RVA 0x{:x}", rva)?,
+				}
+			}
+		}
+
+		Ok(())
+	}
+}
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
 pub struct AssemblyVersion {
 	pub major: u16,
 	pub minor: u16,
@@ -79,6 +591,359 @@ impl Display for AssemblyVersion {
 	}
 }
 
+impl FromStr for AssemblyVersion {
+	type Err = raw::Error;
+
+	/// Parses a version with up to four components, where missing components default to 0.
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let error = raw::Error::InvalidData(Some("Invalid assembly version"));
+		let mut parts = [0; 4];
+		for (i, part) in s.split('.').enumerate() {
+			*parts.get_mut(i).ok_or(error)? = part.trim().parse().map_err(|_| error)?;
+		}
+
+		let [major, minor, build, revision] = parts;
+		Ok(Self {
+			major,
+			minor,
+			build,
+			revision,
+		})
+	}
+}
+
+impl AssemblyVersion {
+	pub fn new(major: u16, minor: u16, build: u16, revision: u16) -> Self {
+		Self {
+			major,
+			minor,
+			build,
+			revision,
+		}
+	}
+
+	/// Whether an assembly with this version can satisfy a reference to `requested` under `policy`.
+	pub fn is_compatible_with(&self, requested: &AssemblyVersion, policy: VersionPolicy) -> bool {
+		match policy {
+			VersionPolicy::Exact => self == requested,
+			VersionPolicy::SameMajorMinor => {
+				self.major == requested.major && self.minor == requested.minor && self >= requested
+			}
+			VersionPolicy::SameMajor => self.major == requested.major && self >= requested,
+			VersionPolicy::AtLeast => self >= requested,
+			VersionPolicy::Any => true,
+		}
+	}
+}
+
+/// How far the version of an assembly may drift from the referenced one.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum VersionPolicy {
+	/// All four components must be equal.
+	Exact,
+	/// Major and minor must be equal, and build and revision may be higher.
+	SameMajorMinor,
+	/// Major must be equal, and the rest may be higher.
+	SameMajor,
+	/// Any version that is not lower, as with .NET Core roll-forward.
+	AtLeast,
+	/// Any version at all, as with simple-named references.
+	Any,
+}
+
+/// A version where components may be `*`, as in `1.2.*`.
+///
+/// Components after a `*` are wildcards as well, while other missing components default to 0
+/// like they do for [`AssemblyVersion`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct VersionPattern {
+	components: [Option<u16>; 4],
+}
+
+impl VersionPattern {
+	/// A pattern matching exactly `version`.
+	pub fn exact(version: &AssemblyVersion) -> Self {
+		let AssemblyVersion {
+			major,
+			minor,
+			build,
+			revision,
+		} = *version;
+		Self {
+			components: [Some(major), Some(minor), Some(build), Some(revision)],
+		}
+	}
+
+	/// The components of the pattern, with `None` for wildcards.
+	pub fn components(&self) -> [Option<u16>; 4] {
+		self.components
+	}
+
+	pub fn matches(&self, version: &AssemblyVersion) -> bool {
+		let values = [version.major, version.minor, version.build, version.revision];
+		self.components
+			.iter()
+			.zip(values)
+			.all(|(component, value)| component.is_none_or(|component| component == value))
+	}
+
+	/// The lowest version matched by the pattern.
+	pub fn min_version(&self) -> AssemblyVersion {
+		let [major, minor, build, revision] = self.components.map(|c| c.unwrap_or(0));
+		AssemblyVersion::new(major, minor, build, revision)
+	}
+}
+
+impl Display for VersionPattern {
+	/// Prints the pattern with trailing wildcards collapsed into a single `*`.
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		let len = self.components.iter().rposition(Option::is_some).map_or(0, |i| i + 1);
+		for (i, component) in self.components.iter().take(len.max(1)).enumerate() {
+			if i != 0 {
+				f.write_str(".")?;
+			}
+			match component {
+				Some(value) => write!(f, "{}", value)?,
+				None => f.write_str("*")?,
+			}
+		}
+
+		if len != 0 && len < 4 {
+			f.write_str(".*")?;
+		}
+		Ok(())
+	}
+}
+
+impl FromStr for VersionPattern {
+	type Err = raw::Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let error = raw::Error::InvalidData(Some("Invalid assembly version pattern"));
+		let mut components = [Some(0); 4];
+		let mut wildcard = false;
+		for (i, part) in s.split('.').enumerate() {
+			let component = components.get_mut(i).ok_or(error)?;
+			*component = match part.trim() {
+				"*" => None,
+				part => Some(part.parse().map_err(|_| error)?),
+			};
+			wildcard = component.is_none();
+		}
+
+		if wildcard {
+			let last = s.split('.').count();
+			components[last..].fill(None);
+		}
+		Ok(Self { components })
+	}
+}
+
+impl From<AssemblyVersion> for VersionPattern {
+	fn from(version: AssemblyVersion) -> Self {
+		Self::exact(&version)
+	}
+}
+
+/// The last 8 bytes of the SHA-1 hash of a public key, in reverse order.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct PublicKeyToken(pub [u8; 8]);
+
+impl PublicKeyToken {
+	pub fn from_public_key(public_key: &[u8]) -> Self {
+		let hash = sha1(public_key);
+		let mut token = [0; 8];
+		token.copy_from_slice(&hash[12..]);
+		token.reverse();
+		Self(token)
+	}
+
+	/// Interprets a public key blob as stored in metadata: either a full public key,
+	/// or an already computed token if `flags` does not contain [`assembly_flags::PUBLIC_KEY`].
+	pub fn from_blob(blob: &[u8], flags: AssemblyFlags) -> Option<Self> {
+		match (blob.len(), flags & assembly_flags::PUBLIC_KEY != 0) {
+			(0, _) => None,
+			(_, true) => Some(Self::from_public_key(blob)),
+			(8, false) => Some(Self(blob.try_into().ok()?)),
+			(_, false) => None,
+		}
+	}
+
+	/// Whether the token was derived from `key`, which may be a full public key or a token.
+	pub fn matches(&self, key: &[u8]) -> bool {
+		match key.len() {
+			8 => self.0 == key,
+			_ => *self == Self::from_public_key(key),
+		}
+	}
+}
+
+impl Display for PublicKeyToken {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		for byte in self.0 {
+			write!(f, "{:02x}", byte)?;
+		}
+		Ok(())
+	}
+}
+
+impl Debug for PublicKeyToken {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		write!(f, "PublicKeyToken({})", self)
+	}
+}
+
+impl FromStr for PublicKeyToken {
+	type Err = raw::Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let error = raw::Error::InvalidData(Some("Invalid public key token"));
+		if s.len() != 16 || !s.is_ascii() {
+			return Err(error);
+		}
+
+		let mut token = [0; 8];
+		for (i, byte) in token.iter_mut().enumerate() {
+			*byte = u8::from_str_radix(&s[i * 2..i * 2 + 2], 16).map_err(|_| error)?;
+		}
+		Ok(Self(token))
+	}
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct AssemblyIdentity {
+	pub name: String,
+	pub culture: String,
+	pub version: AssemblyVersion,
+	pub flags: AssemblyFlags,
+	pub public_key: Vec<u8>,
+}
+
+impl AssemblyIdentity {
+	pub fn public_key_token(&self) -> Option<PublicKeyToken> {
+		PublicKeyToken::from_blob(&self.public_key, self.flags)
+	}
+
+	pub fn full_name(&self) -> String {
+		let retargetable = self.flags & assembly_flags::RETARGETABLE != 0;
+		full_name(&self.name, &self.culture, &self.version, self.public_key_token(), retargetable)
+	}
+
+	/// Whether an assembly with the `definition` identity satisfies this reference.
+	///
+	/// Names and cultures are compared case-insensitively. A strong-named reference requires the same
+	/// public key token and the exact same version, while the version of a simple-named reference is ignored.
+	pub fn matches(&self, definition: &AssemblyIdentity) -> bool {
+		if !self.name.eq_ignore_ascii_case(&definition.name) || !self.culture.eq_ignore_ascii_case(&definition.culture)
+		{
+			return false;
+		}
+
+		match self.public_key_token() {
+			None => true,
+			Some(token) => definition.public_key_token() == Some(token) && self.version == definition.version,
+		}
+	}
+}
+
+impl Display for AssemblyIdentity {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		f.write_str(&self.full_name())
+	}
+}
+
+/// Parses a display name such as `mscorlib, Version=4.0.0.0, Culture=neutral, PublicKeyToken=b77a5c561934e089`.
+/// Missing properties default to version 0.0.0.0, the neutral culture and no public key.
+impl FromStr for AssemblyIdentity {
+	type Err = raw::Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let mut parts = split_display_name(s).into_iter();
+		let name = parts.next().filter(|name| !name.is_empty());
+		let name = name.ok_or(raw::Error::InvalidData(Some("Missing assembly name")))?;
+
+		let mut identity = AssemblyIdentity {
+			name,
+			culture: "".to_string(),
+			version: AssemblyVersion {
+				major: 0,
+				minor: 0,
+				build: 0,
+				revision: 0,
+			},
+			flags: 0,
+			public_key: vec![],
+		};
+
+		for part in parts {
+			let Some((key, value)) = part.split_once('=') else {
+				return Err(raw::Error::InvalidData(Some("Invalid assembly name property")));
+			};
+
+			let value = value.trim();
+			match key.trim().to_ascii_lowercase().as_str() {
+				"version" => identity.version = value.parse()?,
+				"culture" if value.eq_ignore_ascii_case("neutral") => identity.culture.clear(),
+				"culture" => identity.culture = value.to_string(),
+				"publickeytoken" if value.eq_ignore_ascii_case("null") => identity.public_key.clear(),
+				"publickeytoken" => identity.public_key = value.parse::<PublicKeyToken>()?.0.to_vec(),
+				"retargetable" if value.eq_ignore_ascii_case("yes") => identity.flags |= assembly_flags::RETARGETABLE,
+				_ => {}
+			}
+		}
+
+		Ok(identity)
+	}
+}
+
+pub(crate) fn full_name(
+	name: &str,
+	culture: &str,
+	version: &AssemblyVersion,
+	token: Option<PublicKeyToken>,
+	retargetable: bool,
+) -> String {
+	let mut full_name = String::with_capacity(name.len() + 64);
+	for c in name.chars() {
+		if matches!(c, ',' | '=' | '"' | '\'' | '\\') {
+			full_name.push('\\');
+		}
+		full_name.push(c);
+	}
+
+	let culture = match culture.is_empty() {
+		true => "neutral",
+		false => culture,
+	};
+
+	full_name += &format!(", Version={}, Culture={}, PublicKeyToken=", version, culture);
+	match token {
+		None => full_name += "null",
+		Some(token) => full_name += &token.to_string(),
+	}
+
+	if retargetable {
+		full_name += ", Retargetable=Yes";
+	}
+
+	full_name
+}
+
+/// Splits a display name on its unescaped commas, removing the escapes.
+fn split_display_name(s: &str) -> Vec<String> {
+	let mut parts = vec![String::new()];
+	let mut chars = s.chars();
+	while let Some(c) = chars.next() {
+		match c {
+			'\\' => parts.last_mut().unwrap().extend(chars.next()),
+			',' => parts.push(String::new()),
+			_ => parts.last_mut().unwrap().push(c),
+		}
+	}
+
+	parts.iter().map(|part| part.trim().to_string()).collect()
+}
+
 pub(crate) struct AssemblyName {
 	pub(crate) name: String,
 	pub(crate) culture: String,
@@ -87,6 +952,27 @@ pub(crate) struct AssemblyName {
 	pub(crate) public_key: Vec<u8>,
 }
 
+impl AssemblyName {
+	pub(crate) fn identity(&self) -> AssemblyIdentity {
+		AssemblyIdentity {
+			name: self.name.clone(),
+			culture: self.culture.clone(),
+			version: self.version.clone(),
+			flags: self.flags,
+			public_key: self.public_key.clone(),
+		}
+	}
+
+	pub(crate) fn public_key_token(&self) -> Option<PublicKeyToken> {
+		PublicKeyToken::from_blob(&self.public_key, self.flags)
+	}
+
+	pub(crate) fn full_name(&self) -> String {
+		let retargetable = self.flags & assembly_flags::RETARGETABLE != 0;
+		full_name(&self.name, &self.culture, &self.version, self.public_key_token(), retargetable)
+	}
+}
+
 impl Display for AssemblyName {
 	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
 		write!(f, "{} {}", self.name, self.version)
@@ -113,9 +999,32 @@ pub(crate) struct AssemblyRef {
 	pub(crate) flags: AssemblyFlags,
 	pub(crate) public_key: Vec<u8>,
 	pub(crate) hash_value: Vec<u8>,
+	/// The display name of the referenced assembly, without the `Retargetable` property,
+	/// used to look the assembly up in its context.
 	pub(crate) ident_key: String,
 }
 
+impl AssemblyRef {
+	pub(crate) fn identity(&self) -> AssemblyIdentity {
+		AssemblyIdentity {
+			name: self.name.clone(),
+			culture: self.culture.clone(),
+			version: self.version.clone(),
+			flags: self.flags,
+			public_key: self.public_key.clone(),
+		}
+	}
+
+	pub(crate) fn public_key_token(&self) -> Option<PublicKeyToken> {
+		PublicKeyToken::from_blob(&self.public_key, self.flags)
+	}
+
+	pub(crate) fn full_name(&self) -> String {
+		let retargetable = self.flags & assembly_flags::RETARGETABLE != 0;
+		full_name(&self.name, &self.culture, &self.version, self.public_key_token(), retargetable)
+	}
+}
+
 struct Deps<'l>(&'l [AssemblyRef]);
 
 impl Debug for Deps<'_> {
@@ -129,34 +1038,97 @@ impl Debug for Deps<'_> {
 	}
 }
 
-pub(crate) fn get_type(
-	token: MetadataToken,
-	ctx: &Context,
-	types: &Rc<[Type]>,
-	dependencies: &[AssemblyRef],
-	type_refs: &[(MetadataToken, String, String)],
-) -> Option<TypeRef> {
-	match token.token_kind() {
-		MetadataTokenKind::TypeDef => {
-			let index = token.index() - 1;
-			match index < types.len() {
-				true => Some(TypeRef::new(types.clone(), index)),
-				false => None,
+/// Everything needed to resolve a token to a type, borrowed from an assembly that may still be loading.
+pub(crate) struct TypeResolver<'l> {
+	pub(crate) ctx: &'l Context,
+	pub(crate) types: &'l Rc<[Type]>,
+	pub(crate) dependencies: &'l [AssemblyRef],
+	pub(crate) module_refs: &'l [String],
+	pub(crate) type_refs: &'l [(MetadataToken, String, String)],
+	pub(crate) type_ref_cache: &'l [OnceCell<TypeRef>],
+}
+
+impl TypeResolver<'_> {
+	pub(crate) fn get_type(&self, token: MetadataToken) -> Option<TypeRef> {
+		self.get_type_within(token, &mut vec![])
+	}
+
+	/// Like [`TypeResolver::get_type`], failing on the TypeRefs whose enclosing types are being resolved,
+	/// which only cyclic metadata has, or that are nested deeper than [`Context::max_resolution_depth`].
+	fn get_type_within(&self, token: MetadataToken, enclosing: &mut Vec<MetadataToken>) -> Option<TypeRef> {
+		match token.token_kind() {
+			Some(MetadataTokenKind::TypeDef) => {
+				let index = token.index().checked_sub(1)?;
+				match index < self.types.len() {
+					true => Some(TypeRef::new(self.types.clone(), index)),
+					false => None,
+				}
 			}
-		}
 
-		MetadataTokenKind::TypeRef => {
-			let (token, namespace, name) = &type_refs.get(token.index() - 1)?;
-			match token.token_kind() {
-				MetadataTokenKind::AssemblyRef => {
-					let assembly_ref = dependencies.get(token.index() - 1)?;
-					let assembly = ctx.assembly_map.get(&assembly_ref.ident_key)?;
-					let assembly = ctx.assembly_vec.get(*assembly)?;
-					assembly.find_type(&name, &namespace)
+			Some(MetadataTokenKind::TypeRef) => {
+				let index = token.index().checked_sub(1)?;
+				let cache = self.type_ref_cache.get(index);
+				if let Some(ty) = cache.and_then(OnceCell::get) {
+					return Some(ty.clone());
+				}
+
+				if enclosing.contains(&token) || enclosing.len() >= self.ctx.max_resolution_depth() {
+					return None;
 				}
-				_ => unimplemented!("{:?}", token.token_kind()),
+
+				enclosing.push(token);
+				let (scope, namespace, name) = self.type_refs.get(index)?;
+				let ty = match scope.token_kind() {
+					_ if scope.is_null() => return None,
+
+					Some(MetadataTokenKind::AssemblyRef) => {
+						let assembly_ref = self.dependencies.get(scope.index().checked_sub(1)?)?;
+						let assembly = self.ctx.reference_map.get(&assembly_ref.ident_key)?;
+						let assembly = self.ctx.assembly_vec.get(*assembly)?;
+						assembly.find_type(name, namespace)?
+					}
+
+					Some(MetadataTokenKind::Module) => self.find_local_type(name, namespace)?,
+
+					Some(MetadataTokenKind::ModuleRef) => {
+						let module = self.module_refs.get(scope.index().checked_sub(1)?)?;
+						let assembly = self
+							.ctx
+							.assembly_vec
+							.iter()
+							.find(|assembly| assembly.module.name.eq_ignore_ascii_case(module))?;
+						assembly.find_type(name, namespace)?
+					}
+
+					Some(MetadataTokenKind::TypeRef) => {
+						let enclosing = self.get_type_within(*scope, enclosing)?;
+						let mut nested = enclosing.data()?.nested_types();
+						nested.find(|ty| ty.data().map(|data| data.name == *name).unwrap_or(false))?
+					}
+
+					_ => return None,
+				};
+
+				// Types of the assembly being resolved against may still be uninitialized, and must not be kept alive
+				// by the cache either, since the loader still needs unique access to them.
+				if let Some(cache) = cache.filter(|_| !Rc::ptr_eq(ty.container(), self.types)) {
+					let _ = cache.set(ty.clone());
+				}
+
+				Some(ty)
 			}
+			_ => None,
 		}
-		_ => None,
+	}
+
+	/// Finds a top-level type defined in the assembly being resolved against, even if it is not fully loaded yet.
+	fn find_local_type(&self, name: &str, namespace: &str) -> Option<TypeRef> {
+		let index = self.types.iter().position(|ty| {
+			let Some(data) = ty.data() else { return false };
+			let is_nested = data.flags & type_flags::VISIBILITY_MASK > type_flags::PUBLIC;
+			!is_nested && data.name == name && data.namespace == namespace
+		})?;
+
+		Some(TypeRef::new(self.types.clone(), index))
 	}
 }
@@ -0,0 +1,64 @@
+use crate::raw::{AttributeArguments, ByteStream, HeapIndex, MetadataToken};
+use crate::schema::LoadDiagnostic;
+
+#[derive(Debug)]
+pub struct CustomAttribute {
+	pub(crate) token: MetadataToken,
+	pub(crate) parent: MetadataToken,
+	pub(crate) constructor: MetadataToken,
+	pub(crate) attribute_type: String,
+	pub(crate) value: Vec<u8>,
+	pub(crate) value_index: HeapIndex,
+}
+
+impl CustomAttribute {
+	pub fn token(&self) -> MetadataToken {
+		self.token
+	}
+
+	pub fn parent(&self) -> MetadataToken {
+		self.parent
+	}
+
+	/// The MethodDef or MemberRef of the attribute constructor.
+	pub fn constructor(&self) -> MetadataToken {
+		self.constructor
+	}
+
+	/// The full name of the attribute type, e.g. `System.Reflection.DefaultMemberAttribute`.
+	pub fn attribute_type(&self) -> &str {
+		&self.attribute_type
+	}
+
+	pub fn value(&self) -> &[u8] {
+		&self.value
+	}
+
+	/// Reads the first fixed argument of the attribute as a string.
+	/// Only valid for attributes whose constructor takes a string as its first parameter.
+	pub(crate) fn string_argument(&self) -> Option<&str> {
+		let mut stream = ByteStream::new(&self.value);
+		if stream.read::<u16>().ok()? != 0x0001 {
+			return None;
+		}
+
+		stream.read_ser_string().ok()?
+	}
+}
+
+/// The arguments of a custom attribute, along with a diagnostic for every argument that could not be decoded.
+#[derive(Debug, Clone)]
+pub struct DecodedAttribute {
+	pub(crate) arguments: AttributeArguments,
+	pub(crate) diagnostics: Vec<LoadDiagnostic>,
+}
+
+impl DecodedAttribute {
+	pub fn arguments(&self) -> &AttributeArguments {
+		&self.arguments
+	}
+
+	pub fn diagnostics(&self) -> &[LoadDiagnostic] {
+		&self.diagnostics
+	}
+}
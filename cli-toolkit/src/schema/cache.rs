@@ -0,0 +1,117 @@
+use crate::raw::{FieldSignature, HeapIndex, MetadataToken, MethodSignature};
+use crate::schema::DecodedAttribute;
+use std::collections::{HashMap, VecDeque};
+use std::cell::{Cell, RefCell};
+use std::hash::Hash;
+use std::rc::Rc;
+use crate::raw;
+
+/// How a memoization cache has been used since it was last enabled.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct CacheStatistics {
+	pub hits: u64,
+	pub misses: u64,
+	/// The entries dropped to make room for new ones, oldest first.
+	pub evictions: u64,
+	pub len: usize,
+	pub capacity: usize,
+}
+
+/// The statistics of the decoding caches of an assembly, see [`Assembly::set_decode_cache_capacity`](crate::schema::Assembly::set_decode_cache_capacity).
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct DecodeCacheStatistics {
+	pub method_signatures: CacheStatistics,
+	pub field_signatures: CacheStatistics,
+	pub attributes: CacheStatistics,
+}
+
+/// The decoded signatures and attribute blobs of an assembly, keyed by the blob they were decoded from.
+#[derive(Default)]
+pub(crate) struct DecodeCache {
+	pub(crate) method_signatures: MemoCache<HeapIndex, Result<Rc<MethodSignature>, raw::Error>>,
+	pub(crate) field_signatures: MemoCache<HeapIndex, Result<Rc<FieldSignature>, raw::Error>>,
+	/// Keyed by constructor as well, since the constructor parameters determine how the blob is decoded.
+	pub(crate) attributes: MemoCache<(HeapIndex, MetadataToken), DecodedAttribute>,
+}
+
+impl DecodeCache {
+	pub(crate) fn set_capacity(&self, capacity: usize) {
+		self.method_signatures.set_capacity(capacity);
+		self.field_signatures.set_capacity(capacity);
+		self.attributes.set_capacity(capacity);
+	}
+
+	pub(crate) fn statistics(&self) -> DecodeCacheStatistics {
+		DecodeCacheStatistics {
+			method_signatures: self.method_signatures.statistics(),
+			field_signatures: self.field_signatures.statistics(),
+			attributes: self.attributes.statistics(),
+		}
+	}
+}
+
+/// A bounded map that evicts its oldest entries first. A capacity of 0 disables it.
+pub(crate) struct MemoCache<K, V> {
+	entries: RefCell<HashMap<K, V>>,
+	order: RefCell<VecDeque<K>>,
+	statistics: Cell<CacheStatistics>,
+}
+
+impl<K, V> Default for MemoCache<K, V> {
+	fn default() -> Self {
+		Self {
+			entries: RefCell::new(HashMap::new()),
+			order: RefCell::new(VecDeque::new()),
+			statistics: Cell::new(CacheStatistics::default()),
+		}
+	}
+}
+
+impl<K: Copy + Eq + Hash, V: Clone> MemoCache<K, V> {
+	/// Changes the capacity, dropping every entry and resetting the statistics.
+	pub(crate) fn set_capacity(&self, capacity: usize) {
+		self.entries.borrow_mut().clear();
+		self.order.borrow_mut().clear();
+		self.statistics.set(CacheStatistics {
+			capacity,
+			..Default::default()
+		});
+	}
+
+	pub(crate) fn statistics(&self) -> CacheStatistics {
+		self.statistics.get()
+	}
+
+	pub(crate) fn get_or_insert_with(&self, key: K, value: impl FnOnce() -> V) -> V {
+		let mut statistics = self.statistics.get();
+		if statistics.capacity == 0 {
+			return value();
+		}
+
+		if let Some(value) = self.entries.borrow().get(&key) {
+			statistics.hits += 1;
+			self.statistics.set(statistics);
+			return value.clone();
+		}
+
+		// The entries are not borrowed while decoding, which may look other entries up.
+		let value = value();
+		let mut entries = self.entries.borrow_mut();
+		let mut order = self.order.borrow_mut();
+		if !entries.contains_key(&key) {
+			while entries.len() >= statistics.capacity {
+				let Some(oldest) = order.pop_front() else { break };
+				entries.remove(&oldest);
+				statistics.evictions += 1;
+			}
+
+			entries.insert(key, value.clone());
+			order.push_back(key);
+		}
+
+		statistics.misses += 1;
+		statistics.len = entries.len();
+		self.statistics.set(statistics);
+		value
+	}
+}
@@ -0,0 +1,301 @@
+use crate::raw::{MetadataToken, MetadataTokenKind, MethodSignature, PropertySignature, TypeSignature};
+use crate::schema::{Assembly, Context, CustomAttribute, GenericParam, Method, MethodRef, TypeData, TypeRef};
+use std::collections::HashSet;
+use std::rc::Rc;
+
+/// A starting point of [`Context::type_closure`].
+#[derive(Debug, Clone)]
+pub enum ClosureRoot {
+	Type(TypeRef),
+	/// A method, which brings in its declaring type as a whole.
+	Method(MethodRef),
+}
+
+impl From<TypeRef> for ClosureRoot {
+	fn from(ty: TypeRef) -> Self {
+		Self::Type(ty)
+	}
+}
+
+impl From<MethodRef> for ClosureRoot {
+	fn from(method: MethodRef) -> Self {
+		Self::Method(method)
+	}
+}
+
+/// The type definitions reachable from a set of roots, grouped by the assembly defining them.
+#[derive(Debug, Clone, Default)]
+pub struct TypeClosure {
+	/// The assemblies of the context with at least one type in the closure, in load order,
+	/// along with the TypeDef tokens of those types in token order.
+	assemblies: Vec<(Rc<Assembly>, Vec<MetadataToken>)>,
+	/// The TypeRef, TypeSpec and member tokens that could not be resolved, along with the assembly using them.
+	unresolved: Vec<(Rc<Assembly>, MetadataToken)>,
+}
+
+impl TypeClosure {
+	/// The assemblies contributing types to the closure, in load order, along with their TypeDef tokens.
+	pub fn iter(&self) -> impl Iterator<Item = (&Rc<Assembly>, &[MetadataToken])> {
+		self.assemblies
+			.iter()
+			.map(|(assembly, tokens)| (assembly, tokens.as_slice()))
+	}
+
+	/// The TypeDef tokens of the types of `assembly` in the closure, in token order.
+	pub fn types_of(&self, assembly: &Assembly) -> &[MetadataToken] {
+		let tokens = self
+			.assemblies
+			.iter()
+			.find(|(candidate, _)| std::ptr::eq(candidate.as_ref(), assembly));
+		tokens.map_or(&[], |(_, tokens)| tokens)
+	}
+
+	pub fn contains(&self, ty: &TypeData) -> bool {
+		let Some(assembly) = ty.assembly.upgrade() else { return false };
+		self.types_of(&assembly)
+			.binary_search_by_key(&ty.token.0, |token| token.0)
+			.is_ok()
+	}
+
+	/// The number of types in the closure, across all assemblies.
+	pub fn len(&self) -> usize {
+		self.assemblies.iter().map(|(_, tokens)| tokens.len()).sum()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.assemblies.is_empty()
+	}
+
+	/// The references that could not be followed, e.g. TypeRefs into assemblies missing from the context,
+	/// each listed once along with the assembly using it. Whatever they refer to is missing from the closure.
+	pub fn unresolved(&self) -> &[(Rc<Assembly>, MetadataToken)] {
+		&self.unresolved
+	}
+}
+
+impl Context {
+	/// Computes every type definition reachable from `roots`, each listed once.
+	///
+	/// A type brings in its base type, its interfaces, its enclosing type, the constraints of its generic parameters,
+	/// the types of its custom attributes, and the types used by the signatures and attributes of its fields,
+	/// methods, parameters, properties and events, as well as the declaring types of the methods it overrides.
+	/// Generic instantiations bring in both the generic type and its arguments.
+	/// Method bodies are not scanned, so types only used by IL instructions are missing from the closure.
+	pub fn type_closure(&self, roots: impl IntoIterator<Item = impl Into<ClosureRoot>>) -> TypeClosure {
+		let mut walk = ClosureWalk {
+			visited: HashSet::new(),
+			pending: vec![],
+			unresolved: vec![],
+		};
+
+		for root in roots {
+			match root.into() {
+				ClosureRoot::Type(ty) => walk.push(ty),
+				ClosureRoot::Method(method) => walk.push(method.declaring_type),
+			}
+		}
+
+		while let Some(ty) = walk.pending.pop() {
+			let Some(data) = ty.data() else { continue };
+			let Some(assembly) = data.assembly.upgrade() else { continue };
+			walk.visit_type(&assembly, data);
+		}
+
+		let mut closure = TypeClosure::default();
+		for assembly in self.assembly_vec.iter() {
+			let key = Rc::as_ptr(assembly);
+			let mut tokens = walk
+				.visited
+				.iter()
+				.filter(|(owner, _)| *owner == key)
+				.map(|(_, token)| *token)
+				.collect::<Vec<_>>();
+			if !tokens.is_empty() {
+				tokens.sort_by_key(|token| token.0);
+				closure.assemblies.push((assembly.clone(), tokens));
+			}
+		}
+
+		closure.unresolved = walk.unresolved;
+		closure
+	}
+}
+
+struct ClosureWalk {
+	/// The types reached so far, by defining assembly and TypeDef token.
+	visited: HashSet<(*const Assembly, MetadataToken)>,
+	pending: Vec<TypeRef>,
+	unresolved: Vec<(Rc<Assembly>, MetadataToken)>,
+}
+
+impl ClosureWalk {
+	fn push(&mut self, ty: TypeRef) {
+		let Some(data) = ty.data() else { return };
+		let Some(assembly) = data.assembly.upgrade() else { return };
+		if self.visited.insert((Rc::as_ptr(&assembly), data.token)) {
+			self.pending.push(ty);
+		}
+	}
+
+	fn unresolved(&mut self, assembly: &Rc<Assembly>, token: MetadataToken) {
+		let known = self
+			.unresolved
+			.iter()
+			.any(|(owner, unresolved)| Rc::ptr_eq(owner, assembly) && *unresolved == token);
+		if !known {
+			self.unresolved.push((assembly.clone(), token));
+		}
+	}
+
+	fn visit_type(&mut self, assembly: &Rc<Assembly>, data: &TypeData) {
+		if !data.base.is_null() {
+			self.visit_token(assembly, data.base);
+		}
+
+		if let Some(enclosing) = assembly.enclosing_type(data.token) {
+			self.visit_token(assembly, enclosing);
+		}
+
+		for interface in data.interfaces.iter() {
+			self.visit_token(assembly, interface.interface);
+			self.visit_attributes(assembly, &interface.custom_attributes);
+		}
+
+		self.visit_generic_params(assembly, &data.generic_params);
+		self.visit_attributes(assembly, &data.custom_attributes);
+
+		for field in data.fields.iter() {
+			if let Ok(signature) = assembly.field_signature(field) {
+				self.visit_signature(assembly, &signature.ty);
+			}
+			self.visit_attributes(assembly, assembly.attributes_of(field.token));
+		}
+
+		for method in data.methods.iter() {
+			self.visit_method(assembly, method);
+		}
+
+		for property in data.properties.iter() {
+			if let Ok(signature) = PropertySignature::parse(&property.signature) {
+				self.visit_signature(assembly, &signature.ty);
+				for param in signature.params.iter() {
+					self.visit_signature(assembly, param);
+				}
+			}
+			self.visit_attributes(assembly, assembly.attributes_of(property.token));
+		}
+
+		for event in data.events.iter() {
+			if !event.event_type.is_null() {
+				self.visit_token(assembly, event.event_type);
+			}
+			self.visit_attributes(assembly, assembly.attributes_of(event.token));
+		}
+	}
+
+	fn visit_method(&mut self, assembly: &Rc<Assembly>, method: &Method) {
+		if let Ok(signature) = assembly.method_signature(method) {
+			self.visit_method_signature(assembly, &signature);
+		}
+
+		self.visit_generic_params(assembly, &method.generic_params);
+		self.visit_attributes(assembly, &method.custom_attributes);
+		for param in method.params.iter() {
+			self.visit_attributes(assembly, assembly.attributes_of(param.token));
+		}
+
+		for declaration in method.overrides.iter() {
+			self.visit_member(assembly, *declaration);
+		}
+	}
+
+	fn visit_generic_params(&mut self, assembly: &Rc<Assembly>, params: &[GenericParam]) {
+		for param in params {
+			for constraint in param.constraints.iter() {
+				self.visit_token(assembly, *constraint);
+			}
+			self.visit_attributes(assembly, &param.custom_attributes);
+		}
+	}
+
+	fn visit_attributes(&mut self, assembly: &Rc<Assembly>, attributes: &[CustomAttribute]) {
+		for attribute in attributes {
+			self.visit_member(assembly, attribute.constructor);
+		}
+	}
+
+	/// Brings in the declaring type of a MethodDef or MemberRef, falling back to the parent of a MemberRef
+	/// whose signature matches no method of the type, e.g. one of an older version of the assembly.
+	fn visit_member(&mut self, assembly: &Rc<Assembly>, token: MetadataToken) {
+		if let Some(method) = assembly.resolve_method(token) {
+			self.push(method.declaring_type);
+			return;
+		}
+
+		match token.token_kind() {
+			Some(MetadataTokenKind::MemberRef) => {
+				let parent = token
+					.index()
+					.checked_sub(1)
+					.and_then(|index| assembly.member_refs.get(index));
+				match parent {
+					Some((parent, _, _)) if !parent.is_null() => self.visit_token(assembly, *parent),
+					_ => self.unresolved(assembly, token),
+				}
+			}
+			_ => self.unresolved(assembly, token),
+		}
+	}
+
+	fn visit_token(&mut self, assembly: &Rc<Assembly>, token: MetadataToken) {
+		match token.token_kind() {
+			Some(MetadataTokenKind::TypeDef | MetadataTokenKind::TypeRef) => match assembly.get_type(token) {
+				Some(ty) => self.push(ty),
+				None => self.unresolved(assembly, token),
+			},
+
+			Some(MetadataTokenKind::TypeSpec) => match assembly.type_spec(token) {
+				Some(spec) => self.visit_signature(assembly, &spec.signature),
+				None => self.unresolved(assembly, token),
+			},
+
+			// ModuleRefs and MethodDefs are the parents of global members, which belong to no type.
+			_ => {}
+		}
+	}
+
+	fn visit_signature(&mut self, assembly: &Rc<Assembly>, signature: &TypeSignature) {
+		match signature {
+			TypeSignature::Class(token) | TypeSignature::ValueType(token) => self.visit_token(assembly, *token),
+
+			TypeSignature::Ptr(ty)
+			| TypeSignature::ByRef(ty)
+			| TypeSignature::Pinned(ty)
+			| TypeSignature::SzArray(ty)
+			| TypeSignature::Array(ty, _) => self.visit_signature(assembly, ty),
+
+			TypeSignature::GenericInst(ty, arguments) => {
+				self.visit_signature(assembly, ty);
+				for argument in arguments {
+					self.visit_signature(assembly, argument);
+				}
+			}
+
+			TypeSignature::FnPtr(signature) => self.visit_method_signature(assembly, signature),
+
+			TypeSignature::Modified { modifier, ty, .. } => {
+				self.visit_token(assembly, *modifier);
+				self.visit_signature(assembly, ty);
+			}
+
+			_ => {}
+		}
+	}
+
+	fn visit_method_signature(&mut self, assembly: &Rc<Assembly>, signature: &MethodSignature) {
+		self.visit_signature(assembly, &signature.return_type);
+		for param in signature.params.iter().chain(signature.vararg_params.iter()) {
+			self.visit_signature(assembly, param);
+		}
+	}
+}
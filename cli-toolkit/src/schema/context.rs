@@ -1,9 +1,432 @@
-use crate::schema::assembly::Assembly;
+use crate::schema::assembly::{Assembly, AssemblyIdentity};
+use crate::raw::{MetadataToken, TypeSignature};
+use crate::schema::{DumpWriter, ExtensionIndex, Members, MethodRef, NameStyle, Type, TypeDisplay, TypeRef};
 use std::collections::HashMap;
+use std::cell::{Cell, OnceCell};
+use std::fmt::{Debug, Display, Formatter};
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
+use std::io;
 
-#[derive(Debug)]
 pub struct Context {
 	pub(crate) assembly_vec: Vec<Rc<Assembly>>,
 	pub(crate) assembly_map: HashMap<String, usize>,
+	/// The assembly that satisfies each reference, by the identity of the reference, see [`ResolutionRule`].
+	pub(crate) reference_map: HashMap<String, usize>,
+	pub(crate) diagnostics: OnceCell<Vec<LoadDiagnostic>>,
+	pub(crate) load_report: LoadReport,
+	/// The assembly declaring the well-known types, see [`Context::corlib`].
+	pub(crate) corlib: Option<AssemblyIdentity>,
+	/// Built on first use, once every type has been loaded.
+	pub(crate) extension_methods: OnceCell<ExtensionIndex>,
+	/// Built on first use, see [`Context::soft_references`].
+	pub(crate) type_names: OnceCell<HashMap<String, Vec<TypeRef>>>,
+	/// The default of [`Members::hide_synthetic`](crate::schema::Members::hide_synthetic).
+	pub(crate) hide_synthetic_members: Cell<bool>,
+	/// See [`Context::max_resolution_depth`].
+	pub(crate) max_resolution_depth: Cell<usize>,
+}
+
+/// What [`Context::merge`] does when both contexts contain an assembly with the same identity.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum MergePolicy {
+	/// Keep the assembly of the base context.
+	#[default]
+	PreferBase,
+	/// Keep the assembly of the other context, which the base assemblies referring to it will not see.
+	PreferOther,
+	/// Fail with [`Error::DuplicateAssembly`](crate::read::Error::DuplicateAssembly).
+	Fail,
+}
+
+/// A recoverable failure that was recorded instead of aborting the load of a context.
+#[derive(Debug, Clone)]
+pub struct LoadDiagnostic {
+	pub(crate) assembly: String,
+	pub(crate) token: Option<MetadataToken>,
+	pub(crate) reason: String,
+	pub(crate) recovery: Recovery,
+}
+
+impl LoadDiagnostic {
+	pub fn assembly(&self) -> &str {
+		&self.assembly
+	}
+
+	/// The row that failed to load, if the failure concerns a single row.
+	pub fn token(&self) -> Option<MetadataToken> {
+		self.token
+	}
+
+	pub fn reason(&self) -> &str {
+		&self.reason
+	}
+
+	/// What the load did instead of failing.
+	pub fn recovery(&self) -> &Recovery {
+		&self.recovery
+	}
+}
+
+impl Display for LoadDiagnostic {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		match self.token {
+			Some(token) => write!(f, "{} ({:#x}): {}", self.assembly, token.0, self.reason),
+			None => write!(f, "{}: {}", self.assembly, self.reason),
+		}
+	}
+}
+
+/// How a lenient load got past a malformed structure, see [`LoadDiagnostic::recovery`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum RecoveryRule {
+	/// A stream extending past the end of the file was clamped to the available bytes.
+	StreamClamped,
+	/// A row that could not be decoded was left out.
+	RowSkipped,
+	/// A row that could not be decoded kept its slot with null values, so that it never resolves.
+	RowNulled,
+	/// A type definition that could not be loaded was replaced with [`Type::Error`].
+	TypeReplaced,
+	/// A whole table or step of the load was left out, e.g. every assembly reference.
+	SectionSkipped,
+	/// A referenced assembly that was found but could not be read was left unresolved.
+	DependencySkipped,
+	/// Custom attribute arguments that could not be decoded were kept as raw bytes,
+	/// or a custom attribute whose constructor or value could not be read was kept without them.
+	AttributeKeptRaw,
+}
+
+impl RecoveryRule {
+	/// A stable name for the rule, e.g. `"stream-clamped"`, as printed by the [`Display`] implementation of [`Recovery`].
+	pub fn name(&self) -> &'static str {
+		match self {
+			RecoveryRule::StreamClamped => "stream-clamped",
+			RecoveryRule::RowSkipped => "row-skipped",
+			RecoveryRule::RowNulled => "row-nulled",
+			RecoveryRule::TypeReplaced => "type-replaced",
+			RecoveryRule::SectionSkipped => "section-skipped",
+			RecoveryRule::DependencySkipped => "dependency-skipped",
+			RecoveryRule::AttributeKeptRaw => "attribute-kept-raw",
+		}
+	}
+}
+
+/// The rule a lenient load relaxed, along with where and what it found, so that recoveries can be filtered
+/// and compared across files, e.g. to spot the same tampering in several samples.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Recovery {
+	pub(crate) rule: RecoveryRule,
+	pub(crate) offset: Option<usize>,
+	pub(crate) observed: Option<String>,
+	pub(crate) expected: Option<String>,
+}
+
+impl Recovery {
+	pub(crate) fn new(rule: RecoveryRule) -> Self {
+		Self {
+			rule,
+			offset: None,
+			observed: None,
+			expected: None,
+		}
+	}
+
+	pub fn rule(&self) -> RecoveryRule {
+		self.rule
+	}
+
+	/// The absolute file offset of the structure, if it lies in the file.
+	pub fn offset(&self) -> Option<usize> {
+		self.offset
+	}
+
+	/// The value found in the file, e.g. the declared size of a stream or the bytes of a row, in hexadecimal.
+	pub fn observed(&self) -> Option<&str> {
+		self.observed.as_deref()
+	}
+
+	/// The value the structure should have had, if there is a single one.
+	pub fn expected(&self) -> Option<&str> {
+		self.expected.as_deref()
+	}
+}
+
+/// Prints the recovery as space separated `key=value` pairs, e.g. `rule=stream-clamped offset=0x1c4 observed=...`,
+/// leaving out the unknown values.
+impl Display for Recovery {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		write!(f, "rule={}", self.rule.name())?;
+		if let Some(offset) = self.offset {
+			write!(f, " offset={:#x}", offset)?;
+		}
+		if let Some(observed) = &self.observed {
+			write!(f, " observed={}", observed)?;
+		}
+		if let Some(expected) = &self.expected {
+			write!(f, " expected={}", expected)?;
+		}
+		Ok(())
+	}
+}
+
+/// How an assembly reference was matched with an assembly of the context.
+/// When several assemblies qualify for a rule, the one with the highest version is chosen.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ResolutionRule {
+	/// The assembly has the referenced identity.
+	Exact,
+	/// The assembly has the referenced name, culture and public key token, and a higher version.
+	/// Any version unifies with a simple-named reference.
+	Unified,
+	/// The assembly only has the referenced name.
+	Fallback,
+}
+
+/// An assembly reference, and the assembly of the context that satisfied it.
+#[derive(Debug, Clone)]
+pub struct ReferenceResolution {
+	pub(crate) referrer: String,
+	pub(crate) reference: AssemblyIdentity,
+	pub(crate) rule: Option<ResolutionRule>,
+	pub(crate) assembly: Option<String>,
+	pub(crate) path: Option<PathBuf>,
+}
+
+impl ReferenceResolution {
+	/// The full name of the referencing assembly.
+	pub fn referrer(&self) -> &str {
+		&self.referrer
+	}
+
+	pub fn reference(&self) -> &AssemblyIdentity {
+		&self.reference
+	}
+
+	/// The rule the reference was resolved by, or `None` if no assembly of the context satisfies it.
+	pub fn rule(&self) -> Option<ResolutionRule> {
+		self.rule
+	}
+
+	/// The full name of the assembly that satisfied the reference.
+	pub fn assembly(&self) -> Option<&str> {
+		self.assembly.as_deref()
+	}
+
+	/// The file of the assembly that satisfied the reference, if it was loaded by a
+	/// [`ContextBuilder`](crate::read::ContextBuilder).
+	pub fn path(&self) -> Option<&Path> {
+		self.path.as_deref()
+	}
+
+	pub fn is_resolved(&self) -> bool {
+		self.rule.is_some()
+	}
+}
+
+impl Display for ReferenceResolution {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{} -> {}: ", self.referrer, self.reference)?;
+		let (Some(rule), Some(assembly)) = (self.rule, &self.assembly) else {
+			return write!(f, "unresolved");
+		};
+		write!(f, "{:?} {}", rule, assembly)?;
+		match &self.path {
+			Some(path) => write!(f, " ({})", path.display()),
+			None => Ok(()),
+		}
+	}
+}
+
+/// How every assembly reference of a context was resolved, in load order of the referencing assemblies.
+#[derive(Debug, Clone, Default)]
+pub struct LoadReport {
+	pub(crate) references: Vec<ReferenceResolution>,
+}
+
+impl LoadReport {
+	pub fn references(&self) -> &[ReferenceResolution] {
+		&self.references
+	}
+
+	pub fn unresolved(&self) -> impl Iterator<Item = &ReferenceResolution> {
+		self.references.iter().filter(|reference| !reference.is_resolved())
+	}
+}
+
+impl Display for LoadReport {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		for reference in self.references.iter() {
+			writeln!(f, "{}", reference)?;
+		}
+		Ok(())
+	}
+}
+
+/// The outcome of [`Context::reload_assembly`](crate::schema::Context::reload_assembly).
+#[derive(Debug, Clone)]
+pub struct ReloadReport {
+	pub(crate) previous: String,
+	pub(crate) reloaded: String,
+	pub(crate) invalidated: Vec<String>,
+	#[cfg(feature = "checksums")]
+	pub(crate) changed_streams: Vec<String>,
+}
+
+impl ReloadReport {
+	/// The full name of the replaced assembly.
+	pub fn previous(&self) -> &str {
+		&self.previous
+	}
+
+	/// The full name of the assembly that replaced it.
+	pub fn reloaded(&self) -> &str {
+		&self.reloaded
+	}
+
+	/// The full names of the assemblies that referred to the replaced assembly, or now refer to its replacement.
+	/// Their TypeRefs are resolved again, but the base types of their type definitions still point to the old build.
+	pub fn invalidated(&self) -> &[String] {
+		&self.invalidated
+	}
+
+	/// The metadata streams whose checksum differs between the two builds, e.g. only `#US` for a changed string literal.
+	#[cfg(feature = "checksums")]
+	pub fn changed_streams(&self) -> &[String] {
+		&self.changed_streams
+	}
+}
+
+impl Context {
+	/// The default of [`Context::max_resolution_depth`], well above the depth of real type hierarchies.
+	pub const DEFAULT_MAX_RESOLUTION_DEPTH: usize = 128;
+
+	/// The loaded assemblies, in load order.
+	pub fn assemblies(&self) -> &[Rc<Assembly>] {
+		&self.assembly_vec
+	}
+
+	/// A compact listing of every loaded assembly, in load order.
+	pub fn summary(&self) -> ContextSummary<'_> {
+		ContextSummary {
+			context: self,
+			annotated: false,
+		}
+	}
+
+	/// Writes [`Context::summary`] to `writer` in chunks, see [`DumpWriter`].
+	pub fn write_summary<W: io::Write>(&self, writer: W) -> io::Result<W> {
+		let mut writer = DumpWriter::new(writer);
+		writer.write_display(&self.summary())?;
+		writer.finish()
+	}
+
+	/// Formats a type of the context, with options to limit how much of its base type chain is printed.
+	pub fn display<'l>(&self, ty: &'l Type) -> TypeDisplay<'l> {
+		TypeDisplay::new(ty, self.max_resolution_depth())
+	}
+
+	/// Resolves a MethodSpec token of `assembly` to its generic method definition and type arguments.
+	/// The type arguments are expressed in terms of the tokens of `assembly`.
+	pub fn resolve_method_spec<'l>(
+		&self,
+		assembly: &'l Assembly,
+		token: MetadataToken,
+	) -> Option<(MethodRef, &'l [TypeSignature])> {
+		let spec = assembly.method_spec(token)?;
+		let method = assembly.resolve_method(spec.method)?;
+		Some((method, &spec.type_arguments))
+	}
+
+	/// The core library the context was loaded with, see [`ContextBuilder::corlib`](crate::read::ContextBuilder::corlib).
+	/// Without one, well-known types such as `System.Object` are recognized in any assembly.
+	pub fn corlib(&self) -> Option<&AssemblyIdentity> {
+		self.corlib.as_ref()
+	}
+
+	/// Whether the well-known types declared by `assembly` are the ones of the context.
+	/// The version of the core library is ignored, since it varies between runtimes.
+	pub(crate) fn is_corlib(&self, assembly: &Assembly) -> bool {
+		let Some(corlib) = &self.corlib else { return true };
+		let token = corlib.public_key_token();
+		assembly.name.name.eq_ignore_ascii_case(&corlib.name)
+			&& (token.is_none() || assembly.public_key_token() == token)
+	}
+
+	/// Enables the decoding caches of every assembly of the context, see [`Assembly::set_decode_cache_capacity`].
+	pub fn set_decode_cache_capacity(&self, capacity: usize) {
+		for assembly in self.assembly_vec.iter() {
+			assembly.set_decode_cache_capacity(capacity);
+		}
+	}
+
+	/// Which assembly satisfied each assembly reference, and by which rule.
+	pub fn load_report(&self) -> &LoadReport {
+		&self.load_report
+	}
+
+	/// The failures recorded while loading the context in error accumulation mode.
+	pub fn load_diagnostics(&self) -> &[LoadDiagnostic] {
+		self.diagnostics.get().map(Vec::as_slice).unwrap_or_default()
+	}
+
+	/// Whether [`Type::members`] leaves out the members the compiler emits on its own, see [`Members::hide_synthetic`].
+	pub fn hides_synthetic_members(&self) -> bool {
+		self.hide_synthetic_members.get()
+	}
+
+	pub fn set_hide_synthetic_members(&self, hide: bool) {
+		self.hide_synthetic_members.set(hide);
+	}
+
+	/// How many steps a type lookup may take before it gives up: base types followed while loading,
+	/// enclosing types of nested TypeRefs, and assemblies searched for a type through their references.
+	///
+	/// Lookups that run into the limit or into a cycle fail like lookups of missing types do,
+	/// so that cyclic or adversarial metadata cannot hang the loader or overflow the stack.
+	pub fn max_resolution_depth(&self) -> usize {
+		self.max_resolution_depth.get()
+	}
+
+	pub fn set_max_resolution_depth(&self, depth: usize) {
+		self.max_resolution_depth.set(depth);
+	}
+
+	/// Renders the names of every assembly of the context in `style`, see [`Assembly::set_name_style`].
+	/// Assemblies loaded later use the default style.
+	pub fn set_name_style(&self, style: NameStyle) {
+		for assembly in self.assembly_vec.iter() {
+			assembly.set_name_style(style);
+		}
+	}
+}
+
+impl Debug for Context {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("Context")
+			.field("assemblies", &self.assembly_vec)
+			.finish()
+	}
+}
+
+pub struct ContextSummary<'l> {
+	context: &'l Context,
+	annotated: bool,
+}
+
+impl ContextSummary<'_> {
+	/// Annotates the summary of every assembly, see [`AssemblySummary::annotated`](crate::schema::AssemblySummary::annotated).
+	pub fn annotated(mut self, annotated: bool) -> Self {
+		self.annotated = annotated;
+		self
+	}
+}
+
+impl Display for ContextSummary<'_> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		for assembly in self.context.assembly_vec.iter() {
+			Display::fmt(&assembly.summary().annotated(self.annotated), f)?;
+		}
+		Ok(())
+	}
 }
@@ -0,0 +1,699 @@
+use crate::raw::{MetadataToken, MetadataTokenKind, MethodSignature, PropertySignature, TypeSignature};
+use crate::schema::format::strip_arity;
+use crate::schema::{Assembly, Context, Event, Field, Member, Members, Method, Property, Type, TypeData, TypeRef};
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter, Write};
+use std::str::FromStr;
+use crate::raw;
+use std::path::{Path, PathBuf};
+
+/// Generates the documentation IDs the C# compiler writes into XML documentation files,
+/// such as `T:System.Collections.Generic.List`1` or `M:System.String.Join(System.String,System.String[])`.
+///
+/// Custom modifiers are kept as `|` (required) and `!` (optional) suffixes, as the compiler writes them.
+pub struct DocIds<'l> {
+	assembly: &'l Assembly,
+	/// The enclosing type of every nested type definition.
+	enclosing: HashMap<MetadataToken, MetadataToken>,
+}
+
+impl<'l> DocIds<'l> {
+	fn new(assembly: &'l Assembly) -> Self {
+		let types = assembly.types.get().map_or(&[][..], |types| &types[..]);
+		let enclosing = types
+			.iter()
+			.filter_map(Type::data)
+			.flat_map(|data| data.nested_types.iter().map(|nested| (*nested, data.token)))
+			.collect();
+
+		Self { assembly, enclosing }
+	}
+
+	pub fn type_id(&self, ty: &TypeData) -> String {
+		format!("T:{}", self.type_name(ty))
+	}
+
+	pub fn field_id(&self, field: &Field) -> Option<String> {
+		Some(format!("F:{}.{}", self.parent_name(field.parent)?, member_name(&field.name)))
+	}
+
+	/// `None` if the declaring type is not part of the assembly or the signature cannot be decoded.
+	pub fn method_id(&self, method: &Method) -> Option<String> {
+		let signature = self.assembly.method_signature(method).ok()?;
+		let mut id = format!("M:{}.{}", self.parent_name(method.parent)?, member_name(&method.name));
+		if signature.generic_param_count != 0 {
+			write!(id, "``{}", signature.generic_param_count).ok()?;
+		}
+
+		self.write_params(&mut id, &signature.params);
+		if method.name == "op_Implicit" || method.name == "op_Explicit" {
+			id.push('~');
+			self.write_type(&mut id, &signature.return_type);
+		}
+		Some(id)
+	}
+
+	/// Indexers list the types of their parameters, like methods do.
+	pub fn property_id(&self, property: &Property) -> Option<String> {
+		let signature = PropertySignature::parse(&property.signature).ok()?;
+		let mut id = format!("P:{}.{}", self.parent_name(property.parent)?, member_name(&property.name));
+		self.write_params(&mut id, &signature.params);
+		Some(id)
+	}
+
+	pub fn event_id(&self, event: &Event) -> Option<String> {
+		Some(format!("E:{}.{}", self.parent_name(event.parent)?, member_name(&event.name)))
+	}
+
+	pub fn member_id(&self, member: &Member) -> Option<String> {
+		match member {
+			Member::Field(field) => self.field_id(field),
+			Member::Method(method) => self.method_id(method),
+			Member::Property(property) => self.property_id(property),
+			Member::Event(event) => self.event_id(event),
+			Member::NestedType(ty) => ty.data().map(|data| self.type_id(data)),
+		}
+	}
+
+	fn parent_name(&self, parent: MetadataToken) -> Option<String> {
+		self.type_def(parent).map(|data| self.type_name(data))
+	}
+
+	fn type_def(&self, token: MetadataToken) -> Option<&'l TypeData> {
+		self.assembly.types.get()?.get(token.index().checked_sub(1)?)?.data()
+	}
+
+	/// The name of a type definition, with nested types following their enclosing type after a `.`.
+	fn type_name(&self, ty: &TypeData) -> String {
+		let (mut name, mut namespace) = (ty.name.to_string(), &ty.namespace);
+		let mut token = ty.token;
+		while let Some(outer) = self.enclosing.get(&token).and_then(|outer| self.type_def(*outer)) {
+			name = format!("{}.{}", outer.name, name);
+			namespace = &outer.namespace;
+			token = outer.token;
+		}
+
+		match namespace.is_empty() {
+			true => name,
+			false => format!("{}.{}", namespace, name),
+		}
+	}
+
+	/// The name of a TypeDef or TypeRef used in a signature.
+	fn token_name(&self, token: MetadataToken) -> String {
+		match token.token_kind() {
+			Some(MetadataTokenKind::TypeDef) => match self.type_def(token) {
+				Some(data) => self.type_name(data),
+				None => format!("{:?}", token),
+			},
+			Some(MetadataTokenKind::TypeRef) => {
+				let type_ref = token
+					.index()
+					.checked_sub(1)
+					.and_then(|i| self.assembly.type_refs.get(i));
+				let Some((scope, namespace, name)) = type_ref else { return format!("{:?}", token) };
+				match (scope.token_kind(), namespace.is_empty()) {
+					(Some(MetadataTokenKind::TypeRef), _) => format!("{}.{}", self.token_name(*scope), name),
+					(_, true) => name.clone(),
+					(_, false) => format!("{}.{}", namespace, name),
+				}
+			}
+			_ => format!("{:?}", token),
+		}
+	}
+
+	fn write_params(&self, id: &mut String, params: &[TypeSignature]) {
+		if params.is_empty() {
+			return;
+		}
+
+		id.push('(');
+		for (i, param) in params.iter().enumerate() {
+			if i != 0 {
+				id.push(',');
+			}
+			self.write_type(id, param);
+		}
+		id.push(')');
+	}
+
+	fn write_type(&self, id: &mut String, ty: &TypeSignature) {
+		let name = match ty {
+			TypeSignature::Void => "System.Void",
+			TypeSignature::Boolean => "System.Boolean",
+			TypeSignature::Char => "System.Char",
+			TypeSignature::Int8 => "System.SByte",
+			TypeSignature::UInt8 => "System.Byte",
+			TypeSignature::Int16 => "System.Int16",
+			TypeSignature::UInt16 => "System.UInt16",
+			TypeSignature::Int32 => "System.Int32",
+			TypeSignature::UInt32 => "System.UInt32",
+			TypeSignature::Int64 => "System.Int64",
+			TypeSignature::UInt64 => "System.UInt64",
+			TypeSignature::Float => "System.Single",
+			TypeSignature::Double => "System.Double",
+			TypeSignature::String => "System.String",
+			TypeSignature::Object => "System.Object",
+			TypeSignature::IntPtr => "System.IntPtr",
+			TypeSignature::UIntPtr => "System.UIntPtr",
+			TypeSignature::TypedByRef => "System.TypedReference",
+
+			TypeSignature::Class(token) | TypeSignature::ValueType(token) => {
+				return id.push_str(&self.token_name(*token));
+			}
+			TypeSignature::Var(index) => return id.push_str(&format!("`{}", index)),
+			TypeSignature::MVar(index) => return id.push_str(&format!("``{}", index)),
+
+			TypeSignature::Ptr(ty) => {
+				self.write_type(id, ty);
+				return id.push('*');
+			}
+			TypeSignature::ByRef(ty) => {
+				self.write_type(id, ty);
+				return id.push('@');
+			}
+			TypeSignature::SzArray(ty) => {
+				self.write_type(id, ty);
+				return id.push_str("[]");
+			}
+			TypeSignature::Array(ty, shape) => {
+				self.write_type(id, ty);
+				id.push('[');
+				for dimension in 0..shape.rank as usize {
+					if dimension != 0 {
+						id.push(',');
+					}
+					if let Some(lower_bound) = shape.lower_bounds.get(dimension) {
+						write!(id, "{}", lower_bound).ok();
+					}
+					id.push(':');
+					if let Some(size) = shape.sizes.get(dimension) {
+						write!(id, "{}", size).ok();
+					}
+				}
+				return id.push(']');
+			}
+
+			TypeSignature::GenericInst(ty, args) => {
+				let mut name = String::new();
+				self.write_type(&mut name, ty);
+				// Generic instantiations list their arguments instead of their arity.
+				id.push_str(strip_arity(&name));
+				id.push('{');
+				for (i, arg) in args.iter().enumerate() {
+					if i != 0 {
+						id.push(',');
+					}
+					self.write_type(id, arg);
+				}
+				return id.push('}');
+			}
+
+			TypeSignature::FnPtr(method) => return self.write_fn_ptr(id, method),
+			TypeSignature::Pinned(ty) => return self.write_type(id, ty),
+			TypeSignature::Modified { required, modifier, ty } => {
+				self.write_type(id, ty);
+				id.push(if *required { '|' } else { '!' });
+				return id.push_str(&self.token_name(*modifier));
+			}
+		};
+
+		id.push_str(name)
+	}
+
+	fn write_fn_ptr(&self, id: &mut String, method: &MethodSignature) {
+		id.push_str("=FUNC:");
+		self.write_type(id, &method.return_type);
+		self.write_params(id, &method.params);
+	}
+}
+
+/// Explicit interface implementations such as `System.IDisposable.Dispose` and constructors
+/// replace the dots of their names with `#`, and the brackets and commas of generic arguments with `{`, `}` and `@`.
+fn member_name(name: &str) -> String {
+	name.replace('.', "#")
+		.replace('<', "{")
+		.replace('>', "}")
+		.replace(',', "@")
+}
+
+/// What a documentation ID refers to, from the letter before its colon.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum DocIdKind {
+	Namespace,
+	Type,
+	Field,
+	Method,
+	Property,
+	Event,
+}
+
+impl DocIdKind {
+	pub fn prefix(&self) -> char {
+		match self {
+			DocIdKind::Namespace => 'N',
+			DocIdKind::Type => 'T',
+			DocIdKind::Field => 'F',
+			DocIdKind::Method => 'M',
+			DocIdKind::Property => 'P',
+			DocIdKind::Event => 'E',
+		}
+	}
+}
+
+/// A documentation ID split into its parts, e.g. `M:Ns.Type.Convert``1(System.String)~System.Int32`.
+///
+/// Parameter and return types are kept as written, since they can only be compared to other documentation IDs.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct DocId {
+	kind: DocIdKind,
+	name: String,
+	params: Vec<String>,
+	return_type: Option<String>,
+}
+
+impl DocId {
+	pub fn kind(&self) -> DocIdKind {
+		self.kind
+	}
+
+	/// The full name of the type or namespace, or of the declaring type of a member.
+	pub fn type_name(&self) -> &str {
+		match self.kind {
+			DocIdKind::Namespace | DocIdKind::Type => &self.name,
+			_ => self.name.rsplit_once('.').map_or("", |(ty, _)| ty),
+		}
+	}
+
+	/// The name of the member, with `#` in place of dots and the generic arity of methods, e.g. `#ctor` or `Map``1`.
+	pub fn member_name(&self) -> Option<&str> {
+		match self.kind {
+			DocIdKind::Namespace | DocIdKind::Type => None,
+			_ => Some(
+				self.name
+					.rsplit_once('.')
+					.map_or(self.name.as_str(), |(_, member)| member),
+			),
+		}
+	}
+
+	/// The number of generic parameters the type or method declares itself.
+	pub fn generic_arity(&self) -> u32 {
+		let name = self.member_name().unwrap_or(&self.name);
+		let name = name.rsplit('.').next().unwrap_or(name);
+		match name.rsplit_once('`') {
+			Some((_, arity)) => arity.parse().unwrap_or(0),
+			None => 0,
+		}
+	}
+
+	pub fn params(&self) -> &[String] {
+		&self.params
+	}
+
+	/// The return type of a conversion operator.
+	pub fn return_type(&self) -> Option<&str> {
+		self.return_type.as_deref()
+	}
+}
+
+impl Display for DocId {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}:{}", self.kind.prefix(), self.name)?;
+		if !self.params.is_empty() {
+			write!(f, "({})", self.params.join(","))?;
+		}
+		if let Some(return_type) = &self.return_type {
+			write!(f, "~{}", return_type)?;
+		}
+		Ok(())
+	}
+}
+
+impl FromStr for DocId {
+	type Err = raw::Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let error = raw::Error::InvalidData(Some("Invalid documentation ID"));
+		let (prefix, rest) = s.trim().split_once(':').ok_or(error)?;
+		let kind = match prefix {
+			"N" => DocIdKind::Namespace,
+			"T" => DocIdKind::Type,
+			"F" => DocIdKind::Field,
+			"M" => DocIdKind::Method,
+			"P" => DocIdKind::Property,
+			"E" => DocIdKind::Event,
+			_ => return Err(error),
+		};
+
+		let (rest, return_type) = match rest.rsplit_once('~') {
+			Some((rest, return_type)) if !return_type.contains(')') => (rest, Some(return_type.to_string())),
+			_ => (rest, None),
+		};
+
+		let (name, params) = match rest.split_once('(') {
+			None => (rest, vec![]),
+			Some((name, params)) => {
+				let params = params.strip_suffix(')').ok_or(error)?;
+				(name, split_params(params).ok_or(error)?)
+			}
+		};
+
+		if name.is_empty() || (kind != DocIdKind::Namespace && kind != DocIdKind::Type && !name.contains('.')) {
+			return Err(error);
+		}
+		Ok(Self {
+			kind,
+			name: name.to_string(),
+			params,
+			return_type,
+		})
+	}
+}
+
+/// Splits a parameter list on the commas that are not part of generic arguments or array bounds.
+fn split_params(params: &str) -> Option<Vec<String>> {
+	let (mut depth, mut start, mut result) = (0i32, 0, vec![]);
+	for (i, c) in params.char_indices() {
+		match c {
+			'{' | '[' | '(' => depth += 1,
+			'}' | ']' | ')' => depth -= 1,
+			',' if depth == 0 => {
+				result.push(params[start..i].to_string());
+				start = i + 1;
+			}
+			_ => {}
+		}
+		if depth < 0 {
+			return None;
+		}
+	}
+
+	result.push(params[start..].to_string());
+	match depth == 0 && result.iter().all(|param| !param.is_empty()) {
+		true => Some(result),
+		false => None,
+	}
+}
+
+/// A type or member found by [`Context::resolve_doc_id`].
+#[derive(Debug)]
+pub enum DocTarget<'l> {
+	Type(TypeRef),
+	Member(Member<'l>),
+}
+
+impl Context {
+	/// Finds the type or member a documentation ID refers to, in any assembly of the context.
+	/// Members are compared by their own documentation ID, so overloads are told apart by their parameter types.
+	///
+	/// `None` for namespaces, and for IDs that are malformed or match nothing.
+	pub fn resolve_doc_id(&self, id: &str) -> Option<DocTarget<'_>> {
+		let id = id.parse::<DocId>().ok()?;
+		if id.kind == DocIdKind::Namespace {
+			return None;
+		}
+
+		let normalized = id.to_string();
+		let name = id.member_name().map(member_base_name);
+		for assembly in self.assembly_vec.iter() {
+			let ids = assembly.doc_ids();
+			let Some(types) = assembly.types.get() else { continue };
+			for (index, ty) in types.iter().enumerate() {
+				let Some(data) = ty.data() else { continue };
+				if ids.type_name(data) != id.type_name() {
+					continue;
+				}
+
+				if id.kind == DocIdKind::Type {
+					return Some(DocTarget::Type(TypeRef::new(types.clone(), index)));
+				}
+
+				let mut members = Members::new(Some(data)).filter(|member| {
+					let kind = match member {
+						Member::Field(_) => DocIdKind::Field,
+						Member::Method(_) => DocIdKind::Method,
+						Member::Property(_) => DocIdKind::Property,
+						Member::Event(_) => DocIdKind::Event,
+						Member::NestedType(_) => DocIdKind::Type,
+					};
+					kind == id.kind && name == Some(member_name(member.name()).as_str())
+				});
+
+				if let Some(member) = members.find(|member| ids.member_id(member).as_deref() == Some(&normalized)) {
+					return Some(DocTarget::Member(member));
+				}
+			}
+		}
+
+		None
+	}
+}
+
+/// The name of a member without the generic arity of methods.
+fn member_base_name(name: &str) -> &str {
+	name.split_once("``").map_or(name, |(name, _)| name)
+}
+
+impl Member<'_> {
+	/// The documentation ID of the member, as generated by [`DocIds::member_id`].
+	/// Prefer [`Assembly::doc_ids`] when generating the IDs of many members.
+	pub fn doc_id(&self, assembly: &Assembly) -> Option<String> {
+		assembly.doc_ids().member_id(self)
+	}
+}
+
+impl TypeData {
+	/// The documentation ID of the type, e.g. `T:System.Collections.Generic.List`1`.
+	/// `None` once its assembly has been dropped.
+	pub fn doc_id(&self) -> Option<String> {
+		let assembly = self.assembly.upgrade()?;
+		let id = assembly.doc_ids().type_id(self);
+		Some(id)
+	}
+}
+
+/// The `<member>` of an XML documentation file, with its content kept as written.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct DocComment {
+	xml: String,
+}
+
+impl DocComment {
+	/// The content of the `<member>` element, e.g. `<summary>...</summary><param name="x">...</param>`.
+	pub fn xml(&self) -> &str {
+		&self.xml
+	}
+
+	pub fn summary(&self) -> Option<&str> {
+		self.element("summary", None)
+	}
+
+	pub fn remarks(&self) -> Option<&str> {
+		self.element("remarks", None)
+	}
+
+	pub fn returns(&self) -> Option<&str> {
+		self.element("returns", None)
+	}
+
+	pub fn param(&self, name: &str) -> Option<&str> {
+		self.element("param", Some(name))
+	}
+
+	pub fn type_param(&self, name: &str) -> Option<&str> {
+		self.element("typeparam", Some(name))
+	}
+
+	/// The trimmed content of the first `tag` element, or of the first one with the given `name` attribute.
+	/// Nested elements such as `<see cref="..."/>` are kept as written.
+	pub fn element(&self, tag: &str, name: Option<&str>) -> Option<&str> {
+		let mut rest = self.xml.as_str();
+		loop {
+			let start = rest.find('<')?;
+			rest = &rest[start + 1..];
+			let Some(after_tag) = rest.strip_prefix(tag) else { continue };
+			if !after_tag.starts_with(|c: char| c == '>' || c == '/' || c.is_whitespace()) {
+				continue;
+			}
+
+			let end = after_tag.find('>')?;
+			let (attributes, content) = (&after_tag[..end], &after_tag[end + 1..]);
+			if let Some(name) = name {
+				if attribute(attributes, "name").as_deref() != Some(name) {
+					continue;
+				}
+			}
+
+			if attributes.ends_with('/') {
+				return Some("");
+			}
+			let close = content.find(&format!("</{}>", tag))?;
+			return Some(content[..close].trim());
+		}
+	}
+}
+
+/// The comments of an XML documentation file, indexed by documentation ID.
+#[derive(Debug, Clone, Default)]
+pub struct Documentation {
+	members: HashMap<String, DocComment>,
+}
+
+impl Documentation {
+	/// Reads every `<member name="...">` element of a documentation file.
+	/// The parser is lenient: members it cannot make sense of are skipped rather than reported.
+	pub fn parse(xml: &str) -> Self {
+		let mut members = HashMap::new();
+		let mut rest = xml;
+		while let Some(start) = rest.find("<member ") {
+			rest = &rest[start + "<member ".len()..];
+			let Some(end) = rest.find('>') else { break };
+			let attributes = &rest[..end];
+			rest = &rest[end + 1..];
+
+			let Some(name) = attribute(attributes, "name") else { continue };
+			let xml = match attributes.ends_with('/') {
+				true => String::new(),
+				false => {
+					let Some(close) = rest.find("</member>") else { break };
+					let xml = rest[..close].trim().to_string();
+					rest = &rest[close..];
+					xml
+				}
+			};
+
+			members.insert(name, DocComment { xml });
+		}
+
+		Self { members }
+	}
+
+	pub fn load(path: impl AsRef<Path>) -> std::io::Result<Self> {
+		Ok(Self::parse(&std::fs::read_to_string(path)?))
+	}
+
+	/// The documentation file next to an assembly, with the same name and the `.xml` extension,
+	/// as written by the compiler and shipped in reference packs.
+	pub fn companion_path(assembly_path: &Path) -> Option<PathBuf> {
+		let path = assembly_path.with_extension("xml");
+		path.is_file().then_some(path)
+	}
+
+	/// Loads the companion documentation file of an assembly, if there is one.
+	pub fn discover(assembly_path: &Path) -> std::io::Result<Option<Self>> {
+		Self::companion_path(assembly_path).map(Self::load).transpose()
+	}
+
+	pub fn get(&self, id: &str) -> Option<&DocComment> {
+		self.members.get(id)
+	}
+
+	pub fn len(&self) -> usize {
+		self.members.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.members.is_empty()
+	}
+
+	pub fn iter(&self) -> impl Iterator<Item = (&str, &DocComment)> {
+		self.members.iter().map(|(id, comment)| (id.as_str(), comment))
+	}
+}
+
+/// The documentation of an assembly, looked up by the documentation IDs of its types and members.
+///
+/// ```ignore
+/// let docs = assembly.documentation()?.expect("no companion XML file");
+/// for method in ty.methods() {
+///     println!("{}: {:?}", method.name(), docs.method(method).and_then(DocComment::summary));
+/// }
+/// ```
+pub struct AssemblyDocs<'l> {
+	ids: DocIds<'l>,
+	documentation: Documentation,
+}
+
+impl<'l> AssemblyDocs<'l> {
+	pub fn new(assembly: &'l Assembly, documentation: Documentation) -> Self {
+		Self {
+			ids: assembly.doc_ids(),
+			documentation,
+		}
+	}
+
+	pub fn ids(&self) -> &DocIds<'l> {
+		&self.ids
+	}
+
+	pub fn documentation(&self) -> &Documentation {
+		&self.documentation
+	}
+
+	pub fn ty(&self, ty: &TypeData) -> Option<&DocComment> {
+		self.documentation.get(&self.ids.type_id(ty))
+	}
+
+	pub fn field(&self, field: &Field) -> Option<&DocComment> {
+		self.documentation.get(&self.ids.field_id(field)?)
+	}
+
+	pub fn method(&self, method: &Method) -> Option<&DocComment> {
+		self.documentation.get(&self.ids.method_id(method)?)
+	}
+
+	pub fn property(&self, property: &Property) -> Option<&DocComment> {
+		self.documentation.get(&self.ids.property_id(property)?)
+	}
+
+	pub fn event(&self, event: &Event) -> Option<&DocComment> {
+		self.documentation.get(&self.ids.event_id(event)?)
+	}
+
+	pub fn member(&self, member: &Member) -> Option<&DocComment> {
+		self.documentation.get(&self.ids.member_id(member)?)
+	}
+}
+
+impl Assembly {
+	/// Documentation IDs for the types and members of the assembly.
+	pub fn doc_ids(&self) -> DocIds<'_> {
+		DocIds::new(self)
+	}
+
+	/// Loads the companion documentation file of the assembly, see [`Documentation::discover`].
+	/// `None` if there is no such file, or the assembly was not loaded from a file.
+	pub fn documentation(&self) -> std::io::Result<Option<AssemblyDocs<'_>>> {
+		let Some(path) = self.path.as_deref() else { return Ok(None) };
+		let documentation = Documentation::discover(path)?;
+		Ok(documentation.map(|documentation| AssemblyDocs::new(self, documentation)))
+	}
+}
+
+/// The unescaped value of an attribute, in a list such as `name="x" cref="y"`.
+fn attribute(attributes: &str, name: &str) -> Option<String> {
+	let mut rest = attributes;
+	loop {
+		let start = rest.find(name)?;
+		let before = rest[..start].chars().next_back();
+		rest = &rest[start + name.len()..];
+		if before.is_some_and(|c| !c.is_whitespace()) {
+			continue;
+		}
+
+		let Some(value) = rest.trim_start().strip_prefix('=') else { continue };
+		let value = value.trim_start();
+		let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+		let end = value[1..].find(quote)?;
+		return Some(unescape(&value[1..end + 1]));
+	}
+}
+
+fn unescape(text: &str) -> String {
+	text.replace("&lt;", "<")
+		.replace("&gt;", ">")
+		.replace("&quot;", "\"")
+		.replace("&apos;", "'")
+		.replace("&amp;", "&")
+}
@@ -0,0 +1,133 @@
+use std::fmt::Display;
+use std::io;
+
+/// Streams listings such as [`Context::summary`](crate::schema::Context::summary) to an [`io::Write`],
+/// holding at most one chunk of output in memory at a time.
+///
+/// Formatting a listing of a large assembly into a `String` first can take hundreds of megabytes,
+/// while writing it to the output directly turns every formatted piece into a separate write.
+/// The writer implements both [`std::fmt::Write`] and [`io::Write`], so it can be passed to `write!` either way.
+///
+/// ```ignore
+/// let file = std::fs::File::create("CoreLib.txt")?;
+/// let mut writer = DumpWriter::new(file);
+/// writer.write_display(&context.summary())?;
+/// writer.finish()?;
+/// ```
+///
+/// Output still buffered when the writer is dropped is written on a best effort basis;
+/// [`DumpWriter::finish`] reports the errors instead.
+#[derive(Debug)]
+pub struct DumpWriter<W: io::Write> {
+	inner: Option<W>,
+	buffer: Vec<u8>,
+	chunk_size: usize,
+	/// The error behind the last [`std::fmt::Error`], which carries no information of its own.
+	error: Option<io::Error>,
+}
+
+impl<W: io::Write> DumpWriter<W> {
+	pub const DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
+
+	pub fn new(inner: W) -> Self {
+		Self {
+			inner: Some(inner),
+			buffer: Vec::with_capacity(Self::DEFAULT_CHUNK_SIZE),
+			chunk_size: Self::DEFAULT_CHUNK_SIZE,
+			error: None,
+		}
+	}
+
+	/// The amount of output gathered before it is passed on to the inner writer, at least one byte.
+	pub fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+		self.chunk_size = chunk_size.max(1);
+		self.buffer.reserve(self.chunk_size.saturating_sub(self.buffer.len()));
+		self
+	}
+
+	pub fn chunk_size(&self) -> usize {
+		self.chunk_size
+	}
+
+	pub fn get_ref(&self) -> &W {
+		self.inner.as_ref().expect("DumpWriter used after finish")
+	}
+
+	/// Formats `value` straight into the output, e.g. [`AssemblySummary`](crate::schema::AssemblySummary).
+	pub fn write_display(&mut self, value: &dyn Display) -> io::Result<()> {
+		match std::fmt::Write::write_fmt(self, format_args!("{}", value)) {
+			Ok(()) => Ok(()),
+			Err(_) => Err(self.error.take().unwrap_or_else(|| io::Error::other("formatter error"))),
+		}
+	}
+
+	/// Writes the remaining output and returns the inner writer.
+	pub fn finish(mut self) -> io::Result<W> {
+		io::Write::flush(&mut self)?;
+		Ok(self.inner.take().expect("DumpWriter used after finish"))
+	}
+
+	fn append(&mut self, bytes: &[u8]) -> io::Result<()> {
+		self.buffer.extend_from_slice(bytes);
+		match self.buffer.len() >= self.chunk_size {
+			true => self.write_buffer(),
+			false => Ok(()),
+		}
+	}
+
+	fn write_buffer(&mut self) -> io::Result<()> {
+		if self.buffer.is_empty() {
+			return Ok(());
+		}
+
+		let inner = self.inner.as_mut().expect("DumpWriter used after finish");
+		let result = inner.write_all(&self.buffer);
+		self.buffer.clear();
+		result
+	}
+}
+
+#[cfg(feature = "gzip")]
+impl<W: io::Write> DumpWriter<flate2::write::GzEncoder<W>> {
+	/// Compresses the output with gzip before passing it on to `inner`.
+	/// [`DumpWriter::finish_gzip`] writes the gzip trailer, which [`DumpWriter::finish`] leaves out.
+	pub fn gzip(inner: W) -> Self {
+		Self::new(flate2::write::GzEncoder::new(inner, flate2::Compression::default()))
+	}
+
+	pub fn finish_gzip(self) -> io::Result<W> {
+		self.finish()?.finish()
+	}
+}
+
+impl<W: io::Write> std::fmt::Write for DumpWriter<W> {
+	fn write_str(&mut self, s: &str) -> std::fmt::Result {
+		self.append(s.as_bytes()).map_err(|error| {
+			self.error = Some(error);
+			std::fmt::Error
+		})
+	}
+}
+
+impl<W: io::Write> io::Write for DumpWriter<W> {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		self.append(buf)?;
+		Ok(buf.len())
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		self.write_buffer()?;
+		match self.inner.as_mut() {
+			Some(inner) => inner.flush(),
+			None => Ok(()),
+		}
+	}
+}
+
+impl<W: io::Write> Drop for DumpWriter<W> {
+	fn drop(&mut self) {
+		if self.inner.is_some() {
+			let _ = self.write_buffer();
+		}
+	}
+}
@@ -0,0 +1,118 @@
+use crate::raw::{MetadataToken, MethodSignature, TypeSignature};
+use crate::schema::{Context, MethodRef, Type, TypeData, TypeRef};
+use std::collections::HashMap;
+
+pub(crate) const EXTENSION_ATTRIBUTE: &str = "System.Runtime.CompilerServices.ExtensionAttribute";
+
+/// An extension method, along with the type of its `this` parameter.
+#[derive(Debug, Clone)]
+pub struct ExtensionMethod {
+	pub(crate) method: MethodRef,
+	pub(crate) extended_type: TypeSignature,
+	pub(crate) extended_type_name: Option<String>,
+}
+
+impl ExtensionMethod {
+	pub fn method(&self) -> &MethodRef {
+		&self.method
+	}
+
+	/// The type of the `this` parameter, in terms of the tokens of the assembly declaring the method.
+	pub fn extended_type(&self) -> &TypeSignature {
+		&self.extended_type
+	}
+
+	/// The full name of the extended type definition, e.g. ``System.Collections.Generic.IEnumerable`1``
+	/// for every instantiation of it, or `None` for generic parameters, arrays and pointers.
+	pub fn extended_type_name(&self) -> Option<&str> {
+		self.extended_type_name.as_deref()
+	}
+}
+
+/// Every extension method of a context, grouped by the full name of the type they extend.
+#[derive(Debug, Default)]
+pub(crate) struct ExtensionIndex {
+	methods: Vec<ExtensionMethod>,
+	by_type: HashMap<String, Vec<usize>>,
+}
+
+impl Context {
+	/// Every extension method declared in the context, in load order.
+	pub fn extension_methods(&self) -> &[ExtensionMethod] {
+		&self.extension_index().methods
+	}
+
+	/// The extension methods whose `this` parameter is `ty` or an instantiation of it.
+	/// Methods extending the base types or interfaces of `ty` are not included.
+	pub fn extension_methods_for(&self, ty: &TypeData) -> impl Iterator<Item = &ExtensionMethod> {
+		let index = self.extension_index();
+		let name = match ty.namespace.is_empty() {
+			true => ty.name.to_string(),
+			false => format!("{}.{}", ty.namespace, ty.name),
+		};
+
+		let methods = index.by_type.get(&name).map(Vec::as_slice).unwrap_or_default();
+		methods.iter().filter_map(|method| index.methods.get(*method))
+	}
+
+	fn extension_index(&self) -> &ExtensionIndex {
+		self.extension_methods.get_or_init(|| {
+			let mut index = ExtensionIndex::default();
+			for assembly in self.assembly_vec.iter() {
+				let Some(types) = assembly.types.get() else { continue };
+				for (position, ty) in types.iter().enumerate() {
+					let Some(data) = ty.data().filter(|data| data.has_extension_methods()) else { continue };
+					for method in data.methods.iter().filter(|method| method.is_extension()) {
+						let Ok(signature) = MethodSignature::parse(&method.signature) else { continue };
+						let Some(extended_type) = signature.params.into_iter().next() else { continue };
+
+						let extended_type_name = extended_type_name(&extended_type, |token| assembly.type_name(token));
+						if let Some(name) = &extended_type_name {
+							index.by_type.entry(name.clone()).or_default().push(index.methods.len());
+						}
+
+						index.methods.push(ExtensionMethod {
+							method: MethodRef {
+								declaring_type: TypeRef::new(types.clone(), position),
+								token: method.token,
+							},
+							extended_type,
+							extended_type_name,
+						});
+					}
+				}
+			}
+
+			index
+		})
+	}
+}
+
+fn extended_type_name(ty: &TypeSignature, type_name: impl Fn(MetadataToken) -> Option<String>) -> Option<String> {
+	let name = match ty {
+		TypeSignature::Boolean => "System.Boolean",
+		TypeSignature::Char => "System.Char",
+		TypeSignature::Int8 => "System.SByte",
+		TypeSignature::UInt8 => "System.Byte",
+		TypeSignature::Int16 => "System.Int16",
+		TypeSignature::UInt16 => "System.UInt16",
+		TypeSignature::Int32 => "System.Int32",
+		TypeSignature::UInt32 => "System.UInt32",
+		TypeSignature::Int64 => "System.Int64",
+		TypeSignature::UInt64 => "System.UInt64",
+		TypeSignature::Float => "System.Single",
+		TypeSignature::Double => "System.Double",
+		TypeSignature::String => "System.String",
+		TypeSignature::Object => "System.Object",
+		TypeSignature::IntPtr => "System.IntPtr",
+		TypeSignature::UIntPtr => "System.UIntPtr",
+		TypeSignature::Class(token) | TypeSignature::ValueType(token) => return type_name(*token),
+		// `this ref T` and `this in T` extend T itself.
+		TypeSignature::GenericInst(ty, _) | TypeSignature::ByRef(ty) | TypeSignature::Modified { ty, .. } => {
+			return extended_type_name(ty, type_name)
+		}
+		_ => return None,
+	};
+
+	Some(name.to_string())
+}
@@ -0,0 +1,455 @@
+use crate::raw;
+use crate::raw::{
+	AssemblyRefTable, CodedIndexKind, Metadata, MetadataHeap, MetadataTable, MetadataToken, MetadataTokenKind,
+	MethodSignature, ModuleRefTable, NestedClassTable, StringHeap, TableHeap, TableIndex, TypeDefTable, TypeRefTable,
+	TypeSignature,
+};
+use crate::schema::Assembly;
+use std::fmt::{Formatter, Result, Write};
+
+/// How many enclosing types are followed when naming a nested type, so that cycles in malformed metadata end.
+pub(crate) const MAX_NESTING: usize = 64;
+
+/// The syntax names are rendered in, see [`NameStyle`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub enum NameSyntax {
+	/// `ref int`, `int*`, `delegate*<int, void>`, ignoring custom modifiers and pinning.
+	#[default]
+	CSharp,
+	/// `int32&`, `int32*`, `method void *(int32)`, `int32 modreq(System.Runtime.CompilerServices.IsVolatile)`.
+	Il,
+}
+
+/// How the names of types are rendered by [`SignatureFormatter`], [`Context::display`](crate::schema::Context::display)
+/// and the displays of members, set for each assembly with [`Assembly::set_name_style`]
+/// or for every assembly of a context with [`Context::set_name_style`](crate::schema::Context::set_name_style).
+///
+/// The default is [`NameStyle::CSHARP_QUALIFIED`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct NameStyle {
+	pub syntax: NameSyntax,
+	/// Whether type names include their namespace, e.g. `System.Collections.Generic.List` rather than `List`.
+	pub namespaces: bool,
+	/// Separates nested types from their enclosing type, `.` in C# and `/` in IL.
+	pub nested_separator: char,
+	/// Whether primitive types are rendered as keywords, e.g. `int` or `int32`, rather than as `System.Int32`.
+	pub keywords: bool,
+	/// Whether type names keep the number of their generic parameters, e.g. ``List`1<int>``.
+	pub generic_arity: bool,
+}
+
+impl NameStyle {
+	/// `List<int>`
+	pub const CSHARP: Self = Self {
+		syntax: NameSyntax::CSharp,
+		namespaces: false,
+		nested_separator: '.',
+		keywords: true,
+		generic_arity: false,
+	};
+
+	/// `System.Collections.Generic.List<int>`
+	pub const CSHARP_QUALIFIED: Self = Self {
+		namespaces: true,
+		..Self::CSHARP
+	};
+
+	/// ``System.Collections.Generic.List`1<int32>``
+	pub const IL: Self = Self {
+		syntax: NameSyntax::Il,
+		namespaces: true,
+		nested_separator: '/',
+		keywords: true,
+		generic_arity: true,
+	};
+
+	/// Joins the namespace of the outermost type to the names of the types enclosing a type, outermost first,
+	/// and to the name of the type itself.
+	pub fn join<'l>(&self, namespace: &str, names: impl IntoIterator<Item = &'l str>) -> String {
+		let mut joined = match self.namespaces && !namespace.is_empty() {
+			true => format!("{}.", namespace),
+			false => String::new(),
+		};
+
+		for (i, name) in names.into_iter().enumerate() {
+			if i != 0 {
+				joined.push(self.nested_separator);
+			}
+
+			match self.generic_arity {
+				true => joined.push_str(name),
+				false => joined.push_str(strip_arity(name)),
+			}
+		}
+
+		joined
+	}
+}
+
+impl Default for NameStyle {
+	fn default() -> Self {
+		Self::CSHARP_QUALIFIED
+	}
+}
+
+/// Names the types that signatures refer to by token.
+pub trait TypeNameResolver {
+	/// The name of the TypeDef or TypeRef `token`, or `None` to render the token itself.
+	fn type_name(&self, token: MetadataToken) -> Option<String>;
+
+	/// The name of the TypeDef or TypeRef `token` rendered in `style`.
+	/// By default, [`TypeNameResolver::type_name`] with its generic arity removed unless `style` keeps it.
+	fn styled_type_name(&self, token: MetadataToken, style: &NameStyle) -> Option<String> {
+		let name = self.type_name(token)?;
+		match style.generic_arity {
+			true => Some(name),
+			false => Some(strip_arity(&name).to_string()),
+		}
+	}
+}
+
+/// Resolves type names through the loaded assembly, whose references have been matched against a [`Context`](crate::schema::Context).
+impl TypeNameResolver for Assembly {
+	fn type_name(&self, token: MetadataToken) -> Option<String> {
+		Assembly::type_name(self, token)
+	}
+
+	fn styled_type_name(&self, token: MetadataToken, style: &NameStyle) -> Option<String> {
+		Assembly::styled_type_name(self, token, style)
+	}
+}
+
+/// Names types from the TypeDef, TypeRef and AssemblyRef rows of a single module, without loading it into a context.
+///
+/// [`TypeNameResolver::type_name`] renders references as in IL, e.g. ``[System.Runtime]System.Collections.Generic.List`1``,
+/// and nested types are separated from their enclosing type by a `/`. Styled names only name the scope of references
+/// in [`NameSyntax::Il`].
+pub struct MetadataTypeNames<'l> {
+	tables: TableHeap<'l>,
+	strings: StringHeap<'l>,
+}
+
+impl<'l> MetadataTypeNames<'l> {
+	pub fn new(metadata: &Metadata<'l>) -> std::result::Result<Self, raw::Error> {
+		let tables = metadata
+			.tables()?
+			.ok_or(raw::Error::InvalidData(Some("Missing metadata tables")))?;
+
+		Ok(Self {
+			tables,
+			strings: metadata.get_heap()?.unwrap_or_else(StringHeap::empty),
+		})
+	}
+
+	fn type_def_name(&self, index: TableIndex, style: &NameStyle) -> Option<String> {
+		let table = self.tables.get_table::<TypeDefTable>().ok()??;
+		let nested = self.tables.get_table::<NestedClassTable>().ok()?;
+
+		let mut names = vec![];
+		let mut index = index;
+		for _ in 0..MAX_NESTING {
+			let row = table.get(index).ok()?;
+			names.push(self.strings.get_string(row.name()));
+
+			let enclosing = nested.as_ref().and_then(|nested| {
+				let mut rows = nested.iter().filter_map(|row| row.ok());
+				rows.find(|row| row.nested_class() == index)
+					.map(|row| row.enclosing_class())
+			});
+
+			match enclosing {
+				Some(enclosing) => index = enclosing,
+				None => {
+					let namespace = self.strings.get_string(row.namespace());
+					return Some(style.join(namespace, names.into_iter().rev()));
+				}
+			}
+		}
+
+		None
+	}
+
+	fn type_ref_name(&self, index: TableIndex, style: &NameStyle, with_scope: bool) -> Option<String> {
+		let table = self.tables.get_table::<TypeRefTable>().ok()??;
+
+		let mut names = vec![];
+		let mut index = index;
+		for _ in 0..MAX_NESTING {
+			let row = table.get(index).ok()?;
+			names.push(self.strings.get_string(row.type_name()));
+
+			let scope = row.resolution_scope().decode(CodedIndexKind::ResolutionScope)?;
+			let scope_index = TableIndex(scope.index() as u32);
+			let name = || style.join(self.strings.get_string(row.type_namespace()), names.iter().rev().copied());
+			let scope_name = match scope.token_kind() {
+				Some(MetadataTokenKind::TypeRef) => {
+					index = scope_index;
+					continue;
+				}
+				_ if !with_scope => return Some(name()),
+				Some(MetadataTokenKind::AssemblyRef) => {
+					let table = self.tables.get_table::<AssemblyRefTable>().ok()??;
+					self.strings.get_string(table.get(scope_index).ok()?.name()).to_string()
+				}
+				Some(MetadataTokenKind::ModuleRef) => {
+					let table = self.tables.get_table::<ModuleRefTable>().ok()??;
+					format!(".module {}", self.strings.get_string(table.get(scope_index).ok()?.name()))
+				}
+				_ => return Some(name()),
+			};
+
+			return Some(format!("[{}]{}", scope_name, name()));
+		}
+
+		None
+	}
+}
+
+impl TypeNameResolver for MetadataTypeNames<'_> {
+	fn type_name(&self, token: MetadataToken) -> Option<String> {
+		let index = TableIndex(token.index() as u32);
+		match token.token_kind()? {
+			MetadataTokenKind::TypeDef => self.type_def_name(index, &NameStyle::IL),
+			MetadataTokenKind::TypeRef => self.type_ref_name(index, &NameStyle::IL, true),
+			_ => None,
+		}
+	}
+
+	fn styled_type_name(&self, token: MetadataToken, style: &NameStyle) -> Option<String> {
+		let index = TableIndex(token.index() as u32);
+		match token.token_kind()? {
+			MetadataTokenKind::TypeDef => self.type_def_name(index, style),
+			MetadataTokenKind::TypeRef => self.type_ref_name(index, style, style.syntax == NameSyntax::Il),
+			_ => None,
+		}
+	}
+}
+
+/// Renders signatures in a [`NameStyle`], naming the types they refer to by token through a [`TypeNameResolver`].
+///
+/// ```ignore
+/// let names = MetadataTypeNames::new(&assembly.metadata())?;
+/// let formatter = SignatureFormatter::with_resolver(&names).with_style(NameStyle::IL);
+/// println!("{}", formatter.type_to_string(&signature.return_type));
+/// ```
+pub struct SignatureFormatter<'l> {
+	resolver: Option<&'l dyn TypeNameResolver>,
+	style: NameStyle,
+	/// Rendered in place of the generic parameters of the type, see [`SignatureFormatter::with_type_arguments`].
+	type_arguments: &'l [String],
+}
+
+impl<'l> SignatureFormatter<'l> {
+	/// Names types through `assembly`, in the [`NameStyle`] of the assembly.
+	pub(crate) fn new(assembly: Option<&'l Assembly>) -> Self {
+		Self {
+			resolver: assembly.map(|assembly| assembly as &dyn TypeNameResolver),
+			style: assembly.map(Assembly::name_style).unwrap_or_default(),
+			type_arguments: &[],
+		}
+	}
+
+	pub fn with_resolver(resolver: &'l dyn TypeNameResolver) -> Self {
+		Self {
+			resolver: Some(resolver),
+			style: NameStyle::default(),
+			type_arguments: &[],
+		}
+	}
+
+	/// Renders every type as its token, for when no names are available.
+	pub fn without_names() -> Self {
+		Self {
+			resolver: None,
+			style: NameStyle::default(),
+			type_arguments: &[],
+		}
+	}
+
+	pub fn with_style(mut self, style: NameStyle) -> Self {
+		self.style = style;
+		self
+	}
+
+	pub fn style(&self) -> &NameStyle {
+		&self.style
+	}
+
+	/// Renders the generic parameters of the type, `!0`, `!1` and so on, as the given arguments,
+	/// e.g. to compare the signatures of a generic interface with those of a type implementing an instantiation of it.
+	pub(crate) fn with_type_arguments(mut self, type_arguments: &'l [String]) -> Self {
+		self.type_arguments = type_arguments;
+		self
+	}
+
+	pub fn write_token(&self, f: &mut Formatter, token: MetadataToken) -> Result {
+		match self
+			.resolver
+			.and_then(|resolver| resolver.styled_type_name(token, &self.style))
+		{
+			Some(name) => f.write_str(&name),
+			None => write!(f, "{:?}", token),
+		}
+	}
+
+	pub fn write_type(&self, f: &mut Formatter, ty: &TypeSignature) -> Result {
+		let il = self.style.syntax == NameSyntax::Il;
+		let (keyword, il_keyword, name) = match ty {
+			TypeSignature::Void => ("void", "void", "Void"),
+			TypeSignature::Boolean => ("bool", "bool", "Boolean"),
+			TypeSignature::Char => ("char", "char", "Char"),
+			TypeSignature::Int8 => ("sbyte", "int8", "SByte"),
+			TypeSignature::UInt8 => ("byte", "uint8", "Byte"),
+			TypeSignature::Int16 => ("short", "int16", "Int16"),
+			TypeSignature::UInt16 => ("ushort", "uint16", "UInt16"),
+			TypeSignature::Int32 => ("int", "int32", "Int32"),
+			TypeSignature::UInt32 => ("uint", "uint32", "UInt32"),
+			TypeSignature::Int64 => ("long", "int64", "Int64"),
+			TypeSignature::UInt64 => ("ulong", "uint64", "UInt64"),
+			TypeSignature::Float => ("float", "float32", "Single"),
+			TypeSignature::Double => ("double", "float64", "Double"),
+			TypeSignature::String => ("string", "string", "String"),
+			TypeSignature::Object => ("object", "object", "Object"),
+			TypeSignature::IntPtr => ("nint", "native int", "IntPtr"),
+			TypeSignature::UIntPtr => ("nuint", "native uint", "UIntPtr"),
+			// C# has no keyword for typed references.
+			TypeSignature::TypedByRef if il => ("", "typedref", "TypedReference"),
+			TypeSignature::TypedByRef => return f.write_str(&self.style.join("System", ["TypedReference"])),
+
+			TypeSignature::Class(token) | TypeSignature::ValueType(token) => return self.write_token(f, *token),
+			TypeSignature::Var(index) => match self.type_arguments.get(*index as usize) {
+				Some(argument) => return f.write_str(argument),
+				None => return write!(f, "!{}", index),
+			},
+			TypeSignature::MVar(index) => return write!(f, "!!{}", index),
+
+			TypeSignature::Ptr(ty) => {
+				self.write_type(f, ty)?;
+				return f.write_char('*');
+			}
+
+			TypeSignature::ByRef(ty) if il => {
+				self.write_type(f, ty)?;
+				return f.write_char('&');
+			}
+
+			TypeSignature::ByRef(ty) => {
+				f.write_str("ref ")?;
+				return self.write_type(f, ty);
+			}
+
+			TypeSignature::SzArray(ty) => {
+				self.write_type(f, ty)?;
+				return f.write_str("[]");
+			}
+
+			// ilasm syntax, where `2...5` is a dimension with a lower bound of 2 and a size of 4, and a lone `5` a size.
+			TypeSignature::Array(ty, shape) if il => {
+				self.write_type(f, ty)?;
+				f.write_char('[')?;
+				for (index, dimension) in shape.dimensions().enumerate() {
+					if index != 0 {
+						f.write_char(',')?;
+					}
+
+					match (dimension.lower_bound, dimension.size, dimension.upper_bound()) {
+						(Some(lower_bound), _, Some(upper_bound)) => write!(f, "{}...{}", lower_bound, upper_bound)?,
+						// An empty dimension has no upper bound to write, only its size.
+						(_, Some(size), None) => write!(f, "{}", size)?,
+						(Some(lower_bound), None, None) => write!(f, "{}...", lower_bound)?,
+						(None, Some(size), _) => write!(f, "{}", size)?,
+						// Without bounds, a single dimension would read as a vector.
+						(None, None, _) if shape.rank == 1 => f.write_str("...")?,
+						(None, None, _) => {}
+					}
+				}
+				return f.write_char(']');
+			}
+
+			TypeSignature::Array(ty, shape) => {
+				self.write_type(f, ty)?;
+				f.write_char('[')?;
+				for _ in 1..shape.rank {
+					f.write_char(',')?;
+				}
+				return f.write_char(']');
+			}
+
+			TypeSignature::GenericInst(ty, args) => {
+				self.write_type(f, ty)?;
+				f.write_char('<')?;
+				self.write_list(f, args)?;
+				return f.write_char('>');
+			}
+
+			TypeSignature::FnPtr(method) => return self.write_fn_ptr(f, method),
+
+			TypeSignature::Pinned(ty) if il => {
+				self.write_type(f, ty)?;
+				return f.write_str(" pinned");
+			}
+
+			TypeSignature::Modified { required, modifier, ty } if il => {
+				self.write_type(f, ty)?;
+				f.write_str(if *required { " modreq(" } else { " modopt(" })?;
+				self.write_token(f, *modifier)?;
+				return f.write_char(')');
+			}
+
+			TypeSignature::Pinned(ty) | TypeSignature::Modified { ty, .. } => return self.write_type(f, ty),
+		};
+
+		match (self.style.keywords, il) {
+			(true, true) => f.write_str(il_keyword),
+			(true, false) => f.write_str(keyword),
+			(false, _) => f.write_str(&self.style.join("System", [name])),
+		}
+	}
+
+	pub fn type_to_string(&self, ty: &TypeSignature) -> String {
+		struct TypeDisplay<'l>(&'l SignatureFormatter<'l>, &'l TypeSignature);
+		impl std::fmt::Display for TypeDisplay<'_> {
+			fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+				self.0.write_type(f, self.1)
+			}
+		}
+
+		TypeDisplay(self, ty).to_string()
+	}
+
+	pub fn write_list(&self, f: &mut Formatter, types: &[TypeSignature]) -> Result {
+		for (i, ty) in types.iter().enumerate() {
+			if i != 0 {
+				f.write_str(", ")?;
+			}
+			self.write_type(f, ty)?;
+		}
+		Ok(())
+	}
+
+	fn write_fn_ptr(&self, f: &mut Formatter, method: &MethodSignature) -> Result {
+		if self.style.syntax == NameSyntax::Il {
+			f.write_str("method ")?;
+			self.write_type(f, &method.return_type)?;
+			f.write_str(" *(")?;
+			self.write_list(f, &method.params)?;
+			return f.write_char(')');
+		}
+
+		f.write_str("delegate*<")?;
+		for ty in method.params.iter() {
+			self.write_type(f, ty)?;
+			f.write_str(", ")?;
+		}
+		self.write_type(f, &method.return_type)?;
+		f.write_char('>')
+	}
+}
+
+/// Removes the generic arity suffix (e.g. the `` `1`` in ``List`1``) from a type name.
+pub(crate) fn strip_arity(name: &str) -> &str {
+	match name.rsplit_once('`') {
+		Some((name, arity)) if !arity.is_empty() && arity.bytes().all(|c| c.is_ascii_digit()) => name,
+		_ => name,
+	}
+}
@@ -0,0 +1,230 @@
+use crate::raw::{MetadataToken, MetadataTokenKind, TypeSignature};
+use crate::schema::{Assembly, Context, TypeData};
+use std::fmt::{Display, Formatter};
+use std::rc::Rc;
+
+/// What [`Context::export_dot`] includes in the graph.
+///
+/// Every relation is drawn by default, for the types of every assembly of the context.
+/// Edges to types or assemblies left out by the filters are left out as well.
+#[derive(Debug, Clone)]
+pub struct DotOptions {
+	inheritance: bool,
+	interfaces: bool,
+	dependencies: bool,
+	types: bool,
+	namespaces: Vec<String>,
+	assemblies: Vec<String>,
+}
+
+impl Default for DotOptions {
+	fn default() -> Self {
+		Self {
+			inheritance: true,
+			interfaces: true,
+			dependencies: true,
+			types: true,
+			namespaces: vec![],
+			assemblies: vec![],
+		}
+	}
+}
+
+impl DotOptions {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Draws an edge from every type to its base type.
+	pub fn inheritance(mut self, inheritance: bool) -> Self {
+		self.inheritance = inheritance;
+		self
+	}
+
+	/// Draws an edge from every type to the interfaces it implements directly.
+	pub fn interfaces(mut self, interfaces: bool) -> Self {
+		self.interfaces = interfaces;
+		self
+	}
+
+	/// Draws an edge from every assembly to the assemblies its references resolved to.
+	pub fn dependencies(mut self, dependencies: bool) -> Self {
+		self.dependencies = dependencies;
+		self
+	}
+
+	/// Draws the types of the assemblies, disable to draw only their dependencies.
+	pub fn types(mut self, types: bool) -> Self {
+		self.types = types;
+		self
+	}
+
+	/// Keeps the types of `namespace` and of the namespaces nested in it.
+	/// Can be given more than once, and keeps every type if never given.
+	pub fn namespace(mut self, namespace: impl Into<String>) -> Self {
+		self.namespaces.push(namespace.into());
+		self
+	}
+
+	/// Keeps the assembly with the simple name `name`, e.g. `System.Runtime`.
+	/// Can be given more than once, and keeps every assembly if never given.
+	pub fn assembly(mut self, name: impl Into<String>) -> Self {
+		self.assemblies.push(name.into());
+		self
+	}
+
+	fn includes_assembly(&self, assembly: &Assembly) -> bool {
+		self.assemblies.is_empty() || self.assemblies.contains(&assembly.name.name)
+	}
+
+	fn includes_type(&self, data: &TypeData) -> bool {
+		self.namespaces.is_empty()
+			|| self.namespaces.iter().any(|namespace| match data.namespace.strip_prefix(namespace.as_str()) {
+				Some(rest) => rest.is_empty() || rest.starts_with('.'),
+				None => false,
+			})
+	}
+}
+
+/// A GraphViz graph of the assemblies of a context, see [`Context::export_dot`].
+pub struct DotGraph<'l> {
+	context: &'l Context,
+	options: DotOptions,
+}
+
+impl Context {
+	/// Renders the assemblies of the context and their types as a GraphViz graph, to be written out with
+	/// [`DumpWriter`](crate::schema::DumpWriter) and laid out with e.g. `dot -Tsvg`.
+	///
+	/// Each assembly is a cluster holding its types, with its dependencies drawn between the clusters.
+	/// Base types are drawn above the types deriving from them, with solid edges,
+	/// and interfaces with dashed edges. Types of assemblies missing from the context are left out.
+	pub fn export_dot(&self, options: DotOptions) -> DotGraph<'_> {
+		DotGraph { context: self, options }
+	}
+}
+
+impl Display for DotGraph<'_> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		let options = &self.options;
+		let assemblies = &self.context.assembly_vec;
+		writeln!(f, "digraph context {{")?;
+		writeln!(f, "\trankdir=BT;")?;
+		writeln!(f, "\tnode [shape=box];")?;
+
+		for (index, assembly) in assemblies.iter().enumerate() {
+			if !options.includes_assembly(assembly) {
+				continue;
+			}
+
+			writeln!(f, "\tsubgraph cluster_{} {{", index)?;
+			writeln!(f, "\t\tlabel={};", quote(&assembly.name.to_string()))?;
+			writeln!(f, "\t\ta{} [shape=component, label={}];", index, quote(&assembly.name.name))?;
+			for data in self.types_of(assembly) {
+				writeln!(f, "\t\t{} [label={}];", type_node(index, data.token), quote(&full_name(data)))?;
+			}
+			writeln!(f, "\t}}")?;
+		}
+
+		for (index, assembly) in assemblies.iter().enumerate() {
+			if !options.includes_assembly(assembly) {
+				continue;
+			}
+
+			if options.dependencies {
+				let mut targets = assembly
+					.dependencies
+					.iter()
+					.filter_map(|dependency| self.context.reference_map.get(&dependency.ident_key).copied())
+					.filter(|target| *target != index)
+					.filter(|target| assemblies.get(*target).is_some_and(|target| options.includes_assembly(target)))
+					.collect::<Vec<_>>();
+				targets.sort_unstable();
+				targets.dedup();
+
+				for target in targets {
+					writeln!(f, "\ta{} -> a{} [style=dotted];", index, target)?;
+				}
+			}
+
+			for data in self.types_of(assembly) {
+				let node = type_node(index, data.token);
+				if options.inheritance && !data.base.is_null() {
+					if let Some(base) = self.resolve(assembly, data.base) {
+						writeln!(f, "\t{} -> {} [arrowhead=empty];", node, base)?;
+					}
+				}
+
+				if !options.interfaces {
+					continue;
+				}
+
+				for interface in data.interfaces.iter() {
+					if let Some(interface) = self.resolve(assembly, interface.interface) {
+						writeln!(f, "\t{} -> {} [arrowhead=empty, style=dashed];", node, interface)?;
+					}
+				}
+			}
+		}
+
+		writeln!(f, "}}")
+	}
+}
+
+impl DotGraph<'_> {
+	/// The types of `assembly` drawn in the graph, leaving out the `<Module>` type holding its global members.
+	fn types_of<'l>(&'l self, assembly: &'l Assembly) -> impl Iterator<Item = &'l TypeData> + 'l {
+		let options = &self.options;
+		assembly
+			.types()
+			.iter()
+			.filter_map(|ty| ty.data())
+			.filter(|data| data.token.index() != 1)
+			.filter(move |data| options.types && options.includes_type(data))
+	}
+
+	/// The node of the type a TypeDef, TypeRef or generic TypeSpec of `assembly` designates, if it is drawn.
+	fn resolve(&self, assembly: &Assembly, token: MetadataToken) -> Option<String> {
+		let token = match token.token_kind()? {
+			MetadataTokenKind::TypeDef | MetadataTokenKind::TypeRef => token,
+			MetadataTokenKind::TypeSpec => match &assembly.type_spec(token)?.signature {
+				TypeSignature::GenericInst(ty, _) => match ty.as_ref() {
+					TypeSignature::Class(token) | TypeSignature::ValueType(token) => *token,
+					_ => return None,
+				},
+				_ => return None,
+			},
+			_ => return None,
+		};
+
+		let ty = assembly.get_type(token)?;
+		let data = ty.data()?;
+		let target = data.assembly.upgrade()?;
+		let index = self
+			.context
+			.assembly_vec
+			.iter()
+			.position(|candidate| Rc::ptr_eq(candidate, &target))?;
+
+		let drawn = data.token.index() != 1
+			&& self.options.includes_assembly(&target)
+			&& self.options.includes_type(data);
+		drawn.then(|| type_node(index, data.token))
+	}
+}
+
+fn type_node(assembly: usize, token: MetadataToken) -> String {
+	format!("t{}_{:08X}", assembly, token)
+}
+
+fn full_name(data: &TypeData) -> String {
+	match data.namespace.is_empty() {
+		true => data.name.to_string(),
+		false => format!("{}.{}", data.namespace, data.name),
+	}
+}
+
+/// Quotes `value` as a DOT string.
+fn quote(value: &str) -> String {
+	format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
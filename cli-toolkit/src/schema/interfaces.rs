@@ -0,0 +1,328 @@
+use crate::raw::{method_flags, MetadataToken, MetadataTokenKind, TypeSignature};
+use crate::schema::{Assembly, Method, MethodRef, NameStyle, SignatureFormatter, Type, TypeData, TypeRef};
+use std::rc::Rc;
+
+/// How an interface method is implemented, see [`Type::interface_map`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum ImplementationKind {
+	/// By a method naming the interface method in the MethodImpl table, e.g. `void IDisposable.Dispose()`.
+	Explicit,
+	/// By a public virtual method with the same name and signature.
+	Implicit,
+	/// By the body of the interface method itself, or by a more specific interface overriding it.
+	Default,
+}
+
+/// A method of an interface implemented by a type, along with the method implementing it.
+#[derive(Debug, Clone)]
+pub struct InterfaceMapping {
+	pub(crate) interface_method: MethodRef,
+	pub(crate) type_arguments: Vec<String>,
+	pub(crate) implementation: Option<(MethodRef, ImplementationKind)>,
+}
+
+impl InterfaceMapping {
+	/// The method of the generic interface definition, whose declaring type is the interface.
+	pub fn interface_method(&self) -> &MethodRef {
+		&self.interface_method
+	}
+
+	/// The type arguments the interface is implemented with, e.g. `int` for `IEquatable<int>`.
+	/// Empty for interfaces that are not generic.
+	pub fn type_arguments(&self) -> &[String] {
+		&self.type_arguments
+	}
+
+	/// The method implementing the interface method, which may be declared by a base type or by another interface.
+	/// `None` if the method is not implemented, which only happens for abstract types and broken metadata.
+	pub fn implementation(&self) -> Option<&MethodRef> {
+		self.implementation.as_ref().map(|(method, _)| method)
+	}
+
+	pub fn kind(&self) -> Option<ImplementationKind> {
+		self.implementation.as_ref().map(|(_, kind)| *kind)
+	}
+}
+
+/// The interface methods of a type and the methods implementing them, see [`Type::interface_map`].
+#[derive(Debug, Clone, Default)]
+pub struct InterfaceMap {
+	mappings: Vec<InterfaceMapping>,
+}
+
+impl InterfaceMap {
+	/// Every method of every interface the type implements, interface by interface.
+	pub fn mappings(&self) -> &[InterfaceMapping] {
+		&self.mappings
+	}
+
+	/// The method implementing `interface_method`, for the first instantiation of its interface the type implements.
+	pub fn implementation_of(&self, interface_method: &MethodRef) -> Option<&MethodRef> {
+		let mut mappings = self.mappings.iter().filter(|mapping| {
+			mapping.interface_method.token == interface_method.token
+				&& same_type(&mapping.interface_method.declaring_type, &interface_method.declaring_type)
+		});
+		mappings.next()?.implementation()
+	}
+
+	/// The interface methods the type leaves unimplemented.
+	pub fn unimplemented(&self) -> impl Iterator<Item = &InterfaceMapping> {
+		self.mappings.iter().filter(|mapping| mapping.implementation.is_none())
+	}
+
+	pub fn len(&self) -> usize {
+		self.mappings.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.mappings.is_empty()
+	}
+}
+
+/// A type along with the type arguments of its instantiation, rendered in terms of the type being mapped.
+struct Instance {
+	ty: TypeRef,
+	assembly: Rc<Assembly>,
+	arguments: Vec<String>,
+}
+
+impl Instance {
+	fn data(&self) -> &TypeData {
+		self.ty.data().expect("instances are only created for type definitions")
+	}
+
+	/// Resolves a TypeDef, TypeRef or generic TypeSpec of this type, e.g. its base type or one of its interfaces.
+	fn instantiate(&self, token: MetadataToken) -> Option<Instance> {
+		let (token, arguments) = match token.token_kind()? {
+			MetadataTokenKind::TypeDef | MetadataTokenKind::TypeRef => (token, vec![]),
+			MetadataTokenKind::TypeSpec => {
+				let TypeSignature::GenericInst(ty, arguments) = &self.assembly.type_spec(token)?.signature else {
+					return None;
+				};
+				let (TypeSignature::Class(token) | TypeSignature::ValueType(token)) = ty.as_ref() else {
+					return None;
+				};
+				let formatter = self.formatter();
+				let arguments = arguments.iter().map(|argument| formatter.type_to_string(argument));
+				(*token, arguments.collect())
+			}
+			_ => return None,
+		};
+
+		let ty = self.assembly.get_type(token)?;
+		let assembly = ty.data()?.assembly.upgrade()?;
+		Some(Instance {
+			ty,
+			assembly,
+			arguments,
+		})
+	}
+
+	/// Renders the signatures of the members of this type, with its generic parameters replaced by its arguments.
+	fn formatter(&self) -> SignatureFormatter<'_> {
+		SignatureFormatter::new(Some(&self.assembly))
+			.with_style(NameStyle::default())
+			.with_type_arguments(&self.arguments)
+	}
+
+	fn is(&self, other: &Instance) -> bool {
+		same_type(&self.ty, &other.ty) && self.arguments == other.arguments
+	}
+
+	fn method_ref(&self, method: &Method) -> MethodRef {
+		MethodRef {
+			declaring_type: self.ty.clone(),
+			token: method.token,
+		}
+	}
+}
+
+fn same_type(a: &Type, b: &Type) -> bool {
+	match (a.data(), b.data()) {
+		(Some(a), Some(b)) => a.token == b.token && a.assembly.ptr_eq(&b.assembly),
+		_ => false,
+	}
+}
+
+impl Type {
+	/// Maps the methods of every interface the type implements, directly, through its base types
+	/// or through other interfaces, to the methods implementing them.
+	///
+	/// Each interface method is looked up in the type, then in its base types, most derived first.
+	/// At each level, methods naming the interface method in the MethodImpl table come before public virtual methods
+	/// with the same name and signature, where the generic parameters of the interface are replaced by the arguments
+	/// it is implemented with. Interface methods that are still unimplemented fall back to the overrides
+	/// declared by other interfaces of the type, and then to their own body, if they have one.
+	///
+	/// Interfaces have no map of their own. Types of assemblies missing from the context are left out,
+	/// so that their interfaces and the methods they implement are missing from the map.
+	pub fn interface_map(&self) -> InterfaceMap {
+		let mut map = InterfaceMap::default();
+		let Some(data) = self.data().filter(|_| !matches!(self, Type::Interface(_))) else { return map };
+		let Some(assembly) = data.assembly.upgrade() else { return map };
+		let Some(ctx) = assembly.ctx.borrow().upgrade() else { return map };
+		let Some(ty) = assembly.get_type(data.token) else { return map };
+
+		// The type and its base types, most derived first.
+		let mut chain = vec![Instance {
+			ty,
+			assembly,
+			arguments: vec![],
+		}];
+		while chain.len() < ctx.max_resolution_depth() {
+			let current = &chain[chain.len() - 1];
+			let base = current.data().base;
+			if base.is_null() {
+				break;
+			}
+
+			let Some(base) = current.instantiate(base) else { break };
+			if chain.iter().any(|ty| same_type(&ty.ty, &base.ty)) {
+				break;
+			}
+			chain.push(base);
+		}
+
+		// The interfaces of every level of the chain, and the interfaces those interfaces extend.
+		let mut interfaces = Vec::<Instance>::new();
+		let mut pending = chain
+			.iter()
+			.flat_map(|ty| {
+				ty.data()
+					.interfaces
+					.iter()
+					.map(move |interface| (ty, interface.interface))
+			})
+			.filter_map(|(ty, token)| ty.instantiate(token))
+			.collect::<Vec<_>>();
+		pending.reverse();
+
+		while let Some(interface) = pending.pop() {
+			if interfaces.len() >= ctx.max_resolution_depth() || interfaces.iter().any(|known| known.is(&interface)) {
+				continue;
+			}
+
+			let extended = interface.data().interfaces.iter();
+			let mut extended = extended
+				.filter_map(|extended| interface.instantiate(extended.interface))
+				.collect::<Vec<_>>();
+			extended.reverse();
+			interfaces.push(interface);
+			pending.extend(extended);
+		}
+
+		for interface in interfaces
+			.iter()
+			.filter(|interface| matches!(*interface.ty, Type::Interface(_)))
+		{
+			for method in interface.data().methods.iter() {
+				if method.flags & method_flags::VIRTUAL == 0 {
+					continue;
+				}
+
+				let implementation = chain
+					.iter()
+					.find_map(|ty| implementation_in(ty, interface, method))
+					.or_else(|| default_implementation(&interfaces, interface, method));
+
+				map.mappings.push(InterfaceMapping {
+					interface_method: interface.method_ref(method),
+					type_arguments: interface.arguments.clone(),
+					implementation,
+				});
+			}
+		}
+
+		map
+	}
+}
+
+/// The method of `ty` implementing `method` of `interface`, either explicitly or implicitly.
+fn implementation_in(ty: &Instance, interface: &Instance, method: &Method) -> Option<(MethodRef, ImplementationKind)> {
+	let methods = &ty.data().methods;
+	if let Some(explicit) = methods
+		.iter()
+		.find(|candidate| overrides(ty, candidate, interface, method))
+	{
+		return Some((ty.method_ref(explicit), ImplementationKind::Explicit));
+	}
+
+	let implicit = methods.iter().find(|candidate| {
+		candidate.name == method.name
+			&& candidate.is_public()
+			&& candidate.is_static() == method.is_static()
+			&& (candidate.is_static() || candidate.flags & method_flags::VIRTUAL != 0)
+			&& signatures_match((ty, candidate), (interface, method))
+	})?;
+	Some((ty.method_ref(implicit), ImplementationKind::Implicit))
+}
+
+/// The most specific implementation of `method` provided by the interfaces of the type, if any.
+fn default_implementation(
+	interfaces: &[Instance],
+	interface: &Instance,
+	method: &Method,
+) -> Option<(MethodRef, ImplementationKind)> {
+	let overriding = interfaces.iter().find_map(|other| {
+		let methods = &other.data().methods;
+		let candidate = methods
+			.iter()
+			.find(|candidate| overrides(other, candidate, interface, method))?;
+		Some(other.method_ref(candidate))
+	});
+
+	match overriding {
+		Some(overriding) => Some((overriding, ImplementationKind::Default)),
+		None if method.flags & method_flags::ABSTRACT == 0 => {
+			Some((interface.method_ref(method), ImplementationKind::Default))
+		}
+		None => None,
+	}
+}
+
+/// Whether `candidate`, a method of `ty`, names `method` of `interface` in the MethodImpl table.
+fn overrides(ty: &Instance, candidate: &Method, interface: &Instance, method: &Method) -> bool {
+	candidate.overrides.iter().any(|declaration| {
+		let Some(declared) = ty.assembly.resolve_method(*declaration) else { return false };
+		if declared.token != method.token || !same_type(&declared.declaring_type, &interface.ty) {
+			return false;
+		}
+
+		// Types implementing several instantiations of an interface tell them apart by the parent of the MemberRef.
+		let parent = match declaration.token_kind() {
+			Some(MetadataTokenKind::MemberRef) => declaration
+				.index()
+				.checked_sub(1)
+				.and_then(|index| ty.assembly.member_refs.get(index))
+				.map(|(parent, _, _)| *parent),
+			_ => None,
+		};
+
+		match parent.filter(|parent| parent.token_kind() == Some(MetadataTokenKind::TypeSpec)) {
+			Some(parent) => ty.instantiate(parent).is_some_and(|parent| parent.is(interface)),
+			None => true,
+		}
+	})
+}
+
+/// Compares the signatures of two methods, with the generic parameters of their types replaced by their arguments.
+fn signatures_match(a: (&Instance, &Method), b: (&Instance, &Method)) -> bool {
+	let ((a_type, a), (b_type, b)) = (a, b);
+	let (Ok(a), Ok(b)) = (a_type.assembly.method_signature(a), b_type.assembly.method_signature(b)) else {
+		return false;
+	};
+
+	if a.calling_convention != b.calling_convention
+		|| a.generic_param_count != b.generic_param_count
+		|| a.params.len() != b.params.len()
+	{
+		return false;
+	}
+
+	let (a_formatter, b_formatter) = (a_type.formatter(), b_type.formatter());
+	let a_types = std::iter::once(&a.return_type).chain(a.params.iter());
+	let b_types = std::iter::once(&b.return_type).chain(b.params.iter());
+	a_types
+		.zip(b_types)
+		.all(|(a, b)| a_formatter.type_to_string(a) == b_formatter.type_to_string(b))
+}
@@ -0,0 +1,550 @@
+//! A self-contained snapshot of an assembly for code generators, see [`Assembly::to_ir`](crate::schema::Assembly::to_ir).
+//!
+//! The snapshot owns all of its data: it holds no tokens to resolve, no lifetimes and no references into the context,
+//! so it can be transformed freely and outlive the [`Context`](crate::schema::Context) it was taken from.
+//!
+//! ```ignore
+//! let ir = assembly.to_ir();
+//! for ty in ir.types.iter().filter(|ty| ty.visibility == Visibility::Public) {
+//!     println!("{} has {} methods", ty.name, ty.methods.len());
+//! }
+//! ```
+
+use crate::raw::{
+	field_flags, method_flags, param_flags, type_flags, AttributeArguments, MetadataToken, MetadataTokenKind,
+	MethodSignature, TypeSignature,
+};
+use crate::schema::{self, AssemblyIdentity, CustomAttribute, MAX_NESTING};
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Assembly {
+	pub identity: AssemblyIdentity,
+	pub references: Vec<AssemblyIdentity>,
+	/// Every type definition that could be loaded, in token order.
+	pub types: Vec<TypeDefinition>,
+	pub attributes: Vec<Attribute>,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum TypeKind {
+	Class,
+	Struct,
+	Enum,
+	Interface,
+	Delegate,
+	/// A type whose kind could not be determined, e.g. because its base type is not loaded.
+	Other,
+}
+
+/// The accessibility of a type or member, named after the C# keywords.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Visibility {
+	Public,
+	Internal,
+	Protected,
+	ProtectedInternal,
+	PrivateProtected,
+	Private,
+	/// Members that cannot be referenced outside of their module, e.g. `<PrivateImplementationDetails>`.
+	CompilerControlled,
+}
+
+/// The name of a type definition or reference.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct TypeName {
+	/// The name of the assembly defining the type, or `None` if it is defined by the assembly being converted.
+	pub assembly: Option<String>,
+	/// The namespace of the type, or of its outermost enclosing type if it is nested.
+	pub namespace: String,
+	/// The name of the type, preceded by those of its enclosing types separated by a `/`, e.g. ``List`1/Enumerator``.
+	pub name: String,
+}
+
+impl Display for TypeName {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		if let Some(assembly) = &self.assembly {
+			write!(f, "[{}]", assembly)?;
+		}
+
+		match self.namespace.is_empty() {
+			true => f.write_str(&self.name),
+			false => write!(f, "{}.{}", self.namespace, self.name),
+		}
+	}
+}
+
+/// The type of a field, parameter or return value.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum Type {
+	Void,
+	Boolean,
+	Char,
+	Int8,
+	UInt8,
+	Int16,
+	UInt16,
+	Int32,
+	UInt32,
+	Int64,
+	UInt64,
+	Float,
+	Double,
+	String,
+	Object,
+	IntPtr,
+	UIntPtr,
+	TypedReference,
+	Named {
+		name: TypeName,
+		is_value_type: bool,
+	},
+	/// A generic parameter of the enclosing type, by position.
+	TypeParameter(u32),
+	/// A generic parameter of the enclosing method, by position.
+	MethodParameter(u32),
+	Pointer(Box<Type>),
+	Reference(Box<Type>),
+	/// A single-dimensional array with a lower bound of 0, e.g. `int[]`.
+	Vector(Box<Type>),
+	/// An array of any rank, e.g. `int[,]`, along with the bounds of its dimensions.
+	/// Dimensions without a size or lower bound are not listed, see [`ArrayShape`](crate::raw::ArrayShape).
+	Array {
+		element: Box<Type>,
+		rank: u32,
+		sizes: Vec<u32>,
+		lower_bounds: Vec<i32>,
+	},
+	GenericInstance {
+		ty: Box<Type>,
+		arguments: Vec<Type>,
+	},
+	FunctionPointer(Box<Signature>),
+	Modified {
+		required: bool,
+		modifier: TypeName,
+		ty: Box<Type>,
+	},
+	/// A type that could not be named, e.g. because its signature could not be decoded.
+	Unresolved(String),
+}
+
+/// The signature of a function pointer.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Signature {
+	pub has_this: bool,
+	pub return_type: Type,
+	pub params: Vec<Type>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypeDefinition {
+	pub name: TypeName,
+	pub kind: TypeKind,
+	pub visibility: Visibility,
+	pub is_abstract: bool,
+	pub is_sealed: bool,
+	pub base: Option<Type>,
+	pub interfaces: Vec<Type>,
+	pub generic_params: Vec<GenericParameter>,
+	pub fields: Vec<Field>,
+	pub methods: Vec<Method>,
+	pub properties: Vec<Property>,
+	pub events: Vec<Event>,
+	pub attributes: Vec<Attribute>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GenericParameter {
+	pub name: String,
+	pub constraints: Vec<Type>,
+	pub attributes: Vec<Attribute>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+	pub name: String,
+	pub ty: Type,
+	pub visibility: Visibility,
+	pub is_static: bool,
+	pub is_read_only: bool,
+	/// Whether the field is a constant, whose value is only stored in the metadata.
+	pub is_literal: bool,
+	pub attributes: Vec<Attribute>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Method {
+	pub name: String,
+	pub visibility: Visibility,
+	pub is_static: bool,
+	pub is_virtual: bool,
+	pub is_abstract: bool,
+	/// Whether the method is an accessor, an operator or a constructor.
+	pub is_special_name: bool,
+	pub generic_params: Vec<GenericParameter>,
+	pub return_type: Type,
+	pub params: Vec<Parameter>,
+	pub attributes: Vec<Attribute>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Parameter {
+	/// The declared name of the parameter, or `argN` if it has none.
+	pub name: String,
+	pub ty: Type,
+	pub is_in: bool,
+	pub is_out: bool,
+	pub is_optional: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Property {
+	pub name: String,
+	pub ty: Type,
+	/// The parameters of an indexer.
+	pub params: Vec<Type>,
+	pub is_static: bool,
+	/// The names of the accessor methods.
+	pub getter: Option<String>,
+	pub setter: Option<String>,
+	pub attributes: Vec<Attribute>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Event {
+	pub name: String,
+	pub ty: Type,
+	/// The names of the accessor methods.
+	pub add: Option<String>,
+	pub remove: Option<String>,
+	pub attributes: Vec<Attribute>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Attribute {
+	/// The full name of the attribute type, e.g. `System.ObsoleteAttribute`.
+	pub ty: String,
+	pub arguments: AttributeArguments,
+}
+
+impl schema::Assembly {
+	/// Takes a snapshot of the types and members of the assembly that does not borrow from the context.
+	/// Members whose signature cannot be decoded are kept, with [`Type::Unresolved`] in place of their types.
+	pub fn to_ir(&self) -> Assembly {
+		let converter = Converter::new(self);
+		let types = self.types.get().map_or(&[][..], |types| &types[..]);
+		Assembly {
+			identity: self.name.identity(),
+			references: self.dependencies.iter().map(|reference| reference.identity()).collect(),
+			types: types.iter().filter_map(|ty| converter.type_definition(ty)).collect(),
+			attributes: converter.attributes(self.assembly_attributes()),
+		}
+	}
+}
+
+struct Converter<'l> {
+	assembly: &'l schema::Assembly,
+	/// The enclosing type of every nested type definition, by TypeDef row.
+	enclosing: HashMap<usize, usize>,
+}
+
+impl<'l> Converter<'l> {
+	fn new(assembly: &'l schema::Assembly) -> Self {
+		let mut enclosing = HashMap::new();
+		for ty in assembly.types.get().into_iter().flat_map(|types| types.iter()) {
+			let Some(data) = ty.data() else { continue };
+			for nested in data.nested_types.iter() {
+				enclosing.insert(nested.index(), data.token.index());
+			}
+		}
+
+		Self { assembly, enclosing }
+	}
+
+	fn type_definition(&self, ty: &schema::Type) -> Option<TypeDefinition> {
+		let data = ty.data()?;
+		let kind = match ty {
+			_ if ty.is_delegate() => TypeKind::Delegate,
+			schema::Type::Class(_) => TypeKind::Class,
+			schema::Type::Struct(_) => TypeKind::Struct,
+			schema::Type::Enum(_) => TypeKind::Enum,
+			schema::Type::Interface(_) => TypeKind::Interface,
+			_ => TypeKind::Other,
+		};
+
+		let visibility = match data.flags & type_flags::VISIBILITY_MASK {
+			type_flags::PUBLIC | type_flags::NESTED_PUBLIC => Visibility::Public,
+			type_flags::NESTED_PRIVATE => Visibility::Private,
+			type_flags::NESTED_FAMILY => Visibility::Protected,
+			type_flags::NESTED_FAMILY_AND_ASSEMBLY => Visibility::PrivateProtected,
+			type_flags::NESTED_FAMILY_OR_ASSEMBLY => Visibility::ProtectedInternal,
+			_ => Visibility::Internal,
+		};
+
+		let method_name = |token: Option<MetadataToken>| Some(data.method(token?)?.name.to_string());
+		Some(TypeDefinition {
+			name: self.type_name(data.token)?,
+			kind,
+			visibility,
+			is_abstract: data.flags & type_flags::ABSTRACT != 0,
+			is_sealed: data.flags & type_flags::SEALED != 0,
+			base: (!data.base.is_null()).then(|| self.token_type(data.base, false)),
+			interfaces: data
+				.interfaces
+				.iter()
+				.map(|interface| self.token_type(interface.interface, false))
+				.collect(),
+			generic_params: self.generic_params(&data.generic_params),
+			fields: data.fields.iter().map(|field| self.field(field)).collect(),
+			methods: data.methods.iter().map(|method| self.method(method)).collect(),
+			properties: data
+				.properties
+				.iter()
+				.map(|property| {
+					let signature = property.parse_signature().ok();
+					Property {
+						name: property.name.to_string(),
+						ty: signature
+							.as_ref()
+							.map_or_else(invalid_signature, |signature| self.ty(&signature.ty)),
+						params: signature
+							.as_ref()
+							.map_or(vec![], |signature| signature.params.iter().map(|ty| self.ty(ty)).collect()),
+						is_static: signature.is_some_and(|signature| !signature.has_this),
+						getter: method_name(property.getter()),
+						setter: method_name(property.setter()),
+						attributes: self.attributes(self.assembly.attributes_of(property.token)),
+					}
+				})
+				.collect(),
+			events: data
+				.events
+				.iter()
+				.map(|event| Event {
+					name: event.name.to_string(),
+					ty: self.token_type(event.event_type, false),
+					add: method_name(event.add_method()),
+					remove: method_name(event.remove_method()),
+					attributes: self.attributes(self.assembly.attributes_of(event.token)),
+				})
+				.collect(),
+			attributes: self.attributes(&data.custom_attributes),
+		})
+	}
+
+	fn field(&self, field: &schema::Field) -> Field {
+		let visibility = match field.flags & field_flags::FIELD_ACCESS_MASK {
+			field_flags::PUBLIC => Visibility::Public,
+			field_flags::ASSEMBLY => Visibility::Internal,
+			field_flags::FAMILY => Visibility::Protected,
+			field_flags::FAMILY_OR_ASSEMBLY => Visibility::ProtectedInternal,
+			field_flags::FAMILY_AND_ASSEMBLY => Visibility::PrivateProtected,
+			field_flags::PRIVATE => Visibility::Private,
+			_ => Visibility::CompilerControlled,
+		};
+
+		Field {
+			name: field.name.to_string(),
+			ty: self
+				.assembly
+				.field_signature(field)
+				.map_or_else(|_| invalid_signature(), |signature| self.ty(&signature.ty)),
+			visibility,
+			is_static: field.flags & field_flags::STATIC != 0,
+			is_read_only: field.flags & field_flags::INIT_ONLY != 0,
+			is_literal: field.flags & field_flags::LITERAL != 0,
+			attributes: self.attributes(self.assembly.attributes_of(field.token)),
+		}
+	}
+
+	fn method(&self, method: &schema::Method) -> Method {
+		let visibility = match method.flags & method_flags::MEMBER_ACCESS_MASK {
+			method_flags::PUBLIC => Visibility::Public,
+			method_flags::ASSEMBLY => Visibility::Internal,
+			method_flags::FAMILY => Visibility::Protected,
+			method_flags::FAMILY_OR_ASSEMBLY => Visibility::ProtectedInternal,
+			method_flags::FAMILY_AND_ASSEMBLY => Visibility::PrivateProtected,
+			method_flags::PRIVATE => Visibility::Private,
+			_ => Visibility::CompilerControlled,
+		};
+
+		let signature = self.assembly.method_signature(method).ok();
+		let params = signature.as_ref().map_or(&[][..], |signature| &signature.params[..]);
+		let params = params.iter().enumerate().map(|(i, ty)| {
+			let param = method.params.iter().find(|param| param.sequence as usize == i + 1);
+			let flags = param.map_or(0, |param| param.flags);
+			Parameter {
+				name: match param {
+					Some(param) if !param.name.is_empty() => param.name.to_string(),
+					_ => format!("arg{}", i),
+				},
+				ty: self.ty(ty),
+				is_in: flags & param_flags::IN != 0,
+				is_out: flags & param_flags::OUT != 0,
+				is_optional: flags & param_flags::OPTIONAL != 0,
+			}
+		});
+
+		Method {
+			name: method.name.to_string(),
+			visibility,
+			is_static: method.flags & method_flags::STATIC != 0,
+			is_virtual: method.flags & method_flags::VIRTUAL != 0,
+			is_abstract: method.flags & method_flags::ABSTRACT != 0,
+			is_special_name: method.flags & method_flags::SPECIAL_NAME != 0,
+			generic_params: self.generic_params(&method.generic_params),
+			return_type: signature
+				.as_ref()
+				.map_or_else(invalid_signature, |signature| self.ty(&signature.return_type)),
+			params: params.collect(),
+			attributes: self.attributes(&method.custom_attributes),
+		}
+	}
+
+	fn generic_params(&self, params: &[schema::GenericParam]) -> Vec<GenericParameter> {
+		let params = params.iter().map(|param| GenericParameter {
+			name: param.name.to_string(),
+			constraints: param
+				.constraints
+				.iter()
+				.map(|constraint| self.token_type(*constraint, false))
+				.collect(),
+			attributes: self.attributes(&param.custom_attributes),
+		});
+
+		params.collect()
+	}
+
+	fn attributes(&self, attributes: &[CustomAttribute]) -> Vec<Attribute> {
+		let attributes = attributes.iter().map(|attribute| Attribute {
+			ty: attribute.attribute_type.clone(),
+			arguments: self.assembly.decode_attribute(attribute).arguments,
+		});
+
+		attributes.collect()
+	}
+
+	fn ty(&self, ty: &TypeSignature) -> Type {
+		match ty {
+			TypeSignature::Void => Type::Void,
+			TypeSignature::Boolean => Type::Boolean,
+			TypeSignature::Char => Type::Char,
+			TypeSignature::Int8 => Type::Int8,
+			TypeSignature::UInt8 => Type::UInt8,
+			TypeSignature::Int16 => Type::Int16,
+			TypeSignature::UInt16 => Type::UInt16,
+			TypeSignature::Int32 => Type::Int32,
+			TypeSignature::UInt32 => Type::UInt32,
+			TypeSignature::Int64 => Type::Int64,
+			TypeSignature::UInt64 => Type::UInt64,
+			TypeSignature::Float => Type::Float,
+			TypeSignature::Double => Type::Double,
+			TypeSignature::String => Type::String,
+			TypeSignature::Object => Type::Object,
+			TypeSignature::IntPtr => Type::IntPtr,
+			TypeSignature::UIntPtr => Type::UIntPtr,
+			TypeSignature::TypedByRef => Type::TypedReference,
+			TypeSignature::Class(token) => self.token_type(*token, false),
+			TypeSignature::ValueType(token) => self.token_type(*token, true),
+			TypeSignature::Var(index) => Type::TypeParameter(*index),
+			TypeSignature::MVar(index) => Type::MethodParameter(*index),
+			TypeSignature::Ptr(ty) => Type::Pointer(Box::new(self.ty(ty))),
+			TypeSignature::ByRef(ty) => Type::Reference(Box::new(self.ty(ty))),
+			TypeSignature::Pinned(ty) => self.ty(ty),
+			TypeSignature::SzArray(ty) => Type::Vector(Box::new(self.ty(ty))),
+			TypeSignature::Array(ty, shape) => Type::Array {
+				element: Box::new(self.ty(ty)),
+				rank: shape.rank,
+				sizes: shape.sizes.clone(),
+				lower_bounds: shape.lower_bounds.clone(),
+			},
+			TypeSignature::GenericInst(ty, arguments) => Type::GenericInstance {
+				ty: Box::new(self.ty(ty)),
+				arguments: arguments.iter().map(|argument| self.ty(argument)).collect(),
+			},
+			TypeSignature::FnPtr(method) => Type::FunctionPointer(Box::new(self.signature(method))),
+			TypeSignature::Modified { required, modifier, ty } => match self.type_name(*modifier) {
+				Some(modifier) => Type::Modified {
+					required: *required,
+					modifier,
+					ty: Box::new(self.ty(ty)),
+				},
+				None => self.ty(ty),
+			},
+		}
+	}
+
+	fn signature(&self, method: &MethodSignature) -> Signature {
+		Signature {
+			has_this: method.has_this(),
+			return_type: self.ty(&method.return_type),
+			params: method.params.iter().map(|ty| self.ty(ty)).collect(),
+		}
+	}
+
+	/// The type a TypeDef, TypeRef or TypeSpec token stands for.
+	fn token_type(&self, token: MetadataToken, is_value_type: bool) -> Type {
+		if token.token_kind() == Some(MetadataTokenKind::TypeSpec) {
+			return match self.assembly.type_spec(token) {
+				Some(spec) => self.ty(&spec.signature),
+				None => Type::Unresolved(format!("{:?}", token)),
+			};
+		}
+
+		match self.type_name(token) {
+			Some(name) => Type::Named { name, is_value_type },
+			None => Type::Unresolved(format!("{:?}", token)),
+		}
+	}
+
+	fn type_name(&self, token: MetadataToken) -> Option<TypeName> {
+		let mut names = vec![];
+		let mut assembly = None;
+		let mut namespace = String::new();
+		let mut token = token;
+		for _ in 0..MAX_NESTING {
+			match token.token_kind()? {
+				MetadataTokenKind::TypeDef => {
+					let data = self.assembly.types.get()?.get(token.index().checked_sub(1)?)?.data()?;
+					names.push(data.name.as_str());
+					match self.enclosing.get(&token.index()) {
+						Some(enclosing) => token = MetadataToken::new(*enclosing as u32, MetadataTokenKind::TypeDef),
+						None => {
+							namespace = data.namespace.to_string();
+							break;
+						}
+					}
+				}
+				MetadataTokenKind::TypeRef => {
+					let (scope, ref_namespace, name) = self.assembly.type_refs.get(token.index().checked_sub(1)?)?;
+					names.push(name.as_str());
+					match scope.token_kind() {
+						Some(MetadataTokenKind::TypeRef) if !scope.is_null() => token = *scope,
+						scope_kind => {
+							if scope_kind == Some(MetadataTokenKind::AssemblyRef) {
+								let reference = self.assembly.dependencies.get(scope.index().checked_sub(1)?)?;
+								assembly = Some(reference.name.clone());
+							}
+							namespace = ref_namespace.clone();
+							break;
+						}
+					}
+				}
+				_ => return None,
+			}
+		}
+
+		names.reverse();
+		Some(TypeName {
+			assembly,
+			namespace,
+			name: names.join("/"),
+		})
+	}
+}
+
+fn invalid_signature() -> Type {
+	Type::Unresolved("<invalid signature>".to_string())
+}
@@ -0,0 +1,712 @@
+use crate::raw::{
+	field_flags, method_flags, method_semantics_flags, type_flags, EventFlags, FieldFlags, GenericParamFlags,
+	HeapIndex, MetadataToken, MethodFlags, MethodImplFlags, MethodSemanticsFlags, ParamFlags, PropertyFlags,
+	PropertySignature, TypeSignature,
+};
+use crate::schema::format::SignatureFormatter;
+use crate::schema::{CustomAttribute, Name, Type, TypeData, TypeRef, EXTENSION_ATTRIBUTE};
+use std::fmt::{Debug, Display, Formatter};
+use crate::raw;
+
+/// Where the row of a type or member came from, for images written by Edit and Continue.
+///
+/// Tables of such images may be reached through the Ptr tables, which keep the original row order
+/// while rows are added at the end, and the EncLog table records the rows each generation added or changed.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub struct Provenance {
+	/// Whether the row was reached through a FieldPtr, MethodPtr, ParamPtr, PropertyPtr or EventPtr table.
+	pub indirected: bool,
+	/// The generation of the module, if the EncLog records the row as added or changed.
+	/// `None` for rows of the original image.
+	pub generation: Option<u16>,
+}
+
+impl Provenance {
+	pub fn is_original(&self) -> bool {
+		self.generation.is_none()
+	}
+}
+
+#[derive(Debug)]
+pub struct Field {
+	pub(crate) token: MetadataToken,
+	pub(crate) parent: MetadataToken,
+	pub(crate) name: Name,
+	pub(crate) name_index: HeapIndex,
+	pub(crate) flags: FieldFlags,
+	pub(crate) signature: Vec<u8>,
+	pub(crate) signature_index: HeapIndex,
+	pub(crate) provenance: Provenance,
+}
+
+#[derive(Debug)]
+pub struct Method {
+	pub(crate) token: MetadataToken,
+	pub(crate) parent: MetadataToken,
+	pub(crate) name: Name,
+	pub(crate) name_index: HeapIndex,
+	pub(crate) rva: u32,
+	pub(crate) flags: MethodFlags,
+	pub(crate) impl_flags: MethodImplFlags,
+	pub(crate) signature: Vec<u8>,
+	pub(crate) signature_index: HeapIndex,
+	pub(crate) params: Vec<Param>,
+	pub(crate) generic_params: Vec<GenericParam>,
+	pub(crate) custom_attributes: Vec<CustomAttribute>,
+	/// The MethodDef or MemberRef declarations the MethodImpl table maps to this method.
+	pub(crate) overrides: Vec<MetadataToken>,
+	pub(crate) provenance: Provenance,
+}
+
+/// A method implementing an interface method explicitly, as in `void IDisposable.Dispose()`.
+#[derive(Debug, Clone)]
+pub struct ExplicitImplementation {
+	pub(crate) declaration: MetadataToken,
+	pub(crate) interface_method: Option<MethodRef>,
+	pub(crate) interface_name: String,
+	pub(crate) name: String,
+}
+
+/// A generic method instantiation from the MethodSpec table.
+#[derive(Debug, Clone)]
+pub struct MethodSpec {
+	pub(crate) token: MetadataToken,
+	pub(crate) method: MetadataToken,
+	pub(crate) type_arguments: Vec<TypeSignature>,
+}
+
+/// A method definition, along with the type that declares it.
+#[derive(Debug, Clone)]
+pub struct MethodRef {
+	pub(crate) declaring_type: TypeRef,
+	pub(crate) token: MetadataToken,
+}
+
+#[derive(Debug)]
+pub struct Param {
+	pub(crate) token: MetadataToken,
+	pub(crate) name: Name,
+	pub(crate) name_index: HeapIndex,
+	pub(crate) flags: ParamFlags,
+	pub(crate) sequence: u16,
+	pub(crate) provenance: Provenance,
+}
+
+#[derive(Debug)]
+pub struct Property {
+	pub(crate) token: MetadataToken,
+	pub(crate) parent: MetadataToken,
+	pub(crate) name: Name,
+	pub(crate) name_index: HeapIndex,
+	pub(crate) flags: PropertyFlags,
+	pub(crate) signature: Vec<u8>,
+	pub(crate) methods: Vec<(MethodSemanticsFlags, MetadataToken)>,
+	pub(crate) provenance: Provenance,
+}
+
+#[derive(Debug)]
+pub struct Event {
+	pub(crate) token: MetadataToken,
+	pub(crate) parent: MetadataToken,
+	pub(crate) name: Name,
+	pub(crate) name_index: HeapIndex,
+	pub(crate) flags: EventFlags,
+	pub(crate) event_type: MetadataToken,
+	pub(crate) methods: Vec<(MethodSemanticsFlags, MetadataToken)>,
+	pub(crate) provenance: Provenance,
+}
+
+/// A generic parameter of a type or method definition.
+#[derive(Debug)]
+pub struct GenericParam {
+	pub(crate) token: MetadataToken,
+	/// The TypeDef or MethodDef declaring the parameter.
+	pub(crate) owner: MetadataToken,
+	pub(crate) number: u16,
+	pub(crate) name: Name,
+	pub(crate) name_index: HeapIndex,
+	pub(crate) flags: GenericParamFlags,
+	/// The TypeDef, TypeRef or TypeSpec of every constraint, in table order.
+	pub(crate) constraints: Vec<MetadataToken>,
+	pub(crate) custom_attributes: Vec<CustomAttribute>,
+}
+
+/// An interface implemented by a type definition, i.e. a row of the InterfaceImpl table.
+#[derive(Debug)]
+pub struct InterfaceImpl {
+	pub(crate) token: MetadataToken,
+	pub(crate) class: MetadataToken,
+	/// The TypeDef, TypeRef or TypeSpec of the interface.
+	pub(crate) interface: MetadataToken,
+	pub(crate) custom_attributes: Vec<CustomAttribute>,
+}
+
+macro_rules! member_getters {
+	($ty: ident { $($field: ident: $field_ty: ty),* }) => {
+		impl $ty {
+			pub fn token(&self) -> MetadataToken {
+				self.token
+			}
+
+			pub fn name(&self) -> &str {
+				&self.name
+			}
+
+			$(
+				pub fn $field(&self) -> $field_ty {
+					self.$field
+				}
+			)*
+		}
+	};
+}
+
+member_getters!(Field {
+	parent: MetadataToken,
+	flags: FieldFlags,
+	provenance: Provenance
+});
+member_getters!(Method {
+	parent: MetadataToken,
+	rva: u32,
+	flags: MethodFlags,
+	impl_flags: MethodImplFlags,
+	provenance: Provenance
+});
+member_getters!(Param {
+	flags: ParamFlags,
+	sequence: u16,
+	provenance: Provenance
+});
+member_getters!(Property {
+	parent: MetadataToken,
+	flags: PropertyFlags,
+	provenance: Provenance
+});
+member_getters!(Event {
+	parent: MetadataToken,
+	flags: EventFlags,
+	event_type: MetadataToken,
+	provenance: Provenance
+});
+member_getters!(GenericParam {
+	owner: MetadataToken,
+	number: u16,
+	flags: GenericParamFlags
+});
+
+impl GenericParam {
+	pub fn constraints(&self) -> &[MetadataToken] {
+		&self.constraints
+	}
+
+	/// The custom attributes of the parameter, e.g. the `NullableAttribute` of its constraints.
+	pub fn attributes(&self) -> &[CustomAttribute] {
+		&self.custom_attributes
+	}
+}
+
+impl InterfaceImpl {
+	pub fn token(&self) -> MetadataToken {
+		self.token
+	}
+
+	/// The TypeDef implementing the interface.
+	pub fn class(&self) -> MetadataToken {
+		self.class
+	}
+
+	pub fn interface(&self) -> MetadataToken {
+		self.interface
+	}
+
+	/// The custom attributes of the implementation, e.g. the `NullableAttribute` of `IEquatable<T?>`.
+	pub fn attributes(&self) -> &[CustomAttribute] {
+		&self.custom_attributes
+	}
+}
+
+impl Field {
+	pub fn signature(&self) -> &[u8] {
+		&self.signature
+	}
+
+	pub fn is_static(&self) -> bool {
+		self.flags & field_flags::STATIC != 0
+	}
+
+	pub fn is_public(&self) -> bool {
+		self.flags & field_flags::FIELD_ACCESS_MASK == field_flags::PUBLIC
+	}
+}
+
+impl Method {
+	pub fn signature(&self) -> &[u8] {
+		&self.signature
+	}
+
+	pub fn params(&self) -> &[Param] {
+		&self.params
+	}
+
+	pub fn generic_params(&self) -> &[GenericParam] {
+		&self.generic_params
+	}
+
+	pub fn custom_attributes(&self) -> &[CustomAttribute] {
+		&self.custom_attributes
+	}
+
+	/// Whether the method is marked with `ExtensionAttribute`.
+	/// Its declaring type must be marked as well for it to be an extension method, see [`TypeData::has_extension_methods`].
+	pub fn is_extension(&self) -> bool {
+		self.is_static()
+			&& self
+				.custom_attributes
+				.iter()
+				.any(|attribute| attribute.attribute_type == EXTENSION_ATTRIBUTE)
+	}
+
+	/// The interface or base class methods this method overrides explicitly, as recorded by the MethodImpl table.
+	pub fn overrides(&self) -> &[MetadataToken] {
+		&self.overrides
+	}
+
+	/// Unmangles the name of a method implementing an interface method explicitly, e.g. `System.IDisposable.Dispose`.
+	/// `ty` must be the declaring type of the method, and is used to resolve the interface method.
+	///
+	/// Overrides whose declaration cannot be resolved are only recognized by their mangled name.
+	pub fn explicit_interface_implementation(&self, ty: &TypeData) -> Option<ExplicitImplementation> {
+		if self.flags & method_flags::MEMBER_ACCESS_MASK != method_flags::PRIVATE {
+			return None;
+		}
+
+		// Generic interfaces contain dots within their arguments, e.g. `IEnumerable<System.String>.GetEnumerator`.
+		let split = match self.name.rfind('>') {
+			Some(end) => end + self.name[end..].find('.')?,
+			None => self.name.rfind('.')?,
+		};
+
+		let (interface_name, name) = (&self.name[..split], &self.name[split + 1..]);
+		if interface_name.is_empty() || name.is_empty() {
+			return None;
+		}
+
+		let assembly = ty.assembly.upgrade();
+		self.overrides.iter().find_map(|declaration| {
+			let interface_method = assembly
+				.as_ref()
+				.and_then(|assembly| assembly.resolve_method(*declaration));
+			if let Some(method) = &interface_method {
+				let is_match = method.method().map(|method| method.name == name).unwrap_or(false);
+				if !matches!(*method.declaring_type, Type::Interface(_)) || !is_match {
+					return None;
+				}
+			}
+
+			Some(ExplicitImplementation {
+				declaration: *declaration,
+				interface_method,
+				interface_name: interface_name.to_string(),
+				name: name.to_string(),
+			})
+		})
+	}
+
+	pub fn is_static(&self) -> bool {
+		self.flags & method_flags::STATIC != 0
+	}
+
+	pub fn is_public(&self) -> bool {
+		self.flags & method_flags::MEMBER_ACCESS_MASK == method_flags::PUBLIC
+	}
+}
+
+impl ExplicitImplementation {
+	/// The MethodDef or MemberRef of the interface method, as found in the MethodImpl table.
+	pub fn declaration(&self) -> MetadataToken {
+		self.declaration
+	}
+
+	/// The interface method, if the assembly declaring it is part of the context.
+	pub fn interface_method(&self) -> Option<&MethodRef> {
+		self.interface_method.as_ref()
+	}
+
+	/// The interface, as spelled by the mangled name, e.g. `System.Collections.Generic.IEnumerable<T>`.
+	pub fn interface_name(&self) -> &str {
+		&self.interface_name
+	}
+
+	/// The name of the interface method, e.g. `GetEnumerator`.
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+}
+
+impl Display for ExplicitImplementation {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}.{}", self.interface_name, self.name)
+	}
+}
+
+impl MethodSpec {
+	pub fn token(&self) -> MetadataToken {
+		self.token
+	}
+
+	/// The MethodDef or MemberRef of the generic method.
+	pub fn method(&self) -> MetadataToken {
+		self.method
+	}
+
+	pub fn type_arguments(&self) -> &[TypeSignature] {
+		&self.type_arguments
+	}
+}
+
+impl MethodRef {
+	pub fn declaring_type(&self) -> &TypeRef {
+		&self.declaring_type
+	}
+
+	/// The MethodDef token, in the assembly of the declaring type.
+	pub fn token(&self) -> MetadataToken {
+		self.token
+	}
+
+	pub fn method(&self) -> Option<&Method> {
+		self.declaring_type.data()?.method(self.token)
+	}
+}
+
+impl Property {
+	pub fn signature(&self) -> &[u8] {
+		&self.signature
+	}
+
+	/// The accessor methods of the property, along with their semantics.
+	pub fn methods(&self) -> &[(MethodSemanticsFlags, MetadataToken)] {
+		&self.methods
+	}
+
+	pub fn getter(&self) -> Option<MetadataToken> {
+		accessor(&self.methods, method_semantics_flags::GETTER)
+	}
+
+	pub fn setter(&self) -> Option<MetadataToken> {
+		accessor(&self.methods, method_semantics_flags::SETTER)
+	}
+
+	pub fn parse_signature(&self) -> Result<PropertySignature, raw::Error> {
+		PropertySignature::parse(&self.signature)
+	}
+
+	/// Whether the property is the indexer of `ty`, i.e. it takes parameters
+	/// and is named by the `DefaultMemberAttribute` of its declaring type.
+	pub fn is_indexer(&self, ty: &TypeData) -> bool {
+		let has_params = self
+			.parse_signature()
+			.map(|sig| !sig.params.is_empty())
+			.unwrap_or(false);
+		has_params && ty.default_member() == Some(self.name.as_str())
+	}
+
+	/// Renders the property in C# syntax, e.g. `int this[string key] { get; set; }`.
+	pub fn display<'l>(&'l self, ty: &'l TypeData) -> PropertyDisplay<'l> {
+		PropertyDisplay { property: self, ty }
+	}
+}
+
+impl Event {
+	/// The accessor methods of the event, along with their semantics.
+	pub fn methods(&self) -> &[(MethodSemanticsFlags, MetadataToken)] {
+		&self.methods
+	}
+
+	pub fn add_method(&self) -> Option<MetadataToken> {
+		accessor(&self.methods, method_semantics_flags::ADD_ON)
+	}
+
+	pub fn remove_method(&self) -> Option<MetadataToken> {
+		accessor(&self.methods, method_semantics_flags::REMOVE_ON)
+	}
+
+	pub fn raise_method(&self) -> Option<MetadataToken> {
+		accessor(&self.methods, method_semantics_flags::FIRE)
+	}
+
+	/// Renders the event in C# syntax, e.g. `event System.EventHandler Changed`.
+	pub fn display<'l>(&'l self, ty: &'l TypeData) -> EventDisplay<'l> {
+		EventDisplay { event: self, ty }
+	}
+}
+
+fn accessor(
+	methods: &[(MethodSemanticsFlags, MetadataToken)],
+	semantics: MethodSemanticsFlags,
+) -> Option<MetadataToken> {
+	methods
+		.iter()
+		.find(|(flags, _)| flags & semantics != 0)
+		.map(|(_, token)| *token)
+}
+
+pub struct PropertyDisplay<'l> {
+	property: &'l Property,
+	ty: &'l TypeData,
+}
+
+impl Display for PropertyDisplay<'_> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		let Self { property, ty } = self;
+		let Ok(signature) = property.parse_signature() else {
+			return write!(f, "<invalid signature> {}", property.name);
+		};
+
+		let assembly = ty.assembly.upgrade();
+		let formatter = SignatureFormatter::new(assembly.as_deref());
+
+		if !signature.has_this {
+			f.write_str("static ")?;
+		}
+
+		formatter.write_type(f, &signature.ty)?;
+		match property.is_indexer(ty) {
+			true => f.write_str(" this")?,
+			false => write!(f, " {}", property.name)?,
+		}
+
+		if !signature.params.is_empty() {
+			// Parameter names are only recorded on the accessors.
+			let accessor = property
+				.getter()
+				.or(property.setter())
+				.and_then(|token| ty.method(token));
+			let name = |sequence: usize| {
+				let params = accessor.map(|method| method.params.as_slice()).unwrap_or(&[]);
+				params
+					.iter()
+					.find(|param| param.sequence as usize == sequence)
+					.map(|param| param.name.as_str())
+			};
+
+			f.write_str("[")?;
+			for (i, param) in signature.params.iter().enumerate() {
+				if i != 0 {
+					f.write_str(", ")?;
+				}
+
+				formatter.write_type(f, param)?;
+				match name(i + 1) {
+					Some(name) if !name.is_empty() => write!(f, " {}", name)?,
+					_ => write!(f, " arg{}", i)?,
+				}
+			}
+			f.write_str("]")?;
+		}
+
+		f.write_str(" {")?;
+		if property.getter().is_some() {
+			f.write_str(" get;")?;
+		}
+		if property.setter().is_some() {
+			f.write_str(" set;")?;
+		}
+		f.write_str(" }")
+	}
+}
+
+pub struct EventDisplay<'l> {
+	event: &'l Event,
+	ty: &'l TypeData,
+}
+
+impl Display for EventDisplay<'_> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		let assembly = self.ty.assembly.upgrade();
+		let formatter = SignatureFormatter::new(assembly.as_deref());
+
+		let is_static = self
+			.event
+			.add_method()
+			.and_then(|token| self.ty.method(token))
+			.map(|method| method.is_static())
+			.unwrap_or(false);
+
+		if is_static {
+			f.write_str("static ")?;
+		}
+
+		f.write_str("event ")?;
+		formatter.write_token(f, self.event.event_type)?;
+		write!(f, " {}", self.event.name)
+	}
+}
+
+pub enum Member<'l> {
+	Field(&'l Field),
+	Method(&'l Method),
+	Property(&'l Property),
+	Event(&'l Event),
+	NestedType(TypeRef),
+}
+
+impl Member<'_> {
+	pub fn token(&self) -> MetadataToken {
+		match self {
+			Member::Field(field) => field.token,
+			Member::Method(method) => method.token,
+			Member::Property(property) => property.token,
+			Member::Event(event) => event.token,
+			Member::NestedType(ty) => ty.data().map(|data| data.token).unwrap_or(MetadataToken(0)),
+		}
+	}
+
+	pub fn name(&self) -> &str {
+		match self {
+			Member::Field(field) => &field.name,
+			Member::Method(method) => &method.name,
+			Member::Property(property) => &property.name,
+			Member::Event(event) => &event.name,
+			Member::NestedType(ty) => ty.data().map(|data| data.name.as_str()).unwrap_or(""),
+		}
+	}
+}
+
+impl Debug for Member<'_> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Member::Field(field) => write!(f, "Field({:?}, {:?})", field.token, field.name),
+			Member::Method(method) => write!(f, "Method({:?}, {:?})", method.token, method.name),
+			Member::Property(property) => write!(f, "Property({:?}, {:?})", property.token, property.name),
+			Member::Event(event) => write!(f, "Event({:?}, {:?})", event.token, event.name),
+			Member::NestedType(ty) => write!(f, "NestedType({:?}, {:?})", self.token(), self.name()),
+		}
+	}
+}
+
+/// An iterator over the members of a type in token order, with optional filters.
+///
+/// Members of the same kind follow their table order. Across kinds, the token order puts nested types first,
+/// then fields, methods, events and properties.
+pub struct Members<'l> {
+	ty: Option<&'l TypeData>,
+	members: std::vec::IntoIter<Member<'l>>,
+	static_only: bool,
+	public_only: bool,
+	hide_synthetic: bool,
+	pattern: Option<String>,
+}
+
+impl<'l> Members<'l> {
+	pub(crate) fn new(ty: Option<&'l TypeData>) -> Self {
+		let mut members = vec![];
+		if let Some(data) = ty {
+			members.extend(data.fields.iter().map(Member::Field));
+			members.extend(data.methods.iter().map(Member::Method));
+			members.extend(data.properties.iter().map(Member::Property));
+			members.extend(data.events.iter().map(Member::Event));
+			members.extend(data.nested_types().map(Member::NestedType));
+		}
+
+		members.sort_by_key(|member| member.token().0);
+
+		let assembly = ty.and_then(|data| data.assembly.upgrade());
+		let context = assembly.and_then(|assembly| assembly.ctx.borrow().upgrade());
+		Self {
+			ty,
+			members: members.into_iter(),
+			static_only: false,
+			public_only: false,
+			hide_synthetic: context.is_some_and(|context| context.hides_synthetic_members()),
+			pattern: None,
+		}
+	}
+
+	/// Only yields static members. Nested types are considered static.
+	pub fn static_only(mut self) -> Self {
+		self.static_only = true;
+		self
+	}
+
+	pub fn public_only(mut self) -> Self {
+		self.public_only = true;
+		self
+	}
+
+	/// Leaves out the members the compiler emits on its own, see [`TypeData::is_synthetic`].
+	/// Defaults to [`Context::hides_synthetic_members`](crate::schema::Context::hides_synthetic_members).
+	pub fn hide_synthetic(mut self, hide: bool) -> Self {
+		self.hide_synthetic = hide;
+		self
+	}
+
+	/// Only yields members whose name matches `pattern`, where `*` matches any sequence of characters.
+	pub fn named(mut self, pattern: &str) -> Self {
+		self.pattern = Some(pattern.to_string());
+		self
+	}
+
+	fn accessors<'a>(
+		&'a self,
+		methods: &'a [(MethodSemanticsFlags, MetadataToken)],
+	) -> impl Iterator<Item = &'a Method> {
+		methods
+			.iter()
+			.filter_map(|(_, token)| self.ty?.methods.iter().find(|method| method.token == *token))
+	}
+
+	fn accepts(&self, member: &Member) -> bool {
+		if self.hide_synthetic && self.ty.is_some_and(|ty| ty.is_synthetic(member)) {
+			return false;
+		}
+
+		if let Some(pattern) = &self.pattern {
+			if !matches_pattern(pattern, member.name()) {
+				return false;
+			}
+		}
+
+		let (is_static, is_public) = match member {
+			Member::Field(field) => (field.is_static(), field.is_public()),
+			Member::Method(method) => (method.is_static(), method.is_public()),
+			Member::Property(Property { methods, .. }) | Member::Event(Event { methods, .. }) => (
+				self.accessors(methods).any(|method| method.is_static()),
+				self.accessors(methods).any(|method| method.is_public()),
+			),
+			Member::NestedType(ty) => {
+				let flags = ty.data().map(|data| data.flags).unwrap_or(0);
+				(true, flags & type_flags::VISIBILITY_MASK == type_flags::NESTED_PUBLIC)
+			}
+		};
+
+		(!self.static_only || is_static) && (!self.public_only || is_public)
+	}
+}
+
+impl<'l> Iterator for Members<'l> {
+	type Item = Member<'l>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		loop {
+			let member = self.members.next()?;
+			if self.accepts(&member) {
+				return Some(member);
+			}
+		}
+	}
+}
+
+/// Matches `name` against `pattern`, where `*` matches any sequence of characters, as [`Members::named`] does.
+pub fn matches_pattern(pattern: &str, name: &str) -> bool {
+	match pattern.split_once('*') {
+		None => pattern == name,
+		Some((prefix, rest)) => {
+			let Some(name) = name.strip_prefix(prefix) else { return false };
+			(0..=name.len())
+				.filter(|i| name.is_char_boundary(*i))
+				.any(|i| matches_pattern(rest, &name[i..]))
+		}
+	}
+}
@@ -2,10 +2,38 @@ use std::any::type_name;
 mod assembly;
 mod context;
 mod types;
+mod members;
+mod attributes;
+mod module;
+mod format;
+mod extensions;
+mod cache;
+mod reflection;
+mod strings;
+mod docs;
+mod dump;
+mod closure;
+mod interfaces;
+mod graph;
+pub mod ir;
 
 pub use types::*;
+pub use members::*;
+pub use attributes::*;
+pub use module::*;
 pub use context::*;
 pub use assembly::*;
+pub use extensions::*;
+pub use cache::*;
+pub use reflection::*;
+pub use strings::*;
+pub use docs::*;
+pub use dump::*;
+pub use closure::*;
+pub use interfaces::*;
+pub use graph::*;
+pub use format::{MetadataTypeNames, NameStyle, NameSyntax, SignatureFormatter, TypeNameResolver};
+pub(crate) use format::MAX_NESTING;
 
 use std::ops::{Deref, DerefMut};
 use std::fmt::{Debug, Formatter};
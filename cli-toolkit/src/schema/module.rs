@@ -0,0 +1,30 @@
+use crate::raw::{MetadataToken, MetadataTokenKind};
+use crate::schema::CustomAttribute;
+
+/// The module of an assembly, as described by the single row of its Module table.
+#[derive(Debug, Default)]
+pub struct Module {
+	pub(crate) name: String,
+	pub(crate) generation: u16,
+	pub(crate) custom_attributes: Vec<CustomAttribute>,
+}
+
+impl Module {
+	pub fn token(&self) -> MetadataToken {
+		MetadataToken::new(1, MetadataTokenKind::Module)
+	}
+
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	/// The Edit and Continue generation of the module, 0 for images that were not produced by EnC.
+	pub fn generation(&self) -> u16 {
+		self.generation
+	}
+
+	/// The attributes applied to the module, e.g. `System.Security.UnverifiableCodeAttribute`.
+	pub fn custom_attributes(&self) -> &[CustomAttribute] {
+		&self.custom_attributes
+	}
+}
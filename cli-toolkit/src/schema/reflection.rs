@@ -0,0 +1,158 @@
+use crate::raw::{MetadataToken, UserStringHeap, UserStringValue, Utf16Policy};
+use crate::schema::{Context, Member, TypeRef};
+use std::collections::{HashMap, HashSet};
+
+/// A string literal naming a type or member of the context, which may be used through reflection,
+/// e.g. by `Type.GetType("Namespace.Type")` followed by `GetMethod("Member")`.
+#[derive(Debug, Clone)]
+pub struct SoftReference {
+	pub(crate) offset: u32,
+	pub(crate) literal: String,
+	pub(crate) target: SoftReferenceTarget,
+}
+
+#[derive(Debug, Clone)]
+pub enum SoftReferenceTarget {
+	Type(TypeRef),
+	Member {
+		declaring_type: TypeRef,
+		token: MetadataToken,
+	},
+}
+
+impl SoftReference {
+	/// The offset of the literal in the `#US` heap, i.e. the low 24 bits of the `ldstr` tokens loading it.
+	pub fn offset(&self) -> u32 {
+		self.offset
+	}
+
+	pub fn literal(&self) -> &str {
+		&self.literal
+	}
+
+	pub fn target(&self) -> &SoftReferenceTarget {
+		&self.target
+	}
+}
+
+impl Context {
+	/// Scans the string literals of an image for the names of types and members of the context.
+	///
+	/// Types are matched by their reflection name, e.g. ``System.Collections.Generic.List`1`` or `Outer+Inner`,
+	/// optionally qualified by an assembly name. Members are matched either as `Type.Member`,
+	/// or by their name alone if their declaring type is itself referenced by another literal of the image.
+	/// Matches are only plausible references: literals may name types for any other reason.
+	/// Malformed literals are skipped.
+	pub fn soft_references(&self, strings: &UserStringHeap) -> Vec<SoftReference> {
+		let index = self.type_names();
+		let mut literals = vec![];
+		for (offset, string) in strings.iter().flatten() {
+			if let Ok(UserStringValue::Text(literal)) = string.decode(Utf16Policy::Strict) {
+				literals.push((offset, literal));
+			}
+		}
+
+		let mut references = vec![];
+		let mut unmatched = vec![];
+		let mut referenced_types = vec![];
+		for (offset, literal) in literals {
+			// `Type, Assembly, Version=...` and ``Type`1[[Argument, Assembly]]`` name `Type`.
+			let name = literal.split([',', '[']).next().unwrap_or_default().trim();
+			if let Some(types) = index.get(name) {
+				for ty in types {
+					referenced_types.push(ty.clone());
+					references.push(SoftReference {
+						offset,
+						literal: literal.clone(),
+						target: SoftReferenceTarget::Type(ty.clone()),
+					});
+				}
+				continue;
+			}
+
+			let qualified = name
+				.rsplit_once('.')
+				.and_then(|(ty, member)| Some((index.get(ty)?, member)));
+			match qualified {
+				Some((types, member)) => {
+					for ty in types {
+						references.extend(member_references(ty, member, offset, &literal));
+					}
+				}
+				None => unmatched.push((offset, literal)),
+			}
+		}
+
+		let mut seen = HashSet::new();
+		referenced_types.retain(|ty| seen.insert(ty.data().map(|data| (data.assembly.as_ptr(), data.token))));
+		for (offset, literal) in unmatched {
+			for ty in referenced_types.iter() {
+				references.extend(member_references(ty, &literal, offset, &literal));
+			}
+		}
+
+		references.sort_by_key(|reference| reference.offset);
+		references
+	}
+
+	/// The type definitions of every assembly, by reflection name.
+	fn type_names(&self) -> &HashMap<String, Vec<TypeRef>> {
+		self.type_names.get_or_init(|| {
+			let mut index = HashMap::<String, Vec<TypeRef>>::new();
+			for assembly in self.assembly_vec.iter() {
+				let Some(types) = assembly.types.get() else { continue };
+
+				let mut enclosing = HashMap::new();
+				for (position, ty) in types.iter().enumerate() {
+					let Some(data) = ty.data() else { continue };
+					for nested in data.nested_types.iter() {
+						enclosing.insert(nested.index(), position);
+					}
+				}
+
+				for (position, ty) in types.iter().enumerate() {
+					let Some(data) = ty.data() else { continue };
+					let mut name = data.name.to_string();
+					let mut outer = data;
+					let mut depth = 0;
+					while let Some(parent) = enclosing.get(&outer.token.index()).and_then(|p| types[*p].data()) {
+						name = format!("{}+{}", parent.name, name);
+						outer = parent;
+						depth += 1;
+						// Cycles are invalid metadata, and their types get no name.
+						if depth > types.len() {
+							break;
+						}
+					}
+
+					if !outer.namespace.is_empty() {
+						name = format!("{}.{}", outer.namespace, name);
+					}
+
+					index
+						.entry(name)
+						.or_default()
+						.push(TypeRef::new(types.clone(), position));
+				}
+			}
+
+			index
+		})
+	}
+}
+
+fn member_references(ty: &TypeRef, name: &str, offset: u32, literal: &str) -> Vec<SoftReference> {
+	// Accessors such as `get_Name` are looked up by reflection as well.
+	ty.members()
+		.hide_synthetic(false)
+		.filter(|member| !matches!(member, Member::NestedType(_)) && member.name() == name)
+		.map(|member| SoftReference {
+			offset,
+			literal: literal.to_string(),
+			target: SoftReferenceTarget::Member {
+				declaring_type: ty.clone(),
+				token: member.token(),
+			},
+		})
+		.collect()
+}
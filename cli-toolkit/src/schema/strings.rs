@@ -0,0 +1,160 @@
+use crate::raw::{HeapIndex, MetadataHeap, StringHeap};
+use crate::schema::{Assembly, Context, Event, Field, GenericParam, Method, Param, Property, TypeData};
+use std::fmt::{Debug, Display, Formatter};
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+use std::rc::Rc;
+
+/// The name of a type or member, copied out of the `#Strings` heap, or viewed in the copy of the heap
+/// its assembly retained, see [`ContextBuilder::retain_strings`](crate::read::ContextBuilder::retain_strings).
+/// Views share the heap, so loading them allocates nothing per name.
+#[derive(Clone)]
+pub(crate) enum Name {
+	Owned(String),
+	Heap(Rc<[u8]>, HeapIndex),
+}
+
+impl Name {
+	/// Views the name in `retained` if the assembly retains its heap, or copies it out of `heap` otherwise.
+	pub(crate) fn read(heap: StringHeap, retained: Option<&Rc<[u8]>>, index: HeapIndex) -> Self {
+		match retained {
+			Some(retained) => Self::Heap(retained.clone(), index),
+			None => Self::Owned(heap.get_string(index).to_string()),
+		}
+	}
+
+	pub(crate) fn as_str(&self) -> &str {
+		match self {
+			Self::Owned(name) => name,
+			Self::Heap(heap, index) => StringHeap::new(heap).get_string(*index),
+		}
+	}
+}
+
+impl Default for Name {
+	fn default() -> Self {
+		Self::Owned(String::new())
+	}
+}
+
+impl From<&str> for Name {
+	fn from(name: &str) -> Self {
+		Self::Owned(name.to_string())
+	}
+}
+
+impl Deref for Name {
+	type Target = str;
+	fn deref(&self) -> &str {
+		self.as_str()
+	}
+}
+
+impl PartialEq for Name {
+	fn eq(&self, other: &Self) -> bool {
+		self.as_str() == other.as_str()
+	}
+}
+
+impl Eq for Name {}
+
+impl PartialEq<str> for Name {
+	fn eq(&self, other: &str) -> bool {
+		self.as_str() == other
+	}
+}
+
+impl PartialEq<&str> for Name {
+	fn eq(&self, other: &&str) -> bool {
+		self.as_str() == *other
+	}
+}
+
+impl PartialEq<String> for Name {
+	fn eq(&self, other: &String) -> bool {
+		self.as_str() == other
+	}
+}
+
+impl Hash for Name {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		self.as_str().hash(state)
+	}
+}
+
+impl Display for Name {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		Display::fmt(self.as_str(), f)
+	}
+}
+
+impl Debug for Name {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		Debug::fmt(self.as_str(), f)
+	}
+}
+
+/// A type or member whose name is stored in the `#Strings` heap of its assembly.
+pub trait HeapName {
+	fn name_index(&self) -> HeapIndex;
+}
+
+/// Names borrowed from the `#Strings` heap an assembly retained while loading, see [`ContextBuilder::retain_strings`](crate::read::ContextBuilder::retain_strings).
+///
+/// The views are only meaningful for the types and members of the assembly they were obtained from.
+/// Passing the members of another assembly is not unsafe, but returns unrelated or empty strings.
+#[derive(Debug, Copy, Clone)]
+pub struct StringViews<'l> {
+	heap: StringHeap<'l>,
+}
+
+impl<'l> StringViews<'l> {
+	pub fn name(&self, item: &impl HeapName) -> &'l str {
+		self.heap.get_string(item.name_index())
+	}
+
+	pub fn namespace(&self, ty: &TypeData) -> &'l str {
+		self.heap.get_string(ty.namespace_index)
+	}
+
+	pub fn get(&self, index: HeapIndex) -> &'l str {
+		self.heap.get_string(index)
+	}
+}
+
+impl Assembly {
+	/// `None` unless the context was loaded with [`ContextBuilder::retain_strings`](crate::read::ContextBuilder::retain_strings).
+	pub fn string_views(&self) -> Option<StringViews<'_>> {
+		let heap = StringHeap::new(self.strings.as_deref()?);
+		Some(StringViews { heap })
+	}
+}
+
+impl Context {
+	/// The string views of the assembly declaring `ty`, borrowed for as long as the context is,
+	/// so that names can be collected without copying them, e.g. into indexes that live next to the context.
+	///
+	/// `None` if `ty` does not belong to this context or the context was loaded without retaining its strings.
+	pub fn string_views(&self, ty: &TypeData) -> Option<StringViews<'_>> {
+		let assembly = self
+			.assembly_vec
+			.iter()
+			.find(|assembly| std::ptr::eq(Rc::as_ptr(assembly), ty.assembly.as_ptr()))?;
+
+		assembly.string_views()
+	}
+}
+
+macro_rules! heap_name {
+	($($ty: ident),*) => {
+		$(
+			impl HeapName for $ty {
+				fn name_index(&self) -> HeapIndex {
+					self.name_index
+				}
+			}
+		)*
+	};
+}
+
+heap_name!(TypeData, Field, Method, Param, Property, Event, GenericParam);
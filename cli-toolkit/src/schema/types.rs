@@ -1,8 +1,15 @@
 use std::cell::RefCell;
-use crate::raw::{MetadataToken, TableIndex, TypeFlags};
+use crate::raw::{
+	method_flags, HeapIndex, MetadataToken, MetadataTokenKind, MethodSignature, TableIndex, TypeFlags, TypeSignature,
+};
 use std::fmt::{Debug, Display, Formatter};
 use std::ops::Deref;
 use crate::schema::assembly::Assembly;
+use crate::schema::format::SignatureFormatter;
+use crate::schema::{
+	CustomAttribute, Event, Field, GenericParam, InterfaceImpl, Member, Members, Method, Name, Property, Provenance,
+	EXTENSION_ATTRIBUTE,
+};
 use crate::utilities::IndexedRcRef;
 use std::rc::{Rc, Weak};
 use bitvec::mem::elts;
@@ -31,6 +38,8 @@ pub enum Type {
 	Uninitialized(TypeData),
 	CustomUnknown(TypeData),
 	NotLoaded(MetadataToken),
+	/// A type definition that could not be loaded, along with the reason.
+	Error(MetadataToken, String),
 }
 
 impl Type {
@@ -50,13 +59,107 @@ impl Type {
 	}
 }
 
+impl Type {
+	pub fn data(&self) -> Option<&TypeData> {
+		match self {
+			Type::Enum(data)
+			| Type::Class(data)
+			| Type::Struct(data)
+			| Type::Interface(data)
+			| Type::Uninitialized(data)
+			| Type::CustomUnknown(data) => Some(data),
+			_ => None,
+		}
+	}
+
+	/// A short lowercase description of the kind of type, e.g. `class` or `struct`.
+	pub fn kind(&self) -> &'static str {
+		match self {
+			Type::Enum(_) => "enum",
+			Type::Class(_) => "class",
+			Type::Struct(_) => "struct",
+			Type::Interface(_) => "interface",
+			Type::Uninitialized(_) => "uninitialized",
+			Type::CustomUnknown(_) => "unknown",
+			Type::NotLoaded(_) => "not loaded",
+			Type::Error(..) => "error",
+			_ => "primitive",
+		}
+	}
+
+	/// Iterates over the fields, methods, properties, events and nested types of the type in token order.
+	pub fn members(&self) -> Members<'_> {
+		Members::new(self.data())
+	}
+
+	/// The custom attributes applied to the type definition, if the type has one.
+	pub fn attributes(&self) -> &[CustomAttribute] {
+		self.data().map_or(&[], |data| &data.custom_attributes)
+	}
+
+	/// The type initializer, a static method named `.cctor`.
+	pub fn static_constructor(&self) -> Option<&Method> {
+		self.special_method(".cctor", true)
+	}
+
+	/// The instance constructor without parameters, whatever its accessibility.
+	pub fn default_constructor(&self) -> Option<&Method> {
+		self.special_method(".ctor", false)
+	}
+
+	/// The `Finalize` method declared by the type, i.e. the `~T()` destructor of C#.
+	/// It must be a protected virtual instance method, without parameters and returning `void`.
+	pub fn finalizer(&self) -> Option<&Method> {
+		self.data()?.methods.iter().find(|method| {
+			method.name == "Finalize"
+				&& method.flags & (method_flags::STATIC | method_flags::VIRTUAL) == method_flags::VIRTUAL
+				&& method.flags & method_flags::MEMBER_ACCESS_MASK == method_flags::FAMILY
+				&& is_parameterless_void(method)
+		})
+	}
+
+	/// Whether the type is a delegate, i.e. a class deriving from `System.MulticastDelegate`.
+	/// The base type is compared by name, so that delegates are recognized even if the corlib is not loaded.
+	pub fn is_delegate(&self) -> bool {
+		let (Type::Class(data) | Type::CustomUnknown(data)) = self else { return false };
+		let Some(assembly) = data.assembly.upgrade() else { return false };
+		assembly.type_name(data.base).as_deref() == Some("System.MulticastDelegate")
+	}
+
+	/// The signature of the `Invoke` method of a delegate type, which is the signature of the function it represents.
+	pub fn delegate_signature(&self) -> Option<Rc<MethodSignature>> {
+		if !self.is_delegate() {
+			return None;
+		}
+
+		let data = self.data()?;
+		let invoke = data
+			.methods
+			.iter()
+			.find(|method| method.name == "Invoke" && !method.is_static())?;
+		data.assembly.upgrade()?.method_signature(invoke).ok()
+	}
+
+	fn special_method(&self, name: &str, is_static: bool) -> Option<&Method> {
+		self.data()?.methods.iter().find(|method| {
+			method.name == name
+				&& method.flags & method_flags::RT_SPECIAL_NAME != 0
+				&& method.is_static() == is_static
+				&& is_parameterless_void(method)
+		})
+	}
+}
+
+fn is_parameterless_void(method: &Method) -> bool {
+	let Ok(signature) = MethodSignature::parse(&method.signature) else { return false };
+	signature.params.is_empty() && signature.return_type == TypeSignature::Void
+}
+
 impl Display for Type {
 	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-		match self {
-			Type::Class(data) => Display::fmt(data, f),
-			Type::Struct(data) => Display::fmt(data, f),
-			Type::Interface(data) => Display::fmt(data, f),
-			_ => Debug::fmt(self, f),
+		match self.data() {
+			Some(data) => Display::fmt(data, f),
+			None => Debug::fmt(self, f),
 		}
 	}
 }
@@ -66,12 +169,141 @@ pub type TypeRef = IndexedRcRef<Type, [Type]>;
 pub struct TypeData {
 	pub(crate) assembly: Weak<Assembly>,
 
-	pub(crate) name: String,
-	pub(crate) namespace: String,
+	pub(crate) name: Name,
+	pub(crate) namespace: Name,
+	pub(crate) name_index: HeapIndex,
+	pub(crate) namespace_index: HeapIndex,
 	pub(crate) flags: TypeFlags,
 	pub(crate) base: MetadataToken,
 	pub(crate) token: MetadataToken,
-	pub(crate) fields: Vec<TableIndex>,
+	pub(crate) fields: Vec<Field>,
+	pub(crate) methods: Vec<Method>,
+	pub(crate) properties: Vec<Property>,
+	pub(crate) events: Vec<Event>,
+	pub(crate) nested_types: Vec<MetadataToken>,
+	pub(crate) generic_params: Vec<GenericParam>,
+	pub(crate) interfaces: Vec<InterfaceImpl>,
+	pub(crate) custom_attributes: Vec<CustomAttribute>,
+	pub(crate) provenance: Provenance,
+}
+
+impl TypeData {
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	pub fn namespace(&self) -> &str {
+		&self.namespace
+	}
+
+	pub fn flags(&self) -> TypeFlags {
+		self.flags
+	}
+
+	pub fn token(&self) -> MetadataToken {
+		self.token
+	}
+
+	pub fn fields(&self) -> &[Field] {
+		&self.fields
+	}
+
+	pub fn methods(&self) -> &[Method] {
+		&self.methods
+	}
+
+	pub fn properties(&self) -> &[Property] {
+		&self.properties
+	}
+
+	pub fn events(&self) -> &[Event] {
+		&self.events
+	}
+
+	pub fn generic_params(&self) -> &[GenericParam] {
+		&self.generic_params
+	}
+
+	/// The interfaces the type declares it implements, not including those of its base types.
+	pub fn interfaces(&self) -> &[InterfaceImpl] {
+		&self.interfaces
+	}
+
+	pub fn custom_attributes(&self) -> &[CustomAttribute] {
+		&self.custom_attributes
+	}
+
+	pub fn provenance(&self) -> Provenance {
+		self.provenance
+	}
+
+	pub fn method(&self, token: MetadataToken) -> Option<&Method> {
+		self.methods.iter().find(|method| method.token == token)
+	}
+
+	/// Whether the type is marked with `ExtensionAttribute`, as the compilers do for types declaring extension methods.
+	pub fn has_extension_methods(&self) -> bool {
+		self.custom_attributes
+			.iter()
+			.any(|attribute| attribute.attribute_type == EXTENSION_ATTRIBUTE)
+	}
+
+	/// The member name given by the `DefaultMemberAttribute` of the type, which identifies its indexers.
+	pub fn default_member(&self) -> Option<&str> {
+		self.custom_attributes
+			.iter()
+			.find(|attribute| attribute.attribute_type == "System.Reflection.DefaultMemberAttribute")
+			.and_then(|attribute| attribute.string_argument())
+	}
+
+	/// Whether the type was emitted by the compiler rather than declared in source,
+	/// e.g. an anonymous type, a closure display class or the state machine of an async method.
+	/// The C# and VB compilers give them names that are not valid identifiers, starting with `<` or containing `$`.
+	pub fn is_compiler_generated(&self) -> bool {
+		is_generated_name(&self.name)
+	}
+
+	/// Whether a member of the type is noise left by the compiler rather than part of the surface seen from source:
+	/// property and event accessors, backing fields, the fields of field-like events, lambdas, local functions
+	/// and compiler generated nested types. They are left out by [`Members::hide_synthetic`].
+	pub fn is_synthetic(&self, member: &Member) -> bool {
+		match member {
+			Member::Field(field) => {
+				let is_event_field = !field.is_public() && self.events.iter().any(|event| event.name == field.name);
+				is_generated_name(&field.name) || is_event_field
+			}
+			Member::Method(method) => {
+				let accessors = self.properties.iter().flat_map(|property| property.methods.iter());
+				let mut accessors = accessors.chain(self.events.iter().flat_map(|event| event.methods.iter()));
+				is_generated_name(&method.name) || accessors.any(|(_, token)| *token == method.token)
+			}
+			Member::Property(property) => is_generated_name(&property.name),
+			Member::Event(event) => is_generated_name(&event.name),
+			Member::NestedType(ty) => ty.data().is_some_and(TypeData::is_compiler_generated),
+		}
+	}
+
+	pub fn nested_types(&self) -> impl Iterator<Item = TypeRef> {
+		let types = self
+			.assembly
+			.upgrade()
+			.and_then(|assembly| assembly.types.get().cloned());
+		let nested = match types {
+			None => vec![],
+			Some(types) => self
+				.nested_types
+				.iter()
+				.filter(|token| token.index() != 0 && token.index() <= types.len())
+				.map(|token| TypeRef::new(types.clone(), token.index() - 1))
+				.collect(),
+		};
+
+		nested.into_iter()
+	}
+}
+
+fn is_generated_name(name: &str) -> bool {
+	name.starts_with('<') || name.contains('$')
 }
 
 impl Display for TypeData {
@@ -83,31 +315,222 @@ impl Display for TypeData {
 	}
 }
 
+/// How many base types the [`Debug`] output of a [`TypeData`] follows.
+const DEBUG_BASE_DEPTH: usize = 4;
+
+/// Only prints the names of up to [`DEBUG_BASE_DEPTH`] base types and the number of members, so that the output
+/// stays small and does not depend on how other assemblies were loaded: the chain stops at the first TypeRef.
+/// The base type is printed as a token once the assembly has been dropped, see [`Context::display`](crate::schema::Context::display)
+/// for more control over the output.
 impl Debug for TypeData {
 	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-		let Some(assembly) = self.assembly.upgrade() else {
-			return f.write_str("null");
-		};
-
-		let missing = &Type::NotLoaded(self.base);
-		let base = assembly.get_type(self.base);
-
+		let assembly = self.assembly.upgrade();
 		let mut f = f.debug_struct("TypeData");
 		f.field("token", &self.token);
 		f.field("name", &self.name);
 		f.field("namespace", &self.namespace);
 		f.field("flags", &format_args!("0x{:X}", self.flags));
 
-		let base = match base {
-			None => f.field("base", &format_args!("{}", missing)),
-			Some(ty) => f.field("base", &format_args!("{}", ty.deref())),
+		match (self.base.is_null(), assembly) {
+			(true, _) => f.field("base", &format_args!("null")),
+			(false, Some(assembly)) => f.field("base", &self.local_bases(&assembly).join(" : ")),
+			(false, None) => f.field("base", &self.base),
 		};
 
+		f.field("fields", &self.fields.len());
+		f.field("methods", &self.methods.len());
+		f.field("properties", &self.properties.len());
+		f.field("events", &self.events.len());
+		f.field("nested_types", &self.nested_types.len());
 		f.finish()
 	}
 }
 
-pub struct Field {
-	pub(crate) assembly: Weak<Assembly>,
-	pub(crate) parent: MetadataToken,
+impl TypeData {
+	/// The names of up to [`DEBUG_BASE_DEPTH`] base types declared in `assembly`, nearest first,
+	/// followed by the name of the first base type referenced from another assembly.
+	fn local_bases(&self, assembly: &Assembly) -> Vec<String> {
+		let mut bases = vec![];
+		let mut current = None::<TypeRef>;
+		let mut base = self.base;
+		while !base.is_null() && bases.len() < DEBUG_BASE_DEPTH {
+			bases.push(assembly.type_name(base).unwrap_or_else(|| format!("{:?}", base)));
+			if base.token_kind() != Some(MetadataTokenKind::TypeDef) {
+				break;
+			}
+
+			current = assembly.get_type(base);
+			base = match current.as_ref().and_then(|ty| ty.data()) {
+				Some(data) => data.base,
+				None => break,
+			};
+		}
+
+		bases
+	}
+}
+
+/// Formats a type along with its base types, see [`Context::display`](crate::schema::Context::display).
+///
+/// ```ignore
+/// println!("{}", ctx.display(&ty).with_depth(3));
+/// // class MyApp.Button : MyApp.Control : System.Object
+/// ```
+pub struct TypeDisplay<'l> {
+	ty: &'l Type,
+	resolve: bool,
+	depth: usize,
+	max_depth: usize,
+	tokens_only: bool,
+}
+
+impl<'l> TypeDisplay<'l> {
+	pub(crate) fn new(ty: &'l Type, max_depth: usize) -> Self {
+		Self {
+			ty,
+			resolve: true,
+			depth: 1,
+			max_depth,
+			tokens_only: false,
+		}
+	}
+
+	/// How many base types to print, 1 by default and at most [`Context::max_resolution_depth`](crate::schema::Context::max_resolution_depth).
+	/// Base types are never formatted recursively beyond their name.
+	pub fn with_depth(mut self, depth: usize) -> Self {
+		self.depth = depth;
+		self
+	}
+
+	/// Whether to follow base types declared in other assemblies, which is the default.
+	/// When disabled, the chain stops at the first TypeRef, which is printed by name.
+	pub fn resolve(mut self, resolve: bool) -> Self {
+		self.resolve = resolve;
+		self
+	}
+
+	/// Prints tokens instead of names, so that the output does not depend on the names of other assemblies.
+	pub fn tokens_only(mut self) -> Self {
+		self.tokens_only = true;
+		self
+	}
+
+	/// The base types to print, nearest first.
+	fn bases(&self) -> Vec<String> {
+		let mut bases = vec![];
+		let mut current = None::<TypeRef>;
+		let mut data = self.ty.data();
+		while let Some(ty) = data.filter(|ty| !ty.base.is_null() && bases.len() < self.depth.min(self.max_depth)) {
+			let Some(assembly) = ty.assembly.upgrade() else {
+				bases.push(format!("{:?}", ty.base));
+				break;
+			};
+
+			let is_local = ty.base.token_kind() == Some(MetadataTokenKind::TypeDef);
+			let base = match self.resolve || is_local {
+				true => assembly.get_type(ty.base),
+				false => None,
+			};
+
+			let Some(base) = base else {
+				let name = match self.tokens_only {
+					true => None,
+					false => assembly.styled_type_name(ty.base, &assembly.name_style()),
+				};
+				bases.push(name.unwrap_or_else(|| format!("{:?}", ty.base)));
+				break;
+			};
+
+			bases.push(self.label(&base));
+			current = Some(base);
+			data = current.as_ref().and_then(|base| base.data());
+		}
+
+		bases
+	}
+
+	/// The name of `ty` in the [`NameStyle`](crate::schema::NameStyle) of its assembly.
+	fn label(&self, ty: &Type) -> String {
+		match (ty, ty.data()) {
+			(_, Some(data)) if self.tokens_only => format!("{:?}", data.token),
+			(_, Some(data)) => {
+				let assembly = data.assembly.upgrade();
+				let style = assembly
+					.as_ref()
+					.map(|assembly| assembly.name_style())
+					.unwrap_or_default();
+				let name = assembly.and_then(|assembly| assembly.styled_type_name(data.token, &style));
+				name.unwrap_or_else(|| style.join(&data.namespace, [data.name.as_str()]))
+			}
+			(Type::NotLoaded(token) | Type::Error(token, _), _) => format!("{:?}", token),
+			(Type::Object, _) => "System.Object".to_string(),
+			(Type::String, _) => "System.String".to_string(),
+			(ty, _) => format!("{:?}", ty),
+		}
+	}
+}
+
+impl Display for TypeDisplay<'_> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{} {}", self.ty.kind(), self.label(self.ty))?;
+		for base in self.bases() {
+			write!(f, " : {}", base)?;
+		}
+		Ok(())
+	}
+}
+
+impl Debug for TypeDisplay<'_> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		let Some(data) = self.ty.data() else { return Debug::fmt(self.ty, f) };
+		let mut f = f.debug_struct(self.ty.kind());
+		f.field("token", &data.token);
+		if !self.tokens_only {
+			f.field("name", &data.name);
+			f.field("namespace", &data.namespace);
+		}
+
+		f.field("flags", &format_args!("0x{:X}", data.flags));
+		f.field("bases", &self.bases());
+		f.field("fields", &data.fields.len());
+		f.field("methods", &data.methods.len());
+		f.field("properties", &data.properties.len());
+		f.field("events", &data.events.len());
+		f.field("nested_types", &data.nested_types.len());
+		f.finish()
+	}
+}
+
+/// A constructed type from the TypeSpec table, such as a generic instantiation, an array or a pointer.
+#[derive(Debug, Clone)]
+pub struct TypeSpec {
+	pub(crate) token: MetadataToken,
+	pub(crate) signature: TypeSignature,
+}
+
+impl TypeSpec {
+	pub fn token(&self) -> MetadataToken {
+		self.token
+	}
+
+	pub fn signature(&self) -> &TypeSignature {
+		&self.signature
+	}
+
+	/// Renders the type in the [`NameStyle`](crate::schema::NameStyle) of `assembly`,
+	/// e.g. `System.Collections.Generic.List<int>`, naming the types it refers to through `assembly`.
+	pub fn display<'l>(&'l self, assembly: &'l Assembly) -> TypeSpecDisplay<'l> {
+		TypeSpecDisplay { spec: self, assembly }
+	}
+}
+
+pub struct TypeSpecDisplay<'l> {
+	spec: &'l TypeSpec,
+	assembly: &'l Assembly,
+}
+
+impl Display for TypeSpecDisplay<'_> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		SignatureFormatter::new(Some(self.assembly)).write_type(f, &self.spec.signature)
+	}
 }
@@ -0,0 +1,508 @@
+//! Handles and a [`MetadataReader`] shaped like the ones of `System.Reflection.Metadata`,
+//! so that tools written against it can be ported without restructuring them first.
+//!
+//! ```ignore
+//! let reader = MetadataReader::from_assembly(&assembly)?;
+//! for handle in reader.type_definitions() {
+//!     let ty = reader.get_type_definition(handle)?;
+//!     println!("{}.{}", reader.get_string(ty.namespace), reader.get_string(ty.name));
+//!     for method in ty.get_methods() {
+//!         println!("    {}", reader.get_string(reader.get_method_definition(method)?.name));
+//!     }
+//! }
+//! ```
+//!
+//! Handles are thin wrappers around [`TableIndex`], [`HeapIndex`] and [`MetadataToken`].
+//! Unlike the readers of the [`schema`](crate::schema), this one does not follow the Ptr tables of uncompressed metadata,
+//! so member lists are the raw row ranges of the owning table.
+
+use crate::raw::{
+	AssemblyFlags, AssemblyRefTable, BlobHeap, CodedIndexKind, CustomAttributeTable, Error, EventFlags, EventTable,
+	FieldFlags, FieldTable, GuidHeap, HeapIndex, MemberRefTable, Metadata, MetadataHeap, MetadataTable, MetadataToken,
+	MetadataTableImpl, MethodDefTable, MethodFlags, MethodImplFlags, ModuleRefTable, ModuleTable, ParamFlags,
+	ParamTable, PropertyFlags, PropertyTable, StringHeap, TableHeap, TableIndex, TableKind, TypeDefTable, TypeFlags,
+	TypeRefTable, UserStringHeap, Utf16Policy, UserStringValue,
+};
+use crate::schema::AssemblyVersion;
+use std::ops::Range;
+use uuid::Uuid;
+use crate::raw;
+
+/// A reference to any table row, like `EntityHandle`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct EntityHandle(MetadataToken);
+
+impl EntityHandle {
+	pub fn token(&self) -> MetadataToken {
+		self.0
+	}
+
+	/// `None` for the nil handle, e.g. the base type of `System.Object`.
+	pub fn kind(&self) -> Option<TableKind> {
+		match self.is_nil() {
+			true => None,
+			false => self.0.table_kind(),
+		}
+	}
+
+	pub fn is_nil(&self) -> bool {
+		self.0.index() == 0
+	}
+}
+
+impl From<MetadataToken> for EntityHandle {
+	fn from(token: MetadataToken) -> Self {
+		Self(token)
+	}
+}
+
+macro_rules! table_handles {
+	($($handle: ident => $table: ident),* $(,)?) => {
+		$(
+			#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Ord, PartialOrd)]
+			pub struct $handle(TableIndex);
+
+			impl $handle {
+				pub fn is_nil(&self) -> bool {
+					self.0 .0 == 0
+				}
+
+				/// The 1-based row of the handle, as returned by `MetadataTokens.GetRowNumber`.
+				pub fn row_number(&self) -> u32 {
+					self.0 .0
+				}
+
+				pub fn table_index(&self) -> TableIndex {
+					self.0
+				}
+			}
+
+			impl From<TableIndex> for $handle {
+				fn from(index: TableIndex) -> Self {
+					Self(index)
+				}
+			}
+
+			impl From<$handle> for EntityHandle {
+				fn from(handle: $handle) -> Self {
+					Self(MetadataToken::from_table_row(TableKind::$table, handle.0))
+				}
+			}
+
+			impl TryFrom<EntityHandle> for $handle {
+				type Error = Error;
+
+				fn try_from(handle: EntityHandle) -> Result<Self, Self::Error> {
+					match handle.kind() {
+						Some(TableKind::$table) => Ok(Self(TableIndex(handle.0.index() as u32))),
+						_ => Err(Error::InvalidData(Some(concat!("Not a ", stringify!($handle))))),
+					}
+				}
+			}
+		)*
+	};
+}
+
+table_handles! {
+	TypeDefinitionHandle => TypeDef,
+	TypeReferenceHandle => TypeRef,
+	TypeSpecificationHandle => TypeSpec,
+	FieldDefinitionHandle => Field,
+	MethodDefinitionHandle => MethodDef,
+	ParameterHandle => Param,
+	MemberReferenceHandle => MemberRef,
+	PropertyDefinitionHandle => Property,
+	EventDefinitionHandle => Event,
+	CustomAttributeHandle => CustomAttribute,
+	AssemblyReferenceHandle => AssemblyRef,
+	ModuleReferenceHandle => ModuleRef,
+}
+
+macro_rules! heap_handles {
+	($($handle: ident),*) => {
+		$(
+			#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+			pub struct $handle(HeapIndex);
+
+			impl $handle {
+				pub fn is_nil(&self) -> bool {
+					self.0 .0 == 0
+				}
+
+				pub fn heap_index(&self) -> HeapIndex {
+					self.0
+				}
+			}
+
+			impl From<HeapIndex> for $handle {
+				fn from(index: HeapIndex) -> Self {
+					Self(index)
+				}
+			}
+		)*
+	};
+}
+
+heap_handles!(StringHandle, BlobHandle, GuidHandle);
+
+/// The offset of a string of the `#US` heap, i.e. the low 24 bits of an `ldstr` token.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct UserStringHandle(pub u32);
+
+#[derive(Debug, Copy, Clone)]
+pub struct ModuleDefinition {
+	pub generation: u16,
+	pub name: StringHandle,
+	pub mvid: GuidHandle,
+	pub generation_id: GuidHandle,
+	pub base_generation_id: GuidHandle,
+}
+
+#[derive(Debug, Clone)]
+pub struct TypeDefinition {
+	pub attributes: TypeFlags,
+	pub name: StringHandle,
+	pub namespace: StringHandle,
+	pub base_type: EntityHandle,
+	fields: Range<u32>,
+	methods: Range<u32>,
+}
+
+impl TypeDefinition {
+	pub fn get_fields(&self) -> impl Iterator<Item = FieldDefinitionHandle> {
+		self.fields.clone().map(|row| FieldDefinitionHandle(TableIndex(row)))
+	}
+
+	pub fn get_methods(&self) -> impl Iterator<Item = MethodDefinitionHandle> {
+		self.methods.clone().map(|row| MethodDefinitionHandle(TableIndex(row)))
+	}
+}
+
+#[derive(Debug, Clone)]
+pub struct MethodDefinition {
+	pub attributes: MethodFlags,
+	pub impl_attributes: MethodImplFlags,
+	pub relative_virtual_address: u32,
+	pub name: StringHandle,
+	pub signature: BlobHandle,
+	params: Range<u32>,
+}
+
+impl MethodDefinition {
+	pub fn get_parameters(&self) -> impl Iterator<Item = ParameterHandle> {
+		self.params.clone().map(|row| ParameterHandle(TableIndex(row)))
+	}
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct FieldDefinition {
+	pub attributes: FieldFlags,
+	pub name: StringHandle,
+	pub signature: BlobHandle,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct Parameter {
+	pub attributes: ParamFlags,
+	pub sequence_number: u16,
+	pub name: StringHandle,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct TypeReference {
+	pub resolution_scope: EntityHandle,
+	pub name: StringHandle,
+	pub namespace: StringHandle,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct MemberReference {
+	pub parent: EntityHandle,
+	pub name: StringHandle,
+	pub signature: BlobHandle,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct PropertyDefinition {
+	pub attributes: PropertyFlags,
+	pub name: StringHandle,
+	pub signature: BlobHandle,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct EventDefinition {
+	pub attributes: EventFlags,
+	pub name: StringHandle,
+	pub event_type: EntityHandle,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct CustomAttribute {
+	pub parent: EntityHandle,
+	pub constructor: EntityHandle,
+	pub value: BlobHandle,
+}
+
+#[derive(Debug, Clone)]
+pub struct AssemblyReference {
+	pub name: StringHandle,
+	pub culture: StringHandle,
+	pub version: AssemblyVersion,
+	pub flags: AssemblyFlags,
+	pub public_key_or_token: BlobHandle,
+	pub hash_value: BlobHandle,
+}
+
+/// Reads rows and heap entries by handle, like `System.Reflection.Metadata.MetadataReader`.
+#[derive(Clone)]
+pub struct MetadataReader<'l> {
+	tables: TableHeap<'l>,
+	strings: StringHeap<'l>,
+	blobs: BlobHeap<'l>,
+	guids: GuidHeap<'l>,
+	user_strings: UserStringHeap<'l>,
+}
+
+impl<'l> MetadataReader<'l> {
+	/// Fails if the metadata has no `#~` stream or its row counts are cut short. Missing heaps read as empty.
+	pub fn new(metadata: &Metadata<'l>) -> Result<Self, Error> {
+		let tables = metadata
+			.tables()?
+			.ok_or(Error::InvalidData(Some("Missing metadata tables")))?;
+		tables.present_tables()?;
+
+		Ok(Self {
+			tables,
+			strings: metadata.get_heap()?.unwrap_or_else(StringHeap::empty),
+			blobs: metadata.get_heap()?.unwrap_or_else(BlobHeap::empty),
+			guids: metadata.get_heap()?.unwrap_or_else(GuidHeap::empty),
+			user_strings: metadata.get_heap()?.unwrap_or_else(UserStringHeap::empty),
+		})
+	}
+
+	/// Like `PEReader.GetMetadataReader()`.
+	pub fn from_assembly(assembly: &raw::Assembly<'l>) -> Result<Self, Error> {
+		Self::new(&assembly.metadata())
+	}
+
+	pub fn tables(&self) -> &TableHeap<'l> {
+		&self.tables
+	}
+
+	pub fn type_definitions(&self) -> impl Iterator<Item = TypeDefinitionHandle> {
+		self.rows(TableKind::TypeDef).map(TypeDefinitionHandle)
+	}
+
+	pub fn type_references(&self) -> impl Iterator<Item = TypeReferenceHandle> {
+		self.rows(TableKind::TypeRef).map(TypeReferenceHandle)
+	}
+
+	pub fn field_definitions(&self) -> impl Iterator<Item = FieldDefinitionHandle> {
+		self.rows(TableKind::Field).map(FieldDefinitionHandle)
+	}
+
+	pub fn method_definitions(&self) -> impl Iterator<Item = MethodDefinitionHandle> {
+		self.rows(TableKind::MethodDef).map(MethodDefinitionHandle)
+	}
+
+	pub fn member_references(&self) -> impl Iterator<Item = MemberReferenceHandle> {
+		self.rows(TableKind::MemberRef).map(MemberReferenceHandle)
+	}
+
+	pub fn property_definitions(&self) -> impl Iterator<Item = PropertyDefinitionHandle> {
+		self.rows(TableKind::Property).map(PropertyDefinitionHandle)
+	}
+
+	pub fn event_definitions(&self) -> impl Iterator<Item = EventDefinitionHandle> {
+		self.rows(TableKind::Event).map(EventDefinitionHandle)
+	}
+
+	pub fn custom_attributes(&self) -> impl Iterator<Item = CustomAttributeHandle> {
+		self.rows(TableKind::CustomAttribute).map(CustomAttributeHandle)
+	}
+
+	pub fn assembly_references(&self) -> impl Iterator<Item = AssemblyReferenceHandle> {
+		self.rows(TableKind::AssemblyRef).map(AssemblyReferenceHandle)
+	}
+
+	pub fn get_module_definition(&self) -> Result<ModuleDefinition, Error> {
+		let row = self.table::<ModuleTable>()?.get(TableIndex(1))?;
+		Ok(ModuleDefinition {
+			generation: row.generation(),
+			name: StringHandle(row.name()),
+			mvid: GuidHandle(row.module_version_id()),
+			generation_id: GuidHandle(row.enc_id()),
+			base_generation_id: GuidHandle(row.enc_base_id()),
+		})
+	}
+
+	pub fn get_type_definition(&self, handle: TypeDefinitionHandle) -> Result<TypeDefinition, Error> {
+		let table = self.table::<TypeDefTable>()?;
+		let row = table.get(handle.0)?;
+		let next = match handle.0 .0 as usize >= table.len() {
+			true => None,
+			false => Some(table.get(TableIndex(handle.0 .0 + 1))?),
+		};
+		let base_type = row.base_type().decode(CodedIndexKind::TypeDefOrRef);
+
+		Ok(TypeDefinition {
+			attributes: row.flags(),
+			name: StringHandle(row.name()),
+			namespace: StringHandle(row.namespace()),
+			base_type: EntityHandle(base_type.unwrap_or(MetadataToken(0))),
+			fields: self.list(row.fields(), next.as_ref().map(|next| next.fields()), TableKind::Field)?,
+			methods: self.list(row.methods(), next.as_ref().map(|next| next.methods()), TableKind::MethodDef)?,
+		})
+	}
+
+	pub fn get_method_definition(&self, handle: MethodDefinitionHandle) -> Result<MethodDefinition, Error> {
+		let table = self.table::<MethodDefTable>()?;
+		let row = table.get(handle.0)?;
+		let next = match handle.0 .0 as usize >= table.len() {
+			true => None,
+			false => Some(table.get(TableIndex(handle.0 .0 + 1))?),
+		};
+
+		Ok(MethodDefinition {
+			attributes: row.flags(),
+			impl_attributes: row.impl_flags(),
+			relative_virtual_address: row.rva(),
+			name: StringHandle(row.name()),
+			signature: BlobHandle(row.signature()),
+			params: self.list(row.params(), next.map(|next| next.params()), TableKind::Param)?,
+		})
+	}
+
+	pub fn get_field_definition(&self, handle: FieldDefinitionHandle) -> Result<FieldDefinition, Error> {
+		let row = self.table::<FieldTable>()?.get(handle.0)?;
+		Ok(FieldDefinition {
+			attributes: row.flags(),
+			name: StringHandle(row.name()),
+			signature: BlobHandle(row.signature()),
+		})
+	}
+
+	pub fn get_parameter(&self, handle: ParameterHandle) -> Result<Parameter, Error> {
+		let row = self.table::<ParamTable>()?.get(handle.0)?;
+		Ok(Parameter {
+			attributes: row.flags(),
+			sequence_number: row.sequence(),
+			name: StringHandle(row.name()),
+		})
+	}
+
+	pub fn get_type_reference(&self, handle: TypeReferenceHandle) -> Result<TypeReference, Error> {
+		let row = self.table::<TypeRefTable>()?.get(handle.0)?;
+		let scope = row.resolution_scope().decode(CodedIndexKind::ResolutionScope);
+		Ok(TypeReference {
+			resolution_scope: EntityHandle(scope.unwrap_or(MetadataToken(0))),
+			name: StringHandle(row.type_name()),
+			namespace: StringHandle(row.type_namespace()),
+		})
+	}
+
+	pub fn get_member_reference(&self, handle: MemberReferenceHandle) -> Result<MemberReference, Error> {
+		let row = self.table::<MemberRefTable>()?.get(handle.0)?;
+		let parent = row.parent().decode(CodedIndexKind::MemberRefParent);
+		Ok(MemberReference {
+			parent: EntityHandle(parent.unwrap_or(MetadataToken(0))),
+			name: StringHandle(row.name()),
+			signature: BlobHandle(row.signature()),
+		})
+	}
+
+	pub fn get_property_definition(&self, handle: PropertyDefinitionHandle) -> Result<PropertyDefinition, Error> {
+		let row = self.table::<PropertyTable>()?.get(handle.0)?;
+		Ok(PropertyDefinition {
+			attributes: row.flags(),
+			name: StringHandle(row.name()),
+			signature: BlobHandle(row.signature()),
+		})
+	}
+
+	pub fn get_event_definition(&self, handle: EventDefinitionHandle) -> Result<EventDefinition, Error> {
+		let row = self.table::<EventTable>()?.get(handle.0)?;
+		let event_type = row.type_().decode(CodedIndexKind::TypeDefOrRef);
+		Ok(EventDefinition {
+			attributes: row.flags(),
+			name: StringHandle(row.name()),
+			event_type: EntityHandle(event_type.unwrap_or(MetadataToken(0))),
+		})
+	}
+
+	pub fn get_custom_attribute(&self, handle: CustomAttributeHandle) -> Result<CustomAttribute, Error> {
+		let row = self.table::<CustomAttributeTable>()?.get(handle.0)?;
+		let parent = row.parent().decode(CodedIndexKind::HasCustomAttribute);
+		let constructor = row.type_().decode(CodedIndexKind::CustomAttributeType);
+		Ok(CustomAttribute {
+			parent: EntityHandle(parent.unwrap_or(MetadataToken(0))),
+			constructor: EntityHandle(constructor.unwrap_or(MetadataToken(0))),
+			value: BlobHandle(row.value()),
+		})
+	}
+
+	pub fn get_assembly_reference(&self, handle: AssemblyReferenceHandle) -> Result<AssemblyReference, Error> {
+		let row = self.table::<AssemblyRefTable>()?.get(handle.0)?;
+		Ok(AssemblyReference {
+			name: StringHandle(row.name()),
+			culture: StringHandle(row.culture()),
+			version: AssemblyVersion::new(
+				row.major_version(),
+				row.minor_version(),
+				row.build_number(),
+				row.revision_number(),
+			),
+			flags: row.flags(),
+			public_key_or_token: BlobHandle(row.public_key()),
+			hash_value: BlobHandle(row.hash_value()),
+		})
+	}
+
+	pub fn get_module_reference_name(&self, handle: ModuleReferenceHandle) -> Result<StringHandle, Error> {
+		let row = self.table::<ModuleRefTable>()?.get(handle.0)?;
+		Ok(StringHandle(row.name()))
+	}
+
+	pub fn get_string(&self, handle: StringHandle) -> &'l str {
+		self.strings.get_string(handle.0)
+	}
+
+	pub fn get_blob_bytes(&self, handle: BlobHandle) -> Result<&'l [u8], Error> {
+		self.blobs.get_blob(handle.0)
+	}
+
+	pub fn get_guid(&self, handle: GuidHandle) -> Result<Uuid, Error> {
+		self.guids.get(handle.0)
+	}
+
+	/// Unpaired surrogates are replaced with U+FFFD, as .NET does when decoding the string.
+	pub fn get_user_string(&self, handle: UserStringHandle) -> Result<String, Error> {
+		match self.user_strings.get_string(handle.0, Utf16Policy::Replace)? {
+			UserStringValue::Text(text) => Ok(text),
+			UserStringValue::Raw(units) => Ok(String::from_utf16_lossy(&units)),
+		}
+	}
+
+	fn table<T: MetadataTableImpl<'l>>(&self) -> Result<T, Error> {
+		match self.tables.get_table::<T>()? {
+			Some(table) => Ok(table),
+			None => Err(Error::RowIndexOutOfBounds {
+				table: T::cli_identifier(),
+				index: 1,
+				len: 0,
+			}),
+		}
+	}
+
+	/// The row counts were checked to be readable when the reader was created.
+	fn rows(&self, table: TableKind) -> impl Iterator<Item = TableIndex> {
+		(1..=self.tables.row_count(table).unwrap_or(0) as u32).map(TableIndex)
+	}
+
+	/// The rows of `table` from `start` up to the start of the next list, or the end of the table for the last one.
+	fn list(&self, start: TableIndex, next: Option<TableIndex>, table: TableKind) -> Result<Range<u32>, Error> {
+		let end = self.tables.row_count(table)? as u32 + 1;
+		let next = next.map_or(end, |next| next.0.min(end));
+		Ok(start.0.min(next)..next)
+	}
+}
@@ -0,0 +1,768 @@
+//! Generation of small but valid assembly images, so that parsers can be tested without binary fixtures.
+//!
+//! ```ignore
+//! let mut builder = ImageBuilder::new("Tests");
+//! let core = builder.assembly_ref("System.Runtime", [7, 0, 0, 0]);
+//! let object = builder.type_ref(core, "System", "Object");
+//! builder.type_def("Tests", "Foo", type_flags::PUBLIC, Some(object));
+//! builder.method("Bar", method_flags::PUBLIC, &[0x20, 0x00, 0x01]);
+//! let bytes = builder.build();
+//! ```
+
+use std::collections::HashMap;
+use crate::raw::*;
+
+const FILE_ALIGNMENT: u32 = 0x200;
+const SECTION_ALIGNMENT: u32 = 0x2000;
+const CLI_HEADER_SIZE: u32 = 72;
+const RUNTIME_VERSION: &str = "v4.0.30319";
+
+/// Builds the PE image of a single-module assembly, one metadata row at a time.
+///
+/// Members are attached to the type definition added last, and parameters to the method added last,
+/// mirroring the order in which their rows are laid out in the metadata tables.
+/// Every table, and every heap whose indices are not forced to be fat, must stay small enough for 2 byte indices.
+pub struct ImageBuilder {
+	strings: HeapWriter,
+	blobs: HeapWriter,
+	user_strings: Vec<u8>,
+	guids: Vec<[u8; 16]>,
+
+	assembly: [u32; 3],
+	version: [u16; 4],
+	hash_algorithm: u32,
+	assembly_refs: Vec<[u32; 4]>,
+	assembly_ref_versions: Vec<[u16; 4]>,
+	type_refs: Vec<(MetadataToken, u32, u32)>,
+	type_defs: Vec<(TypeFlags, u32, u32, MetadataToken, u32, u32)>,
+	fields: Vec<(FieldFlags, u32, u32)>,
+	methods: Vec<(MethodFlags, u32, u32, u32)>,
+	params: Vec<(ParamFlags, u16, u32)>,
+	member_refs: Vec<(MetadataToken, u32, u32)>,
+	custom_attributes: Vec<(MetadataToken, MetadataToken, u32)>,
+	method_impls: Vec<(MetadataToken, MetadataToken, MetadataToken)>,
+	interface_impls: Vec<(MetadataToken, MetadataToken)>,
+	nested_classes: Vec<(MetadataToken, MetadataToken)>,
+	generic_params: Vec<(u16, GenericParamFlags, MetadataToken, u32)>,
+	generic_param_constraints: Vec<(MetadataToken, MetadataToken)>,
+	type_specs: Vec<u32>,
+	method_specs: Vec<(MetadataToken, u32)>,
+	/// The encoded bodies of the methods that have one, by MethodDef row.
+	method_bodies: Vec<(u32, Vec<u8>)>,
+	fat_heap_indices: FatHeapIndices,
+}
+
+impl ImageBuilder {
+	/// Creates an assembly named `name`, containing the `<Module>` type and nothing else.
+	pub fn new(name: &str) -> Self {
+		let mut builder = Self {
+			strings: HeapWriter::new(),
+			blobs: HeapWriter::new(),
+			user_strings: vec![0],
+			guids: vec![],
+			assembly: [0; 3],
+			version: [1, 0, 0, 0],
+			hash_algorithm: 0x8004,
+			assembly_refs: vec![],
+			assembly_ref_versions: vec![],
+			type_refs: vec![],
+			type_defs: vec![],
+			fields: vec![],
+			methods: vec![],
+			params: vec![],
+			member_refs: vec![],
+			custom_attributes: vec![],
+			method_impls: vec![],
+			interface_impls: vec![],
+			nested_classes: vec![],
+			generic_params: vec![],
+			generic_param_constraints: vec![],
+			type_specs: vec![],
+			method_specs: vec![],
+			method_bodies: vec![],
+			fat_heap_indices: FatHeapIndices::default(),
+		};
+
+		let name = builder.strings.string(name);
+		let module = builder.strings.string(&format!("{}.dll", builder.strings.get(name)));
+		let mvid = (name as u128 + 1).to_le_bytes();
+		builder.guids.push(mvid);
+		builder.assembly = [name, module, builder.guids.len() as u32];
+		builder.type_def("", "<Module>", 0, None);
+		builder
+	}
+
+	/// The token of the module, for module level custom attributes.
+	pub fn module(&self) -> MetadataToken {
+		MetadataToken::new(1, MetadataTokenKind::Module)
+	}
+
+	pub fn version(mut self, version: [u16; 4]) -> Self {
+		self.version = version;
+		self
+	}
+
+	/// Sets the HashAlgId column of the Assembly row, SHA1 by default.
+	pub fn hash_algorithm(mut self, hash_algorithm: u32) -> Self {
+		self.hash_algorithm = hash_algorithm;
+		self
+	}
+
+	/// Writes 4 byte indices into the given heaps even though they are small enough for 2 byte ones,
+	/// like some compilers always do.
+	pub fn fat_heap_indices(mut self, fat: FatHeapIndices) -> Self {
+		self.fat_heap_indices = fat;
+		self
+	}
+
+	pub fn assembly_ref(&mut self, name: &str, version: [u16; 4]) -> MetadataToken {
+		let name = self.strings.string(name);
+		self.assembly_refs.push([0, 0, name, 0]);
+		self.assembly_ref_versions.push(version);
+		MetadataToken::new(self.assembly_refs.len() as u32, MetadataTokenKind::AssemblyRef)
+	}
+
+	pub fn type_ref(&mut self, scope: MetadataToken, namespace: &str, name: &str) -> MetadataToken {
+		let row = (scope, self.strings.string(name), self.strings.string(namespace));
+		self.type_refs.push(row);
+		MetadataToken::new(self.type_refs.len() as u32, MetadataTokenKind::TypeRef)
+	}
+
+	/// Adds a type definition, which will own the fields and methods added after it.
+	/// Interfaces and `System.Object` are the only types that should not extend another type.
+	pub fn type_def(
+		&mut self,
+		namespace: &str,
+		name: &str,
+		flags: TypeFlags,
+		extends: Option<MetadataToken>,
+	) -> MetadataToken {
+		let name = self.strings.string(name);
+		let namespace = self.strings.string(namespace);
+		let field_list = self.fields.len() as u32 + 1;
+		let method_list = self.methods.len() as u32 + 1;
+		self.type_defs.push((
+			flags,
+			name,
+			namespace,
+			extends.unwrap_or(MetadataToken(0)),
+			field_list,
+			method_list,
+		));
+		MetadataToken::new(self.type_defs.len() as u32, MetadataTokenKind::TypeDef)
+	}
+
+	pub fn field(&mut self, name: &str, flags: FieldFlags, signature: &[u8]) -> MetadataToken {
+		let row = (flags, self.strings.string(name), self.blobs.blob(signature));
+		self.fields.push(row);
+		MetadataToken::new(self.fields.len() as u32, MetadataTokenKind::Field)
+	}
+
+	pub fn method(&mut self, name: &str, flags: MethodFlags, signature: &[u8]) -> MetadataToken {
+		let param_list = self.params.len() as u32 + 1;
+		let row = (flags, self.strings.string(name), self.blobs.blob(signature), param_list);
+		self.methods.push(row);
+		MetadataToken::new(self.methods.len() as u32, MetadataTokenKind::Method)
+	}
+
+	/// Gives the method added last an IL body, with a tiny header if the code is short enough.
+	pub fn method_body(&mut self, code: &[u8]) {
+		let mut body = vec![];
+		if code.len() < 64 {
+			body.push((code.len() as u8) << 2 | 0x2);
+		} else {
+			body.extend(0x3003u16.to_le_bytes());
+			body.extend(8u16.to_le_bytes());
+			body.extend((code.len() as u32).to_le_bytes());
+			body.extend(0u32.to_le_bytes());
+		}
+
+		body.extend(code);
+		self.method_bodies.push((self.methods.len() as u32, body));
+	}
+
+	pub fn param(&mut self, sequence: u16, name: &str, flags: ParamFlags) -> MetadataToken {
+		let row = (flags, sequence, self.strings.string(name));
+		self.params.push(row);
+		MetadataToken::new(self.params.len() as u32, MetadataTokenKind::Param)
+	}
+
+	pub fn member_ref(&mut self, parent: MetadataToken, name: &str, signature: &[u8]) -> MetadataToken {
+		let row = (parent, self.strings.string(name), self.blobs.blob(signature));
+		self.member_refs.push(row);
+		MetadataToken::new(self.member_refs.len() as u32, MetadataTokenKind::MemberRef)
+	}
+
+	pub fn custom_attribute(
+		&mut self,
+		parent: MetadataToken,
+		constructor: MetadataToken,
+		value: &[u8],
+	) -> MetadataToken {
+		let row = (parent, constructor, self.blobs.blob(value));
+		self.custom_attributes.push(row);
+		MetadataToken::new(self.custom_attributes.len() as u32, MetadataTokenKind::CustomAttribute)
+	}
+
+	/// Maps `body`, a method of `class`, to the method `declaration` it implements.
+	pub fn method_impl(&mut self, class: MetadataToken, body: MetadataToken, declaration: MetadataToken) {
+		self.method_impls.push((class, body, declaration));
+	}
+
+	/// Declares that `class` implements `interface`. Rows must be added in the order of their classes.
+	pub fn interface_impl(&mut self, class: MetadataToken, interface: MetadataToken) -> MetadataToken {
+		self.interface_impls.push((class, interface));
+		MetadataToken::new(self.interface_impls.len() as u32, MetadataTokenKind::InterfaceImpl)
+	}
+
+	/// Declares `nested` as a type nested in `enclosing`. Rows must be added in the order of the nested types.
+	pub fn nested_class(&mut self, nested: MetadataToken, enclosing: MetadataToken) {
+		self.nested_classes.push((nested, enclosing));
+	}
+
+	/// Adds a generic parameter to the type or method `owner`. Rows must be added in the order of their owners.
+	pub fn generic_param(
+		&mut self,
+		owner: MetadataToken,
+		number: u16,
+		name: &str,
+		flags: GenericParamFlags,
+	) -> MetadataToken {
+		let row = (number, flags, owner, self.strings.string(name));
+		self.generic_params.push(row);
+		MetadataToken::new(self.generic_params.len() as u32, MetadataTokenKind::GenericParam)
+	}
+
+	/// Constrains the generic parameter `owner` to `constraint`.
+	pub fn generic_param_constraint(&mut self, owner: MetadataToken, constraint: MetadataToken) {
+		self.generic_param_constraints.push((owner, constraint));
+	}
+
+	/// Adds a string literal, returning the token `ldstr` loads it with.
+	pub fn user_string(&mut self, value: &str) -> MetadataToken {
+		let mut bytes = value.encode_utf16().flat_map(u16::to_le_bytes).collect::<Vec<_>>();
+		// Set if the string has characters that need more than a byte, or that some cultures sort specially.
+		let terminal = value
+			.encode_utf16()
+			.any(|unit| unit > 0xFF || matches!(unit, 0x01..=0x08 | 0x0E..=0x1F | 0x27 | 0x2D | 0x7F));
+		bytes.push(terminal as u8);
+		self.raw_user_string(&bytes)
+	}
+
+	/// Adds a `#US` entry made of `bytes` and their length, which need not be a well-formed string,
+	/// e.g. an entry of even length that has no terminal byte.
+	pub fn raw_user_string(&mut self, bytes: &[u8]) -> MetadataToken {
+		let offset = self.user_strings.len() as u32;
+		compress(bytes.len() as u32, &mut self.user_strings);
+		self.user_strings.extend_from_slice(bytes);
+		MetadataToken::new(offset, MetadataTokenKind::String)
+	}
+
+	/// Changes the resolution scope of a TypeRef added earlier, e.g. to scope it to itself as hostile images do.
+	pub fn set_type_ref_scope(&mut self, type_ref: MetadataToken, scope: MetadataToken) {
+		self.type_refs[type_ref.index() - 1].0 = scope;
+	}
+
+	pub fn type_spec(&mut self, signature: &[u8]) -> MetadataToken {
+		self.type_specs.push(self.blobs.blob(signature));
+		MetadataToken::new(self.type_specs.len() as u32, MetadataTokenKind::TypeSpec)
+	}
+
+	pub fn method_spec(&mut self, method: MetadataToken, instantiation: &[u8]) -> MetadataToken {
+		self.method_specs.push((method, self.blobs.blob(instantiation)));
+		MetadataToken::new(self.method_specs.len() as u32, MetadataTokenKind::MethodSpec)
+	}
+
+	/// Lays out the metadata and wraps it in a 32 bit PE image with a single `.text` section.
+	pub fn build(&self) -> Vec<u8> {
+		// Method bodies are laid out between the CLI header and the metadata, aligned to 4 bytes.
+		let mut bodies = vec![];
+		let mut body_rvas = HashMap::new();
+		for (method, body) in self.method_bodies.iter() {
+			body_rvas.insert(*method, SECTION_ALIGNMENT + CLI_HEADER_SIZE + bodies.len() as u32);
+			bodies.extend(body);
+			pad(&mut bodies);
+		}
+
+		let metadata = self.build_metadata(&body_rvas);
+		let mut text = vec![];
+
+		// CLI header
+		let metadata_rva = SECTION_ALIGNMENT + CLI_HEADER_SIZE + bodies.len() as u32;
+		text.extend(CLI_HEADER_SIZE.to_le_bytes());
+		text.extend(2u16.to_le_bytes());
+		text.extend(5u16.to_le_bytes());
+		text.extend(metadata_rva.to_le_bytes());
+		text.extend((metadata.len() as u32).to_le_bytes());
+		text.extend(runtime_flags::IL_ONLY.to_le_bytes());
+		text.resize(CLI_HEADER_SIZE as usize, 0);
+		text.extend(bodies);
+		text.extend(metadata);
+
+		let virtual_size = text.len() as u32;
+		let raw_size = align(virtual_size, FILE_ALIGNMENT);
+		text.resize(raw_size as usize, 0);
+
+		let mut image = DOS_HEADER.to_vec();
+
+		// PE file header
+		image.extend(0x4550u32.to_le_bytes());
+		image.extend(0x014Cu16.to_le_bytes());
+		image.extend(1u16.to_le_bytes());
+		image.extend(0i32.to_le_bytes());
+		image.extend(0u32.to_le_bytes());
+		image.extend(0u32.to_le_bytes());
+		image.extend(0xE0u16.to_le_bytes());
+		image.extend(0x2102u16.to_le_bytes());
+
+		// PE optional header, standard fields
+		image.extend(0x10Bu16.to_le_bytes());
+		image.extend([8, 0]);
+		image.extend(raw_size.to_le_bytes());
+		image.extend(0u32.to_le_bytes());
+		image.extend(0u32.to_le_bytes());
+		image.extend(0u32.to_le_bytes());
+		image.extend(SECTION_ALIGNMENT.to_le_bytes());
+		image.extend(0u32.to_le_bytes());
+
+		// PE optional header, NT specific fields
+		image.extend(0x400000u32.to_le_bytes());
+		image.extend(SECTION_ALIGNMENT.to_le_bytes());
+		image.extend(FILE_ALIGNMENT.to_le_bytes());
+		for version in [4u16, 0, 0, 0, 4, 0] {
+			image.extend(version.to_le_bytes());
+		}
+		image.extend(0u32.to_le_bytes());
+		image.extend(align(SECTION_ALIGNMENT + virtual_size, SECTION_ALIGNMENT).to_le_bytes());
+		image.extend(FILE_ALIGNMENT.to_le_bytes());
+		image.extend(0u32.to_le_bytes());
+		image.extend(3u16.to_le_bytes());
+		image.extend(0x8540u16.to_le_bytes());
+		for size in [0x100000u32, 0x1000, 0x100000, 0x1000, 0, 16] {
+			image.extend(size.to_le_bytes());
+		}
+
+		// Data directories, of which only the CLI header is present
+		for index in 0..16 {
+			let (rva, size) = match index {
+				14 => (SECTION_ALIGNMENT, CLI_HEADER_SIZE),
+				_ => (0, 0),
+			};
+			image.extend(rva.to_le_bytes());
+			image.extend(size.to_le_bytes());
+		}
+
+		// Section header
+		image.extend(*b".text\0\0\0");
+		image.extend(virtual_size.to_le_bytes());
+		image.extend(SECTION_ALIGNMENT.to_le_bytes());
+		image.extend(raw_size.to_le_bytes());
+		image.extend(FILE_ALIGNMENT.to_le_bytes());
+		image.extend([0; 12]);
+		let characteristics = section_header_characteristics::IMAGE_SCN_CNT_CODE
+			| section_header_characteristics::IMAGE_SCN_MEM_EXECUTE
+			| section_header_characteristics::IMAGE_SCN_MEM_READ;
+		image.extend(characteristics.to_le_bytes());
+
+		image.resize(FILE_ALIGNMENT as usize, 0);
+		image.extend(text);
+		image
+	}
+
+	fn build_metadata(&self, body_rvas: &HashMap<u32, u32>) -> Vec<u8> {
+		let mut user_strings = self.user_strings.clone();
+		pad(&mut user_strings);
+
+		let streams = [
+			("#~", self.build_tables(body_rvas)),
+			("#Strings", self.strings.padded()),
+			("#US", user_strings),
+			("#GUID", self.guids.concat()),
+			("#Blob", self.blobs.padded()),
+		];
+
+		let mut version = RUNTIME_VERSION.as_bytes().to_vec();
+		version.push(0);
+		pad(&mut version);
+
+		let mut root = vec![];
+		root.extend(0x424A5342u32.to_le_bytes());
+		root.extend(1u16.to_le_bytes());
+		root.extend(1u16.to_le_bytes());
+		root.extend(0u32.to_le_bytes());
+		root.extend((version.len() as u32).to_le_bytes());
+		root.extend(version);
+		root.extend(0u16.to_le_bytes());
+		root.extend((streams.len() as u16).to_le_bytes());
+
+		let headers_size = streams
+			.iter()
+			.map(|(name, _)| 8 + align(name.len() as u32 + 1, 4))
+			.sum::<u32>();
+
+		let mut offset = root.len() as u32 + headers_size;
+		for (name, bytes) in streams.iter() {
+			root.extend(offset.to_le_bytes());
+			root.extend((bytes.len() as u32).to_le_bytes());
+			root.extend(name.as_bytes());
+			root.push(0);
+			pad(&mut root);
+			offset += bytes.len() as u32;
+		}
+
+		for (_, bytes) in streams {
+			root.extend(bytes);
+		}
+
+		root
+	}
+
+	fn build_tables(&self, body_rvas: &HashMap<u32, u32>) -> Vec<u8> {
+		let fat = self.fat_heap_indices;
+		assert!(
+			fat.string || self.strings.bytes.len() <= u16::MAX as usize,
+			"string heap too large for 2 byte indices"
+		);
+		assert!(
+			fat.blob || self.blobs.bytes.len() <= u16::MAX as usize,
+			"blob heap too large for 2 byte indices"
+		);
+
+		let mut tables = TableWriter {
+			rows: HashMap::new(),
+			fat,
+		};
+
+		let [name, module, mvid] = self.assembly;
+		tables.row(TableKind::Module, |row| {
+			row.u16(0);
+			row.string(module);
+			row.guid(mvid);
+			row.guid(0);
+			row.guid(0);
+		});
+
+		for (scope, name, namespace) in self.type_refs.iter() {
+			tables.row(TableKind::TypeRef, |row| {
+				row.coded(*scope, CodedIndexKind::ResolutionScope);
+				row.string(*name);
+				row.string(*namespace);
+			});
+		}
+
+		for (flags, name, namespace, extends, field_list, method_list) in self.type_defs.iter() {
+			tables.row(TableKind::TypeDef, |row| {
+				row.u32(*flags);
+				row.string(*name);
+				row.string(*namespace);
+				row.coded(*extends, CodedIndexKind::TypeDefOrRef);
+				row.index(*field_list);
+				row.index(*method_list);
+			});
+		}
+
+		for (flags, name, signature) in self.fields.iter() {
+			tables.row(TableKind::Field, |row| {
+				row.u16(*flags);
+				row.string(*name);
+				row.blob(*signature);
+			});
+		}
+
+		for (index, (flags, name, signature, param_list)) in self.methods.iter().enumerate() {
+			tables.row(TableKind::MethodDef, |row| {
+				row.u32(body_rvas.get(&(index as u32 + 1)).copied().unwrap_or(0));
+				row.u16(0);
+				row.u16(*flags);
+				row.string(*name);
+				row.blob(*signature);
+				row.index(*param_list);
+			});
+		}
+
+		for (flags, sequence, name) in self.params.iter() {
+			tables.row(TableKind::Param, |row| {
+				row.u16(*flags);
+				row.u16(*sequence);
+				row.string(*name);
+			});
+		}
+
+		for (class, interface) in self.interface_impls.iter() {
+			tables.row(TableKind::InterfaceImpl, |row| {
+				row.index(class.index() as u32);
+				row.coded(*interface, CodedIndexKind::TypeDefOrRef);
+			});
+		}
+
+		for (nested, enclosing) in self.nested_classes.iter() {
+			tables.row(TableKind::NestedClass, |row| {
+				row.index(nested.index() as u32);
+				row.index(enclosing.index() as u32);
+			});
+		}
+
+		for (parent, name, signature) in self.member_refs.iter() {
+			tables.row(TableKind::MemberRef, |row| {
+				row.coded(*parent, CodedIndexKind::MemberRefParent);
+				row.string(*name);
+				row.blob(*signature);
+			});
+		}
+
+		for (parent, constructor, value) in self.custom_attributes.iter() {
+			tables.row(TableKind::CustomAttribute, |row| {
+				row.coded(*parent, CodedIndexKind::HasCustomAttribute);
+				row.coded(*constructor, CodedIndexKind::CustomAttributeType);
+				row.blob(*value);
+			});
+		}
+
+		for (class, body, declaration) in self.method_impls.iter() {
+			tables.row(TableKind::MethodImpl, |row| {
+				row.index(class.index() as u32);
+				row.coded(*body, CodedIndexKind::MethodDefOrRef);
+				row.coded(*declaration, CodedIndexKind::MethodDefOrRef);
+			});
+		}
+
+		for signature in self.type_specs.iter() {
+			tables.row(TableKind::TypeSpec, |row| row.blob(*signature));
+		}
+
+		let [major, minor, build, revision] = self.version;
+		tables.row(TableKind::Assembly, |row| {
+			row.u32(self.hash_algorithm);
+			[major, minor, build, revision].into_iter().for_each(|v| row.u16(v));
+			row.u32(0);
+			row.blob(0);
+			row.string(name);
+			row.string(0);
+		});
+
+		for (reference, version) in self.assembly_refs.iter().zip(self.assembly_ref_versions.iter()) {
+			tables.row(TableKind::AssemblyRef, |row| {
+				version.iter().for_each(|v| row.u16(*v));
+				row.u32(0);
+				row.blob(0);
+				row.string(reference[2]);
+				row.string(0);
+				row.blob(0);
+			});
+		}
+
+		for (method, instantiation) in self.method_specs.iter() {
+			tables.row(TableKind::MethodSpec, |row| {
+				row.coded(*method, CodedIndexKind::MethodDefOrRef);
+				row.blob(*instantiation);
+			});
+		}
+
+		for (number, flags, owner, name) in self.generic_params.iter() {
+			tables.row(TableKind::GenericParam, |row| {
+				row.u16(*number);
+				row.u16(*flags);
+				row.coded(*owner, CodedIndexKind::TypeOrMethodDef);
+				row.string(*name);
+			});
+		}
+
+		for (owner, constraint) in self.generic_param_constraints.iter() {
+			tables.row(TableKind::GenericParamConstraint, |row| {
+				row.index(owner.index() as u32);
+				row.coded(*constraint, CodedIndexKind::TypeDefOrRef);
+			});
+		}
+
+		tables.finish()
+	}
+}
+
+/// Builds the attribute blob of a constructor taking only string parameters, with no named arguments.
+pub fn string_attribute_value(args: &[&str]) -> Vec<u8> {
+	let mut value = vec![0x01, 0x00];
+	for arg in args {
+		compress(arg.len() as u32, &mut value);
+		value.extend(arg.as_bytes());
+	}
+	value.extend([0x00, 0x00]);
+	value
+}
+
+struct HeapWriter {
+	bytes: Vec<u8>,
+	offsets: HashMap<Vec<u8>, u32>,
+}
+
+impl HeapWriter {
+	fn new() -> Self {
+		Self {
+			bytes: vec![0],
+			offsets: HashMap::new(),
+		}
+	}
+
+	fn get(&self, offset: u32) -> String {
+		let bytes = &self.bytes[offset as usize..];
+		let end = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+		String::from_utf8_lossy(&bytes[..end]).into_owned()
+	}
+
+	fn string(&mut self, value: &str) -> u32 {
+		if value.is_empty() {
+			return 0;
+		}
+
+		let mut entry = value.as_bytes().to_vec();
+		entry.push(0);
+		self.insert(entry)
+	}
+
+	fn blob(&mut self, value: &[u8]) -> u32 {
+		if value.is_empty() {
+			return 0;
+		}
+
+		let mut entry = vec![];
+		compress(value.len() as u32, &mut entry);
+		entry.extend(value);
+		self.insert(entry)
+	}
+
+	fn insert(&mut self, entry: Vec<u8>) -> u32 {
+		if let Some(offset) = self.offsets.get(&entry) {
+			return *offset;
+		}
+
+		let offset = self.bytes.len() as u32;
+		self.bytes.extend(&entry);
+		self.offsets.insert(entry, offset);
+		offset
+	}
+
+	fn padded(&self) -> Vec<u8> {
+		let mut bytes = self.bytes.clone();
+		pad(&mut bytes);
+		bytes
+	}
+}
+
+struct TableWriter {
+	rows: HashMap<TableKind, (u32, Vec<u8>)>,
+	fat: FatHeapIndices,
+}
+
+impl TableWriter {
+	fn row(&mut self, kind: TableKind, write: impl FnOnce(&mut RowWriter)) {
+		let (count, bytes) = self.rows.entry(kind).or_default();
+		*count += 1;
+		write(&mut RowWriter(bytes, self.fat));
+	}
+
+	fn finish(self) -> Vec<u8> {
+		let mut kinds = self.rows.keys().copied().collect::<Vec<_>>();
+		kinds.sort();
+
+		let valid = kinds.iter().fold(0u64, |valid, kind| valid | 1 << *kind as u64);
+		let mut heap = vec![];
+		heap.extend(0u32.to_le_bytes());
+		heap.extend([2, 0, self.fat.heap_sizes(), 1]);
+		heap.extend(valid.to_le_bytes());
+		heap.extend(0u64.to_le_bytes());
+
+		for kind in kinds.iter() {
+			let (count, _) = self.rows[kind];
+			assert!(count < 1 << 11, "too many {:?} rows for 2 byte coded indices", kind);
+			heap.extend(count.to_le_bytes());
+		}
+
+		for kind in kinds.iter() {
+			heap.extend(&self.rows[kind].1);
+		}
+
+		pad(&mut heap);
+		heap
+	}
+}
+
+struct RowWriter<'l>(&'l mut Vec<u8>, FatHeapIndices);
+
+impl RowWriter<'_> {
+	fn u16(&mut self, value: u16) {
+		self.0.extend(value.to_le_bytes());
+	}
+
+	fn u32(&mut self, value: u32) {
+		self.0.extend(value.to_le_bytes());
+	}
+
+	/// Writes a table index, which is always 2 bytes wide in the images built here.
+	fn index(&mut self, value: u32) {
+		self.u16(value as u16);
+	}
+
+	fn string(&mut self, value: u32) {
+		self.heap_index(value, self.1.string);
+	}
+
+	fn guid(&mut self, value: u32) {
+		self.heap_index(value, self.1.guid);
+	}
+
+	fn blob(&mut self, value: u32) {
+		self.heap_index(value, self.1.blob);
+	}
+
+	fn heap_index(&mut self, value: u32, fat: bool) {
+		match fat {
+			true => self.u32(value),
+			false => self.u16(value as u16),
+		}
+	}
+
+	fn coded(&mut self, token: MetadataToken, kind: CodedIndexKind) {
+		let index = match token.is_null() {
+			true => 0,
+			false => {
+				token
+					.token_kind()
+					.and_then(|token_kind| CodedIndex::encode(token.index(), token_kind, kind))
+					.unwrap_or_else(|| panic!("{:?} cannot be encoded as {:?}", token, kind))
+					.0
+			}
+		};
+		self.u16(index as u16);
+	}
+}
+
+fn compress(value: u32, bytes: &mut Vec<u8>) {
+	encode_compressed_u32(value, bytes).expect("value too large for a compressed integer");
+}
+
+fn align(value: u32, alignment: u32) -> u32 {
+	value.div_ceil(alignment) * alignment
+}
+
+fn pad(bytes: &mut Vec<u8>) {
+	bytes.resize(align(bytes.len() as u32, 4) as usize, 0);
+}
+
+#[rustfmt::skip]
+const DOS_HEADER: [u8; 128] = [
+	0x4d, 0x5a, 0x90, 0x00, 0x03, 0x00, 0x00, 0x00,
+	0x04, 0x00, 0x00, 0x00, 0xFF, 0xFF, 0x00, 0x00,
+	0xb8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+	0x40, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+	0x00, 0x00, 0x00, 0x00, 0x80, 0x00, 0x00, 0x00,
+	0x0e, 0x1f, 0xba, 0x0e, 0x00, 0xb4, 0x09, 0xcd,
+	0x21, 0xb8, 0x01, 0x4c, 0xcd, 0x21, 0x54, 0x68,
+	0x69, 0x73, 0x20, 0x70, 0x72, 0x6f, 0x67, 0x72,
+	0x61, 0x6d, 0x20, 0x63, 0x61, 0x6e, 0x6e, 0x6f,
+	0x74, 0x20, 0x62, 0x65, 0x20, 0x72, 0x75, 0x6e,
+	0x20, 0x69, 0x6e, 0x20, 0x44, 0x4f, 0x53, 0x20,
+	0x6d, 0x6f, 0x64, 0x65, 0x2e, 0x0d, 0x0d, 0x0a,
+	0x24, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+];
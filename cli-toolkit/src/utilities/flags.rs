@@ -0,0 +1,44 @@
+/// Joins the names of the flags set in `flags` with ` | `.
+///
+/// Each group is a mask along with the names of the values it can take.
+/// Zero values are only named when nothing else is set, and bits without a name are appended in hexadecimal.
+pub(crate) fn flags_to_string<T: Copy + Into<u32>>(
+	flags: T,
+	groups: &[(T, &[(T, &str)])],
+	bits: &[(T, &str)],
+) -> String {
+	let flags = flags.into();
+	let mut remaining = flags;
+	let mut names = vec![];
+	let mut default = None;
+
+	for (mask, values) in groups {
+		let mask = (*mask).into();
+		let Some((value, name)) = values.iter().find(|(value, _)| (*value).into() == flags & mask) else {
+			continue;
+		};
+		match (*value).into() {
+			0 => default = default.or(Some(*name)),
+			_ => names.push(name.to_string()),
+		}
+		remaining &= !mask;
+	}
+
+	for (bit, name) in bits {
+		let bit = (*bit).into();
+		if bit != 0 && flags & bit == bit {
+			names.push(name.to_string());
+			remaining &= !bit;
+		}
+	}
+
+	if remaining != 0 {
+		names.push(format!("0x{:X}", remaining));
+	}
+
+	match (names.is_empty(), default) {
+		(true, Some(default)) => default.to_string(),
+		(true, None) => "0".to_string(),
+		(false, _) => names.join(" | "),
+	}
+}
@@ -1,3 +1,13 @@
+mod flags;
+mod sha1;
+#[cfg(feature = "checksums")]
+mod xxhash;
+
+pub(crate) use flags::flags_to_string;
+pub(crate) use sha1::sha1;
+#[cfg(feature = "checksums")]
+pub(crate) use xxhash::xxh64;
+
 use std::alloc::{Layout, LayoutError};
 use std::fmt::{Debug, Formatter, Pointer};
 use std::ops::{Deref, Index};
@@ -12,6 +22,19 @@ impl<T, C: Index<usize, Output = T> + ?Sized> IndexedRcRef<T, C> {
 	pub fn new(container: Rc<C>, index: usize) -> Self {
 		Self { container, index }
 	}
+
+	pub(crate) fn container(&self) -> &Rc<C> {
+		&self.container
+	}
+}
+
+impl<T, C: Index<usize, Output = T> + ?Sized> Clone for IndexedRcRef<T, C> {
+	fn clone(&self) -> Self {
+		Self {
+			index: self.index,
+			container: self.container.clone(),
+		}
+	}
 }
 
 impl<T, C: Index<usize, Output = T> + ?Sized> Deref for IndexedRcRef<T, C> {
@@ -27,7 +50,3 @@ impl<T: Debug, C: Index<usize, Output = T> + ?Sized> Debug for IndexedRcRef<T, C
 		Debug::fmt(self.deref(), f)
 	}
 }
-
-pub(crate) unsafe fn get_mut_unchecked<'l, T: ?Sized>(rc: &Rc<T>) -> &mut T {
-	&mut *(Rc::as_ptr(&rc) as *mut T)
-}
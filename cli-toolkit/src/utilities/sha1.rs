@@ -0,0 +1,53 @@
+/// A minimal SHA-1, only used to derive public key tokens.
+pub(crate) fn sha1(data: &[u8]) -> [u8; 20] {
+	let mut state: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+
+	let mut message = data.to_vec();
+	message.push(0x80);
+	while message.len() % 64 != 56 {
+		message.push(0);
+	}
+	message.extend_from_slice(&((data.len() as u64) * 8).to_be_bytes());
+
+	for chunk in message.chunks_exact(64) {
+		let mut w = [0u32; 80];
+		for (i, word) in chunk.chunks_exact(4).enumerate() {
+			w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+		}
+		for i in 16..80 {
+			w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+		}
+
+		let [mut a, mut b, mut c, mut d, mut e] = state;
+		for (i, w) in w.iter().enumerate() {
+			let (f, k) = match i {
+				0..=19 => ((b & c) | (!b & d), 0x5A827999),
+				20..=39 => (b ^ c ^ d, 0x6ED9EBA1),
+				40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+				_ => (b ^ c ^ d, 0xCA62C1D6),
+			};
+
+			let temp = a
+				.rotate_left(5)
+				.wrapping_add(f)
+				.wrapping_add(e)
+				.wrapping_add(k)
+				.wrapping_add(*w);
+			e = d;
+			d = c;
+			c = b.rotate_left(30);
+			b = a;
+			a = temp;
+		}
+
+		for (state, value) in state.iter_mut().zip([a, b, c, d, e]) {
+			*state = state.wrapping_add(value);
+		}
+	}
+
+	let mut digest = [0; 20];
+	for (bytes, word) in digest.chunks_exact_mut(4).zip(state) {
+		bytes.copy_from_slice(&word.to_be_bytes());
+	}
+	digest
+}
@@ -0,0 +1,70 @@
+const PRIME_1: u64 = 0x9E3779B185EBCA87;
+const PRIME_2: u64 = 0xC2B2AE3D27D4EB4F;
+const PRIME_3: u64 = 0x165667B19E3779F9;
+const PRIME_4: u64 = 0x85EBCA77C2B2AE63;
+const PRIME_5: u64 = 0x27D4EB2F165667C5;
+
+/// A minimal XXH64, only used to checksum metadata streams.
+pub(crate) fn xxh64(data: &[u8], seed: u64) -> u64 {
+	let read_u64 = |bytes: &[u8]| u64::from_le_bytes(bytes[..8].try_into().unwrap());
+	let read_u32 = |bytes: &[u8]| u32::from_le_bytes(bytes[..4].try_into().unwrap());
+
+	let mut stripes = data.chunks_exact(32);
+	let mut hash = match data.len() >= 32 {
+		false => seed.wrapping_add(PRIME_5),
+		true => {
+			let mut lanes = [
+				seed.wrapping_add(PRIME_1).wrapping_add(PRIME_2),
+				seed.wrapping_add(PRIME_2),
+				seed,
+				seed.wrapping_sub(PRIME_1),
+			];
+			for stripe in stripes.by_ref() {
+				for (lane, bytes) in lanes.iter_mut().zip(stripe.chunks_exact(8)) {
+					*lane = round(*lane, read_u64(bytes));
+				}
+			}
+
+			let [a, b, c, d] = lanes;
+			let hash = a
+				.rotate_left(1)
+				.wrapping_add(b.rotate_left(7))
+				.wrapping_add(c.rotate_left(12))
+				.wrapping_add(d.rotate_left(18));
+			lanes.into_iter().fold(hash, merge)
+		}
+	};
+
+	hash = hash.wrapping_add(data.len() as u64);
+	let mut tail = stripes.remainder();
+	while tail.len() >= 8 {
+		hash ^= round(0, read_u64(tail));
+		hash = hash.rotate_left(27).wrapping_mul(PRIME_1).wrapping_add(PRIME_4);
+		tail = &tail[8..];
+	}
+	if tail.len() >= 4 {
+		hash ^= (read_u32(tail) as u64).wrapping_mul(PRIME_1);
+		hash = hash.rotate_left(23).wrapping_mul(PRIME_2).wrapping_add(PRIME_3);
+		tail = &tail[4..];
+	}
+	for byte in tail {
+		hash ^= (*byte as u64).wrapping_mul(PRIME_5);
+		hash = hash.rotate_left(11).wrapping_mul(PRIME_1);
+	}
+
+	hash ^= hash >> 33;
+	hash = hash.wrapping_mul(PRIME_2);
+	hash ^= hash >> 29;
+	hash = hash.wrapping_mul(PRIME_3);
+	hash ^ (hash >> 32)
+}
+
+fn round(lane: u64, input: u64) -> u64 {
+	lane.wrapping_add(input.wrapping_mul(PRIME_2))
+		.rotate_left(31)
+		.wrapping_mul(PRIME_1)
+}
+
+fn merge(hash: u64, lane: u64) -> u64 {
+	(hash ^ round(0, lane)).wrapping_mul(PRIME_1).wrapping_add(PRIME_4)
+}
@@ -0,0 +1,141 @@
+//! Checks the operations that combine, update or search loaded contexts, on assemblies built with [`ImageBuilder`].
+
+use cli_toolkit::raw::{field_flags, method_flags, type_flags, Assembly as RawAssembly, UserStringHeap};
+use cli_toolkit::read::Error;
+use cli_toolkit::schema::{Context, MergePolicy, SoftReferenceTarget};
+use cli_toolkit::testing::ImageBuilder;
+use std::rc::Rc;
+
+/// An assembly named `name` defining the types `names` in the namespace of the same name.
+fn library(name: &str, names: &[&str]) -> Vec<u8> {
+	let mut builder = ImageBuilder::new(name);
+	let core = builder.assembly_ref("System.Runtime", [7, 0, 0, 0]);
+	let object = builder.type_ref(core, "System", "Object");
+	for type_name in names {
+		builder.type_def(name, type_name, type_flags::PUBLIC, Some(object));
+	}
+	builder.build()
+}
+
+fn load(images: &[&[u8]]) -> Rc<Context> {
+	Context::from_assembly_list(images.iter().copied()).unwrap()
+}
+
+/// The names of the types `context` holds for the assembly named `name`, leaving out `<Module>`.
+fn type_names(context: &Context, name: &str) -> Vec<String> {
+	let assembly = context.assemblies().iter().find(|assembly| assembly.to_string().starts_with(name)).unwrap();
+	let types = assembly.types().iter().filter_map(|ty| ty.data()).skip(1);
+	types.map(|data| data.name().to_string()).collect()
+}
+
+#[test]
+fn merge_policies() {
+	let shared_base = library("Shared", &["FromBase"]);
+	let shared_other = library("Shared", &["FromOther"]);
+	let extra = library("Extra", &["Extra"]);
+
+	let base = load(&[&shared_base]);
+	let merged = Context::merge(&base, load(&[&shared_other, &extra]), MergePolicy::PreferBase).unwrap();
+	assert_eq!(merged.assemblies().len(), 2);
+	assert_eq!(type_names(&merged, "Shared"), ["FromBase"]);
+	assert_eq!(type_names(&merged, "Extra"), ["Extra"]);
+	assert!(Rc::ptr_eq(&merged.assemblies()[0], &base.assemblies()[0]));
+
+	let merged = Context::merge(&base, load(&[&shared_other, &extra]), MergePolicy::PreferOther).unwrap();
+	assert_eq!(merged.assemblies().len(), 2);
+	assert_eq!(type_names(&merged, "Shared"), ["FromOther"]);
+	assert_eq!(type_names(&merged, "Extra"), ["Extra"]);
+
+	let merged = Context::merge(&base, load(&[&shared_other, &extra]), MergePolicy::Fail);
+	assert!(matches!(merged, Err(Error::DuplicateAssembly(name)) if name.starts_with("Shared")));
+
+	let other = load(&[&extra]);
+	let _shared = other.clone();
+	assert!(matches!(Context::merge(&base, other, MergePolicy::Fail), Err(Error::SharedContext)));
+}
+
+#[test]
+fn reload_assembly() {
+	let library_v1 = library("Library", &["Old"]);
+	let library_v2 = library("Library", &["New"]);
+
+	let mut builder = ImageBuilder::new("App");
+	let library_ref = builder.assembly_ref("Library", [1, 0, 0, 0]);
+	let old = builder.type_ref(library_ref, "Library", "Old");
+	let new = builder.type_ref(library_ref, "Library", "New");
+	let app = builder.build();
+
+	let context = load(&[&library_v1, &app]);
+	let app_assembly = context.assemblies()[1].clone();
+	let resolved = app_assembly.get_type(old).unwrap();
+	assert_eq!(resolved.data().unwrap().name(), "Old");
+	drop(resolved);
+	assert!(app_assembly.get_type(new).is_none());
+	drop(app_assembly);
+
+	let identity = context.assemblies()[0].full_name();
+	let (context, report) = context.reload_assembly(&identity, library_v2.as_slice()).unwrap();
+	assert_eq!(report.previous(), identity);
+	assert_eq!(report.reloaded(), identity);
+	assert_eq!(report.invalidated(), [context.assemblies()[1].full_name()]);
+
+	let library = &context.assemblies()[0];
+	assert!(library.find_type("Old", "Library").is_none());
+	let reloaded = library.find_type("New", "Library").unwrap();
+
+	let app = &context.assemblies()[1];
+	let resolved = app.get_type(new).unwrap();
+	assert_eq!(resolved.data().unwrap().token(), reloaded.data().unwrap().token());
+	assert_eq!(resolved.data().unwrap().name(), "New");
+	assert!(app.get_type(old).is_none());
+}
+
+#[test]
+fn soft_references() {
+	let mut builder = ImageBuilder::new("Target");
+	let core = builder.assembly_ref("System.Runtime", [7, 0, 0, 0]);
+	let object = builder.type_ref(core, "System", "Object");
+	builder.type_def("Target", "Widget", type_flags::PUBLIC, Some(object));
+	builder.field("count", field_flags::PRIVATE, &[0x06, 0x08]);
+	builder.method("Run", method_flags::PUBLIC, &[0x20, 0x00, 0x01]);
+	let target = builder.build();
+
+	let mut builder = ImageBuilder::new("App");
+	builder.user_string("Target.Widget.Run");
+	builder.user_string("Unrelated");
+	// An entry without a terminal byte, which cannot be decoded but does not hide the entries after it.
+	let malformed = builder.raw_user_string(&[b'A', 0]);
+	builder.user_string("count");
+	builder.user_string("Target.Widget, Target, Version=1.0.0.0");
+	let app = builder.build();
+
+	let raw = RawAssembly::try_from(app.as_slice()).unwrap();
+	let strings = raw.metadata().get_heap::<UserStringHeap>().unwrap().unwrap();
+	let entries = strings.iter().map(|entry| entry.map(|(offset, _)| offset)).collect::<Vec<_>>();
+	assert_eq!(entries.len(), 5);
+	assert!(entries[2].is_err());
+	assert!(strings.get(malformed.index() as u32).is_err());
+
+	let context = load(&[&target]);
+	let references = context.soft_references(&strings);
+	let references = references
+		.iter()
+		.map(|reference| {
+			let target = match reference.target() {
+				SoftReferenceTarget::Type(ty) => ty.data().unwrap().name().to_string(),
+				SoftReferenceTarget::Member { declaring_type, token } => {
+					let member = declaring_type.members().hide_synthetic(false).find(|member| member.token() == *token);
+					format!("{}.{}", declaring_type.data().unwrap().name(), member.unwrap().name())
+				}
+			};
+			(reference.literal().to_string(), target)
+		})
+		.collect::<Vec<_>>();
+
+	let expected = [
+		("Target.Widget.Run", "Widget.Run"),
+		("count", "Widget.count"),
+		("Target.Widget, Target, Version=1.0.0.0", "Widget"),
+	];
+	assert_eq!(references, expected.map(|(literal, target)| (literal.to_string(), target.to_string())));
+}
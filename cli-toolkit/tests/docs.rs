@@ -0,0 +1,96 @@
+//! Generates the documentation IDs of types and members built with [`ImageBuilder`], including their parameter types,
+//! and checks that they parse back into the same [`DocId`] and resolve to the same type or member.
+
+use cli_toolkit::raw::{method_flags, type_flags};
+use cli_toolkit::schema::{Context, DocId, DocIdKind, DocTarget};
+use cli_toolkit::testing::ImageBuilder;
+
+/// The documentation IDs of the members of `Docs.Outer`, in token order.
+fn member_ids(builder: ImageBuilder) -> Vec<String> {
+	let bytes = builder.build();
+	let context = Context::from_assembly_list([bytes.as_slice()]).unwrap();
+	let assembly = &context.assemblies()[0];
+	let ty = assembly.find_type("Outer", "Docs").unwrap();
+	let ids = ty.members().map(|member| member.doc_id(assembly).unwrap()).collect::<Vec<_>>();
+
+	for id in &ids {
+		let parsed = id.parse::<DocId>().unwrap();
+		assert_eq!(&parsed.to_string(), id);
+		assert_eq!(parsed.to_string().parse::<DocId>().unwrap(), parsed);
+
+		let resolved = match context.resolve_doc_id(id) {
+			Some(DocTarget::Type(ty)) => ty.data().and_then(|data| data.doc_id()),
+			Some(DocTarget::Member(member)) => member.doc_id(assembly),
+			None => panic!("{} does not resolve", id),
+		};
+		assert_eq!(resolved.as_ref(), Some(id));
+	}
+	ids
+}
+
+#[test]
+fn round_trip() {
+	let mut builder = ImageBuilder::new("Docs");
+	let core = builder.assembly_ref("System.Runtime", [7, 0, 0, 0]);
+	let object = builder.type_ref(core, "System", "Object");
+	let outer = builder.type_def("Docs", "Outer", type_flags::PUBLIC, Some(object));
+	let special = method_flags::PUBLIC | method_flags::SPECIAL_NAME;
+	builder.method(".ctor", special | method_flags::RT_SPECIAL_NAME, &[0x20, 0x00, 0x01]);
+	builder.method("Map", method_flags::PUBLIC, &[0x30, 0x01, 0x01, 0x1E, 0x00, 0x1E, 0x00]);
+	builder.method("Swap", method_flags::PUBLIC, &[0x20, 0x01, 0x01, 0x10, 0x08]);
+	builder.method("op_Implicit", special | method_flags::STATIC, &[0x00, 0x01, 0x08, 0x0E]);
+	builder.method("op_Explicit", special | method_flags::STATIC, &[0x00, 0x01, 0x0E, 0x08]);
+	let inner = builder.type_def("", "Inner", type_flags::NESTED_PUBLIC, Some(object));
+	builder.nested_class(inner, outer);
+
+	let ids = member_ids(builder);
+	assert_eq!(
+		ids,
+		[
+			"T:Docs.Outer.Inner",
+			"M:Docs.Outer.#ctor",
+			"M:Docs.Outer.Map``1(``0)",
+			"M:Docs.Outer.Swap(System.Int32@)",
+			"M:Docs.Outer.op_Implicit(System.String)~System.Int32",
+			"M:Docs.Outer.op_Explicit(System.Int32)~System.String",
+		]
+	);
+
+	let inner = ids[0].parse::<DocId>().unwrap();
+	assert_eq!((inner.kind(), inner.type_name(), inner.member_name()), (DocIdKind::Type, "Docs.Outer.Inner", None));
+
+	let map = ids[2].parse::<DocId>().unwrap();
+	assert_eq!((map.member_name(), map.generic_arity()), (Some("Map``1"), 1));
+	assert_eq!(map.params(), ["``0"]);
+
+	let implicit = ids[4].parse::<DocId>().unwrap();
+	assert_eq!(implicit.params(), ["System.String"]);
+	assert_eq!(implicit.return_type(), Some("System.Int32"));
+}
+
+#[test]
+fn array_shapes() {
+	let mut builder = ImageBuilder::new("Docs");
+	let core = builder.assembly_ref("System.Runtime", [7, 0, 0, 0]);
+	let object = builder.type_ref(core, "System", "Object");
+	builder.type_def("Docs", "Outer", type_flags::PUBLIC, Some(object));
+	#[rustfmt::skip]
+	let signature = [
+		0x20, 0x04, 0x01,
+		// int[,] with both lower bounds set to 0 and no sizes.
+		0x14, 0x08, 0x02, 0x00, 0x02, 0x00, 0x00,
+		// int[,] with the sizes 3 and 4 and the lower bounds 1 and 2.
+		0x14, 0x08, 0x02, 0x02, 0x03, 0x04, 0x02, 0x02, 0x04,
+		// A one dimensional array with the lower bound -1 and no size, unlike the SZ array after it.
+		0x14, 0x08, 0x01, 0x00, 0x01, 0x7F,
+		0x1D, 0x08,
+	];
+	builder.method("Fill", method_flags::PUBLIC, &signature);
+
+	let ids = member_ids(builder);
+	let id = "M:Docs.Outer.Fill(System.Int32[0:,0:],System.Int32[1:3,2:4],System.Int32[-1:],System.Int32[])";
+	assert_eq!(ids, [id]);
+
+	let parsed = id.parse::<DocId>().unwrap();
+	assert_eq!(parsed.params(), ["System.Int32[0:,0:]", "System.Int32[1:3,2:4]", "System.Int32[-1:]", "System.Int32[]"]);
+}
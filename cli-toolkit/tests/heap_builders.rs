@@ -0,0 +1,69 @@
+//! Checks the layout of the heaps written by the heap builders.
+
+use cli_toolkit::raw::{BuiltStringHeap, StringHandle, StringHeapBuilder};
+
+/// The null-terminated string the heap holds at the offset of `handle`.
+fn string_at(heap: &BuiltStringHeap, handle: StringHandle) -> &str {
+	let offset = heap.offset(handle).unwrap().get() as usize;
+	let bytes = &heap.bytes()[offset..];
+	let end = bytes.iter().position(|byte| *byte == 0).unwrap();
+	std::str::from_utf8(&bytes[..end]).unwrap()
+}
+
+const SUFFIXES: [&str; 4] = ["ABC", "BC", "XBC", "C"];
+
+#[test]
+fn suffix_sharing() {
+	let mut builder = StringHeapBuilder::new().suffix_sharing(true);
+	let handles = SUFFIXES.map(|value| builder.add(value));
+	let heap = builder.build();
+	for (value, handle) in SUFFIXES.iter().zip(handles) {
+		assert_eq!(string_at(&heap, handle), *value);
+	}
+
+	// Only "XBC" and "ABC" are stored after the leading empty string, "BC" and "C" point into "ABC".
+	assert_eq!(heap.bytes(), b"\0XBC\0ABC\0\0\0\0");
+}
+
+#[test]
+fn duplicates_share_a_handle() {
+	let mut builder = StringHeapBuilder::new().suffix_sharing(true);
+	let first = builder.add("Duplicate");
+	builder.add("Other");
+	assert_eq!(builder.add("Duplicate"), first);
+	assert_eq!(builder.add(""), StringHandle::EMPTY);
+	assert_eq!(builder.len(), 3);
+
+	let heap = builder.build();
+	assert_eq!(heap.offset(StringHandle::EMPTY).unwrap().get(), 0);
+	assert_eq!(string_at(&heap, first), "Duplicate");
+}
+
+#[test]
+fn layout_does_not_depend_on_insertion_order() {
+	let mut forward = StringHeapBuilder::new().suffix_sharing(true);
+	SUFFIXES.iter().for_each(|value| _ = forward.add(value));
+	let mut backward = StringHeapBuilder::new().suffix_sharing(true);
+	SUFFIXES.iter().rev().for_each(|value| _ = backward.add(value));
+
+	let (forward_heap, backward_heap) = (forward.build(), backward.build());
+	assert_eq!(forward_heap.bytes(), backward_heap.bytes());
+	for value in SUFFIXES {
+		let (a, b) = (forward.add(value), backward.add(value));
+		assert_eq!(forward_heap.offset(a), backward_heap.offset(b));
+	}
+}
+
+#[test]
+fn without_suffix_sharing() {
+	let mut builder = StringHeapBuilder::new().suffix_sharing(false);
+	let handles = SUFFIXES.map(|value| builder.add(value));
+	let heap = builder.build();
+
+	let mut offsets = handles.map(|handle| heap.offset(handle).unwrap().get());
+	offsets.sort();
+	assert_eq!(offsets, [1, 5, 8, 12]);
+	for (value, handle) in SUFFIXES.iter().zip(handles) {
+		assert_eq!(string_at(&heap, handle), *value);
+	}
+}
@@ -0,0 +1,214 @@
+//! Reads back the images built with [`ImageBuilder`], both as raw rows and as loaded types,
+//! so that the other tests can rely on the builder writing what they asked for.
+
+use cli_toolkit::raw::*;
+use cli_toolkit::schema::Context;
+use cli_toolkit::testing::{string_attribute_value, ImageBuilder};
+
+/// The tokens [`ImageBuilder`] returned for the rows of [`image`].
+struct Tokens {
+	object: MetadataToken,
+	list: MetadataToken,
+	constructor: MetadataToken,
+	ty: MetadataToken,
+}
+
+/// A type deriving from a TypeRef, with a field, a method with a parameter, and an attribute.
+fn image() -> (Vec<u8>, Tokens) {
+	let (builder, tokens) = builder();
+	(builder.build(), tokens)
+}
+
+fn builder() -> (ImageBuilder, Tokens) {
+	let mut builder = ImageBuilder::new("Built");
+	let core = builder.assembly_ref("System.Runtime", [7, 0, 0, 0]);
+	let object = builder.type_ref(core, "System", "Object");
+	let attribute = builder.type_ref(core, "System", "ObsoleteAttribute");
+	let constructor = builder.member_ref(attribute, ".ctor", &[0x20, 0x01, 0x01, 0x0E]);
+
+	// List`1 is the third TypeRef, encoded as 0x0D in signatures.
+	let list = builder.type_ref(core, "System.Collections.Generic", "List`1");
+
+	let ty = builder.type_def("Built", "Foo", type_flags::PUBLIC | type_flags::SEALED, Some(object));
+	builder.custom_attribute(ty, constructor, &string_attribute_value(&["old"]));
+	builder.field("items", field_flags::PRIVATE, &[0x06, 0x15, 0x12, 0x0D, 0x01, 0x08]);
+	builder.method("Run", method_flags::PUBLIC, &[0x20, 0x01, 0x01, 0x08]);
+	builder.param(1, "value", 0);
+
+	let tokens = Tokens {
+		object,
+		list,
+		constructor,
+		ty,
+	};
+	(builder, tokens)
+}
+
+#[test]
+fn raw_rows() {
+	let (bytes, tokens) = image();
+	assert_raw_rows(&bytes, &tokens);
+
+	let assembly = Assembly::try_from(bytes.as_slice()).unwrap();
+	let report = assembly.metadata().index_widths_report().unwrap().unwrap();
+	let widths = [report.widths.string, report.widths.guid, report.widths.blob];
+	assert_eq!(widths, [IndexSize::Slim; 3]);
+	assert_eq!(report.forced_fat, FatHeapIndices::default());
+}
+
+#[test]
+fn fat_heap_indices() {
+	let (builder, tokens) = builder();
+	let bytes = builder.fat_heap_indices(FatHeapIndices::ALL).build();
+	assert_raw_rows(&bytes, &tokens);
+
+	let assembly = Assembly::try_from(bytes.as_slice()).unwrap();
+	let report = assembly.metadata().index_widths_report().unwrap().unwrap();
+	let widths = [report.widths.string, report.widths.guid, report.widths.blob];
+	assert_eq!(widths, [IndexSize::Fat; 3]);
+	assert_eq!(report.forced_fat, FatHeapIndices::ALL);
+
+	let context = Context::from_assembly_list([bytes.as_slice()]).unwrap();
+	let ty = context.assemblies()[0].find_type("Foo", "Built").unwrap();
+	assert_eq!(ty.data().unwrap().token(), tokens.ty);
+}
+
+/// Reads back the rows of [`image`], whatever the width of its heap indices.
+fn assert_raw_rows(bytes: &[u8], tokens: &Tokens) {
+	let assembly = Assembly::try_from(bytes).unwrap();
+	let metadata = assembly.metadata();
+	let strings = metadata.get_heap::<StringHeap>().unwrap().unwrap();
+	let blobs = metadata.get_heap::<BlobHeap>().unwrap().unwrap();
+	let tables = metadata.tables().unwrap().unwrap();
+
+	let type_defs = tables.get_table::<TypeDefTable>().unwrap().unwrap();
+	assert_eq!(type_defs.len(), 2);
+	assert_eq!(
+		strings.get_string(type_defs.get(TableIndex::new(1).unwrap()).unwrap().name()),
+		"<Module>"
+	);
+
+	let ty = type_defs.get(TableIndex::new(2).unwrap()).unwrap();
+	assert_eq!(strings.get_string(ty.name()), "Foo");
+	assert_eq!(strings.get_string(ty.namespace()), "Built");
+	assert_eq!(ty.flags(), type_flags::PUBLIC | type_flags::SEALED);
+	assert_eq!(ty.fields(), TableIndex::new(1).unwrap());
+	assert_eq!(ty.methods(), TableIndex::new(1).unwrap());
+	let base = ty.base_type().decode(CodedIndexKind::TypeDefOrRef);
+	assert_eq!(base, Some(tokens.object));
+
+	let type_refs = tables.get_table::<TypeRefTable>().unwrap().unwrap();
+	let names = type_refs
+		.iter()
+		.map(|row| {
+			let row = row.unwrap();
+			(strings.get_string(row.type_namespace()), strings.get_string(row.type_name()))
+		})
+		.collect::<Vec<_>>();
+	assert_eq!(
+		names,
+		[
+			("System", "Object"),
+			("System", "ObsoleteAttribute"),
+			("System.Collections.Generic", "List`1"),
+		]
+	);
+
+	let fields = tables.get_table::<FieldTable>().unwrap().unwrap();
+	let field = fields.get(TableIndex::new(1).unwrap()).unwrap();
+	assert_eq!(strings.get_string(field.name()), "items");
+	assert_eq!(field.flags(), field_flags::PRIVATE);
+	assert_eq!(blobs.get_blob(field.signature()).unwrap(), [0x06, 0x15, 0x12, 0x0D, 0x01, 0x08]);
+
+	let methods = tables.get_table::<MethodDefTable>().unwrap().unwrap();
+	let method = methods.get(TableIndex::new(1).unwrap()).unwrap();
+	assert_eq!(strings.get_string(method.name()), "Run");
+	assert_eq!(method.params(), TableIndex::new(1).unwrap());
+
+	let params = tables.get_table::<ParamTable>().unwrap().unwrap();
+	let param = params.get(TableIndex::new(1).unwrap()).unwrap();
+	assert_eq!(strings.get_string(param.name()), "value");
+	assert_eq!(param.sequence(), 1);
+
+	let attributes = tables.get_table::<CustomAttributeTable>().unwrap().unwrap();
+	let attribute = attributes.get(TableIndex::new(1).unwrap()).unwrap();
+	let parent = attribute.parent().decode(CodedIndexKind::HasCustomAttribute);
+	assert_eq!(parent, Some(tokens.ty));
+	let constructor = attribute.type_().decode(CodedIndexKind::CustomAttributeType);
+	assert_eq!(constructor, Some(tokens.constructor));
+	assert_eq!(blobs.get_blob(attribute.value()).unwrap(), string_attribute_value(&["old"]));
+}
+
+#[test]
+fn loaded_types() {
+	let (bytes, tokens) = image();
+	let context = Context::from_assembly_list([bytes.as_slice()]).unwrap();
+	let assembly = &context.assemblies()[0];
+	assert_eq!(assembly.types().len(), 2);
+
+	let ty = assembly.find_type("Foo", "Built").unwrap();
+	let data = ty.data().unwrap();
+	assert_eq!(data.token(), tokens.ty);
+	assert_eq!(data.flags(), type_flags::PUBLIC | type_flags::SEALED);
+
+	let [field] = data.fields() else {
+		panic!("expected one field, got {:?}", data.fields())
+	};
+	assert_eq!(field.name(), "items");
+	let list = TypeSignature::Class(tokens.list);
+	let expected = TypeSignature::GenericInst(Box::new(list), vec![TypeSignature::Int32]);
+	assert_eq!(FieldSignature::parse(field.signature()).unwrap().ty, expected);
+
+	let [method] = data.methods() else {
+		panic!("expected one method, got {:?}", data.methods())
+	};
+	assert_eq!(method.name(), "Run");
+	let signature = MethodSignature::parse(method.signature()).unwrap();
+	assert_eq!(signature.return_type, TypeSignature::Void);
+	assert_eq!(signature.params, [TypeSignature::Int32]);
+	let names = method.params().iter().map(|param| param.name()).collect::<Vec<_>>();
+	assert_eq!(names, ["value"]);
+
+	let [attribute] = data.custom_attributes() else {
+		panic!("expected one attribute")
+	};
+	assert_eq!(attribute.attribute_type(), "System.ObsoleteAttribute");
+	let decoded = assembly.decode_attribute(attribute);
+	let old = AttributeValue::String(Some("old".to_string()));
+	assert_eq!(decoded.arguments().fixed, [AttributeArgument::Value(old)]);
+	assert!(decoded.diagnostics().is_empty());
+}
+
+#[test]
+fn method_spec() {
+	let mut builder = ImageBuilder::new("Specs");
+	let core = builder.assembly_ref("System.Runtime", [7, 0, 0, 0]);
+	let object = builder.type_ref(core, "System", "Object");
+	builder.type_def("Specs", "Foo", type_flags::PUBLIC, Some(object));
+	builder.method("Run", method_flags::PUBLIC, &[0x20, 0x00, 0x01]);
+	let map = builder.method("Map", method_flags::PUBLIC, &[0x30, 0x02, 0x00, 0x01]);
+
+	// GENERICINST with Int32, String and the first TypeRef, System.Object.
+	let instantiation = [0x0A, 0x03, 0x08, 0x0E, 0x12, 0x05];
+	let spec = builder.method_spec(map, &instantiation);
+	let bytes = builder.build();
+
+	let raw = Assembly::try_from(bytes.as_slice()).unwrap();
+	let metadata = raw.metadata();
+	let blobs = metadata.get_heap::<BlobHeap>().unwrap().unwrap();
+	let tables = metadata.tables().unwrap().unwrap();
+	let specs = tables.get_table::<MethodSpecTable>().unwrap().unwrap();
+	assert_eq!(specs.len(), 1);
+	let row = specs.get(TableIndex::new(1).unwrap()).unwrap();
+	assert_eq!(row.method().decode(CodedIndexKind::MethodDefOrRef), Some(map));
+	assert_eq!(blobs.get_blob(row.instantiation()).unwrap(), instantiation);
+
+	let context = Context::from_assembly_list([bytes.as_slice()]).unwrap();
+	let assembly = &context.assemblies()[0];
+	let (method, arguments) = context.resolve_method_spec(assembly, spec).unwrap();
+	assert_eq!(method.token(), map);
+	assert_eq!(method.method().unwrap().name(), "Map");
+	assert_eq!(method.declaring_type().data().unwrap().name(), "Foo");
+	let expected = [TypeSignature::Int32, TypeSignature::String, TypeSignature::Class(object)];
+	assert_eq!(arguments, expected);
+}
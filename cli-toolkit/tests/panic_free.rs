@@ -0,0 +1,129 @@
+//! Runs the fuzzing entry points over malformed variants of images built with [`ImageBuilder`],
+//! including hostile shapes that random mutations are unlikely to produce.
+
+use cli_toolkit::fuzzing::check_panic_free;
+use cli_toolkit::raw::{
+	field_flags, method_flags, type_flags, Assembly as RawAssembly, AssemblyTable, MetadataTable, TypeSignature,
+};
+use cli_toolkit::schema::Context;
+use cli_toolkit::testing::{string_attribute_value, ImageBuilder};
+
+/// A small assembly using every kind of row the builder supports.
+fn well_formed() -> ImageBuilder {
+	let mut builder = ImageBuilder::new("Seed");
+	let core = builder.assembly_ref("System.Runtime", [7, 0, 0, 0]);
+	let object = builder.type_ref(core, "System", "Object");
+	let attribute = builder.type_ref(core, "System", "ObsoleteAttribute");
+	let constructor = builder.member_ref(attribute, ".ctor", &[0x20, 0x01, 0x01, 0x0E]);
+
+	// List`1 is the third TypeRef, encoded as 0x0D in signatures.
+	builder.type_ref(core, "System.Collections.Generic", "List`1");
+	let list_of_int = builder.type_spec(&[0x15, 0x12, 0x0D, 0x01, 0x08]);
+
+	let ty = builder.type_def("Seed", "Foo", type_flags::PUBLIC, Some(object));
+	builder.custom_attribute(ty, constructor, &string_attribute_value(&["old"]));
+	builder.field("items", field_flags::PRIVATE, &[0x06, 0x15, 0x12, 0x0D, 0x01, 0x08]);
+	builder.method("Run", method_flags::PUBLIC, &[0x20, 0x01, 0x01, 0x08]);
+	builder.method_body(&[0x02, 0x7B, 0x01, 0x00, 0x00, 0x04, 0x26, 0x2A]);
+	builder.param(1, "value", 0);
+
+	let nested = builder.type_def("", "Bar", type_flags::NESTED_PUBLIC, Some(object));
+	builder.nested_class(nested, ty);
+	builder.generic_param(nested, 0, "T", 0);
+	builder.interface_impl(nested, list_of_int);
+	builder.field("matrix", field_flags::PRIVATE, &[0x06, 0x14, 0x08, 0x02, 0x01, 0x04, 0x02, 0x00, 0x00]);
+	builder
+}
+
+fn load(bytes: &[u8]) {
+	let Ok(context) = Context::from_assembly_list_lenient([bytes]) else { return };
+	for assembly in context.assemblies() {
+		let _ = assembly.summary().to_string();
+		for ty in assembly.types() {
+			let _ = context.display(ty).to_string();
+		}
+	}
+}
+
+#[test]
+fn well_formed_seed() {
+	let bytes = well_formed().build();
+	assert!(Context::from_assembly_list([bytes.as_slice()]).is_ok());
+	if let Err(case) = check_panic_free(&bytes) {
+		panic!("{} panicked: {:?}", case.entry_point, case.message);
+	}
+}
+
+#[test]
+fn cyclic_type_ref_scope() {
+	let mut builder = well_formed();
+	let cyclic = builder.type_ref(builder.module(), "Seed", "Cyclic");
+	builder.set_type_ref_scope(cyclic, cyclic);
+	let constructor = builder.member_ref(cyclic, ".ctor", &[0x20, 0x00, 0x01]);
+	builder.custom_attribute(builder.module(), constructor, &string_attribute_value(&[]));
+
+	let outer = builder.type_ref(builder.module(), "Seed", "Outer");
+	let inner = builder.type_ref(outer, "", "Inner");
+	builder.set_type_ref_scope(outer, inner);
+	builder.type_def("Seed", "Derived", type_flags::PUBLIC, Some(inner));
+
+	let bytes = builder.build();
+	load(&bytes);
+	if let Err(case) = check_panic_free(&bytes) {
+		panic!("{} panicked: {:?}", case.entry_point, case.message);
+	}
+}
+
+#[test]
+fn nested_class_cycle() {
+	let mut builder = ImageBuilder::new("Nested");
+	let core = builder.assembly_ref("System.Runtime", [7, 0, 0, 0]);
+	let object = builder.type_ref(core, "System", "Object");
+	let a = builder.type_def("", "A", type_flags::NESTED_PUBLIC, Some(object));
+	let b = builder.type_def("", "B", type_flags::NESTED_PUBLIC, Some(object));
+	let c = builder.type_def("", "C", type_flags::NESTED_PUBLIC, Some(object));
+	builder.nested_class(a, b);
+	builder.nested_class(b, a);
+	builder.nested_class(c, c);
+
+	let bytes = builder.build();
+	load(&bytes);
+	if let Err(case) = check_panic_free(&bytes) {
+		panic!("{} panicked: {:?}", case.entry_point, case.message);
+	}
+}
+
+#[test]
+fn huge_array_rank() {
+	// int32[,,...] with a rank of 0x1FFFFFFF and no sizes or lower bounds.
+	let signature = [0x14, 0x08, 0xDF, 0xFF, 0xFF, 0xFF, 0x00, 0x00];
+	assert!(TypeSignature::parse(&signature).is_err());
+
+	let mut builder = well_formed();
+	builder.type_spec(&signature);
+	builder.field("huge", field_flags::PRIVATE, &[0x06, 0x14, 0x08, 0xDF, 0xFF, 0xFF, 0xFF, 0x00, 0x00]);
+
+	let bytes = builder.build();
+	load(&bytes);
+	if let Err(case) = check_panic_free(&bytes) {
+		panic!("{} panicked: {:?}", case.entry_point, case.message);
+	}
+}
+
+#[test]
+fn unknown_hash_algorithm() {
+	let bytes = well_formed().hash_algorithm(0xDEAD_BEEF).build();
+	let context = Context::from_assembly_list([bytes.as_slice()]).unwrap();
+	assert_eq!(context.assemblies()[0].types().len(), 3);
+
+	let assembly = RawAssembly::try_from(bytes.as_slice()).unwrap();
+	let tables = assembly.metadata().tables().unwrap().unwrap();
+	let table = tables.get_table::<AssemblyTable>().unwrap().unwrap();
+	let row = table.iter().next().unwrap().unwrap();
+	assert_eq!(row.hash_algorithm_id(), 0xDEAD_BEEF);
+	assert!(row.hash_algorithm().is_err());
+
+	if let Err(case) = check_panic_free(&bytes) {
+		panic!("{} panicked: {:?}", case.entry_point, case.message);
+	}
+}
@@ -0,0 +1,196 @@
+//! Checks the object model of assemblies built with [`ImageBuilder`]: member iteration, name formatting,
+//! interface maps and the other views computed over loaded types.
+
+use cli_toolkit::raw::{field_flags, method_flags, type_flags, TypeSignature};
+use cli_toolkit::schema::{Context, ImplementationKind, Member};
+use cli_toolkit::testing::ImageBuilder;
+
+#[test]
+fn members_in_token_order() {
+	let mut builder = ImageBuilder::new("Members");
+	let core = builder.assembly_ref("System.Runtime", [7, 0, 0, 0]);
+	let object = builder.type_ref(core, "System", "Object");
+	let outer = builder.type_def("Members", "Outer", type_flags::PUBLIC, Some(object));
+	builder.field("first", field_flags::PUBLIC, &[0x06, 0x08]);
+	builder.field("second", field_flags::PUBLIC | field_flags::STATIC, &[0x06, 0x08]);
+	builder.method("Run", method_flags::PUBLIC, &[0x20, 0x00, 0x01]);
+	let inner = builder.type_def("", "Inner", type_flags::NESTED_PUBLIC, Some(object));
+	builder.nested_class(inner, outer);
+
+	let bytes = builder.build();
+	let context = Context::from_assembly_list([bytes.as_slice()]).unwrap();
+	let ty = context.assemblies()[0].find_type("Outer", "Members").unwrap();
+
+	let members = ty.members().map(|member| (member.token(), member.name().to_string())).collect::<Vec<_>>();
+	let mut sorted = members.clone();
+	sorted.sort_by_key(|(token, _)| format!("{:08X}", token));
+	assert_eq!(members, sorted);
+
+	let names = members.iter().map(|(_, name)| name.as_str()).collect::<Vec<_>>();
+	assert_eq!(names, ["Inner", "first", "second", "Run"]);
+
+	let statics = ty.members().static_only().map(|member| member.name().to_string()).collect::<Vec<_>>();
+	assert_eq!(statics, ["Inner", "second"]);
+	assert!(matches!(ty.members().named("R*").next(), Some(Member::Method(_))));
+}
+
+#[test]
+fn interface_map() {
+	let mut builder = ImageBuilder::new("Maps");
+	let core = builder.assembly_ref("System.Runtime", [7, 0, 0, 0]);
+	let object = builder.type_ref(core, "System", "Object");
+	let abstract_ = method_flags::PUBLIC | method_flags::VIRTUAL | method_flags::NEW_SLOT | method_flags::ABSTRACT;
+	let virtual_ = method_flags::PUBLIC | method_flags::VIRTUAL;
+
+	let interface = type_flags::PUBLIC | type_flags::INTERFACE | type_flags::ABSTRACT;
+	let runner = builder.type_def("Maps", "IRunner", interface, None);
+	builder.method("Run", abstract_, &[0x20, 0x00, 0x01]);
+	let stop = builder.method("Stop", abstract_, &[0x20, 0x00, 0x01]);
+	builder.method("Close", abstract_, &[0x20, 0x00, 0x01]);
+
+	let base = builder.type_def("Maps", "Base", type_flags::PUBLIC, Some(object));
+	builder.method("Close", virtual_, &[0x20, 0x00, 0x01]);
+
+	let derived = builder.type_def("Maps", "Derived", type_flags::PUBLIC, Some(base));
+	builder.interface_impl(derived, runner);
+	builder.method("Run", virtual_, &[0x20, 0x01, 0x01, 0x08]);
+	builder.method("Run", virtual_, &[0x20, 0x00, 0x01]);
+	builder.method("Stop", virtual_, &[0x20, 0x00, 0x01]);
+	let private = method_flags::PRIVATE | method_flags::VIRTUAL | method_flags::FINAL | method_flags::NEW_SLOT;
+	let explicit = builder.method("Maps.IRunner.Stop", private, &[0x20, 0x00, 0x01]);
+	builder.method_impl(derived, explicit, stop);
+
+	let bytes = builder.build();
+	let context = Context::from_assembly_list([bytes.as_slice()]).unwrap();
+	let assembly = &context.assemblies()[0];
+	let map = assembly.find_type("Derived", "Maps").unwrap().interface_map();
+
+	let mappings = map
+		.mappings()
+		.iter()
+		.map(|mapping| {
+			let interface_method = mapping.interface_method().method().unwrap().name().to_string();
+			let implementation = mapping.implementation().unwrap();
+			let declaring_type = implementation.declaring_type().data().unwrap().name().to_string();
+			let method = implementation.method().unwrap();
+			(interface_method, declaring_type, method.name().to_string(), mapping.kind().unwrap())
+		})
+		.collect::<Vec<_>>();
+	let expected = [
+		("Run", "Derived", "Run", ImplementationKind::Implicit),
+		("Stop", "Derived", "Maps.IRunner.Stop", ImplementationKind::Explicit),
+		("Close", "Base", "Close", ImplementationKind::Implicit),
+	];
+	let expected = expected.map(|(interface_method, ty, method, kind)| {
+		(interface_method.to_string(), ty.to_string(), method.to_string(), kind)
+	});
+	assert_eq!(mappings, expected);
+	assert_eq!(map.unimplemented().count(), 0);
+
+	// The implicit Run is the overload without parameters, the sixth MethodDef, not the one declared before it.
+	assert_eq!(map.mappings()[0].implementation().unwrap().token().index(), 6);
+}
+
+#[test]
+fn explicit_interface_implementations() {
+	let mut builder = ImageBuilder::new("Explicit");
+	let core = builder.assembly_ref("System.Runtime", [7, 0, 0, 0]);
+	let object = builder.type_ref(core, "System", "Object");
+	let disposable = builder.type_ref(core, "System", "IDisposable");
+	let dispose = builder.member_ref(disposable, "Dispose", &[0x20, 0x00, 0x01]);
+
+	// IEnumerable`1 is the third TypeRef, encoded as 0x0D in signatures.
+	builder.type_ref(core, "System.Collections.Generic", "IEnumerable`1");
+	let enumerable = builder.type_spec(&[0x15, 0x12, 0x0D, 0x01, 0x0E]);
+	let get_enumerator = builder.member_ref(enumerable, "GetEnumerator", &[0x20, 0x00, 0x1C]);
+
+	let ty = builder.type_def("Explicit", "Foo", type_flags::PUBLIC, Some(object));
+	builder.interface_impl(ty, disposable);
+	builder.interface_impl(ty, enumerable);
+	let private = method_flags::PRIVATE | method_flags::VIRTUAL | method_flags::FINAL | method_flags::NEW_SLOT;
+	let body = builder.method("System.IDisposable.Dispose", private, &[0x20, 0x00, 0x01]);
+	builder.method_impl(ty, body, dispose);
+	let name = "System.Collections.Generic.IEnumerable<System.String>.GetEnumerator";
+	let body = builder.method(name, private, &[0x20, 0x00, 0x1C]);
+	builder.method_impl(ty, body, get_enumerator);
+	builder.method("Not.An.Implementation", method_flags::PUBLIC, &[0x20, 0x00, 0x01]);
+
+	let bytes = builder.build();
+	let context = Context::from_assembly_list([bytes.as_slice()]).unwrap();
+	let ty = context.assemblies()[0].find_type("Foo", "Explicit").unwrap();
+	let data = ty.data().unwrap();
+	let [dispose_body, enumerator_body, public] = data.methods() else {
+		panic!("expected three methods, got {:?}", data.methods())
+	};
+
+	assert_eq!(dispose_body.overrides(), [dispose]);
+	let implementation = dispose_body.explicit_interface_implementation(data).unwrap();
+	assert_eq!(implementation.declaration(), dispose);
+	assert_eq!(implementation.interface_name(), "System.IDisposable");
+	assert_eq!(implementation.name(), "Dispose");
+	assert!(implementation.interface_method().is_none());
+
+	assert_eq!(enumerator_body.overrides(), [get_enumerator]);
+	let implementation = enumerator_body.explicit_interface_implementation(data).unwrap();
+	assert_eq!(implementation.declaration(), get_enumerator);
+	assert_eq!(implementation.interface_name(), "System.Collections.Generic.IEnumerable<System.String>");
+	assert_eq!(implementation.name(), "GetEnumerator");
+	assert_eq!(implementation.to_string(), name);
+
+	assert!(public.overrides().is_empty());
+	assert!(public.explicit_interface_implementation(data).is_none());
+}
+
+#[test]
+fn generic_param_constraints() {
+	let interface = type_flags::PUBLIC | type_flags::INTERFACE | type_flags::ABSTRACT;
+	let mut builder = ImageBuilder::new("Shapes");
+	builder.type_def("Shapes", "IShape", interface, None);
+	let shapes = builder.build();
+
+	let mut builder = ImageBuilder::new("Canvas");
+	let core = builder.assembly_ref("System.Runtime", [7, 0, 0, 0]);
+	let shapes_ref = builder.assembly_ref("Shapes", [1, 0, 0, 0]);
+	let object = builder.type_ref(core, "System", "Object");
+	let shape = builder.type_ref(shapes_ref, "Shapes", "IShape");
+	let drawable = builder.type_def("Canvas", "IDrawable", interface, None);
+	let canvas = builder.type_def("Canvas", "Canvas`1", type_flags::PUBLIC, Some(object));
+	let draw = builder.method("Draw", method_flags::PUBLIC, &[0x30, 0x01, 0x00, 0x01]);
+	let t = builder.type_spec(&[0x13, 0x00]);
+
+	// Rows are sorted by owner, and the MethodDef of Draw sorts before the TypeDef of Canvas`1.
+	let u_param = builder.generic_param(draw, 0, "U", 0);
+	let t_param = builder.generic_param(canvas, 0, "T", 0);
+	builder.generic_param_constraint(u_param, t);
+	builder.generic_param_constraint(t_param, drawable);
+	builder.generic_param_constraint(t_param, shape);
+	let bytes = builder.build();
+
+	let context = Context::from_assembly_list([shapes.as_slice(), bytes.as_slice()]).unwrap();
+	let assembly = &context.assemblies()[1];
+	let ty = assembly.find_type("Canvas`1", "Canvas").unwrap();
+	let data = ty.data().unwrap();
+
+	let [t_param] = data.generic_params() else {
+		panic!("expected one generic parameter, got {:?}", data.generic_params())
+	};
+	assert_eq!((t_param.name(), t_param.owner()), ("T", canvas));
+	let constraints = t_param
+		.constraints()
+		.iter()
+		.map(|constraint| {
+			let ty = assembly.get_type(*constraint).unwrap();
+			let data = ty.data().unwrap();
+			format!("{}.{}", data.namespace(), data.name())
+		})
+		.collect::<Vec<_>>();
+	assert_eq!(constraints, ["Canvas.IDrawable", "Shapes.IShape"]);
+
+	let method = data.method(draw).unwrap();
+	let [u_param] = method.generic_params() else {
+		panic!("expected one generic parameter, got {:?}", method.generic_params())
+	};
+	assert_eq!((u_param.name(), u_param.owner()), ("U", draw));
+	assert_eq!(u_param.constraints(), [t]);
+	assert_eq!(assembly.type_spec(t).unwrap().signature(), &TypeSignature::Var(0));
+}
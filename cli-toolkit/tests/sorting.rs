@@ -0,0 +1,100 @@
+//! Checks that [`SortedInsertion`] keeps the tables ECMA-335 requires to be sorted in order,
+//! and that unsorted tables are detected.
+
+use cli_toolkit::raw::*;
+use cli_toolkit::testing::ImageBuilder;
+
+/// The rows of the sorted tables of [`image`], by the indices of its types and interfaces, in table order.
+#[derive(Debug, Default, Clone)]
+struct Rows {
+	interface_impls: Vec<(usize, usize)>,
+	attributes: Vec<usize>,
+	generic_params: Vec<(usize, u16)>,
+}
+
+/// An assembly with four types and three interfaces, and the given rows written in the given order.
+fn image(rows: &Rows) -> Vec<u8> {
+	let mut builder = ImageBuilder::new("Sorting");
+	let core = builder.assembly_ref("System.Runtime", [7, 0, 0, 0]);
+	let object = builder.type_ref(core, "System", "Object");
+	let interfaces = ["IA", "IB", "IC"].map(|name| builder.type_ref(core, "Sorting", name));
+	let constructor = builder.member_ref(object, ".ctor", &[0x20, 0x00, 0x01]);
+	let types = ["A", "B", "C", "D"].map(|name| builder.type_def("Sorting", name, type_flags::PUBLIC, Some(object)));
+
+	for (class, interface) in rows.interface_impls.iter() {
+		builder.interface_impl(types[*class], interfaces[*interface]);
+	}
+	for parent in rows.attributes.iter() {
+		builder.custom_attribute(types[*parent], constructor, &[0x01, 0x00, 0x00, 0x00]);
+	}
+	for (owner, number) in rows.generic_params.iter() {
+		builder.generic_param(types[*owner], *number, "T", 0);
+	}
+	builder.build()
+}
+
+fn tables(bytes: &[u8]) -> TableHeap<'_> {
+	Assembly::try_from(bytes).unwrap().metadata().tables().unwrap().unwrap()
+}
+
+/// Adds the rows to the `$field` table one at a time, each where [`SortedInsertion::find`] puts it.
+macro_rules! insert_sorted {
+	($table: ty, $field: ident, $rows: expr) => {{
+		let mut inserted = Rows::default();
+		for row in $rows {
+			let current = image(&inserted);
+			let single = image(&Rows {
+				$field: vec![row],
+				..Rows::default()
+			});
+
+			let position = match tables(&current).get_table::<$table>().unwrap() {
+				None => 0,
+				Some(table) => {
+					let new = tables(&single).get_table::<$table>().unwrap().unwrap();
+					let new = new.get(TableIndex::new(1).unwrap()).unwrap();
+					SortedInsertion::find(&table, &new).unwrap().index().get() as usize - 1
+				}
+			};
+			inserted.$field.insert(position, row);
+		}
+		inserted
+	}};
+}
+
+#[test]
+fn insertions_keep_tables_sorted() {
+	let rows = insert_sorted!(InterfaceImplTable, interface_impls, [(3, 0), (1, 2), (2, 1), (1, 0)]);
+	assert_eq!(rows.interface_impls, [(1, 0), (1, 2), (2, 1), (3, 0)]);
+	let bytes = image(&rows);
+	assert_eq!(tables(&bytes).verify_sorted(TableKind::InterfaceImpl).unwrap(), Some(true));
+
+	let rows = insert_sorted!(CustomAttributeTable, attributes, [3, 0, 2, 0]);
+	assert_eq!(rows.attributes, [0, 0, 2, 3]);
+	let bytes = image(&rows);
+	assert_eq!(tables(&bytes).verify_sorted(TableKind::CustomAttribute).unwrap(), Some(true));
+
+	let rows = insert_sorted!(GenericParamTable, generic_params, [(2, 0), (0, 1), (0, 0), (2, 1)]);
+	assert_eq!(rows.generic_params, [(0, 0), (0, 1), (2, 0), (2, 1)]);
+	let bytes = image(&rows);
+	assert_eq!(tables(&bytes).verify_sorted(TableKind::GenericParam).unwrap(), Some(true));
+}
+
+#[test]
+fn unsorted_tables() {
+	let rows = Rows {
+		interface_impls: vec![(3, 0), (1, 0)],
+		attributes: vec![0, 2],
+		..Rows::default()
+	};
+	let bytes = image(&rows);
+	let tables = tables(&bytes);
+
+	let interface_impls = tables.get_table::<InterfaceImplTable>().unwrap().unwrap();
+	assert!(!is_table_sorted(&interface_impls).unwrap());
+	assert_eq!(tables.verify_sorted(TableKind::InterfaceImpl).unwrap(), Some(false));
+
+	let attributes = tables.get_table::<CustomAttributeTable>().unwrap().unwrap();
+	assert!(is_table_sorted(&attributes).unwrap());
+	assert_eq!(tables.verify_sorted(TableKind::TypeDef).unwrap(), None);
+}
@@ -0,0 +1,65 @@
+//! Checks which assembly versions satisfy a reference under each [`VersionPolicy`],
+//! and how version patterns with wildcards are parsed.
+
+use cli_toolkit::schema::{AssemblyVersion, VersionPattern, VersionPolicy};
+
+fn version(s: &str) -> AssemblyVersion {
+	s.parse().unwrap()
+}
+
+#[test]
+fn policies() {
+	let requested = version("1.2.3.4");
+	let candidates = ["1.2.3.4", "1.2.3.5", "1.2.4.0", "1.3.0.0", "2.0.0.0", "1.2.3.3", "0.9.0.0"];
+	let table = [
+		(VersionPolicy::Exact, [true, false, false, false, false, false, false]),
+		(VersionPolicy::SameMajorMinor, [true, true, true, false, false, false, false]),
+		(VersionPolicy::SameMajor, [true, true, true, true, false, false, false]),
+		(VersionPolicy::AtLeast, [true, true, true, true, true, false, false]),
+		(VersionPolicy::Any, [true; 7]),
+	];
+
+	for (policy, expected) in table {
+		for (candidate, expected) in candidates.iter().zip(expected) {
+			let compatible = version(candidate).is_compatible_with(&requested, policy);
+			assert_eq!(compatible, expected, "{} for {} under {:?}", candidate, requested, policy);
+		}
+	}
+}
+
+#[test]
+fn patterns() {
+	let table = [
+		("1.2.3.4", [Some(1), Some(2), Some(3), Some(4)], "1.2.3.4"),
+		("1.2", [Some(1), Some(2), Some(0), Some(0)], "1.2.0.0"),
+		("1.2.3.*", [Some(1), Some(2), Some(3), None], "1.2.3.*"),
+		("1.2.*", [Some(1), Some(2), None, None], "1.2.*"),
+		("1.*", [Some(1), None, None, None], "1.*"),
+		("*", [None; 4], "*"),
+		(" 1 . * ", [Some(1), None, None, None], "1.*"),
+	];
+
+	for (pattern, components, display) in table {
+		let parsed = pattern.parse::<VersionPattern>().unwrap();
+		assert_eq!(parsed.components(), components, "{}", pattern);
+		assert_eq!(parsed.to_string(), display, "{}", pattern);
+		assert_eq!(display.parse::<VersionPattern>().unwrap(), parsed, "{}", pattern);
+	}
+
+	for pattern in ["", "1..2", "1.x", "1.2.3.4.5", "1.2.*.*.*", "65536"] {
+		assert!(pattern.parse::<VersionPattern>().is_err(), "{}", pattern);
+	}
+}
+
+#[test]
+fn pattern_matches() {
+	let pattern = "1.2.*".parse::<VersionPattern>().unwrap();
+	assert!(pattern.matches(&version("1.2.0.0")));
+	assert!(pattern.matches(&version("1.2.9.9")));
+	assert!(!pattern.matches(&version("1.3.0.0")));
+	assert_eq!(pattern.min_version(), version("1.2.0.0"));
+
+	let any = "*".parse::<VersionPattern>().unwrap();
+	assert!(any.matches(&version("65535.65535.65535.65535")));
+	assert_eq!(VersionPattern::from(version("1.2.3.4")), "1.2.3.4".parse().unwrap());
+}